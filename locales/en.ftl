@@ -0,0 +1,645 @@
+# Strings of the user interface in English, the language other translations fall back to.
+
+language = Language
+theme = Theme
+theme-light = Light
+theme-dark = Dark
+theme-organization = Organization
+install-app = Install
+
+## Screens
+
+tabs = Screens
+tab-segment = Segment
+tab-history = History
+tab-compare = Compare
+tab-settings = Settings
+tab-batch = Batch
+tab-flagged = Flagged
+tab-models = Models
+heading-segments = Segments
+heading-compare = Compare two dates
+heading-history = History
+heading-gallery = Gallery
+heading-storage = Storage
+heading-audit = Audit trail
+heading-settings = Settings
+heading-postprocess = Mask post-processing
+heading-batch = Batch processing
+heading-flagged = Flagged for review
+heading-models = Compare models
+heading-report = Report
+heading-comments = Comments
+
+## Segmentation
+
+status-reading = Reading files
+status-uploading = Uploading
+status-processing = Processing
+status-done = Done
+status-failed = Failed
+status-cancelled = Cancelled
+status-elapsed = { $elapsed } of { $timeout }
+status-still-working = Still working: large images can take a few minutes.
+action-cancel = Cancel
+action-close = Close
+action-segment-again = Segment again
+segments-processing = Processing image...
+segments-no-image = No image uploaded yet...
+segments-cancelled = Segmentation was cancelled.
+segments-failed = Could not segment the image.
+mask-alt = Segmentation mask { $name }: { $classes }
+mask-alt-class = class { $class } covers { $percent }%
+mask-alt-undecodable = Segmentation mask { $name }, which could not be read
+mask-undecodable = The server answered with a mask that could not be read.
+mask-undecodable-type = Content type
+mask-undecodable-size = Size
+mask-undecodable-bytes = First bytes
+mask-undecodable-download = Download as received
+mask-download-geojson = Download as GeoJSON
+mask-geojson-failed = Could not export the mask as GeoJSON.
+segments-queued = You are offline. The image was queued and will be segmented when the
+    connection returns; the result will then appear in the history.
+segments-queue-failed = You are offline and the image could not be queued: { $reason }
+permalink-failed = Could not open the shared result.
+
+## Keyboard shortcuts
+
+shortcuts = Keyboard shortcuts
+shortcut-help = Show or hide this list
+shortcut-open = Open an image file
+shortcut-toggle-mask = Show or hide the mask on the map
+shortcut-mask-opacity-up = Make the mask more opaque
+shortcut-mask-opacity-down = Make the mask more transparent
+shortcut-tab-segment = Go to segmentation
+shortcut-tab-history = Go to the history
+shortcut-tab-compare = Go to the comparison
+shortcut-tab-settings = Go to the settings
+shortcut-tab-batch = Go to batch processing
+shortcut-tab-flagged = Go to the results flagged for review
+shortcut-tab-models = Go to the model comparison
+
+## Errors
+
+error-network = Could not reach { $url }: { $message }
+error-timeout = { $url } took too long to answer
+error-corrupted = The image arrived altered at { $url }, most likely cut short by a proxy on the way. Sending it again usually works.
+error-auth = { $url } refused the access token (status { $status })
+error-http = { $url } answered with status { $status }: { $message }
+error-decode = Could not read the server's answer: { $message }
+error-cancelled = The request was cancelled.
+error-hint-auth = Update the access token in the settings and sign in again.
+error-hint-address = Check the server address in the settings.
+error-hint-mixed-content = This page is served over HTTPS, so the browser blocks requests to
+    the plain HTTP address { $url }. Serve the server over HTTPS and use its https:// address in
+    the settings.
+error-hint-cors = The server answers, but the browser hides its answers from this page: the
+    server has to allow the origin { $origin } in its CORS headers, with Access-Control-Allow-Origin,
+    and Access-Control-Allow-Headers for Authorization, Accept, X-Request-Id and X-Image-SHA256.
+error-result-unopened = Could not open the result
+error-result-missing = There is no result { $id } in the history
+error-config-ignored = Parts of the configuration were ignored
+error-show-more = Show more
+action-retry = Retry
+action-open-settings = Open settings
+
+## Settings
+
+settings-server-url = Server URL
+settings-token = Access token
+settings-opacity = Default mask opacity
+settings-keep-results = Keep newest results
+settings-keep-all = all
+settings-keep-days = Keep results for days
+settings-keep-forever = forever
+settings-decoded-cache = Memory for decoded masks, MB
+settings-parallel-requests = Requests sent at once
+settings-large-file = Confirm files over (MB)
+settings-telemetry = Send anonymous usage statistics: image sizes, response times and errors
+settings-reset = Reset to defaults
+
+## Memory
+
+memory-usage = The app holds { $heap } of memory, { $decoded } of it in decoded masks, and keeps { $urls } images ready to show.
+memory-warning = The app holds { $heap } of memory. The browser may close the tab if it runs out.
+memory-free = Free memory
+
+## Debug log
+
+debug-log-copy-failed = Could not copy the log
+debug-log = Debug log
+debug-log-level = Lowest level shown
+debug-log-copy = Copy
+debug-log-download = Download
+debug-log-empty = Nothing was logged yet.
+
+## Crashes
+
+region-image = Image
+crash-title = Something went wrong and the app stopped.
+crash-title-region = Something went wrong in { $region } and the app stopped.
+crash-kept = Your work up to the last autosave is kept and will be offered back after reloading.
+crash-reported = A report of the crash was sent, without your images or their names.
+crash-details = Details
+crash-reload = Reload
+crash-download-log = Download the log
+
+## Batch processing
+
+batch-add = Add images
+batch-pause = Pause
+batch-resume = Resume
+batch-retry-failed = Retry failed
+batch-export = Export all
+batch-remove-done = Remove finished
+batch-limit = Requests at once
+batch-empty = Add images to segment them a few at a time, however many there are. The run goes on while other tabs of the app are open, as long as this page is.
+batch-paused = Paused: images already sent are finished, no new ones are sent.
+batch-connection-lost = The server cannot be reached, so the batch is paused. It resumes by itself once the connection is back.
+batch-history-limit = The history keeps only the newest { $count } results, so older results of this batch will be pruned from it. Export them before.
+batch-progress = { $done } done, { $failed } failed, { $waiting } left.
+batch-remaining = About { $time } to go.
+batch-image = Image
+batch-status = Status
+batch-time = Time
+batch-result = Result
+batch-waiting = Waiting
+batch-running = Segmenting
+batch-done = Done
+batch-failed = Failed
+batch-open = Open
+batch-export-failed = Could not export the batch
+batch-export-incomplete = Some results are missing from the export
+batch-export-missing = { $count } results were pruned from the history since they were segmented.
+batch-add-truths = Add ground truths
+batch-truths = { $count } ground-truth masks, matched to images by name without the extension. Masks of the images segmented from now on are scored against them.
+batch-iou = Mean IoU { $iou }
+batch-evaluation = Scores against the ground truths
+batch-mask-of = Mask of { $name }
+batch-unsegmentable = It cannot be segmented: { $reason }.
+batch-truth-unreadable = The ground truth could not be read
+
+## Roles
+
+role-viewer = viewer
+role-editor = editor
+role-admin = admin
+role-screen-unavailable = This screen is not available to the { $role } role.
+role-viewer-results = Results can be looked at in the history, without changing them.
+
+## Map
+
+map-basemap = Basemap
+map-aoi-imagery = AOI imagery
+map-draw-box = Draw box
+map-draw-polygon = Draw polygon
+map-finish = Finish
+map-zoom = Zoom
+map-segment-area = Segment area
+map-hover = Hover the map for coordinates
+map-layers = Layers
+map-truth-layer = Ground-truth layer
+map-error-map = Error map
+map-saved-aois = Saved areas of interest
+map-use-aoi = Use
+map-stac = STAC scenes
+map-sources = Imagery sources
+layer-basemap = Basemap
+layer-image = Satellite image
+layer-mask = Mask
+layer-ground-truth = Ground truth
+layer-aoi = Area of interest
+layer-show = Show { $layer }
+layer-opacity = { $layer } opacity
+layer-raise = Raise { $layer }
+layer-lower = Lower { $layer }
+sources-remove = Remove
+sources-xyz = XYZ tiles
+sources-name = Name
+sources-endpoint = Endpoint
+sources-url-template = URL template
+sources-layers = Layers
+sources-key-parameter = Key parameter
+sources-key = Key
+sources-add = Add source
+stac-catalog = Catalog
+stac-collection = Collection
+stac-from = From
+stac-to = To
+stac-max-clouds = Max clouds %
+stac-clip = Clip scenes to the drawn area
+stac-search = Search scenes in the drawn area
+stac-none = No scenes found.
+stac-thumbnail = Preview of { $scene }
+stac-clouds = { $percent }% clouds
+stac-segment = Segment
+stac-search-failed = Error searching the catalog: { $error }
+stac-results-unreadable = Error in receiving search results: { $error }
+stac-no-preview = Scene { $scene } has no browser-readable preview
+stac-no-bounds = Scene { $scene } has no bounding box
+stac-preview-failed = Error fetching scene preview: { $error }
+stac-preview-undecodable = Could not decode scene preview: { $error }
+stac-no-overlap = The drawn area does not overlap the scene
+stac-unencodable = Could not encode scene: { $error }
+geocoder-placeholder = Search for a place
+geocoder-search = Search
+geocoder-none = Nothing found.
+geocoder-failed = Error searching for { $query }: { $error }
+geocoder-unreadable = Error in receiving search results: { $error }
+tiles-failed = Error fetching tile { $url }: { $error }
+tiles-empty-area = The selected area is empty
+tiles-too-many = The selected area covers { $count } tiles at zoom { $zoom }, at most { $max } are allowed. Choose a lower zoom.
+
+## Upload
+
+upload-heading = Satellite image
+upload-duplicate = { $name } was already segmented on { $date }.
+upload-open-existing = Open the existing result
+upload-tab-file = Upload file
+upload-tab-map = Map
+upload-none = No file uploaded. Drop an image here, optionally with its world file, or several georeferenced chips to mosaic.
+upload-input = Image, world file or georeferenced chips
+upload-world-file-epsg = World file EPSG
+upload-clip = Clip to a saved area of interest
+upload-clip-select = Clip
+upload-large-title = Read { $files } ({ $size })?
+upload-file-count = { $count } files
+upload-large-cost = This takes about { $memory } of memory and { $time } to upload, and may freeze the tab meanwhile. Downscaling the image or cutting it into tiles before uploading is faster.
+upload-read-anyway = Read anyway
+upload-file-changed = The file changed while it was being read
+upload-unknown-format = it is not an image of a known format
+upload-no-pixels = its { $format } header gives it no pixels
+upload-unreadable-header = its { $format } header cannot be read ({ $error })
+upload-read-failed = Could not read { $name }: { $error }
+upload-refused = { $name } cannot be uploaded: { $error }.
+upload-not-georeferenced = { $name } is not georeferenced
+upload-mosaic-failed = Could not mosaic the chips: { $error }
+duration-seconds = { $count } s
+duration-minutes = { $count } min
+duration-hours = { $count } h
+
+## History
+
+decision-accepted = Accepted
+decision-rejected = Rejected
+decision-needs-edit = Needs edit
+history-review-comment = Review comment
+history-filter-name = Name
+history-filter-tag = Tag
+history-filter-server = Server
+history-filter-class = Class
+history-filter-from = From
+history-filter-to = To
+history-filter-min-share = Min. %
+history-filter-review = Review
+history-filter-any = Any
+history-filter-pending = Not reviewed
+history-empty = No results yet. Segmented images are saved here automatically.
+history-no-match = No results match the search.
+history-review-mode = Review mode
+history-export-decisions = Export decision log
+history-export-comments = Export comments
+history-compare-selected = Compare selected
+history-selected = { $count } of 2 results selected
+history-select = Select for comparison
+history-open = Open
+history-rerun = Re-run
+history-report = Report
+history-delete = Delete
+history-rerun-of = Re-run of the result of { $date } by { $server }
+history-rerun-of-removed = Re-run of a result no longer stored
+history-compare-original = Compare with the original
+history-tags = Tags
+history-tags-placeholder = comma, separated
+history-notes = Notes
+history-review-failed = Could not save the review
+history-delete-failed = Could not delete the result
+history-flag-failed = Could not save the flag
+history-tags-failed = Could not save the tags and notes
+history-tag-filter-failed = Could not filter by tag
+
+## Review
+
+review-deck = Keyboard review
+review-position = { $index } of { $count }, { $date } · { $server }
+review-progress = { $reviewed } of { $count } reviewed.
+review-key-move = previous and next
+review-key-image = image
+review-key-mask = mask
+review-key-next-pending = next not reviewed
+review-none = No results to review.
+
+## Report
+
+report-missing = There is no result { $id } in the history
+report-print = Print
+report-open = Open the result
+report-back = Back to the history
+report-subtitle = Result { $id } · { $date } · { $server }
+report-image = Image
+report-mask = Mask
+report-classes-of = Classes of { $name }
+report-mask-over-image = Mask { $name } over the image
+report-classes = Classes
+report-mask-unreadable = The mask could not be read.
+report-class = Class
+report-pixels = Pixels
+report-share = Share
+report-background = 0 (background)
+report-details = Details
+report-projection = Projection
+report-projection-value = EPSG:{ $epsg }, { $width } × { $height } pixels
+report-bounds = Bounds
+report-bounds-value = Lat { $south } to { $north }, Lon { $west } to { $east }
+report-review = Review
+report-flagged = Flagged
+report-comment-on = On the { $anchor }
+report-resolved = (resolved)
+
+## Flagged results
+
+issue-cloud-cover = Cloud cover
+issue-misclassification = Misclassification
+issue-offset = Offset
+issue-other = Other
+issue-flagged = Flagged: { $reason }
+issue-unflag = Unflag
+issue-flag-for-review = Flag for review…
+issue-comment = What is wrong, optionally
+issue-flag = Flag
+issue-report-failed = The flag was kept, but could not be reported
+issue-queue-empty = No flagged results. Results can be flagged for review from the history.
+issue-reason = Reason
+issue-any = Any ({ $count })
+issue-not-reported = Not reported yet
+issue-report-again = Report again
+issue-resolve = Resolve
+
+## Audit trail
+
+audit-empty = Nothing recorded yet.
+audit-action = Action
+audit-export-trail = Export audit trail
+audit-sent = Also sent to the audit endpoint
+audit-time = Time
+audit-user = User
+audit-subject = Subject
+audit-details = Details
+audit-anonymous = Anonymous
+audit-truncated = Showing the latest { $shown } of { $count }; the export has them all.
+audit-send-failed = Could not send it to the audit endpoint: { $error }
+audit-upload = upload
+audit-edit = edit
+audit-export = export
+audit-review = review
+audit-flag = flag
+audit-comment = comment
+audit-delete = delete
+
+## Model comparison
+
+models-from-settings = Settings
+models-legend = Models
+models-choose = Choose a model
+models-add-server = Add server
+models-run = Segment with { $count } models
+models-mask-by = Mask by { $name }
+models-difference = Difference between two models
+models-and = and
+
+## Ground truth
+
+truth-size-mismatch = The ground truth is { $truth }, but the mask is { $mask }
+truth-true-positive = Green: true positive
+truth-false-positive = Red: false positive
+truth-false-negative = Blue: false negative
+truth-wrong-class = Yellow: wrong class
+truth-unencodable = Could not encode { $name }: { $error }
+truth-file = Ground truth { $name }
+truth-errors-file = Errors of { $name }
+truth-summary = Mean IoU { $iou }, pixel accuracy { $accuracy }
+truth-masks = over { $count } masks
+truth-precision = Precision
+truth-recall = Recall
+truth-view-mask = Model mask
+truth-view-errors = Errors
+truth-shown = Shown mask
+
+## Storage
+
+storage-estimate-failed = Could not estimate storage use: { $error }
+storage-no-quota = The browser does not report its storage quota
+storage-nearly-full = Storage is nearly full ({ $usage } of { $quota } used). New results may fail to save.
+storage-freed = Deleted { $count } old results to free space
+storage-free-failed = Could not free space
+storage-write-failed = A result could not be saved:
+storage-delete-oldest = Delete oldest results
+storage-delete-failed = Could not delete a result
+storage-pruned = Pruned { $count } results from the history
+storage-prune-failed = Could not prune the history
+storage-usage = The app uses { $usage } of { $quota } available to it.
+storage-keep-all = Results are kept until deleted.
+storage-keep-newest = The newest { $count } results are kept.
+storage-keep-days = Results are kept for { $days } days.
+storage-keep-newest-days = The newest { $count } results are kept, for { $days } days.
+storage-total = { $count } results take { $size }.
+storage-policy-hint = The policy is set in the settings.
+storage-delete-selected = Delete selected ({ $count })
+storage-delete-all = Delete all
+storage-prune = Prune now
+storage-date = Date
+storage-size = Size
+
+## Workspaces
+
+workspace = Workspace
+workspace-new = New workspace
+workspace-create = Create
+workspace-delete-confirm = Delete this workspace with all its results?
+workspace-switch-failed = Could not switch workspaces
+workspace-unnamed = The workspace needs a name
+workspace-exists = There already is a workspace named { $name }
+
+## Offline
+
+offline = Offline.
+offline-back-online = Back online
+offline-queued = { $count } segmentation request(s) queued
+offline-failed = { $count } queued request(s) failed
+offline-send-now = Send now
+offline-retry-failed = Retry failed
+offline-sent-later = , they will be sent when the connection returns.
+offline-queue-hint = Images segmented now are queued and sent when the connection returns.
+offline-sent = Segmented { $sent } queued images
+offline-all-failed = All { $failed } queued images failed
+offline-some-failed = { $sent } queued images succeeded, { $failed } failed
+offline-retry-hint = They can be retried from the outbox.
+offline-send-failed = Could not send queued requests
+
+## Post-processing
+
+postprocess-none = Masks are shown as the server sends them.
+postprocess-apply = Apply this step
+postprocess-remove = Remove
+postprocess-threshold = Threshold
+postprocess-morphology = Morphology
+postprocess-remove-small-regions = Remove small regions
+postprocess-recolor = Recolor
+postprocess-opening = Opening
+postprocess-closing = Closing
+postprocess-threshold-at = Threshold at { $level }
+postprocess-morphology-with = { $op } with radius { $radius }
+postprocess-regions-under = Remove regions under { $pixels } pixels
+postprocess-class-becomes = Class { $from } becomes { $to }
+postprocess-level = Level
+postprocess-radius = Radius
+postprocess-minimum-pixels = Minimum pixels
+postprocess-from = From
+postprocess-to = To
+
+## Mask differences
+
+diff-heading = Reviewer correction
+diff-model = Model
+diff-corrected = Corrected
+diff-summary = { $changed } of { $total } pixels changed ({ $percent }%), { $added } regions added, { $removed } regions removed
+diff-class = Class
+diff-gained = Gained
+diff-lost = Lost
+diff-visualization = Changes from { $before } to { $after }, coloured as in the legend
+diff-added = Green: added
+diff-removed = Red: removed
+diff-reclassified = Yellow: reclassified
+diff-unchanged = Grey: unchanged
+diff-undecodable = Could not decode mask: { $error }
+diff-size-mismatch = Mask sizes differ: { $original } and { $edited }
+
+## Change detection
+
+change-heading = Change
+change-before = Before
+change-after = After
+change-empty = Segment an image of each date to see what changed.
+
+## Image viewer
+
+viewer-undecodable = { $name } cannot be shown: its format could not be decoded
+viewer-preview = Showing a preview while the full image is decoded
+viewer-decoding = Decoding { $name }
+viewer-position = Lat { $lat }, Lon { $lng }
+viewer-pixel = Pixel { $col }, { $row }
+viewer-hover = Hover the image for coordinates
+viewer-reset-zoom = Reset zoom
+viewer-close = Close
+
+## NDVI
+
+ndvi-red-band = Red band
+ndvi-nir-band = NIR band
+ndvi-preview = NDVI from red (low) through yellow to green (high)
+ndvi-threshold = Threshold { $threshold }
+ndvi-mask = Vegetation mask: white above the threshold, black below
+ndvi-vegetated = { $percent }% of pixels above the threshold
+ndvi-send-band = Send NDVI band
+ndvi-send-mask = Send vegetation mask
+ndvi-send-image = Send image only
+
+## Projects
+
+project-save = Save project
+project-open = Open project
+project-save-failed = Could not save the project
+project-unreadable = Could not read the project file: { $error }
+project-invalid = Not a valid project file: { $error }
+project-too-new = The project was saved by a newer version of the app (format { $version })
+
+## Sessions
+
+session-unsaved = Unsaved changes
+session-saving = Saving…
+session-saved = Saved at { $time }
+session-failed = Autosave failed: { $reason }
+session-unreadable = Could not read the autosaved session
+session-found = An unsaved session was found. Restore it?
+session-found-for = An unsaved session was found for { $image }. Restore it?
+session-restore = Restore
+session-discard = Discard
+
+## Gallery
+
+gallery-newest = Newest first
+gallery-oldest = Oldest first
+gallery-image-name = Image name
+gallery-server = Server
+gallery-empty = No results yet. Segmented images are shown here automatically.
+gallery-count = { $count } results
+gallery-mask-of = Mask of { $name }
+
+## Areas of interest
+
+aoi-none = No saved areas of interest.
+aoi-vertices = { $count } vertices
+aoi-remove = Remove
+aoi-name = Name of the drawn area
+aoi-save = Save AOI
+aoi-save-failed = Could not save the areas of interest
+
+## Re-running
+
+rerun-with = Re-run with
+rerun-all = Re-run all shown ({ $count })
+rerun-stop = Stop
+rerun-progress = Re-running { $current } of { $total }
+rerun-progress-failed = Re-running { $current } of { $total }, { $failed } failed
+rerun-failed = { $failed } of { $total } results could not be re-run with { $model }
+
+## Links
+
+permalink-copy = Copy link
+permalink-copied = Link copied
+permalink-copy-failed = Could not copy the link
+
+## Notifications
+
+notify-info = Info
+notify-success = Done
+notify-warning = Warning
+notify-error = Error
+notify-close = Close
+notify-show-details = Show details
+notify-hide-details = Hide details
+
+## Review sessions
+
+collab-heading = Review session
+collab-join = Join
+collab-leave = Leave
+collab-connecting = Connecting…
+collab-others = { $count } others here
+collab-lost = Disconnected: { $reason }
+collab-closed = The session endpoint closed the connection
+collab-name = Your name
+collab-name-label = Your name in the session
+collab-anonymous = Anonymous
+collab-comment = Comment for the others
+collab-share-pointer = Share my pointer over the mask
+collab-not-stored = The result is not in the history
+collab-save-failed = Could not save the review
+
+## Comments
+
+comments-point = point { $x }, { $y }
+comments-area = area { $left }–{ $right }, { $top }–{ $bottom }
+comments-hint = Click a point or drag over an area to comment on it
+comments-empty = No comments yet. Click a point or drag over an area of the image to start a thread.
+comments-new-thread = New thread on the { $anchor }
+comments-question = What is wrong here?
+comments-comment = Comment
+comments-cancel = Cancel
+comments-reply = Reply
+comments-resolve = Resolve
+comments-reopen = Reopen
+comments-resolved = Resolved
+comments-show-resolved = Show { $count } resolved
+comments-hide-resolved = Hide { $count } resolved
+comments-save-failed = Could not save the comment
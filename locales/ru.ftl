@@ -0,0 +1,644 @@
+# Strings of the user interface in Russian.
+
+language = Язык
+theme = Тема
+theme-light = Светлая
+theme-dark = Тёмная
+theme-organization = Организации
+install-app = Установить
+
+## Screens
+
+tabs = Экраны
+tab-segment = Сегментация
+tab-history = История
+tab-compare = Сравнение
+tab-settings = Настройки
+tab-batch = Пакет
+tab-flagged = Отмеченные
+tab-models = Модели
+heading-segments = Сегменты
+heading-compare = Сравнение двух дат
+heading-history = История
+heading-gallery = Галерея
+heading-storage = Хранилище
+heading-audit = Журнал действий
+heading-settings = Настройки
+heading-postprocess = Постобработка масок
+heading-batch = Пакетная обработка
+heading-flagged = Отмечено для проверки
+heading-models = Сравнение моделей
+heading-report = Отчёт
+heading-comments = Комментарии
+
+## Segmentation
+
+status-reading = Чтение файлов
+status-uploading = Отправка
+status-processing = Обработка
+status-done = Готово
+status-failed = Ошибка
+status-cancelled = Отменено
+status-elapsed = { $elapsed } из { $timeout }
+status-still-working = Всё ещё идёт работа: большие изображения могут обрабатываться несколько минут.
+action-cancel = Отменить
+action-close = Закрыть
+action-segment-again = Сегментировать снова
+segments-processing = Обработка изображения...
+segments-no-image = Изображение ещё не загружено...
+segments-cancelled = Сегментация отменена.
+segments-failed = Не удалось сегментировать изображение.
+mask-alt = Маска сегментации { $name }: { $classes }
+mask-alt-class = класс { $class } занимает { $percent }%
+mask-alt-undecodable = Маска сегментации { $name }, которую не удалось прочитать
+mask-undecodable = Сервер вернул маску, которую не удалось прочитать.
+mask-undecodable-type = Тип содержимого
+mask-undecodable-size = Размер
+mask-undecodable-bytes = Первые байты
+mask-undecodable-download = Скачать как получено
+mask-download-geojson = Скачать в GeoJSON
+mask-geojson-failed = Не удалось экспортировать маску в GeoJSON.
+segments-queued = Нет подключения к сети. Изображение поставлено в очередь и будет
+    сегментировано, когда подключение восстановится; результат появится в истории.
+segments-queue-failed = Нет подключения к сети, и изображение не удалось поставить в очередь: { $reason }
+permalink-failed = Не удалось открыть общий результат.
+
+## Keyboard shortcuts
+
+shortcuts = Сочетания клавиш
+shortcut-help = Показать или скрыть этот список
+shortcut-open = Открыть файл изображения
+shortcut-toggle-mask = Показать или скрыть маску на карте
+shortcut-mask-opacity-up = Сделать маску непрозрачнее
+shortcut-mask-opacity-down = Сделать маску прозрачнее
+shortcut-tab-segment = Перейти к сегментации
+shortcut-tab-history = Перейти к истории
+shortcut-tab-compare = Перейти к сравнению
+shortcut-tab-settings = Перейти к настройкам
+shortcut-tab-batch = Перейти к пакетной обработке
+shortcut-tab-flagged = Перейти к результатам, отмеченным для проверки
+shortcut-tab-models = Перейти к сравнению моделей
+
+## Errors
+
+error-network = Не удалось связаться с { $url }: { $message }
+error-timeout = { $url } слишком долго не отвечает
+error-corrupted = Изображение пришло на { $url } искажённым, скорее всего его обрезал прокси по пути. Обычно помогает отправить его снова.
+error-auth = { $url } отклонил токен доступа (статус { $status })
+error-http = { $url } ответил статусом { $status }: { $message }
+error-decode = Не удалось прочитать ответ сервера: { $message }
+error-cancelled = Запрос был отменён.
+error-hint-auth = Обновите токен доступа в настройках и войдите снова.
+error-hint-address = Проверьте адрес сервера в настройках.
+error-hint-mixed-content = Эта страница открыта по HTTPS, поэтому браузер блокирует запросы к
+    адресу HTTP { $url }. Настройте для сервера HTTPS и укажите в настройках его адрес https://.
+error-hint-cors = Сервер отвечает, но браузер скрывает его ответы от этой страницы: сервер должен
+    разрешить источник { $origin } в заголовках CORS: Access-Control-Allow-Origin, а также
+    Access-Control-Allow-Headers для Authorization, Accept, X-Request-Id и X-Image-SHA256.
+error-result-unopened = Не удалось открыть результат
+error-result-missing = В истории нет результата { $id }
+error-config-ignored = Часть конфигурации проигнорирована
+error-show-more = Подробнее
+action-retry = Повторить
+action-open-settings = Открыть настройки
+
+## Settings
+
+settings-server-url = Адрес сервера
+settings-token = Токен доступа
+settings-opacity = Непрозрачность маски по умолчанию
+settings-keep-results = Хранить последних результатов
+settings-keep-all = все
+settings-keep-days = Хранить результаты дней
+settings-keep-forever = всегда
+settings-decoded-cache = Память для декодированных масок, МБ
+settings-parallel-requests = Одновременных запросов
+settings-large-file = Подтверждать файлы больше (МБ)
+settings-telemetry = Отправлять анонимную статистику: размеры изображений, время ответа и ошибки
+settings-reset = Сбросить настройки
+
+## Memory
+
+memory-usage = Приложение занимает { $heap } памяти, из них { $decoded } — декодированные маски, и держит наготове { $urls } изображений.
+memory-warning = Приложение занимает { $heap } памяти. Браузер может закрыть вкладку, если память закончится.
+memory-free = Освободить память
+
+## Debug log
+
+debug-log-copy-failed = Не удалось скопировать журнал
+debug-log = Журнал отладки
+debug-log-level = Наименьший показываемый уровень
+debug-log-copy = Копировать
+debug-log-download = Скачать
+debug-log-empty = Пока ничего не записано.
+
+## Crashes
+
+region-image = Изображение
+crash-title = Что-то пошло не так, и приложение остановилось.
+crash-title-region = Что-то пошло не так в разделе «{ $region }», и приложение остановилось.
+crash-kept = Работа до последнего автосохранения сохранена и будет предложена для восстановления после перезагрузки.
+crash-reported = Отчёт о сбое отправлен, без ваших изображений и их названий.
+crash-details = Подробности
+crash-reload = Перезагрузить
+crash-download-log = Скачать журнал
+
+## Batch processing
+
+batch-add = Добавить изображения
+batch-pause = Пауза
+batch-resume = Продолжить
+batch-retry-failed = Повторить неудавшиеся
+batch-export = Экспортировать всё
+batch-remove-done = Убрать готовые
+batch-limit = Запросов одновременно
+batch-empty = Добавьте изображения, чтобы сегментировать их по нескольку за раз, сколько бы их ни было. Обработка продолжается, пока открыты другие вкладки приложения, если открыта эта страница.
+batch-paused = Пауза: уже отправленные изображения будут обработаны, новые не отправляются.
+batch-connection-lost = Сервер недоступен, поэтому обработка приостановлена. Она продолжится сама, когда связь восстановится.
+batch-history-limit = История хранит только { $count } последних результатов, поэтому более старые результаты этого пакета будут из неё удалены. Экспортируйте их заранее.
+batch-progress = Готово: { $done }, с ошибкой: { $failed }, осталось: { $waiting }.
+batch-remaining = Осталось около { $time }.
+batch-image = Изображение
+batch-status = Состояние
+batch-time = Время
+batch-result = Результат
+batch-waiting = Ожидает
+batch-running = Сегментируется
+batch-done = Готово
+batch-failed = Ошибка
+batch-open = Открыть
+batch-export-failed = Не удалось экспортировать пакет
+batch-export-incomplete = В экспорте не хватает некоторых результатов
+batch-export-missing = Результатов удалено из истории после сегментации: { $count }.
+batch-add-truths = Добавить эталонные маски
+batch-truths = Эталонных масок: { $count }. Они сопоставляются с изображениями по имени без расширения. Маски изображений, сегментированных с этого момента, сравниваются с ними.
+batch-iou = Средний IoU { $iou }
+batch-evaluation = Оценка по эталонным маскам
+batch-mask-of = Маска { $name }
+batch-unsegmentable = Его нельзя сегментировать: { $reason }.
+batch-truth-unreadable = Не удалось прочитать эталонную маску
+
+## Roles
+
+role-viewer = наблюдатель
+role-editor = редактор
+role-admin = администратор
+role-screen-unavailable = Этот экран недоступен для роли «{ $role }».
+role-viewer-results = Результаты можно посмотреть в истории, не изменяя их.
+
+## Map
+
+map-basemap = Подложка
+map-aoi-imagery = Снимки для области
+map-draw-box = Нарисовать прямоугольник
+map-draw-polygon = Нарисовать многоугольник
+map-finish = Завершить
+map-zoom = Масштаб
+map-segment-area = Сегментировать область
+map-hover = Наведите на карту, чтобы увидеть координаты
+map-layers = Слои
+map-truth-layer = Слой эталонной разметки
+map-error-map = Карта ошибок
+map-saved-aois = Сохранённые области интереса
+map-use-aoi = Выбрать
+map-stac = Сцены STAC
+map-sources = Источники снимков
+layer-basemap = Подложка
+layer-image = Спутниковый снимок
+layer-mask = Маска
+layer-ground-truth = Эталонная разметка
+layer-aoi = Область интереса
+layer-show = Показать слой «{ $layer }»
+layer-opacity = Непрозрачность слоя «{ $layer }»
+layer-raise = Поднять слой «{ $layer }»
+layer-lower = Опустить слой «{ $layer }»
+sources-remove = Удалить
+sources-xyz = Тайлы XYZ
+sources-name = Название
+sources-endpoint = Адрес сервиса
+sources-url-template = Шаблон URL
+sources-layers = Слои
+sources-key-parameter = Параметр ключа
+sources-key = Ключ
+sources-add = Добавить источник
+stac-catalog = Каталог
+stac-collection = Коллекция
+stac-from = С
+stac-to = По
+stac-max-clouds = Облачность не более, %
+stac-clip = Обрезать сцены по нарисованной области
+stac-search = Искать сцены в нарисованной области
+stac-none = Сцены не найдены.
+stac-thumbnail = Превью сцены { $scene }
+stac-clouds = облачность { $percent }%
+stac-segment = Сегментировать
+stac-search-failed = Ошибка поиска в каталоге: { $error }
+stac-results-unreadable = Ошибка при получении результатов поиска: { $error }
+stac-no-preview = У сцены { $scene } нет превью, которое может показать браузер
+stac-no-bounds = У сцены { $scene } нет охвата
+stac-preview-failed = Ошибка загрузки превью сцены: { $error }
+stac-preview-undecodable = Не удалось декодировать превью сцены: { $error }
+stac-no-overlap = Нарисованная область не пересекается со сценой
+stac-unencodable = Не удалось закодировать сцену: { $error }
+geocoder-placeholder = Найти место
+geocoder-search = Найти
+geocoder-none = Ничего не найдено.
+geocoder-failed = Ошибка поиска «{ $query }»: { $error }
+geocoder-unreadable = Ошибка при получении результатов поиска: { $error }
+tiles-failed = Ошибка загрузки тайла { $url }: { $error }
+tiles-empty-area = Выбранная область пуста
+tiles-too-many = Выбранная область занимает { $count } тайлов на масштабе { $zoom }, а допускается не больше { $max }. Выберите масштаб меньше.
+
+## Upload
+
+upload-heading = Спутниковый снимок
+upload-duplicate = { $name } уже сегментирован { $date }.
+upload-open-existing = Открыть готовый результат
+upload-tab-file = Загрузить файл
+upload-tab-map = Карта
+upload-none = Файл не загружен. Перетащите сюда снимок, при желании вместе с его файлом привязки, или несколько привязанных фрагментов, чтобы сшить их.
+upload-input = Снимок, файл привязки или привязанные фрагменты
+upload-world-file-epsg = EPSG файла привязки
+upload-clip = Обрезать по сохранённой области интереса
+upload-clip-select = Обрезать
+upload-large-title = Прочитать { $files } ({ $size })?
+upload-file-count = файлы: { $count }
+upload-large-cost = Это займёт около { $memory } памяти и { $time } на загрузку, а вкладка может на это время зависнуть. Быстрее уменьшить снимок или разрезать его на тайлы перед загрузкой.
+upload-read-anyway = Всё равно прочитать
+upload-file-changed = Файл изменился, пока его читали
+upload-unknown-format = это не изображение известного формата
+upload-no-pixels = по его заголовку { $format } в нём нет пикселей
+upload-unreadable-header = его заголовок { $format } не читается ({ $error })
+upload-read-failed = Не удалось прочитать { $name }: { $error }
+upload-refused = { $name } нельзя загрузить: { $error }.
+upload-not-georeferenced = У { $name } нет привязки
+upload-mosaic-failed = Не удалось сшить фрагменты: { $error }
+duration-seconds = { $count } с
+duration-minutes = { $count } мин
+duration-hours = { $count } ч
+
+## History
+
+decision-accepted = Принят
+decision-rejected = Отклонён
+decision-needs-edit = Нужна правка
+history-review-comment = Комментарий к проверке
+history-filter-name = Имя
+history-filter-tag = Метка
+history-filter-server = Сервер
+history-filter-class = Класс
+history-filter-from = С
+history-filter-to = По
+history-filter-min-share = Мин. %
+history-filter-review = Проверка
+history-filter-any = Любая
+history-filter-pending = Не проверен
+history-empty = Результатов пока нет. Сегментированные снимки сохраняются здесь автоматически.
+history-no-match = Под условия поиска ничего не подходит.
+history-review-mode = Режим проверки
+history-export-decisions = Выгрузить журнал решений
+history-export-comments = Выгрузить комментарии
+history-compare-selected = Сравнить выбранные
+history-selected = Выбрано результатов: { $count } из 2
+history-select = Выбрать для сравнения
+history-open = Открыть
+history-rerun = Перезапустить
+history-report = Отчёт
+history-delete = Удалить
+history-rerun-of = Перезапуск результата от { $date }, полученного на { $server }
+history-rerun-of-removed = Перезапуск результата, которого больше нет
+history-compare-original = Сравнить с исходным
+history-tags = Метки
+history-tags-placeholder = через запятую
+history-notes = Заметки
+history-review-failed = Не удалось сохранить проверку
+history-delete-failed = Не удалось удалить результат
+history-flag-failed = Не удалось сохранить отметку
+history-tags-failed = Не удалось сохранить метки и заметки
+history-tag-filter-failed = Не удалось отфильтровать по метке
+
+## Review
+
+review-deck = Проверка с клавиатуры
+review-position = { $index } из { $count }, { $date } · { $server }
+review-progress = Проверено { $reviewed } из { $count }.
+review-key-move = предыдущий и следующий
+review-key-image = снимок
+review-key-mask = маска
+review-key-next-pending = следующий непроверенный
+review-none = Нет результатов для проверки.
+
+## Report
+
+report-missing = В истории нет результата { $id }
+report-print = Печать
+report-open = Открыть результат
+report-back = Назад к истории
+report-subtitle = Результат { $id } · { $date } · { $server }
+report-image = Снимок
+report-mask = Маска
+report-classes-of = Классы маски { $name }
+report-mask-over-image = Маска { $name } поверх снимка
+report-classes = Классы
+report-mask-unreadable = Не удалось прочитать маску.
+report-class = Класс
+report-pixels = Пикселей
+report-share = Доля
+report-background = 0 (фон)
+report-details = Сведения
+report-projection = Проекция
+report-projection-value = EPSG:{ $epsg }, { $width } × { $height } пикселей
+report-bounds = Охват
+report-bounds-value = Широта от { $south } до { $north }, долгота от { $west } до { $east }
+report-review = Проверка
+report-flagged = Отмечен
+report-comment-on = К объекту: { $anchor }
+report-resolved = (решён)
+
+## Flagged results
+
+issue-cloud-cover = Облачность
+issue-misclassification = Неверный класс
+issue-offset = Смещение
+issue-other = Другое
+issue-flagged = Отмечен: { $reason }
+issue-unflag = Снять отметку
+issue-flag-for-review = Отметить для разбора…
+issue-comment = Что не так (необязательно)
+issue-flag = Отметить
+issue-report-failed = Отметка сохранена, но отправить её не удалось
+issue-queue-empty = Отмеченных результатов нет. Отметить результат для разбора можно в истории.
+issue-reason = Причина
+issue-any = Любая ({ $count })
+issue-not-reported = Ещё не отправлено
+issue-report-again = Отправить снова
+issue-resolve = Решено
+
+## Audit trail
+
+audit-empty = Пока ничего не записано.
+audit-action = Действие
+audit-export-trail = Выгрузить журнал действий
+audit-sent = Также отправляется в журнал аудита на сервере
+audit-time = Время
+audit-user = Пользователь
+audit-subject = Объект
+audit-details = Подробности
+audit-anonymous = Аноним
+audit-truncated = Показаны последние { $shown } из { $count }; в выгрузке есть все.
+audit-send-failed = Не удалось отправить в журнал аудита: { $error }
+audit-upload = загрузка
+audit-edit = правка
+audit-export = выгрузка
+audit-review = проверка
+audit-flag = отметка
+audit-comment = комментарий
+audit-delete = удаление
+
+## Model comparison
+
+models-from-settings = Настройки
+models-legend = Модели
+models-choose = Выберите модель
+models-add-server = Добавить сервер
+models-run = Сегментировать моделями: { $count }
+models-mask-by = Маска модели { $name }
+models-difference = Разница между двумя моделями
+models-and = и
+
+## Ground truth
+
+truth-size-mismatch = Эталонная разметка размером { $truth }, а маска — { $mask }
+truth-true-positive = Зелёный: верно найдено
+truth-false-positive = Красный: ложное срабатывание
+truth-false-negative = Синий: пропуск
+truth-wrong-class = Жёлтый: не тот класс
+truth-unencodable = Не удалось закодировать { $name }: { $error }
+truth-file = Эталонная разметка { $name }
+truth-errors-file = Ошибки { $name }
+truth-summary = Средний IoU { $iou }, точность по пикселям { $accuracy }
+truth-masks = по { $count } маскам
+truth-precision = Точность
+truth-recall = Полнота
+truth-view-mask = Маска модели
+truth-view-errors = Ошибки
+truth-shown = Показываемая маска
+
+## Storage
+
+storage-estimate-failed = Не удалось оценить занятое место: { $error }
+storage-no-quota = Браузер не сообщает, сколько места доступно
+storage-nearly-full = Место почти закончилось (занято { $usage } из { $quota }). Новые результаты могут не сохраниться.
+storage-freed = Удалено старых результатов, чтобы освободить место: { $count }
+storage-free-failed = Не удалось освободить место
+storage-write-failed = Не удалось сохранить результат:
+storage-delete-oldest = Удалить самые старые результаты
+storage-delete-failed = Не удалось удалить результат
+storage-pruned = Из истории удалено результатов: { $count }
+storage-prune-failed = Не удалось почистить историю
+storage-usage = Приложение занимает { $usage } из доступных ему { $quota }.
+storage-keep-all = Результаты хранятся, пока их не удалят.
+storage-keep-newest = Хранятся последние результаты: { $count }.
+storage-keep-days = Результаты хранятся { $days } дн.
+storage-keep-newest-days = Хранятся последние результаты ({ $count }), не дольше { $days } дн.
+storage-total = Результатов: { $count }, занимают { $size }.
+storage-policy-hint = Правила хранения задаются в настройках.
+storage-delete-selected = Удалить выбранные ({ $count })
+storage-delete-all = Удалить все
+storage-prune = Почистить сейчас
+storage-date = Дата
+storage-size = Размер
+
+## Workspaces
+
+workspace = Рабочее пространство
+workspace-new = Новое пространство
+workspace-create = Создать
+workspace-delete-confirm = Удалить это рабочее пространство со всеми его результатами?
+workspace-switch-failed = Не удалось сменить рабочее пространство
+workspace-unnamed = У рабочего пространства должно быть имя
+workspace-exists = Рабочее пространство { $name } уже есть
+
+## Offline
+
+offline = Нет сети.
+offline-back-online = Сеть снова доступна
+offline-queued = Запросов на сегментацию в очереди: { $count }
+offline-failed = Не удалось выполнить запросов из очереди: { $count }
+offline-send-now = Отправить сейчас
+offline-retry-failed = Повторить неудавшиеся
+offline-sent-later = , они будут отправлены, когда появится сеть.
+offline-queue-hint = Сегментируемые сейчас снимки ставятся в очередь и отправляются, когда появится сеть.
+offline-sent = Сегментировано снимков из очереди: { $sent }
+offline-all-failed = Не удалось сегментировать ни один из снимков в очереди ({ $failed })
+offline-some-failed = Снимков из очереди сегментировано: { $sent }, не удалось: { $failed }
+offline-retry-hint = Их можно повторить из очереди.
+offline-send-failed = Не удалось отправить запросы из очереди
+
+## Post-processing
+
+postprocess-none = Маски показываются такими, какими их присылает сервер.
+postprocess-apply = Применять этот шаг
+postprocess-remove = Удалить
+postprocess-threshold = Порог
+postprocess-morphology = Морфология
+postprocess-remove-small-regions = Удаление мелких областей
+postprocess-recolor = Замена класса
+postprocess-opening = Размыкание
+postprocess-closing = Замыкание
+postprocess-threshold-at = Порог { $level }
+postprocess-morphology-with = { $op } с радиусом { $radius }
+postprocess-regions-under = Удалить области меньше { $pixels } пикселей
+postprocess-class-becomes = Класс { $from } становится { $to }
+postprocess-level = Уровень
+postprocess-radius = Радиус
+postprocess-minimum-pixels = Минимум пикселей
+postprocess-from = Из
+postprocess-to = В
+
+## Mask differences
+
+diff-heading = Исправление проверяющего
+diff-model = Модель
+diff-corrected = Исправлено
+diff-summary = Изменилось пикселей: { $changed } из { $total } ({ $percent }%), добавлено областей: { $added }, удалено: { $removed }
+diff-class = Класс
+diff-gained = Прибавилось
+diff-lost = Убыло
+diff-visualization = Изменения от «{ $before }» к «{ $after }», раскрашенные как в легенде
+diff-added = Зелёный: добавлено
+diff-removed = Красный: удалено
+diff-reclassified = Жёлтый: другой класс
+diff-unchanged = Серый: без изменений
+diff-undecodable = Не удалось декодировать маску: { $error }
+diff-size-mismatch = Размеры масок различаются: { $original } и { $edited }
+
+## Change detection
+
+change-heading = Изменения
+change-before = До
+change-after = После
+change-empty = Сегментируйте снимок каждой даты, чтобы увидеть, что изменилось.
+
+## Image viewer
+
+viewer-undecodable = { $name } нельзя показать: его формат не удалось декодировать
+viewer-preview = Пока полный снимок декодируется, показано превью
+viewer-decoding = Декодирование { $name }
+viewer-position = Шир. { $lat }, долг. { $lng }
+viewer-pixel = Пиксель { $col }, { $row }
+viewer-hover = Наведите курсор на снимок, чтобы увидеть координаты
+viewer-reset-zoom = Сбросить масштаб
+viewer-close = Закрыть
+
+## NDVI
+
+ndvi-red-band = Красный канал
+ndvi-nir-band = Канал БИК
+ndvi-preview = NDVI от красного (низкий) через жёлтый к зелёному (высокий)
+ndvi-threshold = Порог { $threshold }
+ndvi-mask = Маска растительности: белое выше порога, чёрное ниже
+ndvi-vegetated = Пикселей выше порога: { $percent }%
+ndvi-send-band = Отправить канал NDVI
+ndvi-send-mask = Отправить маску растительности
+ndvi-send-image = Отправить только снимок
+
+## Projects
+
+project-save = Сохранить проект
+project-open = Открыть проект
+project-save-failed = Не удалось сохранить проект
+project-unreadable = Не удалось прочитать файл проекта: { $error }
+project-invalid = Это не файл проекта: { $error }
+project-too-new = Проект сохранён более новой версией приложения (формат { $version })
+
+## Sessions
+
+session-unsaved = Есть несохранённые изменения
+session-saving = Сохранение…
+session-saved = Сохранено в { $time }
+session-failed = Не удалось автосохранение: { $reason }
+session-unreadable = Не удалось прочитать автосохранённый сеанс
+session-found = Найден несохранённый сеанс. Восстановить его?
+session-found-for = Найден несохранённый сеанс для { $image }. Восстановить его?
+session-restore = Восстановить
+session-discard = Отбросить
+
+## Gallery
+
+gallery-newest = Сначала новые
+gallery-oldest = Сначала старые
+gallery-image-name = Имя снимка
+gallery-server = Сервер
+gallery-empty = Результатов пока нет. Сегментированные снимки появляются здесь автоматически.
+gallery-count = Результатов: { $count }
+gallery-mask-of = Маска { $name }
+
+## Areas of interest
+
+aoi-none = Сохранённых областей интереса нет.
+aoi-vertices = Вершин: { $count }
+aoi-remove = Удалить
+aoi-name = Название нарисованной области
+aoi-save = Сохранить область
+aoi-save-failed = Не удалось сохранить области интереса
+
+## Re-running
+
+rerun-with = Перезапустить с
+rerun-all = Перезапустить все показанные ({ $count })
+rerun-stop = Остановить
+rerun-progress = Перезапуск { $current } из { $total }
+rerun-progress-failed = Перезапуск { $current } из { $total }, не удалось: { $failed }
+rerun-failed = Не удалось перезапустить с { $model } результатов: { $failed } из { $total }
+
+## Links
+
+permalink-copy = Копировать ссылку
+permalink-copied = Ссылка скопирована
+permalink-copy-failed = Не удалось скопировать ссылку
+
+## Notifications
+
+notify-info = Сведения
+notify-success = Готово
+notify-warning = Предупреждение
+notify-error = Ошибка
+notify-close = Закрыть
+notify-show-details = Показать подробности
+notify-hide-details = Скрыть подробности
+
+## Review sessions
+
+collab-heading = Совместная проверка
+collab-join = Присоединиться
+collab-leave = Выйти
+collab-connecting = Подключение…
+collab-others = Других участников: { $count }
+collab-lost = Соединение потеряно: { $reason }
+collab-closed = Сервер сеансов закрыл соединение
+collab-name = Ваше имя
+collab-name-label = Ваше имя в сеансе
+collab-anonymous = Аноним
+collab-comment = Комментарий для остальных
+collab-share-pointer = Показывать другим мой указатель над маской
+collab-not-stored = Результата нет в истории
+collab-save-failed = Не удалось сохранить проверку
+
+## Comments
+
+comments-point = точка { $x }, { $y }
+comments-area = область { $left }–{ $right }, { $top }–{ $bottom }
+comments-hint = Щёлкните точку или выделите область, чтобы прокомментировать её
+comments-empty = Комментариев пока нет. Щёлкните точку или выделите область снимка, чтобы начать обсуждение.
+comments-new-thread = Новое обсуждение: { $anchor }
+comments-question = Что здесь не так?
+comments-comment = Комментировать
+comments-cancel = Отмена
+comments-reply = Ответить
+comments-resolve = Закрыть обсуждение
+comments-reopen = Открыть снова
+comments-resolved = Закрыто
+comments-show-resolved = Показать закрытые ({ $count })
+comments-hide-resolved = Скрыть закрытые ({ $count })
+comments-save-failed = Не удалось сохранить комментарий
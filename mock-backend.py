@@ -4,11 +4,60 @@ iVBORw0KGgoAAAANSUhEUgAABAAAAAQACAQAAADVFOMIAAAA0GVYSWZJSSoACAAAAAoAAAEEAAEAAAAA
 '''.strip()
 
 app = flask.Flask(__name__)
+import base64
+import hashlib
 import time
+import uuid
+
+# Uploaded images and their masks, by result id, so results can be shared as links.
+results = {}
+
 @app.route('/segment', methods=['POST'])
 def analyze():
     time.sleep(3)
-    return flask.jsonify({'data': img, 'file_name': 'mock-data.png', 'file_type': 'image/png'})
+    mask = {'data': img, 'file_name': 'mock-data.png', 'file_type': 'image/png'}
+    upload = flask.request.files.get('f[]')
+    # Results are stored under the id the frontend gave the request, so that a session
+    # replayed with ?replay= gets the same links every time.
+    request_id = flask.request.headers.get('X-Request-Id')
+    app.logger.info('Segmenting request %s', request_id)
+    result_id = None
+    digest = None
+    if upload is not None:
+        data = upload.read()
+        digest = hashlib.sha256(data).hexdigest()
+        sent = flask.request.headers.get('X-Image-SHA256')
+        if sent is not None and sent.lower() != digest:
+            return flask.jsonify({
+                'error': 'The image arrived altered',
+                'hint': f'Its SHA-256 is {digest}, not {sent}',
+            }), 422
+        result_id = request_id or uuid.uuid4().hex
+        results[result_id] = {
+            'image': {
+                'data': base64.b64encode(data).decode(),
+                'file_name': upload.filename,
+                'file_type': upload.mimetype,
+            },
+            'mask': mask,
+        }
+    # Frontends that accept it get the mask as it is, without base64 in JSON.
+    if flask.request.accept_mimetypes.best_match(['image/png', 'application/json']) == 'image/png':
+        response = flask.Response(base64.b64decode(img), mimetype='image/png')
+        response.headers['X-File-Name'] = mask['file_name']
+        if result_id is not None:
+            response.headers['X-Result-Id'] = result_id
+    else:
+        response = flask.jsonify({**mask, 'result_id': result_id})
+    if digest is not None:
+        response.headers['X-Image-SHA256'] = digest
+    return response
+
+@app.route('/results/<result_id>')
+def result(result_id):
+    if result_id not in results:
+        flask.abort(404)
+    return flask.jsonify(results[result_id])
 
 if __name__ == '__main__':
     app.run('0.0.0.0', 5000)
\ No newline at end of file
@@ -0,0 +1,95 @@
+// Keeps the app usable offline: the page, the wasm bundle and the map and UI libraries are
+// cached on install and as they are fetched, and served from the cache when the network is
+// unavailable.
+// Requests to the segmentation server are never cached; the app queues them itself.
+
+const CACHE = 'map-segmentation-v2';
+
+// Cached on install, so that the app installed in its own window starts offline even
+// before it was used online again.
+const SHELL = [
+  './',
+  './index.html',
+  './manifest.webmanifest',
+  './icon-192.png',
+  './icon-512.png',
+  './icon-maskable-512.png',
+];
+
+// The wasm bundles, scripts and stylesheets the page links to, whose hashed names are only
+// known from the built page.
+function linkedAssets(html) {
+  const urls = [...html.matchAll(/(?:href|src)="([^"#]+\.(?:js|wasm|css))"/g)].map((match) => match[1]);
+  return [...new Set(urls)].filter((url) => isStaticAsset(new URL(url, self.location.href)));
+}
+
+self.addEventListener('install', (event) => {
+  event.waitUntil(
+    caches.open(CACHE).then(async (cache) => {
+      await cache.addAll(SHELL);
+      const page = await cache.match('./index.html');
+      const assets = linkedAssets(await page.text());
+      // Libraries from CDNs are best effort: they are cached on first use otherwise.
+      await Promise.all(assets.map((url) => cache.add(url).catch(() => {})));
+    }),
+  );
+  self.skipWaiting();
+});
+
+self.addEventListener('activate', (event) => {
+  event.waitUntil(
+    caches.keys()
+      .then((keys) => Promise.all(keys.filter((key) => key !== CACHE).map((key) => caches.delete(key))))
+      .then(() => self.clients.claim()),
+  );
+});
+
+function isStaticAsset(url) {
+  return url.origin === self.location.origin
+    || url.hostname === 'cdn.jsdelivr.net'
+    || url.hostname === 'unpkg.com';
+}
+
+self.addEventListener('fetch', (event) => {
+  const request = event.request;
+  const url = new URL(request.url);
+  if (request.method !== 'GET' || !isStaticAsset(url)) {
+    return;
+  }
+  if (request.mode === 'navigate') {
+    // The page itself is fetched fresh when possible so new builds are picked up.
+    event.respondWith(
+      fetch(request)
+        .then((response) => {
+          const copy = response.clone();
+          caches.open(CACHE).then((cache) => cache.put('./index.html', copy));
+          return response;
+        })
+        .catch(() => caches.match('./index.html')),
+    );
+    return;
+  }
+  // The deployment's configuration is fetched fresh too, falling back to the cached copy.
+  if (url.origin === self.location.origin && url.pathname.endsWith('/config.json')) {
+    event.respondWith(
+      fetch(request)
+        .then((response) => {
+          const copy = response.clone();
+          caches.open(CACHE).then((cache) => cache.put(request, copy));
+          return response;
+        })
+        .catch(() => caches.match(request)),
+    );
+    return;
+  }
+  // Everything else has hashed or versioned names, so a cached copy never goes stale.
+  event.respondWith(
+    caches.match(request).then((cached) => cached || fetch(request).then((response) => {
+      if (response.ok || response.type === 'opaque') {
+        const copy = response.clone();
+        caches.open(CACHE).then((cache) => cache.put(request, copy));
+      }
+      return response;
+    })),
+  );
+});
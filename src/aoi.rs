@@ -0,0 +1,216 @@
+//! Named areas of interest kept in local storage as a GeoJSON feature collection, so
+//! recurring AOIs can be redrawn for fresh imagery or used to clip uploads.
+
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use shadow_clone::shadow_clone;
+use std::rc::Rc;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    geo::LatLng,
+    i18n::{self, use_language},
+    notify, workspace,
+};
+
+pub const STORAGE_KEY: &str = "saved-aois";
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(from = "Feature", into = "Feature")]
+pub struct SavedAoi {
+    pub name: String,
+    /// Polygon vertices, without repeating the first one at the end.
+    pub points: Vec<LatLng>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+struct FeatureCollection {
+    features: Vec<SavedAoi>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+struct Feature {
+    properties: Properties,
+    geometry: Polygon,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Properties {
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+struct Polygon {
+    /// Linear rings of `[longitude, latitude]` positions, the first being the exterior.
+    coordinates: Vec<Vec<[f64; 2]>>,
+}
+
+impl From<Feature> for SavedAoi {
+    fn from(feature: Feature) -> Self {
+        let mut ring = feature
+            .geometry
+            .coordinates
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        if ring.len() > 1 && ring.first() == ring.last() {
+            ring.pop();
+        }
+        SavedAoi {
+            name: feature.properties.name,
+            points: ring
+                .into_iter()
+                .map(|[lng, lat]| LatLng { lat, lng })
+                .collect(),
+        }
+    }
+}
+
+impl From<SavedAoi> for Feature {
+    fn from(aoi: SavedAoi) -> Self {
+        let ring = aoi
+            .points
+            .iter()
+            .chain(aoi.points.first())
+            .map(|p| [p.lng, p.lat])
+            .collect();
+        Feature {
+            properties: Properties { name: aoi.name },
+            geometry: Polygon {
+                coordinates: vec![ring],
+            },
+        }
+    }
+}
+
+/// AOIs saved in earlier sessions. Unreadable storage is treated as empty.
+pub fn load() -> Vec<SavedAoi> {
+    LocalStorage::get::<FeatureCollection>(workspace::key(STORAGE_KEY))
+        .map(|collection| collection.features)
+        .unwrap_or_default()
+}
+
+fn store(aois: &[SavedAoi]) {
+    let collection = FeatureCollection {
+        features: aois.to_vec(),
+    };
+    if let Err(why) = LocalStorage::set(workspace::key(STORAGE_KEY), collection) {
+        notify::warn(i18n::t("aoi-save-failed"), why);
+    }
+}
+
+/// List of saved AOIs. When `area` is given, it can be saved under a new name.
+#[autoprops_component(SavedAoisPane)]
+pub fn saved_aois_pane(
+    #[prop_or_default] area: Option<Rc<Vec<LatLng>>>,
+    select_label: AttrValue,
+    onselect: Callback<Rc<SavedAoi>>,
+) -> Html {
+    let language = use_language();
+    let aois = use_state(|| Rc::new(load()));
+    let name = use_state(String::new);
+
+    let update = {
+        shadow_clone!(aois);
+        move |new_aois: Vec<SavedAoi>| {
+            store(&new_aois);
+            aois.set(Rc::new(new_aois));
+        }
+    };
+
+    let onname = {
+        shadow_clone!(name);
+        move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            name.set(input.value());
+        }
+    };
+
+    let onsave = {
+        shadow_clone!(aois, name, area, update);
+        move |_| {
+            let Some(area) = &area else {
+                return;
+            };
+            let new_name = name.trim().to_string();
+            if new_name.is_empty() {
+                return;
+            }
+            // Saving under an existing name replaces that AOI.
+            let mut new_aois: Vec<_> = aois
+                .iter()
+                .filter(|a| a.name != new_name)
+                .cloned()
+                .collect();
+            new_aois.push(SavedAoi {
+                name: new_name,
+                points: area.to_vec(),
+            });
+            update(new_aois);
+            name.set(String::new());
+        }
+    };
+
+    html! {
+        <div>
+            if aois.is_empty() {
+                <p class="text-body-secondary">{language.t("aoi-none")}</p>
+            } else {
+                <ul class="list-group mb-2">
+                {
+                    for aois.iter().enumerate().map(|(i, aoi)| {
+                        let onclick = {
+                            shadow_clone!(onselect);
+                            let aoi = Rc::new(aoi.clone());
+                            move |_| onselect.emit(aoi.clone())
+                        };
+                        let onremove = {
+                            shadow_clone!(aois, update);
+                            move |_| {
+                                let mut new_aois = (**aois).clone();
+                                new_aois.remove(i);
+                                update(new_aois);
+                            }
+                        };
+                        html! {
+                            <li class="list-group-item d-flex justify-content-between align-items-center">
+                                <span>
+                                    <strong>{&aoi.name}</strong>
+                                    <small class="text-body-secondary ms-2">
+                                        {language.t_args("aoi-vertices", &[("count", &aoi.points.len())])}
+                                    </small>
+                                </span>
+                                <div class="btn-group btn-group-sm">
+                                    <button class="btn btn-outline-primary" {onclick}>
+                                        {select_label.clone()}
+                                    </button>
+                                    <button class="btn btn-outline-danger" onclick={onremove}>
+                                        {language.t("aoi-remove")}
+                                    </button>
+                                </div>
+                            </li>
+                        }
+                    })
+                }
+                </ul>
+            }
+            if area.is_some() {
+                <div class="input-group input-group-sm">
+                    <input
+                        type="text"
+                        class="form-control"
+                        placeholder={language.t("aoi-name")}
+                        value={(*name).clone()}
+                        oninput={onname}
+                    />
+                    <button class="btn btn-outline-success" onclick={onsave}>{language.t("aoi-save")}</button>
+                </div>
+            }
+        </div>
+    }
+}
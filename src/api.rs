@@ -0,0 +1,280 @@
+//! Calls to the segmentation server, behind a trait so that other transports or a mock
+//! server can stand in for the HTTP one without touching the components.
+
+use gloo::net::http::Request;
+use std::{future::Future, ops::Deref, pin::Pin, rc::Rc};
+use web_sys::FormData;
+use yew::prelude::*;
+
+use crate::{
+    clock, digest,
+    error::AppError,
+    http::{self, AbortHandle, Progress},
+    schema::{SegmentResponse, StoredResult, IMAGE_DIGEST_HEADER, SEGMENT_ACCEPT},
+    settings::Settings,
+    FileDetails,
+};
+
+/// How long the server may take to take an image and answer with its mask.
+pub const SEGMENT_TIMEOUT_MS: u32 = 5 * 60 * 1000;
+
+/// Header carrying the id of a request, so that it can be found in the server's logs.
+const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Whether the image arrived as it was sent, as far as the server tells: servers that do
+/// not echo a digest, and images sent without one, are taken to have.
+fn arrived_intact(sent: Option<&str>, received: Option<&str>) -> bool {
+    match (sent, received) {
+        (Some(sent), Some(received)) => sent.eq_ignore_ascii_case(received.trim()),
+        _ => true,
+    }
+}
+
+pub type ApiFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, AppError>> + 'a>>;
+
+/// What the caller of a request hears about it as it moves on, and how it stops it.
+#[derive(Clone, Default)]
+pub struct RequestControl {
+    /// Aborts the request, which then fails with [`AppError::Cancelled`].
+    pub abort: Option<AbortHandle>,
+    /// Called as the body is sent.
+    pub onupload: Callback<Progress>,
+    /// Called once the server starts answering.
+    pub onanswer: Callback<()>,
+}
+
+pub trait SegmentationApi {
+    /// Sends `image` to be segmented, returning the mask the server answers with.
+    fn segment(
+        &self,
+        image: FileDetails,
+        control: RequestControl,
+    ) -> ApiFuture<'_, SegmentResponse>;
+
+    /// The image and mask the server stored under `result_id`.
+    fn fetch_result<'a>(&'a self, result_id: &'a str) -> ApiFuture<'a, StoredResult>;
+}
+
+/// The segmentation server set in the settings, spoken to over HTTP.
+pub struct HttpApi {
+    settings: Rc<Settings>,
+}
+
+impl HttpApi {
+    pub fn new(settings: Rc<Settings>) -> Self {
+        Self { settings }
+    }
+
+    /// Headers of every request: the id it goes by and the token, if users set one.
+    fn headers(&self, request_id: &str) -> Vec<(&'static str, String)> {
+        let mut headers = vec![(REQUEST_ID_HEADER, request_id.to_string())];
+        if let Some(token) = &self.settings.token {
+            headers.push(("Authorization", format!("Bearer {token}")));
+        }
+        headers
+    }
+}
+
+impl SegmentationApi for HttpApi {
+    fn segment(
+        &self,
+        image: FileDetails,
+        control: RequestControl,
+    ) -> ApiFuture<'_, SegmentResponse> {
+        Box::pin(async move {
+            let url = self.settings.segment_url();
+            let request_id = clock::request_id();
+            log::debug!(
+                "Sending {} to {url} as request {request_id}",
+                image.file_name
+            );
+            let form = FormData::new().map_err(|e| AppError::Validation(format!("{e:?}")))?;
+            let append = |name: &str, file: &FileDetails| {
+                let cannot_send =
+                    |e| AppError::Validation(format!("{} cannot be sent: {e}", file.file_name));
+                // Files as picked are streamed by the browser, others are built from their bytes.
+                let blob = match &file.source {
+                    Some(source) => source.clone(),
+                    None => http::file_blob(&file.data, &file.file_type).map_err(cannot_send)?,
+                };
+                form.append_with_blob_and_filename(name, &blob, &file.file_name)
+                    .map_err(|e| cannot_send(http::Error::Network(format!("{e:?}"))))
+            };
+            append("f[]", &image)?;
+            if let Some(aux) = &image.auxiliary {
+                append("aux[]", aux)?;
+            }
+            let mut headers = self.headers(&request_id);
+            headers.push(("Accept", SEGMENT_ACCEPT.to_string()));
+            // The server can tell from the digest when a proxy cut the image short.
+            let sent_digest = digest::sha256(&image.data)
+                .await
+                .inspect_err(|why| {
+                    log::warn!("Sending {} without its digest: {why}", image.file_name)
+                })
+                .ok();
+            if let Some(digest) = &sent_digest {
+                headers.push((IMAGE_DIGEST_HEADER, digest.clone()));
+            }
+            let answer = http::upload(
+                &url,
+                &form,
+                &headers,
+                SEGMENT_TIMEOUT_MS,
+                control.abort.as_ref(),
+                &control.onupload,
+                &control.onanswer,
+            )
+            .await
+            .map_err(|e| AppError::from_request(&url, e))?;
+            if !arrived_intact(sent_digest.as_deref(), answer.header(IMAGE_DIGEST_HEADER)) {
+                return Err(AppError::Corrupted { url });
+            }
+            SegmentResponse::from_answer(answer)
+                .map_err(|e| AppError::from_request(&url, http::Error::Decode(e)))
+        })
+    }
+
+    fn fetch_result<'a>(&'a self, result_id: &'a str) -> ApiFuture<'a, StoredResult> {
+        Box::pin(async move {
+            let url = self
+                .settings
+                .result_url(&String::from(js_sys::encode_uri_component(result_id)));
+            let request_id = clock::request_id();
+            log::debug!("Fetching result {result_id} as request {request_id}");
+            let mut request = Request::get(&url);
+            for (name, value) in self.headers(&request_id) {
+                request = request.header(name, &value);
+            }
+            let response = http::send(request, None)
+                .await
+                .map_err(|e| AppError::from_request(&url, e))?;
+            let body = response
+                .binary()
+                .await
+                .map_err(|e| AppError::from_request(&url, e.into()))?;
+            StoredResult::from_json(&body)
+                .map_err(|e| AppError::Decode(format!("result {result_id}: {e}")))
+        })
+    }
+}
+
+/// The API components talk to, provided through context.
+#[derive(Clone)]
+pub struct Api(pub Rc<dyn SegmentationApi>);
+
+impl Api {
+    pub fn http(settings: Rc<Settings>) -> Self {
+        Self(Rc::new(HttpApi::new(settings)))
+    }
+}
+
+impl PartialEq for Api {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Deref for Api {
+    type Target = dyn SegmentationApi;
+
+    fn deref(&self) -> &Self::Target {
+        &*self.0
+    }
+}
+
+/// The API provided through context, or the HTTP one for the current settings outside
+/// of any provider.
+#[hook]
+pub fn use_api() -> Api {
+    let settings = use_context::<Rc<Settings>>().unwrap_or_default();
+    use_context::<Api>().unwrap_or_else(|| Api::http(settings))
+}
+
+/// A server answering with canned responses, for tests. These run natively, without a
+/// browser, so they leave out whatever goes through web APIs: the history in IndexedDB,
+/// the codec worker and the uploader's file handling are only tested by hand.
+#[cfg(test)]
+pub mod mock {
+    use std::{
+        cell::RefCell,
+        collections::VecDeque,
+        future::{ready, Future},
+        pin::pin,
+        task::{Context, Poll, Waker},
+    };
+
+    use super::{ApiFuture, RequestControl, SegmentationApi};
+    use crate::{
+        error::AppError,
+        schema::{SegmentResponse, StoredResult},
+        FileDetails,
+    };
+
+    #[derive(Default)]
+    pub struct MockApi {
+        /// Answers to segmentation requests, in the order they are made.
+        pub answers: RefCell<VecDeque<Result<SegmentResponse, AppError>>>,
+        /// Names of the images sent so far.
+        pub sent: RefCell<Vec<String>>,
+    }
+
+    impl MockApi {
+        pub fn answering(
+            answers: impl IntoIterator<Item = Result<SegmentResponse, AppError>>,
+        ) -> Self {
+            MockApi {
+                answers: RefCell::new(answers.into_iter().collect()),
+                ..Default::default()
+            }
+        }
+    }
+
+    impl SegmentationApi for MockApi {
+        fn segment(
+            &self,
+            image: FileDetails,
+            control: RequestControl,
+        ) -> ApiFuture<'_, SegmentResponse> {
+            self.sent.borrow_mut().push(image.file_name);
+            let answer = self
+                .answers
+                .borrow_mut()
+                .pop_front()
+                .expect("MockApi was asked for more answers than it was given");
+            if answer.is_ok() {
+                control.onanswer.emit(());
+            }
+            Box::pin(ready(answer))
+        }
+
+        fn fetch_result<'a>(&'a self, result_id: &'a str) -> ApiFuture<'a, StoredResult> {
+            Box::pin(ready(Err(AppError::Http {
+                url: format!("mock://results/{result_id}"),
+                status: 404,
+                message: "Not found".to_string(),
+            })))
+        }
+    }
+
+    /// Runs `future` to completion, which must not wait on anything, as with [`MockApi`].
+    pub fn block_on<F: Future>(future: F) -> F::Output {
+        match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(output) => output,
+            Poll::Pending => panic!("the future waited on something"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::arrived_intact;
+
+    #[test]
+    fn images_are_intact_unless_the_server_received_other_bytes() {
+        assert!(arrived_intact(Some("ab12"), Some("AB12")));
+        assert!(!arrived_intact(Some("ab12"), Some("ab13")));
+        assert!(arrived_intact(Some("ab12"), None));
+        assert!(arrived_intact(None, Some("ab12")));
+    }
+}
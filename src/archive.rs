@@ -0,0 +1,105 @@
+//! ZIP archives of several files, offered as one download. Files are stored as they are,
+//! since masks are PNGs and compressed already.
+
+/// The CRC-32 checksum ZIP archives keep of every file.
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &byte| {
+        (0..8).fold(crc ^ u32::from(byte), |crc, _| {
+            (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+/// Names are flagged as UTF-8, which plain ASCII names are too.
+const UTF8_NAMES: u16 = 0x0800;
+/// 1 January 1980, the earliest date ZIP archives can tell, as no other is known.
+const DOS_DATE: u16 = 0x0021;
+
+/// The fields local headers and the central directory share, from the version needed to
+/// the length of the name.
+fn common_fields(out: &mut Vec<u8>, name: &str, data: &[u8]) {
+    out.extend_from_slice(&20u16.to_le_bytes());
+    out.extend_from_slice(&UTF8_NAMES.to_le_bytes());
+    // Stored, with no compression, at midnight.
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out.extend_from_slice(&DOS_DATE.to_le_bytes());
+    out.extend_from_slice(&crc32(data).to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+}
+
+/// A ZIP archive of `files`, each a name and its bytes. Archives past what plain ZIP can
+/// tell, 65535 files or 4 GiB, are refused.
+pub fn zip(files: &[(String, &[u8])]) -> Result<Vec<u8>, String> {
+    let total: usize = files
+        .iter()
+        .map(|(name, data)| name.len() + data.len())
+        .sum();
+    if files.len() > usize::from(u16::MAX) || total + files.len() * 100 > u32::MAX as usize {
+        return Err(format!(
+            "{} files are too many or too large for one archive",
+            files.len()
+        ));
+    }
+    let mut out = Vec::with_capacity(total + files.len() * 100);
+    let mut directory = Vec::new();
+    for (name, data) in files {
+        let offset = out.len() as u32;
+        out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        common_fields(&mut out, name, data);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(data);
+
+        directory.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        directory.extend_from_slice(&20u16.to_le_bytes());
+        common_fields(&mut directory, name, data);
+        // No comment, on the first disk, with no attributes.
+        directory.extend_from_slice(&[0; 10]);
+        directory.extend_from_slice(&offset.to_le_bytes());
+        directory.extend_from_slice(name.as_bytes());
+    }
+    let directory_offset = out.len() as u32;
+    out.extend_from_slice(&directory);
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+    out.extend_from_slice(&directory_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crc32, zip};
+
+    #[test]
+    fn checksums_match_the_standard_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+        assert_eq!(crc32(b""), 0);
+    }
+
+    #[test]
+    fn archives_list_their_files_after_the_data() {
+        let files = [
+            ("a.png".to_string(), &b"first"[..]),
+            ("b.csv".to_string(), &b"second"[..]),
+        ];
+        let archive = zip(&files).unwrap();
+
+        assert!(archive.starts_with(&[0x50, 0x4b, 0x03, 0x04]));
+        let end = &archive[archive.len() - 22..];
+        assert!(end.starts_with(&[0x50, 0x4b, 0x05, 0x06]));
+        assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+        let offset = u32::from_le_bytes(end[16..20].try_into().unwrap()) as usize;
+        assert!(archive[offset..].starts_with(&[0x50, 0x4b, 0x01, 0x02]));
+        // The second file's data follows its name in its local header.
+        let second = 30 + "a.png".len() + "first".len();
+        assert_eq!(&archive[second + 30..second + 35], b"b.csv");
+        assert_eq!(&archive[second + 35..second + 41], b"second");
+    }
+}
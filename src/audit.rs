@@ -0,0 +1,307 @@
+//! A trail of what users did to images and results, such as uploads, corrections, review
+//! decisions and exports, kept in the browser for compliance and also sent to the audit
+//! endpoint named in `config.json`, if any.
+
+use gloo::net::http::Request;
+use js_sys::{Date, Object, Reflect};
+use serde::Serialize;
+use shadow_clone::shadow_clone;
+use wasm_bindgen::JsValue;
+use web_sys::HtmlSelectElement;
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    clock,
+    collab::user_name,
+    config, http,
+    i18n::{self, use_language},
+    idb,
+    metadata::csv_field,
+    project, roles,
+    settings::Settings,
+};
+
+/// What was done.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    Upload,
+    /// A mask or what is known about a result was changed.
+    Edit,
+    Export,
+    Review,
+    Flag,
+    Comment,
+    Delete,
+}
+
+impl AuditAction {
+    const ALL: [AuditAction; 7] = [
+        AuditAction::Upload,
+        AuditAction::Edit,
+        AuditAction::Export,
+        AuditAction::Review,
+        AuditAction::Flag,
+        AuditAction::Comment,
+        AuditAction::Delete,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            AuditAction::Upload => "upload",
+            AuditAction::Edit => "edit",
+            AuditAction::Export => "export",
+            AuditAction::Review => "review",
+            AuditAction::Flag => "flag",
+            AuditAction::Comment => "comment",
+            AuditAction::Delete => "delete",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        AuditAction::ALL.into_iter().find(|a| a.name() == name)
+    }
+}
+
+/// Something a user did, and to what.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+pub struct AuditEvent {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: f64,
+    /// Who the access token was issued to, or else the name users go by, empty until they
+    /// give one.
+    pub user: String,
+    pub action: AuditAction,
+    /// The result, by its id, or the file the action was about.
+    pub subject: String,
+    pub details: String,
+}
+
+impl AuditEvent {
+    fn to_js(&self) -> JsValue {
+        let object = Object::new();
+        let set = |key: &str, value: JsValue| Reflect::set(&object, &key.into(), &value).unwrap();
+        set("timestamp", self.timestamp.into());
+        set("user", self.user.as_str().into());
+        set("action", self.action.name().into());
+        set("subject", self.subject.as_str().into());
+        set("details", self.details.as_str().into());
+        object.into()
+    }
+
+    fn from_js(value: &JsValue) -> Option<Self> {
+        let get = |key: &str| Reflect::get(value, &key.into()).unwrap_or(JsValue::UNDEFINED);
+        Some(AuditEvent {
+            timestamp: get("timestamp").as_f64()?,
+            user: get("user").as_string().unwrap_or_default(),
+            action: AuditAction::parse(&get("action").as_string()?)?,
+            subject: get("subject").as_string().unwrap_or_default(),
+            details: get("details").as_string().unwrap_or_default(),
+        })
+    }
+}
+
+async fn store(event: &AuditEvent) -> Result<(), String> {
+    let db = idb::open().await?;
+    idb::put(&db, idb::AUDIT_STORE, &event.to_js()).await?;
+    if let Some(url) = &config::current().audit_log_url {
+        http::send_json(Request::post(url), event, None)
+            .await
+            .map_err(|e| i18n::t_args("audit-send-failed", &[("error", &e)]))?;
+    }
+    Ok(())
+}
+
+/// Who is using the app, as far as it can tell.
+fn current_user() -> String {
+    Settings::load()
+        .token
+        .as_deref()
+        .and_then(roles::subject)
+        .unwrap_or_else(user_name)
+}
+
+/// Records that the current user did `action` to `subject`, in the background. Called once
+/// the action succeeded.
+pub fn record(action: AuditAction, subject: impl Into<String>, details: impl Into<String>) {
+    let event = AuditEvent {
+        timestamp: clock::now(),
+        user: current_user(),
+        action,
+        subject: subject.into(),
+        details: details.into(),
+    };
+    spawn_local(async move {
+        if let Err(why) = store(&event).await {
+            log::warn!(
+                "Could not record {} of {}: {why}",
+                event.action.name(),
+                event.subject
+            );
+        }
+    });
+}
+
+/// Every recorded event, most recent first.
+pub async fn list() -> Result<Vec<AuditEvent>, String> {
+    let db = idb::open().await?;
+    let mut events: Vec<_> = idb::get_all(&db, idb::AUDIT_STORE)
+        .await?
+        .iter()
+        .filter_map(|value| AuditEvent::from_js(&value))
+        .collect();
+    events.reverse();
+    Ok(events)
+}
+
+/// `events` as CSV, a line each.
+fn to_csv(events: &[AuditEvent], date: fn(f64) -> String) -> String {
+    let mut csv = "time,user,action,subject,details\n".to_string();
+    for event in events {
+        csv += &format!(
+            "{},{},{},{},{}\n",
+            date(event.timestamp),
+            csv_field(&event.user),
+            event.action.name(),
+            csv_field(&event.subject),
+            csv_field(&event.details),
+        );
+    }
+    csv
+}
+
+fn iso_date(ms: f64) -> String {
+    Date::new(&ms.into()).to_iso_string().into()
+}
+
+fn local_date(ms: f64) -> String {
+    Date::new(&ms.into())
+        .to_locale_string("default", &JsValue::UNDEFINED)
+        .into()
+}
+
+/// Most events listed at once; all of them are exported.
+const SHOWN_EVENTS: usize = 200;
+
+/// The recorded events, reloaded whenever `revision` changes, narrowed down by action and
+/// exported as CSV.
+#[autoprops_component(AuditLogPane)]
+pub fn audit_log_pane(revision: i32) -> Html {
+    let language = use_language();
+    let events = use_state(|| None::<Result<Vec<AuditEvent>, String>>);
+    let shown_action = use_state(|| None::<AuditAction>);
+
+    {
+        shadow_clone!(events);
+        use_effect_with(revision, move |_| {
+            spawn_local(async move { events.set(Some(list().await)) });
+        });
+    }
+
+    let events = match &*events {
+        None => return html!(<span class="spinner-border text-success"></span>),
+        Some(Err(why)) => return html!(<div class="alert alert-danger" role="alert">{why}</div>),
+        Some(Ok(events)) if events.is_empty() => return html!(<p>{language.t("audit-empty")}</p>),
+        Some(Ok(events)) => events,
+    };
+
+    let onaction = {
+        shadow_clone!(shown_action);
+        move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            shown_action.set(AuditAction::parse(&select.value()));
+        }
+    };
+    let onexport = {
+        let events = events.clone();
+        move |_| {
+            let csv = to_csv(&events, iso_date);
+            project::download("audit.csv", "text/csv", csv.as_bytes());
+        }
+    };
+    let shown: Vec<_> = events
+        .iter()
+        .filter(|event| shown_action.is_none_or(|action| action == event.action))
+        .collect();
+
+    html! {
+        <div>
+            <div class="d-flex align-items-center gap-2 mb-2">
+                <div class="input-group input-group-sm w-auto">
+                    <span class="input-group-text">{language.t("audit-action")}</span>
+                    <select class="form-select" onchange={onaction}>
+                        <option value="" selected={shown_action.is_none()}>{language.t("history-filter-any")}</option>
+                        {for AuditAction::ALL.into_iter().map(|action| html! {
+                            <option value={action.name()} selected={*shown_action == Some(action)}>
+                                {language.t(&format!("audit-{}", action.name()))}
+                            </option>
+                        })}
+                    </select>
+                </div>
+                <button class="btn btn-sm btn-outline-secondary" onclick={onexport}>
+                    {language.t("audit-export-trail")}
+                </button>
+                if config::current().audit_log_url.is_some() {
+                    <small class="text-body-secondary">{language.t("audit-sent")}</small>
+                }
+            </div>
+            <table class="table table-sm small">
+                <thead>
+                    <tr>
+                        <th scope="col">{language.t("audit-time")}</th>
+                        <th scope="col">{language.t("audit-user")}</th>
+                        <th scope="col">{language.t("audit-action")}</th>
+                        <th scope="col">{language.t("audit-subject")}</th>
+                        <th scope="col">{language.t("audit-details")}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {for shown.iter().take(SHOWN_EVENTS).map(|event| html! {
+                        <tr>
+                            <td class="text-nowrap">{local_date(event.timestamp)}</td>
+                            <td>
+                                if event.user.is_empty() {
+                                    {language.t("audit-anonymous")}
+                                } else {
+                                    {&event.user}
+                                }
+                            </td>
+                            <td>{language.t(&format!("audit-{}", event.action.name()))}</td>
+                            <td class="text-break">{&event.subject}</td>
+                            <td>{&event.details}</td>
+                        </tr>
+                    })}
+                </tbody>
+            </table>
+            if shown.len() > SHOWN_EVENTS {
+                <small class="text-body-secondary">
+                    {language.t_args("audit-truncated", &[("shown", &SHOWN_EVENTS), ("count", &shown.len())])}
+                </small>
+            }
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{to_csv, AuditAction, AuditEvent};
+
+    #[test]
+    fn events_are_exported_a_line_each() {
+        let events = [AuditEvent {
+            timestamp: 5.0,
+            user: "Ana".to_string(),
+            action: AuditAction::Review,
+            subject: "result 3".to_string(),
+            details: "needs-edit: roads, bridges".to_string(),
+        }];
+        assert_eq!(
+            to_csv(&events, |ms| ms.to_string()),
+            "time,user,action,subject,details\n\
+             5,Ana,review,result 3,\"needs-edit: roads, bridges\"\n"
+        );
+        let json = serde_json::to_value(&events[0]).unwrap();
+        assert_eq!(json["action"], "review");
+    }
+}
@@ -0,0 +1,955 @@
+//! Segmenting many images in one run left unattended, such as a few hundred overnight:
+//! every image with its status, timing, result and error, and controls for the queue as a
+//! whole. Results go to the history, so that only a small thumbnail of each is kept here.
+//! Masks of images with a ground truth are scored against it, and the scores pooled over
+//! the whole run.
+
+use gloo::{
+    events::EventListener,
+    timers::callback::{Interval, Timeout},
+};
+use js_sys::{Function, Promise, Reflect};
+use shadow_clone::shadow_clone;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Event, File, HtmlInputElement};
+use yew::{platform::spawn_local, prelude::*};
+
+use crate::{
+    api::{use_api, RequestControl, SegmentationApi},
+    archive,
+    audit::{self, AuditAction},
+    clock,
+    codec::EncodeJob,
+    diff::decode_mask,
+    error::AppError,
+    evaluation::{self, Evaluation, MetricsTable},
+    flags::{self, Flag},
+    geo::Georeference,
+    history::{self, HistoryEntry},
+    i18n::{self, use_language},
+    metadata::csv_field,
+    notify, offline, postprocess, project,
+    route::Route,
+    settings::Settings,
+    state::{use_app_state, AppAction},
+    status::format_elapsed,
+    storage::format_size,
+    uploader::{check_header, format_duration, media_type, read_in_chunks},
+    viewer::BlobUrl,
+    workers::encode,
+    FileDetails,
+};
+
+/// Pixels the thumbnails of masks are shrunk to fit, small enough for hundreds of them.
+const THUMBNAIL_SIZE: u32 = 64;
+
+#[derive(Clone, PartialEq)]
+enum Status {
+    Waiting,
+    Running {
+        started: f64,
+    },
+    Done {
+        ms: f64,
+        /// The key of the result in the history.
+        result: f64,
+        thumbnail: Option<BlobUrl>,
+        /// Scores against the image's ground truth, if it had one.
+        evaluation: Option<Result<Rc<Evaluation>, String>>,
+    },
+    Failed {
+        ms: f64,
+        error: String,
+    },
+}
+
+#[derive(Clone, PartialEq)]
+struct Item {
+    /// Where the item's file is kept, which is the order it was added in.
+    id: usize,
+    name: String,
+    status: Status,
+}
+
+/// Why no new images are sent.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Pause {
+    /// Paused by the user, until they resume.
+    User,
+    /// The server could not be reached, so sending more would only fail them all.
+    Connection,
+}
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+struct Counts {
+    waiting: usize,
+    running: usize,
+    done: usize,
+    failed: usize,
+}
+
+#[derive(Clone, PartialEq)]
+struct Batch {
+    items: Vec<Item>,
+    paused: Option<Pause>,
+    /// Images sent to the server at once.
+    limit: usize,
+}
+
+/// What an image segmented leaves behind: its key in the history, the thumbnail of its mask
+/// and its scores.
+type Finished = (f64, Option<BlobUrl>, Option<Result<Rc<Evaluation>, String>>);
+
+enum BatchAction {
+    /// Images to segment, by their id and name.
+    Add(Vec<(usize, String)>),
+    Start(usize),
+    Finish(usize, Result<Finished, String>),
+    /// Puts an image back in line, its request having failed for want of a connection.
+    Requeue(usize),
+    Pause(Pause),
+    Resume,
+    Limit(usize),
+    RetryFailed,
+    RemoveDone,
+}
+
+impl Batch {
+    fn new(limit: usize) -> Self {
+        Batch {
+            items: Vec::new(),
+            paused: None,
+            limit: limit.max(1),
+        }
+    }
+
+    fn counts(&self) -> Counts {
+        let mut counts = Counts::default();
+        for item in &self.items {
+            match item.status {
+                Status::Waiting => counts.waiting += 1,
+                Status::Running { .. } => counts.running += 1,
+                Status::Done { .. } => counts.done += 1,
+                Status::Failed { .. } => counts.failed += 1,
+            }
+        }
+        counts
+    }
+
+    /// The images to send now, oldest first, so that `limit` requests are under way.
+    fn startable(&self) -> Vec<usize> {
+        if self.paused.is_some() {
+            return Vec::new();
+        }
+        let free = self.limit.saturating_sub(self.counts().running);
+        self.items
+            .iter()
+            .filter(|item| item.status == Status::Waiting)
+            .map(|item| item.id)
+            .take(free)
+            .collect()
+    }
+
+    /// How long the images not done yet should take, going by those done so far.
+    fn remaining_ms(&self) -> Option<f64> {
+        let durations: Vec<f64> = self
+            .items
+            .iter()
+            .filter_map(|item| match item.status {
+                Status::Done { ms, .. } => Some(ms),
+                _ => None,
+            })
+            .collect();
+        let counts = self.counts();
+        let left = counts.waiting + counts.running;
+        if durations.is_empty() || left == 0 {
+            return None;
+        }
+        let average = durations.iter().sum::<f64>() / durations.len() as f64;
+        Some(average * left as f64 / self.limit as f64)
+    }
+
+    /// The scores of all images with a ground truth, pooled, if there are any.
+    fn evaluation(&self) -> Option<Evaluation> {
+        self.items
+            .iter()
+            .filter_map(|item| match &item.status {
+                Status::Done {
+                    evaluation: Some(Ok(evaluation)),
+                    ..
+                } => Some(evaluation),
+                _ => None,
+            })
+            .fold(None, |total: Option<Evaluation>, evaluation| {
+                let mut total = total.unwrap_or_default();
+                total.merge(evaluation);
+                Some(total)
+            })
+    }
+
+    /// What became of every image, as CSV, with the mean IoU of those with a ground truth.
+    fn report(&self) -> String {
+        let mut report = "image,status,seconds,result,mean_iou,error\n".to_string();
+        for item in &self.items {
+            let (status, ms, result, iou, error) = match &item.status {
+                Status::Waiting => ("waiting", None, None, None, ""),
+                Status::Running { .. } => ("running", None, None, None, ""),
+                Status::Done {
+                    ms,
+                    result,
+                    evaluation,
+                    ..
+                } => {
+                    let iou = match evaluation {
+                        Some(Ok(evaluation)) => evaluation.mean_iou(),
+                        _ => None,
+                    };
+                    ("done", Some(*ms), Some(*result), iou, "")
+                }
+                Status::Failed { ms, error } => ("failed", Some(*ms), None, None, error.as_str()),
+            };
+            report += &format!(
+                "{},{status},{},{},{},{}\n",
+                csv_field(&item.name),
+                ms.map_or(String::new(), |ms| format!("{:.1}", ms / 1000.0)),
+                result.map_or(String::new(), |result| result.to_string()),
+                iou.map_or(String::new(), |iou| format!("{iou:.4}")),
+                csv_field(error),
+            );
+        }
+        report
+    }
+}
+
+impl Reducible for Batch {
+    type Action = BatchAction;
+
+    fn reduce(self: Rc<Self>, action: BatchAction) -> Rc<Self> {
+        let mut batch = (*self).clone();
+        let now = clock::now();
+        let status = |batch: &mut Batch, id: usize, status: Status| {
+            if let Some(item) = batch.items.iter_mut().find(|item| item.id == id) {
+                item.status = status;
+            }
+        };
+        let started = |batch: &Batch, id: usize| {
+            batch
+                .items
+                .iter()
+                .find_map(|item| match item.status {
+                    Status::Running { started } if item.id == id => Some(started),
+                    _ => None,
+                })
+                .unwrap_or(now)
+        };
+        match action {
+            BatchAction::Add(images) => {
+                batch
+                    .items
+                    .extend(images.into_iter().map(|(id, name)| Item {
+                        id,
+                        name,
+                        status: Status::Waiting,
+                    }))
+            }
+            BatchAction::Start(id) => status(&mut batch, id, Status::Running { started: now }),
+            BatchAction::Finish(id, outcome) => {
+                let ms = now - started(&batch, id);
+                let finished = match outcome {
+                    Ok((result, thumbnail, evaluation)) => Status::Done {
+                        ms,
+                        result,
+                        thumbnail,
+                        evaluation,
+                    },
+                    Err(error) => Status::Failed { ms, error },
+                };
+                status(&mut batch, id, finished);
+            }
+            BatchAction::Requeue(id) => {
+                status(&mut batch, id, Status::Waiting);
+                batch.paused = Some(Pause::Connection);
+            }
+            BatchAction::Pause(pause) => batch.paused = Some(pause),
+            BatchAction::Resume => batch.paused = None,
+            BatchAction::Limit(limit) => batch.limit = limit.max(1),
+            BatchAction::RetryFailed => {
+                for item in &mut batch.items {
+                    if matches!(item.status, Status::Failed { .. }) {
+                        item.status = Status::Waiting;
+                    }
+                }
+            }
+            BatchAction::RemoveDone => batch
+                .items
+                .retain(|item| !matches!(item.status, Status::Done { .. })),
+        }
+        Rc::new(batch)
+    }
+}
+
+/// `name` without its extension, by which images and their ground truths are matched.
+fn stem(name: &str) -> &str {
+    name.rsplit_once('.').map_or(name, |(stem, _)| stem)
+}
+
+/// Names for the masks of `results`, each the name of its image and of its mask, in an
+/// archive: the image's name with the mask's extension, told apart when images share a
+/// name.
+fn mask_names(results: &[(String, String)]) -> Vec<String> {
+    let mut used = HashSet::new();
+    results
+        .iter()
+        .map(|(image, mask)| {
+            let stem = stem(image);
+            let extension = mask
+                .rsplit_once('.')
+                .map_or("png", |(_, extension)| extension);
+            let mut name = format!("masks/{stem}.{extension}");
+            let mut n = 1;
+            while !used.insert(name.clone()) {
+                n += 1;
+                name = format!("masks/{stem}-{n}.{extension}");
+            }
+            name
+        })
+        .collect()
+}
+
+/// `file` read and segmented, with the mask post-processed by the settings' steps.
+async fn segment(
+    api: &dyn SegmentationApi,
+    settings: &Settings,
+    file: &File,
+) -> Result<(FileDetails, FileDetails), AppError> {
+    let data = read_in_chunks(file, &Callback::noop(), &|| false)
+        .await
+        .map_err(AppError::Validation)?
+        .ok_or(AppError::Cancelled)?;
+    check_header(&data).map_err(|why| {
+        AppError::Validation(i18n::t_args("batch-unsegmentable", &[("reason", &why)]))
+    })?;
+    let image = FileDetails {
+        file_name: file.name(),
+        file_type: media_type(&file.type_(), &data),
+        georef: Georeference::from_geotiff(&data),
+        data,
+        auxiliary: None,
+        source: Some(file.clone().into()),
+    };
+    let response = api
+        .segment(image.clone(), RequestControl::default())
+        .await?;
+    let mask =
+        postprocess::apply(&settings.postprocess, response.mask).map_err(AppError::Decode)?;
+    Ok((image, mask))
+}
+
+/// Scores `mask` against the ground truth in `truth`.
+async fn score(truth: &File, mask: &FileDetails) -> Result<Rc<Evaluation>, String> {
+    let data = read_in_chunks(truth, &Callback::noop(), &|| false)
+        .await?
+        .ok_or_else(|| i18n::t("batch-truth-unreadable"))?;
+    let truth = decode_mask(&data)?;
+    evaluation::evaluate(&decode_mask(&mask.data)?, &truth).map(Rc::new)
+}
+
+/// Stores the result in the history, answering with its key and a thumbnail of the mask.
+async fn save(
+    settings: &Settings,
+    image: FileDetails,
+    mask: FileDetails,
+) -> Result<(f64, Option<BlobUrl>), String> {
+    let thumbnail = encode(EncodeJob::Thumbnail {
+        data: mask.data.clone(),
+        size: THUMBNAIL_SIZE,
+    })
+    .await
+    .map(|png| BlobUrl::new(&png, "image/png"))
+    .inspect_err(|why| log::warn!("Could not make the thumbnail of a batch result: {why}"))
+    .ok();
+    let (file_name, size) = (image.file_name.clone(), image.data.len() as u64);
+    let entry = HistoryEntry::new(Rc::new(image), Rc::new(mask), settings.segment_url());
+    let result = history::add(&entry).await?;
+    audit::record(
+        AuditAction::Upload,
+        file_name,
+        format!("{} in a batch, result {result}", format_size(size)),
+    );
+    Ok((result, thumbnail))
+}
+
+/// The masks of the images done, stored in the history, and the report of the whole batch
+/// as one archive. Answers how many results the history no longer had.
+async fn export(batch: &Batch) -> Result<usize, String> {
+    let mut results = Vec::new();
+    let mut missing = 0;
+    for item in &batch.items {
+        let Status::Done { result, .. } = item.status else {
+            continue;
+        };
+        match history::get_entry(result).await? {
+            Some(entry) => results.push((item.name.clone(), entry.mask)),
+            None => missing += 1,
+        }
+    }
+    let names = mask_names(
+        &results
+            .iter()
+            .map(|(image, mask)| (image.clone(), mask.file_name.clone()))
+            .collect::<Vec<_>>(),
+    );
+    let report = batch.report();
+    let mut files: Vec<_> = names
+        .into_iter()
+        .zip(&results)
+        .map(|(name, (_, mask))| (name, &mask.data[..]))
+        .collect();
+    files.push(("report.csv".to_string(), report.as_bytes()));
+    let archive = archive::zip(&files)?;
+    project::download("batch.zip", "application/zip", &archive);
+    Ok(missing)
+}
+
+/// Asks the browser to keep the screen on, which keeps the tab running, answering with the
+/// lock to release. Browsers without the Screen Wake Lock API answer with an error.
+async fn keep_awake() -> Result<JsValue, String> {
+    let error = |e: JsValue| format!("Could not keep the screen on: {e:?}");
+    let wake_lock =
+        Reflect::get(&gloo::utils::window().navigator(), &"wakeLock".into()).map_err(error)?;
+    if wake_lock.is_undefined() {
+        return Err("The browser cannot keep the screen on".to_string());
+    }
+    let request: Function = Reflect::get(&wake_lock, &"request".into())
+        .map_err(error)?
+        .dyn_into()
+        .map_err(error)?;
+    let promise: Promise = request
+        .call1(&wake_lock, &"screen".into())
+        .map_err(error)?
+        .dyn_into()
+        .map_err(error)?;
+    JsFuture::from(promise).await.map_err(error)
+}
+
+fn release(lock: &JsValue) {
+    let released = Reflect::get(lock, &"release".into())
+        .and_then(|release| release.dyn_into::<Function>())
+        .and_then(|release| release.call0(lock));
+    if let Err(e) = released {
+        log::warn!("Could not release the screen wake lock: {e:?}");
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct BatchRowProps {
+    item: Item,
+    now: f64,
+}
+
+#[function_component(BatchRow)]
+fn batch_row(props: &BatchRowProps) -> Html {
+    let language = use_language();
+    let item = &props.item;
+    let (status, time) = match &item.status {
+        Status::Waiting => (language.t("batch-waiting"), None),
+        Status::Running { started } => (
+            language.t("batch-running"),
+            Some(format_elapsed(props.now - started)),
+        ),
+        Status::Done { ms, .. } => (language.t("batch-done"), Some(format_elapsed(*ms))),
+        Status::Failed { ms, .. } => (language.t("batch-failed"), Some(format_elapsed(*ms))),
+    };
+    let class = match item.status {
+        Status::Done { .. } => "text-success",
+        Status::Failed { .. } => "text-danger",
+        _ => "",
+    };
+    html! {
+        <tr>
+            <td class="text-break">{&item.name}</td>
+            <td class={class}>
+                if matches!(item.status, Status::Running { .. }) {
+                    <span class="spinner-border spinner-border-sm me-1" aria-hidden="true"></span>
+                }
+                {status}
+            </td>
+            <td>{time}</td>
+            <td>
+                {
+                    match &item.status {
+                        Status::Done { result, thumbnail, evaluation, .. } => html! {
+                            <>
+                                <a href={Route::Result(*result as u64).href()}>
+                                    if let Some(src) = thumbnail {
+                                        <img
+                                            src={src.to_string()}
+                                            alt={language.t_args("batch-mask-of", &[("name", &item.name)])}
+                                            style="max-height: 64px; max-width: 64px;"
+                                        />
+                                    } else {
+                                        {language.t("batch-open")}
+                                    }
+                                </a>
+                                {
+                                    match evaluation {
+                                        Some(Ok(evaluation)) => html! {
+                                            <small class="ms-2">
+                                                {language.t_args("batch-iou", &[
+                                                    ("iou", &evaluation::percent(evaluation.mean_iou())),
+                                                ])}
+                                            </small>
+                                        },
+                                        Some(Err(why)) => html!(<small class="ms-2 text-warning">{why}</small>),
+                                        None => html!(),
+                                    }
+                                }
+                            </>
+                        },
+                        Status::Failed { error, .. } => html!(<small class="text-danger">{error}</small>),
+                        _ => html!(),
+                    }
+                }
+            </td>
+        </tr>
+    }
+}
+
+/// A queue of images segmented a few at a time, which goes on while other tabs are open as
+/// long as the pane stays mounted. Runs pause by themselves when the server cannot be
+/// reached, and resume once it can, so that a lost connection does not fail the rest.
+#[function_component(BatchPane)]
+pub fn batch_pane() -> Html {
+    let language = use_language();
+    let state = use_app_state();
+    let settings = state.settings.clone();
+    let api = use_api();
+    let batch = use_reducer(|| Batch::new(settings.parallel_requests as usize));
+    // Files of the images added, by their id, let go once they are done.
+    let files = use_mut_ref(Vec::<Option<File>>::new);
+    // Ground-truth masks, by the name of their image without its extension.
+    let truths = use_mut_ref(HashMap::<String, File>::new);
+    let truth_count = use_state(|| 0);
+    let now = use_state(clock::now);
+    let exporting = use_state(|| false);
+    let counts = batch.counts();
+
+    {
+        shadow_clone!(batch, files, truths, api, settings, state);
+        use_effect_with((counts, batch.paused, batch.limit), move |_| {
+            for id in batch.startable() {
+                let Some(file) = files.borrow().get(id).cloned().flatten() else {
+                    continue;
+                };
+                batch.dispatch(BatchAction::Start(id));
+                let truth = truths.borrow().get(stem(&file.name())).cloned();
+                shadow_clone!(batch, files, api, settings, state);
+                spawn_local(async move {
+                    let outcome = match segment(&*api, &settings, &file).await {
+                        Ok((image, mask)) => {
+                            offline::reached();
+                            let evaluation = match &truth {
+                                Some(truth) => Some(score(truth, &mask).await),
+                                None => None,
+                            };
+                            save(&settings, image, mask)
+                                .await
+                                .map(|(result, thumbnail)| (result, thumbnail, evaluation))
+                        }
+                        Err(AppError::Network { .. }) if offline::unreachable() => {
+                            batch.dispatch(BatchAction::Requeue(id));
+                            return;
+                        }
+                        Err(error) => Err(error.to_string()),
+                    };
+                    if outcome.is_ok() {
+                        files.borrow_mut()[id] = None;
+                        state.dispatch(AppAction::HistoryChanged);
+                    }
+                    batch.dispatch(BatchAction::Finish(id, outcome));
+                });
+            }
+        });
+    }
+
+    // The clocks of the images under way tick while there are any.
+    {
+        shadow_clone!(now);
+        use_effect_with(counts.running > 0, move |&running| {
+            let ticking = running.then(|| Interval::new(1000, move || now.set(clock::now())));
+            move || drop(ticking)
+        });
+    }
+
+    // A run paused for the connection is tried again when the browser is back online, or
+    // every while, since browsers on flaky networks often never say they were offline.
+    {
+        shadow_clone!(batch);
+        use_effect_with(batch.paused, move |&paused| {
+            let watching = (paused == Some(Pause::Connection)).then(|| {
+                let resume = {
+                    shadow_clone!(batch);
+                    move || batch.dispatch(BatchAction::Resume)
+                };
+                let ononline = {
+                    shadow_clone!(resume);
+                    EventListener::new(&gloo::utils::window(), "online", move |_| resume())
+                };
+                let retrying = Timeout::new(offline::RETRY_INTERVAL_MS, resume);
+                (ononline, retrying)
+            });
+            move || drop(watching)
+        });
+    }
+
+    // While images are under way, the screen is kept on, as tabs of sleeping computers
+    // stop, and leaving the page asks first.
+    use_effect_with(counts.running > 0, move |&running| {
+        let lock = Rc::new(RefCell::new(None::<JsValue>));
+        // Set once the images are done, which may be before the browser grants the lock.
+        let done = Rc::new(Cell::new(false));
+        let onbeforeunload = running.then(|| {
+            shadow_clone!(lock, done);
+            spawn_local(async move {
+                match keep_awake().await {
+                    Ok(sentinel) if done.get() => release(&sentinel),
+                    Ok(sentinel) => *lock.borrow_mut() = Some(sentinel),
+                    Err(why) => log::info!("{why}"),
+                }
+            });
+            EventListener::new(&gloo::utils::window(), "beforeunload", |e| {
+                e.prevent_default()
+            })
+        });
+        move || {
+            drop(onbeforeunload);
+            done.set(true);
+            if let Some(sentinel) = lock.borrow_mut().take() {
+                release(&sentinel);
+            }
+        }
+    });
+
+    let onadd = {
+        shadow_clone!(batch, files);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let Some(list) = input.files() else {
+                return;
+            };
+            let mut files = files.borrow_mut();
+            let images = (0..list.length())
+                .filter_map(|i| list.get(i))
+                .map(|file| {
+                    let name = file.name();
+                    files.push(Some(file));
+                    (files.len() - 1, name)
+                })
+                .collect();
+            batch.dispatch(BatchAction::Add(images));
+            // The same files can then be added again.
+            input.set_value("");
+        }
+    };
+    let ontruths = {
+        shadow_clone!(truths, truth_count);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let Some(list) = input.files() else {
+                return;
+            };
+            let mut truths = truths.borrow_mut();
+            for file in (0..list.length()).filter_map(|i| list.get(i)) {
+                truths.insert(stem(&file.name()).to_string(), file);
+            }
+            truth_count.set(truths.len());
+            input.set_value("");
+        }
+    };
+    let onlimit = {
+        shadow_clone!(batch);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(limit) = input.value().parse() {
+                batch.dispatch(BatchAction::Limit(limit));
+            }
+        }
+    };
+    let onpause = {
+        shadow_clone!(batch);
+        move |_| batch.dispatch(BatchAction::Pause(Pause::User))
+    };
+    let onresume = {
+        shadow_clone!(batch);
+        move |_| batch.dispatch(BatchAction::Resume)
+    };
+    let onretry = {
+        shadow_clone!(batch);
+        move |_| batch.dispatch(BatchAction::RetryFailed)
+    };
+    let onremove = {
+        shadow_clone!(batch);
+        move |_| batch.dispatch(BatchAction::RemoveDone)
+    };
+    let onexport = {
+        shadow_clone!(batch, exporting, language);
+        move |_| {
+            shadow_clone!(batch, exporting);
+            exporting.set(true);
+            spawn_local(async move {
+                match export(&batch).await {
+                    Ok(0) => {}
+                    Ok(missing) => notify::warn(
+                        language.t("batch-export-incomplete"),
+                        language.t_args("batch-export-missing", &[("count", &missing)]),
+                    ),
+                    Err(why) => notify::error(language.t("batch-export-failed"), why),
+                }
+                exporting.set(false);
+            });
+        }
+    };
+
+    let pruned = settings
+        .keep_results
+        .filter(|&keep| (keep as usize) < counts.done + counts.waiting + counts.running);
+
+    html! {
+        <>
+            <div class="d-flex flex-wrap align-items-center gap-2 mb-2">
+                <label class="btn btn-primary mb-0">
+                    {language.t("batch-add")}
+                    <input type="file" class="d-none" accept="image/*" multiple={true} onchange={onadd} />
+                </label>
+                if flags::enabled(Flag::Evaluation) {
+                    <label class="btn btn-outline-primary mb-0">
+                        {language.t("batch-add-truths")}
+                        <input type="file" class="d-none" accept="image/*" multiple={true} onchange={ontruths} />
+                    </label>
+                }
+                if batch.paused.is_some() {
+                    <button class="btn btn-outline-secondary" onclick={onresume}>
+                        {language.t("batch-resume")}
+                    </button>
+                } else {
+                    <button
+                        class="btn btn-outline-secondary"
+                        onclick={onpause}
+                        disabled={counts.waiting + counts.running == 0}
+                    >
+                        {language.t("batch-pause")}
+                    </button>
+                }
+                <button class="btn btn-outline-secondary" onclick={onretry} disabled={counts.failed == 0}>
+                    {language.t("batch-retry-failed")}
+                </button>
+                <button
+                    class="btn btn-outline-secondary"
+                    onclick={onexport}
+                    disabled={counts.done == 0 || *exporting}
+                >
+                    {language.t("batch-export")}
+                </button>
+                <button class="btn btn-outline-secondary" onclick={onremove} disabled={counts.done == 0}>
+                    {language.t("batch-remove-done")}
+                </button>
+                <div class="input-group w-auto ms-auto">
+                    <label class="input-group-text" for="batch-limit">{language.t("batch-limit")}</label>
+                    <input
+                        id="batch-limit"
+                        class="form-control"
+                        style="width: 5em;"
+                        type="number"
+                        min="1"
+                        value={batch.limit.to_string()}
+                        onchange={onlimit}
+                    />
+                </div>
+            </div>
+            {
+                match batch.paused {
+                    Some(Pause::User) => html! {
+                        <div class="alert alert-secondary">{language.t("batch-paused")}</div>
+                    },
+                    Some(Pause::Connection) => html! {
+                        <div class="alert alert-warning">{language.t("batch-connection-lost")}</div>
+                    },
+                    None => html!(),
+                }
+            }
+            if *truth_count > 0 {
+                <p class="text-body-secondary">
+                    {language.t_args("batch-truths", &[("count", &*truth_count)])}
+                </p>
+            }
+            if let Some(keep) = pruned {
+                <div class="alert alert-warning">
+                    {language.t_args("batch-history-limit", &[("count", &keep)])}
+                </div>
+            }
+            if batch.items.is_empty() {
+                <p class="text-body-secondary">{language.t("batch-empty")}</p>
+            } else {
+                <p role="status">
+                    {language.t_args("batch-progress", &[
+                        ("done", &counts.done),
+                        ("failed", &counts.failed),
+                        ("waiting", &(counts.waiting + counts.running)),
+                    ])}
+                    if let Some(ms) = batch.remaining_ms() {
+                        {" "}
+                        {language.t_args("batch-remaining", &[("time", &format_duration(ms / 1000.0))])}
+                    }
+                </p>
+                <table class="table table-sm align-middle">
+                    <thead>
+                        <tr>
+                            <th scope="col">{language.t("batch-image")}</th>
+                            <th scope="col">{language.t("batch-status")}</th>
+                            <th scope="col">{language.t("batch-time")}</th>
+                            <th scope="col">{language.t("batch-result")}</th>
+                        </tr>
+                    </thead>
+                    <tbody>
+                        {for batch.items.iter().map(|item| html! {
+                            <BatchRow key={item.id} item={item.clone()} now={*now} />
+                        })}
+                    </tbody>
+                </table>
+                if let Some(evaluation) = batch.evaluation() {
+                    <h2 class="h5">{language.t("batch-evaluation")}</h2>
+                    <MetricsTable evaluation={Rc::new(evaluation)} />
+                }
+            }
+        </>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::GrayImage;
+    use std::rc::Rc;
+    use yew::Reducible;
+
+    use super::{mask_names, Batch, BatchAction, Pause, Status};
+    use crate::{
+        clock::{self, Replay},
+        evaluation::evaluate,
+    };
+
+    fn batch(limit: usize, images: usize) -> Rc<Batch> {
+        clock::install(Replay::new(0.0, 1.0));
+        let images = (0..images).map(|id| (id, format!("{id}.tif"))).collect();
+        Rc::new(Batch::new(limit)).reduce(BatchAction::Add(images))
+    }
+
+    #[test]
+    fn images_are_sent_up_to_the_limit_unless_paused() {
+        let batch = batch(2, 4);
+        assert_eq!(batch.startable(), [0, 1]);
+
+        let batch = batch.reduce(BatchAction::Start(0));
+        assert_eq!(batch.startable(), [1]);
+
+        let batch = batch.reduce(BatchAction::Pause(Pause::User));
+        assert!(batch.startable().is_empty());
+
+        let batch = batch
+            .reduce(BatchAction::Resume)
+            .reduce(BatchAction::Limit(3));
+        assert_eq!(batch.startable(), [1, 2]);
+    }
+
+    #[test]
+    fn connection_losses_requeue_the_image_and_pause() {
+        let batch = batch(1, 2)
+            .reduce(BatchAction::Start(0))
+            .reduce(BatchAction::Requeue(0));
+
+        assert!(batch.items[0].status == Status::Waiting);
+        assert_eq!(batch.paused, Some(Pause::Connection));
+        assert_eq!(batch.reduce(BatchAction::Resume).startable(), [0]);
+    }
+
+    #[test]
+    fn failed_images_can_be_retried() {
+        let batch = batch(1, 2)
+            .reduce(BatchAction::Start(0))
+            .reduce(BatchAction::Finish(0, Err("Server error".to_string())));
+        assert_eq!(batch.counts().failed, 1);
+        assert_eq!(batch.startable(), [1]);
+
+        let batch = batch.reduce(BatchAction::RetryFailed);
+        assert_eq!(batch.counts().failed, 0);
+        assert_eq!(batch.startable(), [0]);
+    }
+
+    #[test]
+    fn remaining_time_goes_by_the_images_done() {
+        let mut batch = (*batch(2, 4)).clone();
+        assert_eq!(batch.remaining_ms(), None);
+        batch.items[0].status = Status::Done {
+            ms: 1000.0,
+            result: 1.0,
+            thumbnail: None,
+            evaluation: None,
+        };
+        batch.items[1].status = Status::Done {
+            ms: 3000.0,
+            result: 2.0,
+            thumbnail: None,
+            evaluation: None,
+        };
+        // Two images left, two at a time, two seconds each.
+        assert_eq!(batch.remaining_ms(), Some(2000.0));
+    }
+
+    #[test]
+    fn reports_quote_names_and_errors_that_need_it() {
+        let mut batch = (*batch(1, 2)).clone();
+        batch.items[0].status = Status::Done {
+            ms: 1500.0,
+            result: 7.0,
+            thumbnail: None,
+            // Background missed on one pixel of two, where it was taken for the class 1.
+            evaluation: Some(Ok(Rc::new(
+                evaluate(
+                    &GrayImage::from_raw(2, 1, vec![1, 1]).unwrap(),
+                    &GrayImage::from_raw(2, 1, vec![0, 1]).unwrap(),
+                )
+                .unwrap(),
+            ))),
+        };
+        batch.items[1].name = "field, north.tif".to_string();
+        batch.items[1].status = Status::Failed {
+            ms: 200.0,
+            error: "Server said \"no\"".to_string(),
+        };
+
+        assert_eq!(
+            batch.report(),
+            "image,status,seconds,result,mean_iou,error\n\
+             0.tif,done,1.5,7,0.2500,\n\
+             \"field, north.tif\",failed,0.2,,,\"Server said \"\"no\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn masks_are_named_after_their_images() {
+        let results = [
+            ("a.tif".to_string(), "mask.png".to_string()),
+            ("a.tif".to_string(), "mask.png".to_string()),
+            ("b".to_string(), "b_mask".to_string()),
+        ];
+        assert_eq!(
+            mask_names(&results),
+            ["masks/a.png", "masks/a-2.png", "masks/b.png"]
+        );
+    }
+}
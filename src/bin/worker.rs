@@ -0,0 +1,15 @@
+//! The web worker the app hands its encoding and compositing to, built by Trunk next to the
+//! app.
+
+use gloo::worker::Registrable;
+
+#[path = "../codec.rs"]
+mod codec;
+// Only mosaicking is used here.
+#[allow(dead_code, unused_imports)]
+#[path = "../geo/mod.rs"]
+mod geo;
+
+fn main() {
+    codec::Encoder::registrar().register();
+}
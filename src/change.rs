@@ -0,0 +1,112 @@
+//! Change detection between segmentations of the same area on two dates.
+
+use shadow_clone::shadow_clone;
+use std::rc::Rc;
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    decoded,
+    diff::{diff_masks, DiffSummary},
+    history::HistoryEntry,
+    i18n::use_language,
+    segments::SegmentsPane,
+    uploader::UploadPane,
+    FileDetails,
+};
+
+/// Compares the segmentations of two dates, either uploaded here or, through `results`,
+/// two results taken from the history.
+#[autoprops_component(ChangePane)]
+pub fn change_pane(
+    #[prop_or_default] results: Option<(Rc<HistoryEntry>, Rc<HistoryEntry>)>,
+) -> Html {
+    let language = use_language();
+    let before_image = use_state(|| Rc::new(None::<FileDetails>));
+    let after_image = use_state(|| Rc::new(None::<FileDetails>));
+    let before_mask = use_state(|| None::<Rc<FileDetails>>);
+    let after_mask = use_state(|| None::<Rc<FileDetails>>);
+    // Masks of the results from the history, shown without asking the server again.
+    let before_known = use_state(|| None::<Rc<FileDetails>>);
+    let after_known = use_state(|| None::<Rc<FileDetails>>);
+
+    {
+        shadow_clone!(before_image, after_image, before_known, after_known);
+        use_effect_with(results.clone(), move |results| {
+            let Some((before, after)) = results else {
+                return;
+            };
+            before_known.set(Some(before.mask.clone()));
+            after_known.set(Some(after.mask.clone()));
+            before_image.set(Rc::new(Some((*before.image).clone())));
+            after_image.set(Rc::new(Some((*after.image).clone())));
+        });
+    }
+
+    let date_column = |title: String,
+                       image: &UseStateHandle<Rc<Option<FileDetails>>>,
+                       mask: &UseStateHandle<Option<Rc<FileDetails>>>,
+                       known: &UseStateHandle<Option<Rc<FileDetails>>>| {
+        let onupload = {
+            shadow_clone!(image, mask, known);
+            move |newdata| {
+                mask.set(None);
+                known.set(None);
+                image.set(newdata);
+            }
+        };
+        let onresult = {
+            shadow_clone!(mask);
+            move |result| mask.set(result)
+        };
+        html! {
+            <div class="col">
+                <h2>{title}</h2>
+                <UploadPane {onupload} />
+                <SegmentsPane
+                    image_data={(**image).clone()}
+                    known_mask={(**known).clone()}
+                    {onresult}
+                />
+            </div>
+        }
+    };
+
+    // Pixels turning from background into a class are new (e.g. buildings), pixels of a
+    // class turning into background are lost (e.g. vegetation).
+    let change = use_memo(
+        ((*before_mask).clone(), (*after_mask).clone()),
+        |(before, after)| {
+            let (before, after) = (before.as_ref()?, after.as_ref()?);
+            Some(
+                decoded::mask(&before.data)
+                    .and_then(|a| decoded::mask(&after.data).map(|b| (a, b)))
+                    .and_then(|(a, b)| diff_masks(&a, &b))
+                    .map(Rc::new),
+            )
+        },
+    );
+
+    let summary = match *change {
+        None => html!(<p>{language.t("change-empty")}</p>),
+        Some(Err(ref why)) => html!(<div class="alert alert-danger" role="alert">{why}</div>),
+        Some(Ok(ref change)) => {
+            html!(<DiffSummary
+                diff={change.clone()}
+                before={language.t("change-before")}
+                after={language.t("change-after")}
+            />)
+        }
+    };
+
+    html! {
+        <div>
+            <div class="row">
+                {date_column(language.t("change-before"), &before_image, &before_mask, &before_known)}
+                {date_column(language.t("change-after"), &after_image, &after_mask, &after_known)}
+            </div>
+            <h2>{language.t("change-heading")}</h2>
+            {summary}
+        </div>
+    }
+}
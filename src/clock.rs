@@ -0,0 +1,120 @@
+//! Where the app takes the time and request ids from. They come from the browser unless
+//! another source is installed: tests install a [`Replay`] to get the same times and ids on
+//! every run, and so does a visit with `?replay=` in the address, so that a session against
+//! the mock backend can be run again exactly.
+
+use js_sys::{Date, Math};
+use std::cell::RefCell;
+use web_sys::UrlSearchParams;
+
+pub trait Source {
+    /// Milliseconds since the Unix epoch.
+    fn now(&mut self) -> f64;
+    /// A new id to tell one request from the others in logs on both sides.
+    fn request_id(&mut self) -> String;
+}
+
+/// The browser's clock, and random ids.
+pub struct Browser;
+
+impl Source for Browser {
+    fn now(&mut self) -> f64 {
+        Date::now()
+    }
+
+    fn request_id(&mut self) -> String {
+        let random = || (Math::random() * f64::from(u32::MAX)) as u32;
+        format!("{:08x}{:08x}", random(), random())
+    }
+}
+
+/// A clock starting at `start` and moving on by `step_ms` each time it is read, and ids
+/// counting up from 1.
+pub struct Replay {
+    now: f64,
+    step_ms: f64,
+    requests: u64,
+}
+
+impl Replay {
+    pub fn new(start: f64, step_ms: f64) -> Self {
+        Replay {
+            now: start,
+            step_ms,
+            requests: 0,
+        }
+    }
+}
+
+impl Source for Replay {
+    fn now(&mut self) -> f64 {
+        let now = self.now;
+        self.now += self.step_ms;
+        now
+    }
+
+    fn request_id(&mut self) -> String {
+        self.requests += 1;
+        format!("replay-{:06}", self.requests)
+    }
+}
+
+thread_local! {
+    static SOURCE: RefCell<Box<dyn Source>> = RefCell::new(Box::new(Browser));
+}
+
+/// Takes times and ids from `source` from now on.
+pub fn install(source: impl Source + 'static) {
+    SOURCE.with(|current| *current.borrow_mut() = Box::new(source));
+}
+
+/// Milliseconds since the Unix epoch.
+pub fn now() -> f64 {
+    SOURCE.with(|source| source.borrow_mut().now())
+}
+
+/// A new id for a request, sent along as `X-Request-Id`.
+pub fn request_id() -> String {
+    SOURCE.with(|source| source.borrow_mut().request_id())
+}
+
+/// Installs a [`Replay`] clock when the address asks for one with `?replay=<start>`, the
+/// time in milliseconds since the Unix epoch the session pretends to start at.
+pub fn init() {
+    let search = gloo::utils::window()
+        .location()
+        .search()
+        .unwrap_or_default();
+    let start = UrlSearchParams::new_with_str(&search)
+        .ok()
+        .and_then(|params| params.get("replay"))
+        .and_then(|start| start.parse().ok());
+    if let Some(start) = start {
+        log::info!("Replaying with a clock starting at {start}");
+        install(Replay::new(start, 1.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{install, now, request_id, Replay};
+
+    #[test]
+    fn replays_give_the_same_times_and_ids() {
+        let run = || {
+            install(Replay::new(1_700_000_000_000.0, 250.0));
+            (now(), now(), request_id(), request_id())
+        };
+        let first = run();
+        assert_eq!(
+            first,
+            (
+                1_700_000_000_000.0,
+                1_700_000_000_250.0,
+                "replay-000001".to_string(),
+                "replay-000002".to_string()
+            )
+        );
+        assert_eq!(run(), first);
+    }
+}
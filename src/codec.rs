@@ -0,0 +1,80 @@
+//! Encoding and compositing heavy enough to freeze the page, run in a web worker instead.
+//! This module is also compiled into the worker itself, `src/bin/worker.rs`, so it only
+//! uses other crates and `geo`, which the worker compiles too.
+
+use gloo::worker::oneshot::oneshot;
+use image::{
+    codecs::png::{CompressionType, FilterType, PngEncoder},
+    ImageOutputFormat,
+};
+use serde::{Deserialize, Serialize};
+use std::{io::Cursor, rc::Rc};
+
+use crate::geo::{self, Georeference};
+
+/// Bytes are shared with the app until the job is sent, so waiting jobs cost no copies.
+#[derive(Serialize, Deserialize)]
+pub enum EncodeJob {
+    /// `data`, decoded and shrunk to fit `size` pixels.
+    Thumbnail { data: Rc<[u8]>, size: u32 },
+    /// `data`, decoded and encoded again at full size, for formats browsers do not show.
+    /// Compression is kept light, since the PNG is only shown, never stored.
+    Png { data: Rc<[u8]> },
+    /// Georeferenced chips stitched and blended by [`geo::mosaic`].
+    Mosaic {
+        chips: Vec<(Rc<[u8]>, Georeference)>,
+    },
+    /// Map tiles stitched by [`geo::stitch_tiles`].
+    Stitch {
+        tiles: Vec<(Rc<[u8]>, i64, i64)>,
+        width: u32,
+        height: u32,
+        polygon: Vec<(f64, f64)>,
+    },
+}
+
+/// Runs an [`EncodeJob`], answering with a PNG, or why there is none, such as images that
+/// cannot be decoded.
+#[oneshot]
+pub async fn Encoder(job: EncodeJob) -> Result<Vec<u8>, String> {
+    let decode = |data: &[u8]| {
+        image::load_from_memory(data).map_err(|e| format!("Could not decode the image: {e}"))
+    };
+    let mut png = Vec::new();
+    match job {
+        EncodeJob::Thumbnail { data, size } => decode(&data)?
+            .thumbnail(size, size)
+            .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+            .map_err(|e| format!("Could not encode the thumbnail: {e}"))?,
+        EncodeJob::Png { data } => {
+            let encoder = PngEncoder::new_with_quality(
+                Cursor::new(&mut png),
+                CompressionType::Fast,
+                FilterType::NoFilter,
+            );
+            decode(&data)?
+                .write_with_encoder(encoder)
+                .map_err(|e| format!("Could not encode the image: {e}"))?
+        }
+        EncodeJob::Mosaic { chips } => {
+            let chips: Vec<_> = chips
+                .iter()
+                .map(|(data, georef)| (&data[..], *georef))
+                .collect();
+            png = geo::mosaic(&chips)?.0;
+        }
+        EncodeJob::Stitch {
+            tiles,
+            width,
+            height,
+            polygon,
+        } => {
+            let tiles: Vec<_> = tiles
+                .iter()
+                .map(|(data, x, y)| (&data[..], *x, *y))
+                .collect();
+            png = geo::stitch_tiles(&tiles, width, height, &polygon)?;
+        }
+    }
+    Ok(png)
+}
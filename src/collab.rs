@@ -0,0 +1,619 @@
+//! Reviewing a result together: everyone who opened the same result, by the id the server
+//! gave it, sees the others' review decisions and comments, and the pointers of those who
+//! share theirs over the mask, relayed by the session endpoint named in `config.json`.
+
+use gloo::{
+    events::EventListener,
+    storage::{LocalStorage, Storage},
+};
+use serde::{Deserialize, Serialize};
+use shadow_clone::shadow_clone;
+use std::{collections::BTreeMap, rc::Rc};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, HtmlInputElement, MessageEvent, WebSocket};
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    clock, config,
+    history::{self, decision_badge, decision_label},
+    i18n::{use_language, Language},
+    metadata::{self, Decision, Review},
+    notify,
+    state::{use_app_state, AppAction},
+    throttle::use_throttled,
+};
+
+/// Key of the name users go by in review sessions, shared by all workspaces.
+const NAME_KEY: &str = "review-session-name";
+/// Least time between two pointer positions sent.
+const POINTER_INTERVAL_MS: u32 = 100;
+
+/// The name users go by in review sessions and comments, empty until they give one.
+pub fn user_name() -> String {
+    LocalStorage::get::<String>(NAME_KEY).unwrap_or_default()
+}
+
+pub fn set_user_name(name: &str) {
+    if let Err(why) = LocalStorage::set(NAME_KEY, name) {
+        log::warn!("Could not save the name: {why}");
+    }
+}
+
+/// Someone's review of the result, as sent to the others.
+#[derive(Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+struct SharedReview {
+    /// Name of the decision, see [`Decision::name`].
+    decision: Option<String>,
+    comment: String,
+}
+
+impl SharedReview {
+    fn decision(&self) -> Option<Decision> {
+        self.decision.as_deref().and_then(Decision::parse)
+    }
+
+    fn from_stored(review: &Review) -> Self {
+        SharedReview {
+            decision: Some(review.decision.name().to_string()),
+            comment: review.comment.clone(),
+        }
+    }
+
+    /// The review to store in the history, once a decision was made.
+    fn to_stored(&self, timestamp: f64) -> Option<Review> {
+        Some(Review {
+            decision: self.decision()?,
+            comment: self.comment.clone(),
+            timestamp,
+        })
+    }
+}
+
+/// The key the result the server stored as `result_id` is kept under in the history, the
+/// newest one should it have been kept twice.
+async fn stored_result(result_id: &str) -> Result<Option<f64>, String> {
+    Ok(metadata::ids_with_result_id(result_id)
+        .await?
+        .into_iter()
+        .max()
+        .map(|id| id as f64))
+}
+
+/// The review stored in the history for the result the server stored as `result_id`, if
+/// any.
+async fn stored_review(result_id: &str) -> Result<Option<Review>, String> {
+    let Some(id) = stored_result(result_id).await? else {
+        return Ok(None);
+    };
+    Ok(metadata::get(id).await?.and_then(|stored| stored.review))
+}
+
+/// What is sent through the session endpoint, which passes it on to everyone else who
+/// opened the same result.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum SessionMessage {
+    /// Someone is in the session: sent on joining and, as a `reply`, to those who join.
+    Presence {
+        peer: String,
+        name: String,
+        review: SharedReview,
+        reply: bool,
+    },
+    Review {
+        peer: String,
+        review: SharedReview,
+    },
+    /// Where someone points on the mask, in fractions of its width and height, if on it.
+    Pointer {
+        peer: String,
+        at: Option<(f64, f64)>,
+    },
+    Left {
+        peer: String,
+    },
+}
+
+#[derive(Clone, Default, PartialEq, Debug)]
+struct Peer {
+    name: String,
+    review: SharedReview,
+    pointer: Option<(f64, f64)>,
+}
+
+impl Peer {
+    fn name(&self, language: Language) -> String {
+        if self.name.is_empty() {
+            language.t("collab-anonymous")
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+/// The others in a session, by their peer id.
+#[derive(Clone, Default, PartialEq, Debug)]
+struct Peers(BTreeMap<String, Peer>);
+
+enum PeersAction {
+    Message(SessionMessage),
+    /// Everyone left, as the session was left or lost.
+    Clear,
+}
+
+impl Reducible for Peers {
+    type Action = PeersAction;
+
+    fn reduce(self: Rc<Self>, action: PeersAction) -> Rc<Self> {
+        let mut peers = (*self).clone();
+        match action {
+            PeersAction::Message(SessionMessage::Presence {
+                peer, name, review, ..
+            }) => {
+                let peer = peers.0.entry(peer).or_default();
+                peer.name = name;
+                peer.review = review;
+            }
+            PeersAction::Message(SessionMessage::Review { peer, review }) => {
+                peers.0.entry(peer).or_default().review = review;
+            }
+            PeersAction::Message(SessionMessage::Pointer { peer, at }) => {
+                peers.0.entry(peer).or_default().pointer = at;
+            }
+            PeersAction::Message(SessionMessage::Left { peer }) => {
+                peers.0.remove(&peer);
+            }
+            PeersAction::Clear => peers.0.clear(),
+        }
+        Rc::new(peers)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+enum SessionStatus {
+    Connecting,
+    Open,
+    /// The session was lost, and why, as far as the browser tells.
+    Lost(String),
+}
+
+/// Sends `message` through `socket`, once it is open.
+fn send(socket: &WebSocket, message: &SessionMessage) {
+    if socket.ready_state() != WebSocket::OPEN {
+        return;
+    }
+    let sent = serde_json::to_string(message)
+        .map_err(|e| e.to_string())
+        .and_then(|text| socket.send_with_str(&text).map_err(|e| format!("{e:?}")));
+    if let Err(why) = sent {
+        log::warn!("Could not send to the review session: {why}");
+    }
+}
+
+/// An open connection to the session endpoint, closed once dropped.
+struct Connection {
+    socket: WebSocket,
+    _listeners: [EventListener; 3],
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let _ = self.socket.close();
+    }
+}
+
+/// `children`, the mask of the result the server stored as `result_id`, with a session to
+/// review it along with everyone else who opened it. Pointers shared by the others are
+/// shown over `children`. The review starts as stored in the history, and decisions made
+/// in the session are stored there, just as when reviewing the history.
+#[autoprops_component(ReviewSession)]
+pub fn review_session(result_id: AttrValue, children: Children) -> Html {
+    let language = use_language();
+    let peer = use_state(clock::request_id);
+    let name = use_state(user_name);
+    let review = use_state(SharedReview::default);
+    let joined = use_state(|| false);
+    let status = use_state(|| SessionStatus::Connecting);
+    let share_pointer = use_state(|| false);
+    let peers = use_reducer(Peers::default);
+    let connection = use_mut_ref(|| None::<Connection>);
+    // What the others are told on joining, kept current for the socket's listeners.
+    let me = use_mut_ref(|| (String::new(), SharedReview::default()));
+    *me.borrow_mut() = ((*name).clone(), (*review).clone());
+    let state = use_app_state();
+
+    {
+        shadow_clone!(review, me);
+        use_effect_with(result_id.clone(), move |result_id| {
+            shadow_clone!(result_id);
+            spawn_local(async move {
+                match stored_review(&result_id).await {
+                    // Unless a decision was made meanwhile.
+                    Ok(Some(stored)) if me.borrow().1 == SharedReview::default() => {
+                        review.set(SharedReview::from_stored(&stored))
+                    }
+                    Ok(_) => {}
+                    Err(why) => log::warn!("Could not read the stored review: {why}"),
+                }
+            });
+        });
+    }
+
+    {
+        shadow_clone!(peer, status, peers, connection, me);
+        use_effect_with((result_id.clone(), *joined), move |(result_id, joined)| {
+            let url = config::current().review_session_url.clone();
+            if let (Some(url), true) = (url, *joined) {
+                status.set(SessionStatus::Connecting);
+                let address = format!(
+                    "{url}?result={}",
+                    String::from(js_sys::encode_uri_component(result_id))
+                );
+                match WebSocket::new(&address) {
+                    Ok(socket) => {
+                        let presence = {
+                            shadow_clone!(peer, me);
+                            move |reply| {
+                                let (name, review) = me.borrow().clone();
+                                SessionMessage::Presence {
+                                    peer: (*peer).clone(),
+                                    name,
+                                    review,
+                                    reply,
+                                }
+                            }
+                        };
+                        let onopen = {
+                            shadow_clone!(socket, status, presence);
+                            EventListener::new(&socket.clone(), "open", move |_| {
+                                status.set(SessionStatus::Open);
+                                send(&socket, &presence(false));
+                            })
+                        };
+                        let onmessage = {
+                            shadow_clone!(socket, peers);
+                            EventListener::new(&socket.clone(), "message", move |e| {
+                                let e: &MessageEvent = e.unchecked_ref();
+                                let Some(text) = e.data().as_string() else {
+                                    return;
+                                };
+                                match serde_json::from_str::<SessionMessage>(&text) {
+                                    Ok(message) => {
+                                        if let SessionMessage::Presence { reply: false, .. } =
+                                            message
+                                        {
+                                            send(&socket, &presence(true));
+                                        }
+                                        peers.dispatch(PeersAction::Message(message));
+                                    }
+                                    Err(why) => {
+                                        log::warn!("Ignoring a review session message: {why}")
+                                    }
+                                }
+                            })
+                        };
+                        let onclose = {
+                            shadow_clone!(status, peers);
+                            EventListener::new(&socket, "close", move |_| {
+                                status.set(SessionStatus::Lost(language.t("collab-closed")));
+                                peers.dispatch(PeersAction::Clear);
+                            })
+                        };
+                        *connection.borrow_mut() = Some(Connection {
+                            socket,
+                            _listeners: [onopen, onmessage, onclose],
+                        });
+                    }
+                    Err(e) => status.set(SessionStatus::Lost(format!("{e:?}"))),
+                }
+            }
+            move || {
+                if let Some(connection) = connection.borrow_mut().take() {
+                    send(
+                        &connection.socket,
+                        &SessionMessage::Left {
+                            peer: (*peer).clone(),
+                        },
+                    );
+                }
+                peers.dispatch(PeersAction::Clear);
+            }
+        });
+    }
+
+    let send_now = {
+        shadow_clone!(connection);
+        move |message: SessionMessage| {
+            if let Some(connection) = &*connection.borrow() {
+                send(&connection.socket, &message);
+            }
+        }
+    };
+    let send_pointer = use_throttled(
+        Callback::from({
+            shadow_clone!(peer, send_now);
+            move |at: Option<(f64, f64)>| {
+                send_now(SessionMessage::Pointer {
+                    peer: (*peer).clone(),
+                    at,
+                })
+            }
+        }),
+        POINTER_INTERVAL_MS,
+    );
+
+    let onmousemove = {
+        shadow_clone!(share_pointer, send_pointer);
+        move |e: MouseEvent| {
+            if !*share_pointer {
+                return;
+            }
+            let Some(area) = e
+                .current_target()
+                .and_then(|t| t.dyn_into::<Element>().ok())
+            else {
+                return;
+            };
+            let rect = area.get_bounding_client_rect();
+            send_pointer.emit(Some((
+                (e.client_x() as f64 - rect.left()) / rect.width().max(1.0),
+                (e.client_y() as f64 - rect.top()) / rect.height().max(1.0),
+            )));
+        }
+    };
+    let onmouseleave = {
+        shadow_clone!(share_pointer, send_pointer);
+        move |_| {
+            if *share_pointer {
+                send_pointer.emit(None);
+            }
+        }
+    };
+
+    let onname = {
+        shadow_clone!(name);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let value = input.value().trim().to_string();
+            set_user_name(&value);
+            name.set(value);
+        }
+    };
+    let onjoin = {
+        shadow_clone!(joined);
+        move |_| joined.set(!*joined)
+    };
+    let change_review = |change: fn(&mut SharedReview, String)| {
+        shadow_clone!(review, peer, send_now, state, result_id);
+        move |value: String| {
+            let mut changed = (*review).clone();
+            change(&mut changed, value);
+            send_now(SessionMessage::Review {
+                peer: (*peer).clone(),
+                review: changed.clone(),
+            });
+            // Only decisions are stored, so taking one back clears the stored review.
+            let stored = changed.to_stored(clock::now());
+            if stored.is_some() || review.decision().is_some() {
+                shadow_clone!(state, result_id);
+                spawn_local(async move {
+                    let saved = match stored_result(&result_id).await {
+                        Ok(Some(id)) => history::save_review(id, stored).await,
+                        Ok(None) => Err(language.t("collab-not-stored")),
+                        Err(why) => Err(why),
+                    };
+                    match saved {
+                        Ok(()) => state.dispatch(AppAction::HistoryChanged),
+                        Err(why) => notify::error(language.t("collab-save-failed"), why),
+                    }
+                });
+            }
+            review.set(changed);
+        }
+    };
+    let decide = change_review(|review, name| {
+        review.decision = (review.decision.as_deref() != Some(&name)).then_some(name)
+    });
+    let oncomment = {
+        let comment = change_review(|review, comment| review.comment = comment);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            comment(input.value());
+        }
+    };
+    let onsharepointer = {
+        shadow_clone!(share_pointer, send_pointer);
+        move |_| {
+            if *share_pointer {
+                send_pointer.emit(None);
+            }
+            share_pointer.set(!*share_pointer);
+        }
+    };
+
+    let pointers = peers.0.values().filter_map(|peer| {
+        let (x, y) = peer.pointer?;
+        Some(html! {
+            <div
+                class="position-absolute small text-nowrap"
+                style={format!("left: {:.2}%; top: {:.2}%; pointer-events: none;", x * 100.0, y * 100.0)}
+                aria-hidden="true"
+            >
+                {"▲ "}<span class="badge text-bg-info">{peer.name(language)}</span>
+            </div>
+        })
+    });
+
+    html! {
+        <>
+            <div class="position-relative" {onmousemove} {onmouseleave}>
+                {children.clone()}
+                {for pointers}
+            </div>
+            <div class="border rounded p-2 mt-2 d-print-none">
+                <div class="d-flex align-items-center gap-2">
+                    <strong>{language.t("collab-heading")}</strong>
+                    if *joined {
+                        <small class="text-body-secondary" role="status">
+                            {match &*status {
+                                SessionStatus::Connecting => language.t("collab-connecting"),
+                                SessionStatus::Open => {
+                                    language.t_args("collab-others", &[("count", &peers.0.len())])
+                                }
+                                SessionStatus::Lost(why) => {
+                                    language.t_args("collab-lost", &[("reason", why)])
+                                }
+                            }}
+                        </small>
+                    } else {
+                        <input
+                            class="form-control form-control-sm w-auto"
+                            placeholder={language.t("collab-name")}
+                            aria-label={language.t("collab-name-label")}
+                            value={(*name).clone()}
+                            onchange={onname}
+                        />
+                    }
+                    <button class="btn btn-sm btn-outline-primary ms-auto" onclick={onjoin}>
+                        {language.t(if *joined { "collab-leave" } else { "collab-join" })}
+                    </button>
+                </div>
+                if *joined {
+                    <ul class="list-unstyled small my-2">
+                        {for peers.0.values().map(|peer| html! {
+                            <li>
+                                <strong>{peer.name(language)}</strong>
+                                if let Some(decision) = peer.review.decision() {
+                                    {decision_badge(language, decision)}
+                                }
+                                if !peer.review.comment.is_empty() {
+                                    <span class="text-body-secondary ms-1">{&peer.review.comment}</span>
+                                }
+                            </li>
+                        })}
+                    </ul>
+                    <div class="input-group input-group-sm">
+                        {for Decision::ALL.into_iter().map(|decision| {
+                            let onclick = {
+                                shadow_clone!(decide);
+                                move |_| decide(decision.name().to_string())
+                            };
+                            let active = review.decision() == Some(decision);
+                            html! {
+                                <button
+                                    class={classes!("btn", if active { "btn-secondary" } else { "btn-outline-secondary" })}
+                                    aria-pressed={active.to_string()}
+                                    {onclick}
+                                >
+                                    {decision_label(language, decision)}
+                                </button>
+                            }
+                        })}
+                        <input
+                            class="form-control"
+                            placeholder={language.t("collab-comment")}
+                            value={review.comment.clone()}
+                            onchange={oncomment}
+                        />
+                    </div>
+                    <div class="form-check form-switch mt-1">
+                        <input
+                            class="form-check-input"
+                            type="checkbox"
+                            id="share-pointer"
+                            checked={*share_pointer}
+                            onchange={onsharepointer}
+                        />
+                        <label class="form-check-label small" for="share-pointer">
+                            {language.t("collab-share-pointer")}
+                        </label>
+                    </div>
+                }
+            </div>
+        </>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use yew::Reducible;
+
+    use super::{Peers, PeersAction, SessionMessage, SharedReview};
+    use crate::{
+        i18n::Language,
+        metadata::{Decision, Review},
+    };
+
+    #[test]
+    fn messages_are_tagged_by_type() {
+        let message: SessionMessage =
+            serde_json::from_str(r#"{"type": "pointer", "peer": "a1", "at": [0.5, 0.25]}"#)
+                .unwrap();
+        assert_eq!(
+            message,
+            SessionMessage::Pointer {
+                peer: "a1".to_string(),
+                at: Some((0.5, 0.25)),
+            }
+        );
+        let left = serde_json::to_value(SessionMessage::Left {
+            peer: "a1".to_string(),
+        })
+        .unwrap();
+        assert_eq!(left["type"], "left");
+    }
+
+    #[test]
+    fn peers_follow_presence_reviews_and_departures() {
+        let message = |message| PeersAction::Message(message);
+        let peers = Rc::new(Peers::default())
+            .reduce(message(SessionMessage::Presence {
+                peer: "a1".to_string(),
+                name: "Ana".to_string(),
+                review: SharedReview::default(),
+                reply: false,
+            }))
+            .reduce(message(SessionMessage::Review {
+                peer: "a1".to_string(),
+                review: SharedReview {
+                    decision: Some("needs-edit".to_string()),
+                    comment: "road cut off".to_string(),
+                },
+            }))
+            .reduce(message(SessionMessage::Pointer {
+                peer: "b2".to_string(),
+                at: Some((0.1, 0.2)),
+            }));
+
+        let ana = &peers.0["a1"];
+        assert_eq!(ana.name(Language::En), "Ana");
+        assert_eq!(ana.review.decision(), Some(Decision::NeedsEdit));
+        // Pointers may come before the presence of whoever moves them.
+        assert_eq!(peers.0["b2"].name(Language::En), "Anonymous");
+
+        let peers = peers.reduce(message(SessionMessage::Left {
+            peer: "a1".to_string(),
+        }));
+        assert_eq!(peers.0.keys().collect::<Vec<_>>(), ["b2"]);
+    }
+
+    #[test]
+    fn reviews_are_stored_once_decided() {
+        let undecided = SharedReview {
+            decision: None,
+            comment: "looking".to_string(),
+        };
+        assert_eq!(undecided.to_stored(5.0), None);
+
+        let stored = Review {
+            decision: Decision::Accepted,
+            comment: "fine".to_string(),
+            timestamp: 5.0,
+        };
+        let shared = SharedReview::from_stored(&stored);
+        assert_eq!(shared.decision(), Some(Decision::Accepted));
+        assert_eq!(shared.to_stored(5.0), Some(stored));
+    }
+}
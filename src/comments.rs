@@ -0,0 +1,455 @@
+//! Threads of comments anchored to a point or an area of the image of a stored result, for
+//! reviewers and the ML team to discuss specific misclassifications. Threads are kept with
+//! the result's metadata, resolved once settled, and exported with the history.
+
+use js_sys::Date;
+use shadow_clone::shadow_clone;
+use std::rc::Rc;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Element, HtmlInputElement};
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    audit::{self, AuditAction},
+    clock,
+    collab::{set_user_name, user_name},
+    i18n::{use_language, Language},
+    metadata::{self, Anchor, Comment, Reply},
+    notify,
+    state::{use_app_state, AppAction},
+    viewer::FileViewer,
+};
+
+/// A change to the comments of a result, to threads told apart by when they were started.
+#[derive(Clone, PartialEq, Debug)]
+enum Edit {
+    Start(Comment),
+    Reply(f64, Reply),
+    Resolve(f64, bool),
+}
+
+impl Edit {
+    fn apply(self, comments: &mut Vec<Comment>) {
+        match self {
+            Edit::Start(comment) => comments.push(comment),
+            Edit::Reply(thread, reply) => {
+                if let Some(comment) = comments.iter_mut().find(|c| c.timestamp == thread) {
+                    comment.replies.push(reply);
+                }
+            }
+            Edit::Resolve(thread, resolved) => {
+                if let Some(comment) = comments.iter_mut().find(|c| c.timestamp == thread) {
+                    comment.resolved = resolved;
+                }
+            }
+        }
+    }
+}
+
+/// Makes `edit` to the comments of the result `id`, reading its metadata afresh so that the
+/// rest of it is kept, and answers with the comments as stored.
+async fn change(id: u64, edit: Edit) -> Result<Vec<Comment>, String> {
+    let details = match &edit {
+        Edit::Start(comment) => format!(
+            "new thread on the {}",
+            comment.anchor.describe(Language::En)
+        ),
+        Edit::Reply(..) => "reply".to_string(),
+        Edit::Resolve(_, true) => "resolved a thread".to_string(),
+        Edit::Resolve(_, false) => "reopened a thread".to_string(),
+    };
+    let mut stored = metadata::get(id as f64).await?.unwrap_or_default();
+    edit.apply(&mut stored.comments);
+    metadata::put(id as f64, &stored).await?;
+    audit::record(AuditAction::Comment, format!("result {id}"), details);
+    Ok(stored.comments)
+}
+
+/// `name`, or what those who gave none go by.
+fn author(language: Language, name: &str) -> String {
+    if name.is_empty() {
+        language.t("collab-anonymous")
+    } else {
+        name.to_string()
+    }
+}
+
+fn date(ms: f64) -> String {
+    Date::new(&ms.into())
+        .to_locale_string("default", &JsValue::UNDEFINED)
+        .into()
+}
+
+/// Where `e` is over the image within the element it is listened on, in fractions of the
+/// image's width and height as it is shown, zoomed or not, or `None` when it is not over it.
+fn position(e: &MouseEvent) -> Option<(f64, f64)> {
+    let area: Element = e.current_target()?.dyn_into().ok()?;
+    let rect = area.query_selector("img").ok()??.get_bounding_client_rect();
+    let at = (
+        (e.client_x() as f64 - rect.left()) / rect.width().max(1.0),
+        (e.client_y() as f64 - rect.top()) / rect.height().max(1.0),
+    );
+    ((0.0..=1.0).contains(&at.0) && (0.0..=1.0).contains(&at.1)).then_some(at)
+}
+
+/// The input named `name` in the form `e` submits.
+fn field(e: &SubmitEvent, name: &str) -> Option<HtmlInputElement> {
+    let form: Element = e.target_unchecked_into();
+    form.query_selector(&format!("input[name={name}]"))
+        .ok()??
+        .dyn_into()
+        .ok()
+}
+
+/// `anchor` drawn over the image, numbered `label`.
+fn marker(anchor: Anchor, label: String, class: &'static str) -> Html {
+    match anchor {
+        Anchor::Point { x, y } => html! {
+            <span
+                class={classes!("position-absolute", "badge", "rounded-pill", class)}
+                style={format!(
+                    "left: {:.2}%; top: {:.2}%; transform: translate(-50%, -50%); pointer-events: none;",
+                    x * 100.0,
+                    y * 100.0
+                )}
+            >
+                {label}
+            </span>
+        },
+        Anchor::Area {
+            left,
+            top,
+            right,
+            bottom,
+        } => html! {
+            <div
+                class={classes!("position-absolute", "border", "border-2", class)}
+                style={format!(
+                    "left: {:.2}%; top: {:.2}%; width: {:.2}%; height: {:.2}%; \
+                     --bs-bg-opacity: 0.15; pointer-events: none;",
+                    left * 100.0,
+                    top * 100.0,
+                    (right - left) * 100.0,
+                    (bottom - top) * 100.0
+                )}
+            >
+                <span class={classes!("badge", class)}>{label}</span>
+            </div>
+        },
+    }
+}
+
+/// The comment threads of the result stored as `result`, over its image, the app's current
+/// one. Clicking the image starts a thread on a point, dragging over it on an area.
+#[autoprops_component(CommentsPane)]
+pub fn comments_pane(result: u64) -> Html {
+    let language = use_language();
+    let state = use_app_state();
+    let comments = use_state(|| None::<Result<Vec<Comment>, String>>);
+    // Where a drag started and where it is now.
+    let drag = use_state(|| None::<((f64, f64), (f64, f64))>);
+    // Where the thread being written is anchored.
+    let draft = use_state(|| None::<Anchor>);
+    let selected = use_state(|| None::<f64>);
+    let show_resolved = use_state(|| false);
+
+    {
+        shadow_clone!(comments);
+        use_effect_with(result, move |&result| {
+            spawn_local(async move {
+                comments.set(Some(
+                    metadata::get(result as f64)
+                        .await
+                        .map(|stored| stored.unwrap_or_default().comments),
+                ));
+            });
+        });
+    }
+
+    let save = {
+        shadow_clone!(comments, state);
+        Callback::from(move |edit: Edit| {
+            shadow_clone!(comments, state);
+            spawn_local(async move {
+                match change(result, edit).await {
+                    Ok(stored) => {
+                        comments.set(Some(Ok(stored)));
+                        state.dispatch(AppAction::HistoryChanged);
+                    }
+                    Err(why) => notify::error(language.t("comments-save-failed"), why),
+                }
+            });
+        })
+    };
+
+    let Some(image) = (*state.image).clone() else {
+        return html!();
+    };
+    let comments = match &*comments {
+        None => return html!(<span class="spinner-border text-success"></span>),
+        Some(Err(why)) => return html!(<div class="alert alert-danger" role="alert">{why}</div>),
+        Some(Ok(comments)) => comments,
+    };
+
+    let onmousedown = {
+        shadow_clone!(drag);
+        move |e: MouseEvent| {
+            if let Some(at) = position(&e) {
+                e.prevent_default();
+                drag.set(Some((at, at)));
+            }
+        }
+    };
+    let onmousemove = {
+        shadow_clone!(drag);
+        move |e: MouseEvent| {
+            if let (Some((start, _)), Some(at)) = (*drag, position(&e)) {
+                drag.set(Some((start, at)));
+            }
+        }
+    };
+    let onmouseup = {
+        shadow_clone!(drag, draft);
+        move |e: MouseEvent| {
+            if let Some((start, end)) = *drag {
+                draft.set(Some(Anchor::from_drag(start, position(&e).unwrap_or(end))));
+            }
+            drag.set(None);
+        }
+    };
+    let onmouseleave = {
+        shadow_clone!(drag);
+        move |_| drag.set(None)
+    };
+
+    let oncomment = {
+        shadow_clone!(draft, save, selected);
+        move |e: SubmitEvent| {
+            e.prevent_default();
+            let value = |name| {
+                field(&e, name)
+                    .map(|input| input.value().trim().to_string())
+                    .unwrap_or_default()
+            };
+            let (text, author) = (value("text"), value("author"));
+            let Some(anchor) = *draft else {
+                return;
+            };
+            if text.is_empty() {
+                return;
+            }
+            set_user_name(&author);
+            let comment = Comment {
+                timestamp: clock::now(),
+                anchor,
+                author,
+                text,
+                replies: vec![],
+                resolved: false,
+            };
+            selected.set(Some(comment.timestamp));
+            save.emit(Edit::Start(comment));
+            draft.set(None);
+        }
+    };
+    let oncanceldraft = {
+        shadow_clone!(draft);
+        move |_| draft.set(None)
+    };
+    let ontoggleresolved = {
+        shadow_clone!(show_resolved);
+        move |_| show_resolved.set(!*show_resolved)
+    };
+
+    let shown: Vec<(usize, &Comment)> = comments
+        .iter()
+        .enumerate()
+        .filter(|(_, comment)| *show_resolved || !comment.resolved)
+        .collect();
+    let resolved = comments.iter().filter(|comment| comment.resolved).count();
+    let dragged = (*drag).map(|(start, end)| Anchor::from_drag(start, end));
+
+    let threads = shown.iter().map(|&(i, comment)| {
+        let thread = comment.timestamp;
+        let onselect = {
+            shadow_clone!(selected);
+            move |_| selected.set(Some(thread))
+        };
+        let onresolve = {
+            shadow_clone!(save);
+            let resolved = !comment.resolved;
+            move |_| {
+                save.emit(Edit::Resolve(thread, resolved))
+            }
+        };
+        let onreply = {
+            shadow_clone!(save);
+            move |e: SubmitEvent| {
+                e.prevent_default();
+                let Some(input) = field(&e, "reply") else {
+                    return;
+                };
+                let text = input.value().trim().to_string();
+                if text.is_empty() {
+                    return;
+                }
+                input.set_value("");
+                let reply = Reply {
+                    author: user_name(),
+                    text,
+                    timestamp: clock::now(),
+                };
+                save.emit(Edit::Reply(thread, reply))
+            }
+        };
+        let active = *selected == Some(thread);
+        html! {
+            <li class={classes!("list-group-item", active.then_some("list-group-item-warning"))}>
+                <div class="d-flex align-items-center gap-2" role="button" onclick={onselect}>
+                    <span class="badge text-bg-info">{i + 1}</span>
+                    <small class="text-body-secondary">{comment.anchor.describe(language)}</small>
+                    if comment.resolved {
+                        <span class="badge text-bg-success">{language.t("comments-resolved")}</span>
+                    }
+                </div>
+                <p class="mb-1">
+                    <strong>{author(language, &comment.author)}</strong>
+                    <small class="text-body-secondary ms-1">{date(comment.timestamp)}</small>
+                    <br />
+                    {&comment.text}
+                </p>
+                {for comment.replies.iter().map(|reply| html! {
+                    <p class="mb-1 ms-3 small">
+                        <strong>{author(language, &reply.author)}</strong>
+                        <span class="text-body-secondary ms-1">{date(reply.timestamp)}</span>
+                        <br />
+                        {&reply.text}
+                    </p>
+                })}
+                <form class="input-group input-group-sm" onsubmit={onreply}>
+                    <input
+                        class="form-control"
+                        name="reply"
+                        placeholder={language.t("comments-reply")}
+                        aria-label={language.t("comments-reply")}
+                    />
+                    <button class="btn btn-outline-secondary" type="submit">
+                        {language.t("comments-reply")}
+                    </button>
+                    <button class="btn btn-outline-success" type="button" onclick={onresolve}>
+                        {language.t(if comment.resolved { "comments-reopen" } else { "comments-resolve" })}
+                    </button>
+                </form>
+            </li>
+        }
+    });
+
+    html! {
+        <div class="row g-2">
+            <div class="col-12 col-md-8">
+                // Listened on around the viewer, as its overlay lets events through to the
+                // image for it to be zoomed.
+                <div
+                    style="cursor: crosshair;"
+                    title={language.t("comments-hint")}
+                    {onmousedown}
+                    {onmousemove}
+                    {onmouseup}
+                    {onmouseleave}
+                >
+                    <FileViewer file={Rc::new(image)}>
+                        {for shown.iter().map(|&(i, comment)| {
+                            let class = if *selected == Some(comment.timestamp) {
+                                "text-bg-warning"
+                            } else {
+                                "text-bg-info"
+                            };
+                            marker(comment.anchor, (i + 1).to_string(), class)
+                        })}
+                        if let Some(anchor) = dragged.or(*draft) {
+                            {marker(anchor, "+".to_string(), "text-bg-primary")}
+                        }
+                    </FileViewer>
+                </div>
+            </div>
+            <div class="col-12 col-md-4">
+                if let Some(anchor) = *draft {
+                    <form class="mb-2" onsubmit={oncomment}>
+                        <small class="text-body-secondary">{language.t_args("comments-new-thread", &[("anchor", &anchor.describe(language))])}</small>
+                        <input
+                            class="form-control form-control-sm mb-1"
+                            name="author"
+                            placeholder={language.t("collab-name")}
+                            aria-label={language.t("collab-name")}
+                            value={user_name()}
+                        />
+                        <input
+                            class="form-control form-control-sm mb-1"
+                            name="text"
+                            placeholder={language.t("comments-question")}
+                            aria-label={language.t("comments-comment")}
+                        />
+                        <div class="d-flex gap-1">
+                            <button class="btn btn-sm btn-primary" type="submit">{language.t("comments-comment")}</button>
+                            <button class="btn btn-sm btn-outline-secondary" type="button" onclick={oncanceldraft}>
+                                {language.t("comments-cancel")}
+                            </button>
+                        </div>
+                    </form>
+                } else if comments.is_empty() {
+                    <p class="small text-body-secondary">
+                        {language.t("comments-empty")}
+                    </p>
+                }
+                if resolved > 0 {
+                    <button class="btn btn-sm btn-link p-0 mb-1" onclick={ontoggleresolved}>
+                        {language.t_args(
+                            if *show_resolved { "comments-hide-resolved" } else { "comments-show-resolved" },
+                            &[("count", &resolved)],
+                        )}
+                    </button>
+                }
+                <ul class="list-group">
+                    {for threads}
+                </ul>
+            </div>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Edit;
+    use crate::metadata::{Anchor, Comment, Reply};
+
+    fn thread(timestamp: f64) -> Comment {
+        Comment {
+            timestamp,
+            anchor: Anchor::Point { x: 0.5, y: 0.5 },
+            author: "Ana".to_string(),
+            text: "Roof taken for a road".to_string(),
+            replies: vec![],
+            resolved: false,
+        }
+    }
+
+    #[test]
+    fn edits_reach_the_thread_they_name() {
+        let mut comments = vec![thread(1.0)];
+        Edit::Start(thread(2.0)).apply(&mut comments);
+        let reply = Reply {
+            author: "ML".to_string(),
+            text: "Known issue".to_string(),
+            timestamp: 3.0,
+        };
+        Edit::Reply(2.0, reply.clone()).apply(&mut comments);
+        Edit::Resolve(2.0, true).apply(&mut comments);
+        // Threads deleted meanwhile are left alone.
+        Edit::Resolve(9.0, true).apply(&mut comments);
+
+        assert_eq!(comments[0], thread(1.0));
+        assert_eq!(comments[1].replies, [reply]);
+        assert!(comments[1].resolved);
+    }
+}
@@ -0,0 +1,372 @@
+//! Deployment configuration, read once at startup from the `config.json` served next to
+//! `index.html`, so the same build can be deployed to several environments.
+
+use gloo::net::http::Request;
+use serde::Deserialize;
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use crate::{
+    flags,
+    map::{SourceKind, TileSource, WMS_CRSS},
+    roles::Role,
+};
+
+const CONFIG_URL: &str = "config.json";
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Segmentation server used until users set one of their own.
+    pub server_url: String,
+    /// Servers offered as candidates when comparing models on the same image.
+    pub models: Vec<ModelConfig>,
+    /// Basemaps offered on the map, the first one being shown.
+    pub tile_sources: Vec<TileSourceConfig>,
+    /// Feature flags by name, see [`flags::Flag`].
+    pub features: BTreeMap<String, bool>,
+    pub branding: Branding,
+    /// Collector usage statistics are sent to by users who opt in, none meaning they
+    /// are not offered.
+    pub telemetry_url: Option<String>,
+    /// Endpoint crash reports are posted to, none meaning they are not sent.
+    pub crash_report_url: Option<String>,
+    /// Endpoint results flagged for review are reported to, none meaning they are only
+    /// kept in the browser.
+    pub issue_report_url: Option<String>,
+    /// WebSocket endpoint relaying review sessions between users who opened the same
+    /// result, none meaning results are reviewed alone.
+    pub review_session_url: Option<String>,
+    /// Endpoint every recorded user action is posted to, none meaning they are only kept
+    /// in the browser.
+    pub audit_log_url: Option<String>,
+    /// What users may do, see [`roles::Role`], unless their access token says otherwise.
+    /// Admin when missing, so that deployments from before roles keep working, but viewer
+    /// when misspelled, so that a typo never grants more than was meant.
+    pub role: String,
+    /// Notes for whoever edits the file, as JSON has no comments. Ignored.
+    #[serde(rename = "$comment")]
+    pub comment: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            server_url: env!("SERVER_URL").to_string(),
+            models: Vec::new(),
+            tile_sources: Vec::new(),
+            features: BTreeMap::new(),
+            branding: Branding::default(),
+            telemetry_url: None,
+            crash_report_url: None,
+            issue_report_url: None,
+            review_session_url: None,
+            audit_log_url: None,
+            role: Role::Admin.name().to_string(),
+            comment: None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Branding {
+    /// Shown as the page title and next to the logo.
+    pub title: String,
+    pub logo_url: Option<String>,
+    /// CSS custom properties of the organization theme by name, such as `--bs-primary`.
+    pub colors: BTreeMap<String, String>,
+}
+
+impl Default for Branding {
+    fn default() -> Self {
+        Branding {
+            title: "Infrastructure recognition".to_string(),
+            logo_url: None,
+            colors: BTreeMap::new(),
+        }
+    }
+}
+
+/// A segmentation server standing for a model, under the name users know it by.
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ModelConfig {
+    pub name: String,
+    pub server_url: String,
+}
+
+#[derive(Clone, PartialEq, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase", deny_unknown_fields)]
+pub enum TileSourceConfig {
+    Xyz {
+        name: String,
+        url_template: String,
+    },
+    Wms {
+        name: String,
+        url: String,
+        layers: String,
+        #[serde(default = "default_wms_crs")]
+        crs: String,
+    },
+}
+
+fn default_wms_crs() -> String {
+    "EPSG:3857".to_string()
+}
+
+impl TileSourceConfig {
+    fn name(&self) -> &str {
+        match self {
+            TileSourceConfig::Xyz { name, .. } | TileSourceConfig::Wms { name, .. } => name,
+        }
+    }
+
+    fn to_source(&self) -> TileSource {
+        let kind = match self.clone() {
+            TileSourceConfig::Xyz { url_template, .. } => SourceKind::Xyz { url_template },
+            TileSourceConfig::Wms {
+                url, layers, crs, ..
+            } => SourceKind::Wms { url, layers, crs },
+        };
+        TileSource {
+            name: self.name().to_string(),
+            kind,
+            auth: None,
+        }
+    }
+}
+
+fn is_http_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+impl Config {
+    /// Replaces values that cannot work with their defaults, or an invalid role with the
+    /// one allowed least, returning what was wrong.
+    fn validate(&mut self) -> Vec<String> {
+        let defaults = Config::default();
+        let mut problems = Vec::new();
+        if !is_http_url(&self.server_url) {
+            problems.push(format!(
+                "server_url {:?} is not an http(s) URL",
+                self.server_url
+            ));
+            self.server_url = defaults.server_url;
+        }
+        self.server_url = self.server_url.trim_end_matches('/').to_string();
+        self.models.retain_mut(|model| {
+            if !is_http_url(&model.server_url) {
+                problems.push(format!(
+                    "Ignoring model {:?}: its server_url is not an http(s) URL",
+                    model.name
+                ));
+                return false;
+            }
+            model.server_url = model.server_url.trim_end_matches('/').to_string();
+            true
+        });
+        self.tile_sources.retain(|source| {
+            let problem = match source {
+                TileSourceConfig::Xyz { url_template, .. } => (!["{z}", "{x}", "{y}"]
+                    .iter()
+                    .all(|placeholder| url_template.contains(placeholder)))
+                .then_some("its url_template lacks {z}, {x} or {y}"),
+                TileSourceConfig::Wms {
+                    url, layers, crs, ..
+                } => {
+                    if !is_http_url(url) {
+                        Some("its url is not an http(s) URL")
+                    } else if layers.is_empty() {
+                        Some("it names no layers")
+                    } else if !WMS_CRSS.contains(&crs.as_str()) {
+                        Some("its crs is neither EPSG:3857 nor EPSG:4326")
+                    } else {
+                        None
+                    }
+                }
+            };
+            if let Some(problem) = problem {
+                problems.push(format!(
+                    "Ignoring tile source {:?}: {problem}",
+                    source.name()
+                ));
+            }
+            problem.is_none()
+        });
+        for name in flags::unknown(&self.features) {
+            problems.push(format!("Ignoring unknown feature flag {name:?}"));
+        }
+        if let Some(url) = self.telemetry_url.take_if(|url| !is_http_url(url)) {
+            problems.push(format!("telemetry_url {url:?} is not an http(s) URL"));
+        }
+        if let Some(url) = self.audit_log_url.take_if(|url| !is_http_url(url)) {
+            problems.push(format!("audit_log_url {url:?} is not an http(s) URL"));
+        }
+        if let Some(url) = self
+            .review_session_url
+            .take_if(|url| !url.starts_with("ws://") && !url.starts_with("wss://"))
+        {
+            problems.push(format!("review_session_url {url:?} is not a ws(s) URL"));
+        }
+        if Role::parse(&self.role).is_none() {
+            problems.push(format!(
+                "role {:?} is not viewer, editor or admin",
+                self.role
+            ));
+            self.role = Role::Viewer.name().to_string();
+        }
+        self.branding.colors.retain(|name, value| {
+            let valid = name.starts_with("--")
+                && !value.trim().is_empty()
+                && !value.contains([';', '{', '}']);
+            if !valid {
+                problems.push(format!("Ignoring branding color {name:?}: {value:?}"));
+            }
+            valid
+        });
+        if self.branding.title.trim().is_empty() {
+            problems.push("branding.title is empty".to_string());
+            self.branding.title = defaults.branding.title;
+        }
+        problems
+    }
+
+    /// Basemaps to offer, OpenStreetMap when none are configured.
+    pub fn tile_sources(&self) -> Vec<TileSource> {
+        if self.tile_sources.is_empty() {
+            return vec![TileSource::osm()];
+        }
+        self.tile_sources
+            .iter()
+            .map(TileSourceConfig::to_source)
+            .collect()
+    }
+}
+
+thread_local! {
+    static CONFIG: RefCell<Rc<Config>> = RefCell::new(Rc::new(Config::default()));
+}
+
+/// The configuration loaded at startup, or the defaults before that.
+pub fn current() -> Rc<Config> {
+    CONFIG.with(|config| config.borrow().clone())
+}
+
+/// Reads `config.json`, keeping the defaults for anything it lacks or gets wrong, and
+/// makes it the current configuration. Returns what was wrong with it, if anything.
+pub async fn load() -> Result<(), String> {
+    let (config, result) = match fetch().await {
+        Ok(Some(mut config)) => {
+            let problems = config.validate();
+            let result = if problems.is_empty() {
+                Ok(())
+            } else {
+                Err(problems.join("; "))
+            };
+            (config, result)
+        }
+        Ok(None) => (Config::default(), Ok(())),
+        Err(why) => (Config::default(), Err(why)),
+    };
+    CONFIG.with(|current| *current.borrow_mut() = Rc::new(config));
+    result
+}
+
+/// The served configuration, or `None` when there is none.
+async fn fetch() -> Result<Option<Config>, String> {
+    let response = Request::get(CONFIG_URL)
+        .send()
+        .await
+        .map_err(|e| format!("Could not fetch {CONFIG_URL}: {e}"))?;
+    if response.status() == 404 {
+        return Ok(None);
+    }
+    if !response.ok() {
+        return Err(format!(
+            "Could not fetch {CONFIG_URL}: status {}",
+            response.status()
+        ));
+    }
+    let text = response
+        .text()
+        .await
+        .map_err(|e| format!("Could not read {CONFIG_URL}: {e}"))?;
+    serde_json::from_str(&text)
+        .map(Some)
+        .map_err(|e| format!("{CONFIG_URL} is not valid: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, TileSourceConfig};
+
+    #[test]
+    fn missing_values_take_their_defaults() {
+        let config: Config = serde_json::from_str(r#"{"features": {"map": false}}"#).unwrap();
+        assert_eq!(config.server_url, Config::default().server_url);
+        assert_eq!(config.features.get("map"), Some(&false));
+        assert_eq!(config.role, "admin");
+    }
+
+    #[test]
+    fn the_example_is_valid() {
+        let mut config: Config =
+            serde_json::from_str(include_str!("../config.example.json")).unwrap();
+        assert_eq!(config.validate(), Vec::<String>::new());
+        assert_eq!(config.role, "editor");
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        assert!(serde_json::from_str::<Config>(r#"{"server": "https://a.example"}"#).is_err());
+    }
+
+    #[test]
+    fn validation_replaces_what_cannot_work() {
+        let mut config: Config = serde_json::from_str(
+            r##"{
+                "server_url": "ftp://a.example/",
+                "models": [
+                    {"name": "Current", "server_url": "https://m.example/v2/"},
+                    {"name": "Local", "server_url": "localhost:5000"}
+                ],
+                "tile_sources": [
+                    {"type": "xyz", "name": "Good", "url_template": "https://t.example/{z}/{x}/{y}.png"},
+                    {"type": "xyz", "name": "Bad", "url_template": "https://t.example/tile.png"},
+                    {"type": "wms", "name": "Local", "url": "https://w.example", "layers": "a", "crs": "EPSG:32637"}
+                ],
+                "features": {"teleport": true},
+                "telemetry_url": "collector",
+                "review_session_url": "https://s.example/sessions",
+                "role": "owner",
+                "branding": {"colors": {"--bs-primary": "#0055a4", "color": "red; x: y"}}
+            }"##,
+        )
+        .unwrap();
+
+        let problems = config.validate();
+
+        assert_eq!(problems.len(), 9);
+        assert_eq!(config.server_url, Config::default().server_url);
+        assert_eq!(
+            config
+                .models
+                .iter()
+                .map(|m| &m.server_url[..])
+                .collect::<Vec<_>>(),
+            ["https://m.example/v2"]
+        );
+        assert!(matches!(
+            config.tile_sources.as_slice(),
+            [TileSourceConfig::Xyz { name, .. }] if name == "Good"
+        ));
+        assert_eq!(config.telemetry_url, None);
+        assert_eq!(config.review_session_url, None);
+        assert_eq!(config.role, "viewer");
+        assert_eq!(
+            config.branding.colors.keys().collect::<Vec<_>>(),
+            ["--bs-primary"]
+        );
+    }
+}
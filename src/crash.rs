@@ -0,0 +1,213 @@
+//! Recovery from crashes. A panic cannot be caught in WebAssembly: it stops the app in the
+//! middle of whatever it was doing, leaving a page that no longer answers. So instead of
+//! failing silently, the panic hook covers the page with a card saying what broke and where,
+//! with the log to download and a button to reload. The session was autosaved, and is
+//! offered back after the reload. Deployments naming a crash report endpoint in
+//! `config.json` are sent what broke, with the last action and a summary of the state
+//! that tell nothing of the images or servers used.
+
+use serde::Serialize;
+use std::{any::Any, cell::RefCell, panic::PanicHookInfo};
+use wasm_bindgen::JsCast;
+use web_sys::{Document, Element, HtmlElement};
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use crate::{config, debuglog, i18n};
+
+/// What the app was doing, as last told by the state.
+#[derive(Default)]
+struct Context {
+    last_action: &'static str,
+    state: String,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Context> = RefCell::new(Context::default());
+}
+
+/// Keeps the name of the last action the state handled, for crash reports.
+pub fn note_action(name: &'static str) {
+    CONTEXT.with(|context| context.borrow_mut().last_action = name);
+}
+
+/// Keeps `summary` of the state, for crash reports.
+pub fn note_state(summary: String) {
+    CONTEXT.with(|context| context.borrow_mut().state = summary);
+}
+
+/// What is posted to the crash report endpoint.
+#[derive(Serialize)]
+struct Report<'a> {
+    app_version: &'static str,
+    message: &'a str,
+    location: Option<String>,
+    region: Option<String>,
+    last_action: &'a str,
+    state: &'a str,
+}
+
+/// Posts `report` to the deployment's endpoint, answering whether the browser took it.
+/// A beacon, since the app has stopped and cannot wait for an answer.
+fn send_report(report: &Report) -> bool {
+    let Some(url) = config::current().crash_report_url.clone() else {
+        return false;
+    };
+    let Ok(body) = serde_json::to_string(report) else {
+        return false;
+    };
+    gloo::utils::window()
+        .navigator()
+        .send_beacon_with_opt_str(&url, Some(&body))
+        .unwrap_or(false)
+}
+
+/// Marks a part of the page, so that a crash can say which part users were working in.
+#[autoprops_component(ErrorBoundary)]
+pub fn error_boundary(
+    name: AttrValue,
+    children: Children,
+    #[prop_or_default] class: Classes,
+) -> Html {
+    html! {
+        <section class={class.clone()} data-region={name.clone()} aria-label={name.clone()}>
+            {children.clone()}
+        </section>
+    }
+}
+
+/// What the panic said, for the few payloads panics carry.
+fn message(payload: &dyn Any) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+/// The name of the region users were last working in, found from the focused element.
+fn current_region(document: &Document) -> Option<String> {
+    document
+        .active_element()?
+        .closest("[data-region]")
+        .ok()??
+        .get_attribute("data-region")
+}
+
+/// Appends a new `tag` element to `parent`, with `class` and `text`.
+fn append(parent: &Element, tag: &str, class: &str, text: &str) -> Option<Element> {
+    let element = parent.owner_document()?.create_element(tag).ok()?;
+    element.set_class_name(class);
+    if !text.is_empty() {
+        element.set_text_content(Some(text));
+    }
+    parent.append_child(&element).ok()?;
+    Some(element)
+}
+
+/// Covers the page with the recovery card, built without Yew since it stopped with the app.
+/// It tells whether the crash was `reported`.
+fn show_recovery_card(details: &str, reported: bool) -> Option<()> {
+    let document = gloo::utils::document();
+    let title = match current_region(&document) {
+        Some(region) => i18n::t_args("crash-title-region", &[("region", &region)]),
+        None => i18n::t("crash-title"),
+    };
+
+    let body: Element = document.body()?.into();
+    let overlay = append(&body, "div", "modal d-block", "")?;
+    overlay.set_attribute("role", "alertdialog").ok()?;
+    overlay.set_attribute("aria-modal", "true").ok()?;
+    overlay
+        .set_attribute("style", "background: rgba(0, 0, 0, 0.5);")
+        .ok()?;
+    let dialog = append(&overlay, "div", "modal-dialog modal-dialog-centered", "")?;
+    let content = append(&dialog, "div", "modal-content", "")?;
+    let card = append(&content, "div", "modal-body", "")?;
+    append(&card, "h5", "", &title)?;
+    append(&card, "p", "", &i18n::t("crash-kept"))?;
+    if reported {
+        append(
+            &card,
+            "p",
+            "small text-body-secondary",
+            &i18n::t("crash-reported"),
+        )?;
+    }
+    let summary = append(&card, "details", "mb-3", "")?;
+    append(&summary, "summary", "", &i18n::t("crash-details"))?;
+    append(&summary, "pre", "small mb-0", details)?;
+
+    let buttons = append(&card, "div", "d-flex gap-2", "")?;
+    let reload = append(
+        &buttons,
+        "button",
+        "btn btn-primary",
+        &i18n::t("crash-reload"),
+    )?;
+    reload.set_attribute("type", "button").ok()?;
+    reload
+        .set_attribute("onclick", "window.location.reload()")
+        .ok()?;
+    // The link carries the log itself, since nothing can be computed once the app stopped.
+    let download = append(
+        &buttons,
+        "a",
+        "btn btn-outline-secondary",
+        &i18n::t("crash-download-log"),
+    )?;
+    let log = format!("{}{details}\n", debuglog::text());
+    let href = format!(
+        "data:text/plain;charset=utf-8,{}",
+        js_sys::encode_uri_component(&log)
+    );
+    download.set_attribute("href", &href).ok()?;
+    download
+        .set_attribute("download", "segmentation-crash.txt")
+        .ok()?;
+    reload.dyn_into::<HtmlElement>().ok()?.focus().ok()
+}
+
+/// Shows the recovery card on panics. Called once, first thing at startup after the log.
+pub fn install() {
+    std::panic::set_hook(Box::new(|info: &PanicHookInfo| {
+        let message = message(info.payload());
+        let location = info.location().map(ToString::to_string);
+        let details = match &location {
+            Some(location) => format!("{message} at {location}"),
+            None => message.to_string(),
+        };
+        log::error!("The app crashed: {details}");
+        let reported = CONTEXT.with(|context| {
+            // A panic while the context is being noted leaves it borrowed.
+            let Ok(context) = context.try_borrow() else {
+                return false;
+            };
+            send_report(&Report {
+                app_version: env!("CARGO_PKG_VERSION"),
+                message,
+                location,
+                region: current_region(&gloo::utils::document()),
+                last_action: context.last_action,
+                state: &context.state,
+            })
+        });
+        if show_recovery_card(&details, reported).is_none() {
+            log::error!("Could not show the recovery card");
+        }
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::message;
+
+    #[test]
+    fn panic_messages_are_read_from_either_payload() {
+        assert_eq!(message(&"index out of bounds"), "index out of bounds");
+        assert_eq!(message(&String::from("formatted 3")), "formatted 3");
+        assert_eq!(message(&3), "unknown panic");
+    }
+}
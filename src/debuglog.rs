@@ -0,0 +1,255 @@
+//! The app's log, kept in memory as well as written to the console, so that users can copy
+//! or download it for a bug report without opening the browser's developer tools.
+
+use gloo::console;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::{cell::RefCell, collections::VecDeque, fmt::Write};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+use crate::{clock, i18n::use_language, notify, project::download};
+
+/// Records kept, the oldest being dropped first.
+const CAPACITY: usize = 1_000;
+
+#[derive(Clone, PartialEq, Debug)]
+struct Entry {
+    /// Milliseconds since the Unix epoch.
+    timestamp: f64,
+    level: Level,
+    target: String,
+    message: String,
+}
+
+thread_local! {
+    static ENTRIES: RefCell<VecDeque<Entry>> = const { RefCell::new(VecDeque::new()) };
+    static LISTENER: RefCell<Option<Callback<()>>> = const { RefCell::new(None) };
+}
+
+struct Logger;
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Debug
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let entry = Entry {
+            timestamp: clock::now(),
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        };
+        let line = format!("{} {}: {}", entry.level, entry.target, entry.message);
+        match entry.level {
+            Level::Error => console::error!(line),
+            Level::Warn => console::warn!(line),
+            Level::Info => console::info!(line),
+            Level::Debug | Level::Trace => console::debug!(line),
+        }
+        ENTRIES.with(|entries| {
+            let mut entries = entries.borrow_mut();
+            if entries.len() == CAPACITY {
+                entries.pop_front();
+            }
+            entries.push_back(entry);
+        });
+        if let Some(listener) = LISTENER.with(|listener| listener.borrow().clone()) {
+            listener.emit(());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Installs the logger. Called once, first thing at startup.
+pub fn init() {
+    if log::set_logger(&Logger).is_ok() {
+        log::set_max_level(LevelFilter::Debug);
+    }
+}
+
+/// `timestamp` as the UTC time of day, to the millisecond.
+fn time_of_day(timestamp: f64) -> String {
+    let ms = timestamp.rem_euclid(86_400_000.0) as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1_000 % 60,
+        ms % 1_000
+    )
+}
+
+/// The entries at `level` or more severe, one per line.
+fn format(entries: &[Entry], level: Level) -> String {
+    let mut text = String::new();
+    for entry in entries.iter().filter(|entry| entry.level <= level) {
+        let _ = writeln!(
+            text,
+            "{} {:<5} {}: {}",
+            time_of_day(entry.timestamp),
+            entry.level,
+            entry.target,
+            entry.message
+        );
+    }
+    text
+}
+
+/// What a bug report needs besides the log: the app's version and the browser it ran in.
+fn header() -> String {
+    let window = gloo::utils::window();
+    format!(
+        "{} {}\n{}\n{}\n\n",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        window.navigator().user_agent().unwrap_or_default(),
+        window.location().href().unwrap_or_default(),
+    )
+}
+
+/// The whole kept log, for a bug report. Records being written as this is called are left
+/// out, which only happens when logging itself failed.
+pub fn text() -> String {
+    let entries: Vec<Entry> = ENTRIES.with(|entries| {
+        entries
+            .try_borrow()
+            .map(|entries| entries.iter().cloned().collect())
+            .unwrap_or_default()
+    });
+    header() + &format(&entries, Level::Debug)
+}
+
+/// The kept log, filtered by level, with buttons to copy or download it.
+#[function_component(DebugLogPanel)]
+pub fn debug_log_panel() -> Html {
+    let language = use_language();
+    let level = use_state(|| Level::Info);
+    let open = use_state(|| false);
+    let update = use_force_update();
+
+    // Only an open panel follows new records, so that logging stays cheap otherwise.
+    use_effect_with(*open, move |open| {
+        if *open {
+            LISTENER.with(|listener| {
+                *listener.borrow_mut() = Some(Callback::from(move |_| update.force_update()))
+            });
+        }
+        || LISTENER.with(|listener| *listener.borrow_mut() = None)
+    });
+
+    let ontoggle = {
+        let open = open.clone();
+        move |e: Event| {
+            let details: web_sys::HtmlDetailsElement = e.target_unchecked_into();
+            open.set(details.open());
+        }
+    };
+    let onlevel = {
+        let level = level.clone();
+        move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Ok(picked) = select.value().parse() {
+                level.set(picked);
+            }
+        }
+    };
+
+    let entries: Vec<Entry> = ENTRIES.with(|entries| entries.borrow().iter().cloned().collect());
+    let text = format(&entries, *level);
+    let oncopy = {
+        let text = text.clone();
+        move |_| {
+            let promise = gloo::utils::window()
+                .navigator()
+                .clipboard()
+                .write_text(&(header() + &text));
+            spawn_local(async move {
+                if let Err(e) = JsFuture::from(promise).await {
+                    notify::warn(language.t("debug-log-copy-failed"), format!("{e:?}"));
+                }
+            });
+        }
+    };
+    let ondownload = {
+        let text = text.clone();
+        move |_| {
+            let file_name = format!("segmentation-log-{}.txt", clock::now() as u64);
+            download(&file_name, "text/plain", (header() + &text).as_bytes());
+        }
+    };
+
+    html! {
+        <details class="mt-3" open={*open} {ontoggle}>
+            <summary>{language.t("debug-log")}</summary>
+            <div class="d-flex align-items-center gap-2 my-2">
+                <select
+                    class="form-select form-select-sm w-auto"
+                    aria-label={language.t("debug-log-level")}
+                    onchange={onlevel}
+                >
+                    {
+                        for [Level::Error, Level::Warn, Level::Info, Level::Debug].into_iter().map(|option| html! {
+                            <option value={option.as_str()} selected={option == *level}>
+                                {option.as_str()}
+                            </option>
+                        })
+                    }
+                </select>
+                <button class="btn btn-sm btn-outline-secondary" onclick={oncopy}>
+                    {language.t("debug-log-copy")}
+                </button>
+                <button class="btn btn-sm btn-outline-secondary" onclick={ondownload}>
+                    {language.t("debug-log-download")}
+                </button>
+            </div>
+            if *open {
+                <pre class="border rounded p-2 small" style="max-height: 20em; overflow: auto;">
+                    if text.is_empty() { {language.t("debug-log-empty")} } else { {text} }
+                </pre>
+            }
+        </details>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use log::Level;
+
+    use super::{format, time_of_day, Entry};
+
+    fn entry(timestamp: f64, level: Level, message: &str) -> Entry {
+        Entry {
+            timestamp,
+            level,
+            target: "frontend::api".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn timestamps_show_the_time_of_day() {
+        // 2024-03-01T13:04:05.067Z
+        assert_eq!(time_of_day(1_709_298_245_067.0), "13:04:05.067");
+    }
+
+    #[test]
+    fn entries_below_the_level_are_left_out() {
+        let entries = [
+            entry(0.0, Level::Debug, "Sending"),
+            entry(1_000.0, Level::Warn, "Slow answer"),
+            entry(2_000.0, Level::Error, "Gave up"),
+        ];
+        assert_eq!(
+            format(&entries, Level::Warn),
+            "00:00:01.000 WARN  frontend::api: Slow answer\n\
+             00:00:02.000 ERROR frontend::api: Gave up\n"
+        );
+        assert_eq!(format(&entries, Level::Debug).lines().count(), 3);
+    }
+}
@@ -0,0 +1,145 @@
+//! Masks decoded from their PNG bytes, kept while memory allows so that going back and forth
+//! between history items or compare views does not decode the same masks again.
+
+use image::GrayImage;
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    ptr,
+    rc::{Rc, Weak},
+};
+
+use crate::diff::decode_mask;
+
+/// Masks by the bytes they were decoded from, least recently used first, taking up to
+/// `budget` bytes. Bytes no longer used anywhere can never be asked for again, so their
+/// masks are dropped whatever the budget.
+struct Lru {
+    budget: usize,
+    used: usize,
+    entries: VecDeque<(Weak<[u8]>, Rc<GrayImage>)>,
+}
+
+impl Lru {
+    fn new(budget: usize) -> Self {
+        Lru {
+            budget,
+            used: 0,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// The mask decoded from `data`, now the most recently used.
+    fn get(&mut self, data: &Rc<[u8]>) -> Option<Rc<GrayImage>> {
+        let index = self
+            .entries
+            .iter()
+            .position(|(bytes, _)| ptr::addr_eq(bytes.as_ptr(), Rc::as_ptr(data)))?;
+        let entry = self.entries.remove(index)?;
+        let mask = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(mask)
+    }
+
+    /// Keeps `mask`, decoded from `data`, dropping the least recently used masks past the
+    /// budget. Masks larger than the whole budget are not kept.
+    fn insert(&mut self, data: &Rc<[u8]>, mask: Rc<GrayImage>) {
+        self.entries.retain(|(bytes, mask)| {
+            let alive = bytes.strong_count() > 0;
+            if !alive {
+                self.used -= size(mask);
+            }
+            alive
+        });
+        if size(&mask) > self.budget {
+            return;
+        }
+        self.used += size(&mask);
+        self.entries.push_back((Rc::downgrade(data), mask));
+        self.shrink();
+    }
+
+    fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.shrink();
+    }
+
+    fn shrink(&mut self) {
+        while self.used > self.budget {
+            let Some((_, mask)) = self.entries.pop_front() else {
+                break;
+            };
+            self.used -= size(&mask);
+        }
+    }
+}
+
+fn size(mask: &GrayImage) -> usize {
+    mask.as_raw().len()
+}
+
+thread_local! {
+    static MASKS: RefCell<Lru> = RefCell::new(Lru::new(0));
+}
+
+/// Sets how many megabytes decoded masks may take, dropping those past it.
+pub fn set_budget(megabytes: u32) {
+    MASKS.with(|masks| masks.borrow_mut().set_budget((megabytes as usize) << 20));
+}
+
+/// Bytes taken by the decoded masks kept.
+pub fn usage() -> usize {
+    MASKS.with(|masks| masks.borrow().used)
+}
+
+/// Drops every decoded mask kept.
+pub fn clear() {
+    MASKS.with(|masks| {
+        let mut masks = masks.borrow_mut();
+        masks.entries.clear();
+        masks.used = 0;
+    });
+}
+
+/// The mask in `data`, decoded only if it was not already.
+pub fn mask(data: &Rc<[u8]>) -> Result<Rc<GrayImage>, String> {
+    if let Some(mask) = MASKS.with(|masks| masks.borrow_mut().get(data)) {
+        return Ok(mask);
+    }
+    let mask = Rc::new(decode_mask(data)?);
+    MASKS.with(|masks| masks.borrow_mut().insert(data, mask.clone()));
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lru;
+    use image::GrayImage;
+    use std::rc::Rc;
+
+    #[test]
+    fn least_recently_used_masks_go_first() {
+        let mask = |side| Rc::new(GrayImage::new(side, side));
+        let bytes: Vec<Rc<[u8]>> = (0..3u8).map(|i| Rc::from(vec![i])).collect();
+        let mut lru = Lru::new(20);
+        lru.insert(&bytes[0], mask(2));
+        lru.insert(&bytes[1], mask(3));
+        assert!(lru.get(&bytes[0]).is_some());
+        // 4 + 9 + 9 is past the budget, and the mask of the second bytes was used last.
+        lru.insert(&bytes[2], mask(3));
+        assert!(lru.get(&bytes[1]).is_none());
+        assert!(lru.get(&bytes[0]).is_some());
+        assert_eq!(lru.used, 13);
+
+        // Equal bytes elsewhere are other bytes.
+        assert!(lru.get(&Rc::from(vec![0])).is_none());
+        lru.set_budget(10);
+        assert!(lru.get(&bytes[2]).is_none());
+        lru.insert(&bytes[1], mask(5));
+        assert_eq!(lru.used, 4);
+
+        drop(bytes);
+        lru.insert(&Rc::from(vec![3]), mask(1));
+        assert_eq!(lru.used, 1);
+    }
+}
@@ -0,0 +1,320 @@
+use image::{DynamicImage, GrayImage, ImageOutputFormat, Rgba, RgbaImage};
+use shadow_clone::shadow_clone;
+use std::{collections::BTreeMap, io::Cursor, rc::Rc};
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    audit::{self, AuditAction},
+    decoded,
+    i18n::{self, use_language},
+    uploader::UploadPane,
+    viewer::BlobUrl,
+    FileDetails,
+};
+
+/// Pixel counts of a single class in an original mask (e.g. the model output) and in its
+/// edited or later counterpart.
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct ClassDiff {
+    pub original: u64,
+    pub edited: u64,
+    pub gained: u64,
+    pub lost: u64,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct MaskDiff {
+    pub classes: BTreeMap<u8, ClassDiff>,
+    pub changed_pixels: u64,
+    pub total_pixels: u64,
+    pub added_regions: usize,
+    pub removed_regions: usize,
+    /// PNG highlighting added (green), removed (red) and reclassified (yellow) pixels.
+    pub visualization: Vec<u8>,
+}
+
+pub fn decode_mask(data: &[u8]) -> Result<GrayImage, String> {
+    image::load_from_memory(data)
+        .map(|img| img.to_luma8())
+        .map_err(|e| i18n::t_args("diff-undecodable", &[("error", &e)]))
+}
+
+/// Labels 4-connected regions of equal class, returning the label of every pixel
+/// and the class of every label.
+pub fn label_regions(mask: &GrayImage) -> (Vec<u32>, Vec<u8>) {
+    let (w, h) = (mask.width() as usize, mask.height() as usize);
+    let pixels = mask.as_raw();
+    let mut labels = vec![u32::MAX; pixels.len()];
+    let mut classes = vec![];
+    let mut stack = vec![];
+
+    for start in 0..pixels.len() {
+        if labels[start] != u32::MAX {
+            continue;
+        }
+        let label = classes.len() as u32;
+        let class = pixels[start];
+        classes.push(class);
+        labels[start] = label;
+        stack.push(start);
+
+        while let Some(i) = stack.pop() {
+            let (x, y) = (i % w, i / w);
+            let neighbours = [
+                (x > 0).then(|| i - 1),
+                (x + 1 < w).then(|| i + 1),
+                (y > 0).then(|| i - w),
+                (y + 1 < h).then(|| i + w),
+            ];
+            for n in neighbours.into_iter().flatten() {
+                if labels[n] == u32::MAX && pixels[n] == class {
+                    labels[n] = label;
+                    stack.push(n);
+                }
+            }
+        }
+    }
+
+    (labels, classes)
+}
+
+/// Counts non-background regions of `mask` that share no pixel of the same class with `other`.
+fn unmatched_regions(mask: &GrayImage, other: &GrayImage) -> usize {
+    let (labels, classes) = label_regions(mask);
+    let mut matched = vec![false; classes.len()];
+    for (i, (a, b)) in mask.as_raw().iter().zip(other.as_raw()).enumerate() {
+        if a == b {
+            matched[labels[i] as usize] = true;
+        }
+    }
+    classes
+        .iter()
+        .zip(matched)
+        .filter(|(class, matched)| **class != 0 && !matched)
+        .count()
+}
+
+pub fn diff_masks(original: &GrayImage, edited: &GrayImage) -> Result<MaskDiff, String> {
+    if original.dimensions() != edited.dimensions() {
+        return Err(i18n::t_args(
+            "diff-size-mismatch",
+            &[
+                (
+                    "original",
+                    &format!("{}x{}", original.width(), original.height()),
+                ),
+                ("edited", &format!("{}x{}", edited.width(), edited.height())),
+            ],
+        ));
+    }
+
+    let mut classes: BTreeMap<u8, ClassDiff> = BTreeMap::new();
+    let mut changed_pixels = 0;
+    let mut visualization = RgbaImage::new(original.width(), original.height());
+
+    for ((a, b), out) in original
+        .as_raw()
+        .iter()
+        .zip(edited.as_raw())
+        .zip(visualization.pixels_mut())
+    {
+        classes.entry(*a).or_default().original += 1;
+        classes.entry(*b).or_default().edited += 1;
+
+        *out = if a == b {
+            let v = a / 3;
+            Rgba([v, v, v, 255])
+        } else {
+            changed_pixels += 1;
+            classes.entry(*a).or_default().lost += 1;
+            classes.entry(*b).or_default().gained += 1;
+            match (a, b) {
+                (0, _) => Rgba([40, 200, 70, 255]),
+                (_, 0) => Rgba([220, 50, 50, 255]),
+                _ => Rgba([240, 200, 40, 255]),
+            }
+        };
+    }
+
+    let mut png = vec![];
+    DynamicImage::ImageRgba8(visualization)
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .map_err(|e| format!("Could not encode diff image: {e}"))?;
+
+    Ok(MaskDiff {
+        classes,
+        changed_pixels,
+        total_pixels: original.as_raw().len() as u64,
+        added_regions: unmatched_regions(edited, original),
+        removed_regions: unmatched_regions(original, edited),
+        visualization: png,
+    })
+}
+
+/// Change summary, per-class table and visualization of `diff`, with `before` and `after`
+/// naming the two masks in the table header.
+#[autoprops_component(DiffSummary)]
+pub fn diff_summary(diff: Rc<MaskDiff>, before: AttrValue, after: AttrValue) -> Html {
+    let language = use_language();
+    let visualization = use_memo(diff.clone(), |diff| {
+        BlobUrl::new(&diff.visualization, "image/png")
+    });
+
+    html! {
+        <div>
+            <p>
+                {language.t_args(
+                    "diff-summary",
+                    &[
+                        ("changed", &diff.changed_pixels),
+                        ("total", &diff.total_pixels),
+                        (
+                            "percent",
+                            &format!(
+                                "{:.2}",
+                                100.0 * diff.changed_pixels as f64 / diff.total_pixels.max(1) as f64
+                            ),
+                        ),
+                        ("added", &diff.added_regions),
+                        ("removed", &diff.removed_regions),
+                    ],
+                )}
+            </p>
+            <table class="table table-sm">
+                <thead>
+                    <tr>
+                        <th>{language.t("diff-class")}</th>
+                        <th>{before.clone()}</th>
+                        <th>{after.clone()}</th>
+                        <th>{language.t("diff-gained")}</th>
+                        <th>{language.t("diff-lost")}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                {
+                    for diff.classes.iter().map(|(class, counts)| html! {
+                        <tr>
+                            <td>{class}</td>
+                            <td>{counts.original}</td>
+                            <td>{counts.edited}</td>
+                            <td>{counts.gained}</td>
+                            <td>{counts.lost}</td>
+                        </tr>
+                    })
+                }
+                </tbody>
+            </table>
+            <img
+                width={"100%"}
+                src={visualization.to_string()}
+                alt={language.t_args("diff-visualization", &[("before", &before), ("after", &after)])}
+            />
+            // Colours are named as well as shown, for readers who cannot tell them apart.
+            <ul class="list-inline small">
+                {
+                    for [
+                        ("rgb(40, 200, 70)", "diff-added"),
+                        ("rgb(220, 50, 50)", "diff-removed"),
+                        ("rgb(240, 200, 40)", "diff-reclassified"),
+                        ("rgb(85, 85, 85)", "diff-unchanged"),
+                    ]
+                    .into_iter()
+                    .map(|(color, label)| html! {
+                        <li class="list-inline-item">
+                            <span
+                                class="d-inline-block me-1 border"
+                                style={format!("width: 1em; height: 1em; background: {color};")}
+                                aria-hidden="true"
+                            ></span>
+                            {language.t(label)}
+                        </li>
+                    })
+                }
+            </ul>
+        </div>
+    }
+}
+
+#[autoprops_component(DiffPane)]
+pub fn diff_pane(original: Rc<FileDetails>) -> Html {
+    let language = use_language();
+    let edited_state: UseStateHandle<Rc<Option<FileDetails>>> = use_state(|| Rc::new(None));
+
+    let onupload = {
+        shadow_clone!(edited_state, original);
+        move |newdata: Rc<Option<FileDetails>>| {
+            if let Some(edited) = &*newdata {
+                audit::record(
+                    AuditAction::Edit,
+                    original.file_name.clone(),
+                    format!("corrected mask {}", edited.file_name),
+                );
+            }
+            edited_state.set(newdata)
+        }
+    };
+
+    let diff = use_memo(
+        (original.clone(), (*edited_state).clone()),
+        |(original, edited)| {
+            let edited = (**edited).as_ref()?;
+            Some(
+                decoded::mask(&original.data)
+                    .and_then(|a| decoded::mask(&edited.data).map(|b| (a, b)))
+                    .and_then(|(a, b)| diff_masks(&a, &b))
+                    .map(Rc::new),
+            )
+        },
+    );
+
+    let summary = match *diff {
+        None => html!(),
+        Some(Err(ref why)) => html!(
+            <div class="alert alert-danger" role="alert">{why}</div>
+        ),
+        Some(Ok(ref diff)) => {
+            html!(<DiffSummary
+                diff={diff.clone()}
+                before={language.t("diff-model")}
+                after={language.t("diff-corrected")}
+            />)
+        }
+    };
+
+    html! {
+        <div>
+            <h2>{language.t("diff-heading")}</h2>
+            <UploadPane {onupload} />
+            {summary}
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_mask, diff_masks};
+    use crate::fixtures;
+
+    #[test]
+    fn corrections_are_counted_per_pixel_and_region() {
+        let model = decode_mask(fixtures::MASK).unwrap();
+        let corrected = decode_mask(fixtures::MASK_CORRECTED).unwrap();
+
+        let diff = diff_masks(&model, &corrected).unwrap();
+
+        assert_eq!(diff.changed_pixels, 1);
+        assert_eq!(diff.total_pixels, 64);
+        assert_eq!(diff.removed_regions, 1);
+        assert_eq!(diff.added_regions, 0);
+        assert_eq!(diff.classes[&2].lost, 1);
+    }
+
+    #[test]
+    fn masks_of_different_sizes_cannot_be_compared() {
+        let model = decode_mask(fixtures::MASK).unwrap();
+        let image = decode_mask(fixtures::IMAGE).unwrap();
+        let cropped = image::imageops::crop_imm(&image, 0, 0, 4, 4).to_image();
+        assert!(diff_masks(&model, &cropped).is_err());
+    }
+}
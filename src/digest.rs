@@ -0,0 +1,53 @@
+//! SHA-256 digests of uploaded images, by which images segmented before are recognized.
+//! They are taken by the browser's Web Crypto API, which works off the main thread.
+
+use js_sys::{ArrayBuffer, Function, Promise, Reflect, Uint8Array};
+use std::fmt::Write;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+/// `bytes` in lowercase hexadecimal.
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+/// The SHA-256 digest of `data`, in hexadecimal.
+pub async fn sha256(data: &[u8]) -> Result<String, String> {
+    let error = |e: JsValue| format!("Could not hash the image: {e:?}");
+    let subtle = Reflect::get(&js_sys::global(), &"crypto".into())
+        .and_then(|crypto| Reflect::get(&crypto, &"subtle".into()))
+        .map_err(error)?;
+    if subtle.is_undefined() {
+        // Web Crypto is only there for pages served over HTTPS or from localhost.
+        return Err("Hashing needs the page to be served over HTTPS".to_string());
+    }
+    let digest: Function = Reflect::get(&subtle, &"digest".into())
+        .map_err(error)?
+        .dyn_into()
+        .map_err(error)?;
+    let promise: Promise = digest
+        .call2(&subtle, &"SHA-256".into(), &Uint8Array::from(data))
+        .map_err(error)?
+        .dyn_into()
+        .map_err(error)?;
+    let buffer: ArrayBuffer = JsFuture::from(promise)
+        .await
+        .map_err(error)?
+        .dyn_into()
+        .map_err(error)?;
+    Ok(to_hex(&Uint8Array::new(&buffer).to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::to_hex;
+
+    #[test]
+    fn digests_are_written_in_hexadecimal() {
+        assert_eq!(to_hex(&[0x00, 0x0f, 0xa5, 0xff]), "000fa5ff");
+        assert_eq!(to_hex(&[]), "");
+    }
+}
@@ -0,0 +1,362 @@
+//! What can go wrong talking to the segmentation server, and how users can recover.
+
+use std::fmt;
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    http,
+    i18n::{self, use_language},
+    route::Route,
+};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum AppError {
+    /// The server at `url` could not be reached.
+    Network { url: String, message: String },
+    /// The browser is offline; `message` tells what became of the request.
+    Offline(String),
+    /// The server at `url` took too long to answer.
+    Timeout { url: String },
+    /// The server at `url` received other bytes than those of the image sent, cut short or
+    /// altered on the way.
+    Corrupted { url: String },
+    /// The server refused the access token from the settings.
+    Auth { url: String, status: u16 },
+    /// The server answered with an error status.
+    Http {
+        url: String,
+        status: u16,
+        message: String,
+    },
+    /// The server's answer could not be read.
+    Decode(String),
+    /// The input was rejected before anything was sent.
+    Validation(String),
+    /// The request was given up on before an answer came.
+    Cancelled,
+}
+
+impl AppError {
+    /// Classifies an error from a request to `url`.
+    pub fn from_request(url: &str, e: http::Error) -> Self {
+        let url = url.to_string();
+        match e {
+            http::Error::Timeout => AppError::Timeout { url },
+            http::Error::Aborted => AppError::Cancelled,
+            http::Error::Status {
+                status: status @ (401 | 403),
+                ..
+            } => AppError::Auth { url, status },
+            http::Error::Status { status, message } => AppError::Http {
+                url,
+                status,
+                message,
+            },
+            http::Error::Decode(message) => AppError::Decode(message),
+            http::Error::Network(message) => AppError::Network { url, message },
+        }
+    }
+
+    /// Whether trying the same request again may succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            AppError::Network { .. } | AppError::Timeout { .. } | AppError::Corrupted { .. } => {
+                true
+            }
+            AppError::Http { status, .. } => *status >= 500 || *status == 429,
+            _ => false,
+        }
+    }
+
+    /// Short name of the kind of error, as reported in usage statistics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::Network { .. } => "network",
+            AppError::Offline(_) => "offline",
+            AppError::Timeout { .. } => "timeout",
+            AppError::Corrupted { .. } => "corrupted",
+            AppError::Auth { .. } => "auth",
+            AppError::Http { .. } => "http",
+            AppError::Decode(_) => "decode",
+            AppError::Validation(_) => "validation",
+            AppError::Cancelled => "cancelled",
+        }
+    }
+
+    /// What went wrong as it was reported, for users to pass on when asking for help.
+    pub fn details(&self) -> String {
+        let mut lines = vec![format!("kind: {}", self.kind())];
+        match self {
+            AppError::Network { url, message } => {
+                lines.push(format!("url: {url}"));
+                lines.push(format!("message: {message}"));
+            }
+            AppError::Timeout { url } | AppError::Corrupted { url } => {
+                lines.push(format!("url: {url}"))
+            }
+            AppError::Auth { url, status } => {
+                lines.push(format!("url: {url}"));
+                lines.push(format!("status: {status}"));
+            }
+            AppError::Http {
+                url,
+                status,
+                message,
+            } => {
+                lines.push(format!("url: {url}"));
+                lines.push(format!("status: {status}"));
+                lines.push(format!("message: {message}"));
+            }
+            AppError::Offline(message)
+            | AppError::Decode(message)
+            | AppError::Validation(message) => lines.push(format!("message: {message}")),
+            AppError::Cancelled => {}
+        }
+        lines.join("\n")
+    }
+
+    /// Whether the server address or access token in the settings are likely wrong.
+    pub fn needs_settings(&self) -> bool {
+        matches!(
+            self,
+            AppError::Auth { .. } | AppError::Http { status: 404, .. } | AppError::Network { .. }
+        )
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            AppError::Network { url, message } => {
+                i18n::t_args("error-network", &[("url", url), ("message", message)])
+            }
+            AppError::Offline(message) | AppError::Validation(message) => message.clone(),
+            AppError::Timeout { url } => i18n::t_args("error-timeout", &[("url", url)]),
+            AppError::Corrupted { url } => i18n::t_args("error-corrupted", &[("url", url)]),
+            AppError::Auth { url, status } => {
+                i18n::t_args("error-auth", &[("url", url), ("status", status)])
+            }
+            AppError::Http {
+                url,
+                status,
+                message,
+            } => i18n::t_args(
+                "error-http",
+                &[("url", url), ("status", status), ("message", message)],
+            ),
+            AppError::Decode(message) => i18n::t_args("error-decode", &[("message", message)]),
+            AppError::Cancelled => i18n::t("error-cancelled"),
+        };
+        f.write_str(&text)
+    }
+}
+
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.to_string()
+    }
+}
+
+/// `scheme://host:port` of `url`, if it has one.
+fn origin(url: &str) -> Option<&str> {
+    let (scheme, rest) = url.split_once("://")?;
+    let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    Some(&url[..scheme.len() + 3 + end])
+}
+
+/// Whether the browser blocks requests from the page at `page` to `url` as mixed content:
+/// HTTPS pages may not ask HTTP addresses, except on the local machine.
+fn is_mixed_content(page: &str, url: &str) -> bool {
+    let local = ["http://localhost", "http://127.0.0.1", "http://[::1]"];
+    page.starts_with("https:")
+        && url.starts_with("http:")
+        && !local.iter().any(|local| {
+            origin(url)
+                .is_some_and(|origin| origin == *local || origin.starts_with(&format!("{local}:")))
+        })
+}
+
+/// The hint for a request to `url` that could not get through, telling the mistakes
+/// browsers keep quiet about: mixed content and servers that do not allow the page's origin.
+#[hook]
+fn use_network_hint(error: &AppError) -> Option<String> {
+    let language = use_language();
+    let cors = use_state(|| false);
+    let page = gloo::utils::window().location();
+    let page_origin = page.origin().unwrap_or_default();
+    let url = match error {
+        AppError::Network { url, .. } => Some(url.clone()),
+        _ => None,
+    };
+    {
+        let cors = cors.clone();
+        let page_origin = page_origin.clone();
+        use_effect_with(url.clone(), move |url| {
+            cors.set(false);
+            let cross_origin = url
+                .clone()
+                .filter(|url| origin(url).is_some_and(|origin| origin != page_origin));
+            if let Some(url) = cross_origin {
+                spawn_local(async move { cors.set(http::answers_opaquely(&url).await) });
+            }
+        });
+    }
+
+    let url = url?;
+    if is_mixed_content(&page.href().unwrap_or_default(), &url) {
+        return Some(language.t_args("error-hint-mixed-content", &[("url", &url)]));
+    }
+    if *cors {
+        return Some(language.t_args("error-hint-cors", &[("origin", &page_origin)]));
+    }
+    Some(language.t("error-hint-address"))
+}
+
+/// `error`, prefixed with `title`, with its details on demand and the ways out of it that
+/// apply: trying again when `onretry` is given, and opening the settings. Dismissed errors
+/// stay hidden until another one replaces them.
+#[autoprops_component(ErrorAlert)]
+pub fn error_alert(
+    error: AppError,
+    #[prop_or_default] title: Option<AttrValue>,
+    #[prop_or_default] onretry: Option<Callback<()>>,
+) -> Html {
+    let language = use_language();
+    let network_hint = use_network_hint(&error);
+    let hint = match error {
+        AppError::Auth { .. } => Some(language.t("error-hint-auth")),
+        AppError::Http { status: 404, .. } => Some(language.t("error-hint-address")),
+        AppError::Network { .. } => network_hint,
+        _ => None,
+    };
+    let retry = onretry.clone().filter(|_| error.is_retryable());
+    let dismissed = use_state(|| false);
+    {
+        let dismissed = dismissed.clone();
+        use_effect_with(error.clone(), move |_| dismissed.set(false));
+    }
+    let ondismiss = {
+        let dismissed = dismissed.clone();
+        move |_| dismissed.set(true)
+    };
+
+    if *dismissed {
+        return html!();
+    }
+
+    html! {
+        <div class="alert alert-danger alert-dismissible" role="alert">
+            if let Some(title) = title {
+                <strong>{title}{" "}</strong>
+            }
+            {error.to_string()}
+            if let Some(hint) = hint {
+                <div class="small">{hint}</div>
+            }
+            <details class="small mt-1">
+                <summary>{language.t("error-show-more")}</summary>
+                <pre class="mb-0 text-wrap">{error.details()}</pre>
+            </details>
+            if retry.is_some() || error.needs_settings() {
+                <div class="mt-2 d-flex gap-2">
+                    if let Some(onretry) = retry {
+                        <button
+                            class="btn btn-sm btn-outline-light"
+                            onclick={move |_| onretry.emit(())}
+                        >
+                            {language.t("action-retry")}
+                        </button>
+                    }
+                    if error.needs_settings() {
+                        <a class="btn btn-sm btn-outline-light" href={Route::Settings.href()}>
+                            {language.t("action-open-settings")}
+                        </a>
+                    }
+                </div>
+            }
+            <button
+                type="button"
+                class="btn-close"
+                aria-label={language.t("action-close")}
+                onclick={ondismiss}
+            ></button>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_mixed_content, origin, AppError};
+
+    fn http(status: u16) -> AppError {
+        AppError::Http {
+            url: "mock://segment".to_string(),
+            status,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn server_side_and_transient_errors_are_retryable() {
+        assert!(http(500).is_retryable());
+        assert!(http(503).is_retryable());
+        assert!(http(429).is_retryable());
+        assert!(AppError::Timeout {
+            url: "mock://segment".to_string()
+        }
+        .is_retryable());
+        assert!(!http(400).is_retryable());
+        assert!(!AppError::Decode(String::new()).is_retryable());
+        assert!(!AppError::Validation(String::new()).is_retryable());
+    }
+
+    #[test]
+    fn wrong_addresses_and_tokens_point_to_the_settings() {
+        assert!(AppError::Auth {
+            url: "mock://segment".to_string(),
+            status: 401
+        }
+        .needs_settings());
+        assert!(http(404).needs_settings());
+        assert!(!http(500).needs_settings());
+        assert!(!AppError::Offline(String::new()).needs_settings());
+    }
+
+    #[test]
+    fn details_keep_what_the_server_said() {
+        let error = AppError::Http {
+            url: "mock://segment".to_string(),
+            status: 502,
+            message: "Bad gateway".to_string(),
+        };
+        assert_eq!(
+            error.details(),
+            "kind: http\nurl: mock://segment\nstatus: 502\nmessage: Bad gateway"
+        );
+        assert_eq!(AppError::Cancelled.details(), "kind: cancelled");
+    }
+
+    #[test]
+    fn http_servers_are_blocked_from_https_pages_unless_local() {
+        assert_eq!(
+            origin("https://maps.example.org:8443/api/segment?x=1"),
+            Some("https://maps.example.org:8443")
+        );
+        assert_eq!(origin("http://gpu-01"), Some("http://gpu-01"));
+        assert_eq!(origin("/api"), None);
+
+        let page = "https://maps.example.org/app/";
+        assert!(is_mixed_content(page, "http://gpu-01:8000/segment"));
+        assert!(!is_mixed_content(page, "https://gpu-01:8000/segment"));
+        assert!(!is_mixed_content(page, "http://localhost:8000/segment"));
+        assert!(is_mixed_content(
+            page,
+            "http://localhost.example.org/segment"
+        ));
+        assert!(!is_mixed_content(
+            "http://maps.example.org/",
+            "http://gpu-01/segment"
+        ));
+    }
+}
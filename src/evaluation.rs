@@ -0,0 +1,520 @@
+//! Scoring masks against ground-truth masks drawn by hand, class by class, so that models
+//! can be evaluated on a few labelled images without leaving the app, and showing where
+//! they went wrong.
+
+use image::{DynamicImage, GrayImage, ImageOutputFormat, Rgba, RgbaImage};
+use shadow_clone::shadow_clone;
+use std::{collections::BTreeMap, io::Cursor, rc::Rc};
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    decoded,
+    i18n::{self, use_language, Language},
+    state::{use_app_state, AppAction},
+    uploader::UploadPane,
+    viewer::FileViewer,
+    FileDetails,
+};
+
+/// Pixels of a class in the predicted mask and in the ground truth, by whether they agree.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ClassCounts {
+    /// Pixels of the class in both masks.
+    pub true_positive: u64,
+    /// Pixels of the class in the prediction only.
+    pub false_positive: u64,
+    /// Pixels of the class in the ground truth only.
+    pub false_negative: u64,
+}
+
+/// `part / whole`, unless there is nothing to divide, as for a class in neither mask.
+fn ratio(part: u64, whole: u64) -> Option<f64> {
+    (whole > 0).then(|| part as f64 / whole as f64)
+}
+
+impl ClassCounts {
+    pub fn iou(&self) -> Option<f64> {
+        ratio(
+            self.true_positive,
+            self.true_positive + self.false_positive + self.false_negative,
+        )
+    }
+
+    pub fn dice(&self) -> Option<f64> {
+        ratio(
+            2 * self.true_positive,
+            2 * self.true_positive + self.false_positive + self.false_negative,
+        )
+    }
+
+    pub fn precision(&self) -> Option<f64> {
+        ratio(self.true_positive, self.true_positive + self.false_positive)
+    }
+
+    pub fn recall(&self) -> Option<f64> {
+        ratio(self.true_positive, self.true_positive + self.false_negative)
+    }
+}
+
+/// How a predicted mask, or several, agree with their ground truth.
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub struct Evaluation {
+    /// Every class found in either mask.
+    pub classes: BTreeMap<u8, ClassCounts>,
+    pub correct_pixels: u64,
+    pub total_pixels: u64,
+    /// Masks scored together.
+    pub masks: usize,
+}
+
+impl Evaluation {
+    /// Adds the pixels of `other`, so that classes are scored over all masks at once rather
+    /// than averaged, which would let small images count as much as large ones.
+    pub fn merge(&mut self, other: &Evaluation) {
+        for (class, counts) in &other.classes {
+            let total = self.classes.entry(*class).or_default();
+            total.true_positive += counts.true_positive;
+            total.false_positive += counts.false_positive;
+            total.false_negative += counts.false_negative;
+        }
+        self.correct_pixels += other.correct_pixels;
+        self.total_pixels += other.total_pixels;
+        self.masks += other.masks;
+    }
+
+    pub fn accuracy(&self) -> Option<f64> {
+        ratio(self.correct_pixels, self.total_pixels)
+    }
+
+    /// The IoU averaged over classes, each counting the same however large.
+    pub fn mean_iou(&self) -> Option<f64> {
+        let scores: Vec<f64> = self.classes.values().filter_map(ClassCounts::iou).collect();
+        (!scores.is_empty()).then(|| scores.iter().sum::<f64>() / scores.len() as f64)
+    }
+}
+
+fn size_mismatch(prediction: &GrayImage, truth: &GrayImage) -> String {
+    i18n::t_args(
+        "truth-size-mismatch",
+        &[
+            ("truth", &format!("{}x{}", truth.width(), truth.height())),
+            (
+                "mask",
+                &format!("{}x{}", prediction.width(), prediction.height()),
+            ),
+        ],
+    )
+}
+
+pub fn evaluate(prediction: &GrayImage, truth: &GrayImage) -> Result<Evaluation, String> {
+    if prediction.dimensions() != truth.dimensions() {
+        return Err(size_mismatch(prediction, truth));
+    }
+    let mut evaluation = Evaluation {
+        total_pixels: prediction.as_raw().len() as u64,
+        masks: 1,
+        ..Default::default()
+    };
+    for (predicted, actual) in prediction.as_raw().iter().zip(truth.as_raw()) {
+        if predicted == actual {
+            evaluation.classes.entry(*actual).or_default().true_positive += 1;
+            evaluation.correct_pixels += 1;
+        } else {
+            evaluation
+                .classes
+                .entry(*predicted)
+                .or_default()
+                .false_positive += 1;
+            evaluation
+                .classes
+                .entry(*actual)
+                .or_default()
+                .false_negative += 1;
+        }
+    }
+    Ok(evaluation)
+}
+
+const CLEAR: Rgba<u8> = Rgba([0, 0, 0, 0]);
+
+/// Colours of the classes of ground truths, cycled through, in purples and oranges so
+/// that they are not taken for the model's grey masks. The background is left clear.
+const TRUTH_COLORS: [Rgba<u8>; 4] = [
+    Rgba([170, 60, 220, 255]),
+    Rgba([250, 140, 30, 255]),
+    Rgba([220, 60, 160, 255]),
+    Rgba([120, 80, 250, 255]),
+];
+
+/// `truth` in its own colours, over a clear background.
+pub fn truth_colors(truth: &GrayImage) -> RgbaImage {
+    RgbaImage::from_fn(truth.width(), truth.height(), |x, y| {
+        match truth.get_pixel(x, y).0[0] {
+            0 => CLEAR,
+            class => TRUTH_COLORS[usize::from(class - 1) % TRUTH_COLORS.len()],
+        }
+    })
+}
+
+/// How a pixel of the prediction stands against the ground truth, other than the background
+/// found where it is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PixelError {
+    TruePositive,
+    /// A class predicted over the background.
+    FalsePositive,
+    /// A class of the ground truth taken for the background.
+    FalseNegative,
+    /// A class taken for another, a false positive of one and a false negative of the other.
+    WrongClass,
+}
+
+impl PixelError {
+    pub const ALL: [PixelError; 4] = [
+        PixelError::TruePositive,
+        PixelError::FalsePositive,
+        PixelError::FalseNegative,
+        PixelError::WrongClass,
+    ];
+
+    pub fn of(predicted: u8, actual: u8) -> Option<Self> {
+        match (predicted, actual) {
+            (0, 0) => None,
+            (p, a) if p == a => Some(PixelError::TruePositive),
+            (_, 0) => Some(PixelError::FalsePositive),
+            (0, _) => Some(PixelError::FalseNegative),
+            _ => Some(PixelError::WrongClass),
+        }
+    }
+
+    pub fn color(self) -> Rgba<u8> {
+        match self {
+            PixelError::TruePositive => Rgba([40, 200, 70, 255]),
+            PixelError::FalsePositive => Rgba([220, 50, 50, 255]),
+            PixelError::FalseNegative => Rgba([50, 110, 230, 255]),
+            PixelError::WrongClass => Rgba([240, 200, 40, 255]),
+        }
+    }
+
+    /// The colour and the name of the error, for legends.
+    pub fn label(self, language: Language) -> String {
+        language.t(match self {
+            PixelError::TruePositive => "truth-true-positive",
+            PixelError::FalsePositive => "truth-false-positive",
+            PixelError::FalseNegative => "truth-false-negative",
+            PixelError::WrongClass => "truth-wrong-class",
+        })
+    }
+}
+
+/// Every pixel of `prediction` coloured by its [`PixelError`], the background found where
+/// it is left clear.
+pub fn error_map(prediction: &GrayImage, truth: &GrayImage) -> Result<RgbaImage, String> {
+    if prediction.dimensions() != truth.dimensions() {
+        return Err(size_mismatch(prediction, truth));
+    }
+    Ok(RgbaImage::from_fn(
+        prediction.width(),
+        prediction.height(),
+        |x, y| {
+            PixelError::of(prediction.get_pixel(x, y).0[0], truth.get_pixel(x, y).0[0])
+                .map_or(CLEAR, PixelError::color)
+        },
+    ))
+}
+
+/// The ground truth of a mask in its own colours, and the error map of the mask against
+/// it, as PNGs placed where `truth` lies.
+#[derive(Clone, PartialEq)]
+pub struct TruthLayers {
+    pub truth: Rc<FileDetails>,
+    pub errors: Rc<FileDetails>,
+}
+
+/// `image` as a PNG named `file_name`, placed where `like` lies.
+pub fn png(
+    image: RgbaImage,
+    file_name: String,
+    like: &FileDetails,
+) -> Result<Rc<FileDetails>, String> {
+    let mut data = vec![];
+    DynamicImage::ImageRgba8(image)
+        .write_to(&mut Cursor::new(&mut data), ImageOutputFormat::Png)
+        .map_err(|e| i18n::t_args("truth-unencodable", &[("name", &file_name), ("error", &e)]))?;
+    Ok(Rc::new(FileDetails {
+        file_name,
+        file_type: "image/png".to_string(),
+        data: data.into(),
+        georef: like.georef,
+        auxiliary: None,
+        source: None,
+    }))
+}
+
+pub fn truth_layers(mask: &FileDetails, truth: &FileDetails) -> Result<TruthLayers, String> {
+    let prediction = decoded::mask(&mask.data)?;
+    let actual = decoded::mask(&truth.data)?;
+    let errors = error_map(&prediction, &actual)?;
+    Ok(TruthLayers {
+        truth: png(
+            truth_colors(&actual),
+            i18n::t_args("truth-file", &[("name", &truth.file_name)]),
+            truth,
+        )?,
+        errors: png(
+            errors,
+            i18n::t_args("truth-errors-file", &[("name", &mask.file_name)]),
+            truth,
+        )?,
+    })
+}
+
+/// A score as a percentage, or a dash when there is nothing to score.
+pub fn percent(score: Option<f64>) -> String {
+    score.map_or("–".to_string(), |score| format!("{:.1}%", score * 100.0))
+}
+
+/// Per-class IoU, Dice, precision and recall of `evaluation`, with the mean IoU and the
+/// share of pixels classified right.
+#[autoprops_component(MetricsTable)]
+pub fn metrics_table(evaluation: Rc<Evaluation>) -> Html {
+    let language = use_language();
+    html! {
+        <div>
+            <p>
+                {language.t_args("truth-summary", &[
+                    ("iou", &percent(evaluation.mean_iou())),
+                    ("accuracy", &percent(evaluation.accuracy())),
+                ])}
+                if evaluation.masks > 1 {
+                    {", "}{language.t_args("truth-masks", &[("count", &evaluation.masks)])}
+                }
+            </p>
+            <table class="table table-sm">
+                <thead>
+                    <tr>
+                        <th scope="col">{language.t("report-class")}</th>
+                        <th scope="col">{"IoU"}</th>
+                        <th scope="col">{"Dice"}</th>
+                        <th scope="col">{language.t("truth-precision")}</th>
+                        <th scope="col">{language.t("truth-recall")}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                {
+                    for evaluation.classes.iter().map(|(class, counts)| html! {
+                        <tr>
+                            <td>{class}</td>
+                            <td>{percent(counts.iou())}</td>
+                            <td>{percent(counts.dice())}</td>
+                            <td>{percent(counts.precision())}</td>
+                            <td>{percent(counts.recall())}</td>
+                        </tr>
+                    })
+                }
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
+/// What [`GroundTruthPane`] shows of a mask and its ground truth.
+#[derive(Clone, Copy, PartialEq)]
+enum TruthView {
+    Mask,
+    Truth,
+    Errors,
+}
+
+impl TruthView {
+    const ALL: [TruthView; 3] = [TruthView::Mask, TruthView::Truth, TruthView::Errors];
+
+    fn name(self, language: Language) -> String {
+        language.t(match self {
+            TruthView::Mask => "truth-view-mask",
+            TruthView::Truth => "layer-ground-truth",
+            TruthView::Errors => "truth-view-errors",
+        })
+    }
+}
+
+/// Scoring `mask` against a ground-truth mask uploaded alongside the image, and switching
+/// between the mask, the ground truth and the map of their errors.
+#[autoprops_component(GroundTruthPane)]
+pub fn ground_truth_pane(mask: Rc<FileDetails>) -> Html {
+    let language = use_language();
+    let state = use_app_state();
+    let view = use_state(|| TruthView::Errors);
+
+    let onupload = {
+        shadow_clone!(state);
+        move |truth: Rc<Option<FileDetails>>| {
+            state.dispatch(AppAction::GroundTruth((*truth).clone().map(Rc::new)))
+        }
+    };
+
+    let evaluation = use_memo(
+        (mask.clone(), state.ground_truth.clone()),
+        |(mask, truth)| {
+            let truth = truth.as_ref()?;
+            Some(
+                decoded::mask(&mask.data)
+                    .and_then(|prediction| {
+                        decoded::mask(&truth.data).map(|truth| (prediction, truth))
+                    })
+                    .and_then(|(prediction, truth)| evaluate(&prediction, &truth))
+                    .and_then(|evaluation| Ok((evaluation, truth_layers(mask, truth)?)))
+                    .map(|(evaluation, layers)| (Rc::new(evaluation), layers)),
+            )
+        },
+    );
+
+    let metrics = match *evaluation {
+        None => html!(),
+        Some(Err(ref why)) => html!(
+            <div class="alert alert-danger" role="alert">{why}</div>
+        ),
+        Some(Ok((ref evaluation, ref layers))) => {
+            let shown = match *view {
+                TruthView::Mask => mask.clone(),
+                TruthView::Truth => layers.truth.clone(),
+                TruthView::Errors => layers.errors.clone(),
+            };
+            html! {
+                <>
+                    <MetricsTable evaluation={evaluation.clone()} />
+                    <div class="btn-group btn-group-sm mb-2" role="group" aria-label={language.t("truth-shown")}>
+                        {for TruthView::ALL.into_iter().map(|option| {
+                            let onclick = {
+                                shadow_clone!(view);
+                                move |_| view.set(option)
+                            };
+                            html! {
+                                <button
+                                    class={classes!("btn", "btn-outline-secondary", (*view == option).then_some("active"))}
+                                    aria-pressed={(*view == option).to_string()}
+                                    {onclick}
+                                >
+                                    {option.name(language)}
+                                </button>
+                            }
+                        })}
+                    </div>
+                    <FileViewer file={shown} />
+                    if *view == TruthView::Errors {
+                        <ErrorLegend />
+                    }
+                </>
+            }
+        }
+    };
+
+    html! {
+        <div>
+            <h2>{language.t("layer-ground-truth")}</h2>
+            <UploadPane {onupload} />
+            {metrics}
+        </div>
+    }
+}
+
+/// The colours of the error map, named as well as shown, for readers who cannot tell them
+/// apart.
+#[function_component(ErrorLegend)]
+pub fn error_legend() -> Html {
+    let language = use_language();
+    html! {
+        <ul class="list-inline small">
+            {for PixelError::ALL.into_iter().map(|error| {
+                let Rgba([r, g, b, _]) = error.color();
+                html! {
+                    <li class="list-inline-item">
+                        <span
+                            class="d-inline-block me-1 border"
+                            style={format!("width: 1em; height: 1em; background: rgb({r}, {g}, {b});")}
+                            aria-hidden="true"
+                        ></span>
+                        {error.label(language)}
+                    </li>
+                }
+            })}
+        </ul>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::GrayImage;
+
+    use super::{error_map, evaluate, truth_colors, ClassCounts, PixelError};
+
+    fn mask(pixels: &[u8]) -> GrayImage {
+        GrayImage::from_raw(pixels.len() as u32, 1, pixels.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn classes_are_scored_by_the_pixels_they_share() {
+        let prediction = mask(&[0, 0, 1, 1, 1, 2]);
+        let truth = mask(&[0, 1, 1, 1, 2, 2]);
+
+        let evaluation = evaluate(&prediction, &truth).unwrap();
+
+        let buildings = evaluation.classes[&1];
+        assert_eq!(
+            buildings,
+            ClassCounts {
+                true_positive: 2,
+                false_positive: 1,
+                false_negative: 1,
+            }
+        );
+        assert_eq!(buildings.iou(), Some(0.5));
+        assert_eq!(buildings.dice(), Some(4.0 / 6.0));
+        assert_eq!(buildings.precision(), Some(2.0 / 3.0));
+        assert_eq!(buildings.recall(), Some(2.0 / 3.0));
+        assert_eq!(evaluation.accuracy(), Some(4.0 / 6.0));
+        // Background 1/2, buildings 1/2, roads 1/2.
+        assert_eq!(evaluation.mean_iou(), Some(0.5));
+    }
+
+    #[test]
+    fn batches_are_scored_over_all_their_pixels() {
+        let mut total = evaluate(&mask(&[1, 1, 1, 1]), &mask(&[1, 1, 1, 1])).unwrap();
+        total.merge(&evaluate(&mask(&[0, 0]), &mask(&[1, 1])).unwrap());
+
+        assert_eq!(total.masks, 2);
+        assert_eq!(total.classes[&1].iou(), Some(4.0 / 6.0));
+        assert_eq!(total.classes[&0].precision(), Some(0.0));
+        assert_eq!(total.classes[&0].recall(), None);
+    }
+
+    #[test]
+    fn masks_of_different_sizes_cannot_be_scored() {
+        assert!(evaluate(&mask(&[0, 1]), &mask(&[0, 1, 2])).is_err());
+        assert!(error_map(&mask(&[0, 1]), &mask(&[0, 1, 2])).is_err());
+    }
+
+    #[test]
+    fn error_maps_leave_the_background_found_clear() {
+        let prediction = mask(&[0, 1, 1, 0, 2]);
+        let truth = mask(&[0, 1, 0, 1, 1]);
+
+        let errors = error_map(&prediction, &truth).unwrap();
+
+        let colors: Vec<_> = errors.pixels().copied().collect();
+        assert_eq!(colors[0].0[3], 0);
+        assert_eq!(colors[1], PixelError::TruePositive.color());
+        assert_eq!(colors[2], PixelError::FalsePositive.color());
+        assert_eq!(colors[3], PixelError::FalseNegative.color());
+        assert_eq!(colors[4], PixelError::WrongClass.color());
+    }
+
+    #[test]
+    fn ground_truths_are_coloured_but_for_the_background() {
+        let colored = truth_colors(&mask(&[0, 1, 5]));
+        assert_eq!(colored.get_pixel(0, 0).0[3], 0);
+        // Classes past the palette start it over.
+        assert_eq!(colored.get_pixel(1, 0), colored.get_pixel(2, 0));
+    }
+}
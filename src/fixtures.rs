@@ -0,0 +1,24 @@
+//! Images and masks for tests, from `fixtures/`: an 8×8 RGB image, its mask with a 4×4
+//! square of class 1 and a lone pixel of class 2 at (6, 6), and that mask corrected by a
+//! reviewer who removed the lone pixel.
+//!
+//! `fixtures/` also holds a world file placing the image on a 10 m grid in UTM zone 33N
+//! and a 4×2 GeoTIFF tied to the same corner, which the georeferencing tests include
+//! directly since they are built into the worker too.
+
+use crate::FileDetails;
+
+pub const IMAGE: &[u8] = include_bytes!("../fixtures/image.png");
+pub const MASK: &[u8] = include_bytes!("../fixtures/mask.png");
+pub const MASK_CORRECTED: &[u8] = include_bytes!("../fixtures/mask-corrected.png");
+
+pub fn png(file_name: &str, data: &[u8]) -> FileDetails {
+    FileDetails {
+        file_name: file_name.to_string(),
+        file_type: "image/png".to_string(),
+        data: data.into(),
+        georef: None,
+        auxiliary: None,
+        source: None,
+    }
+}
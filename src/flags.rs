@@ -0,0 +1,107 @@
+//! Feature flags, turning parts of the app on or off for a deployment in `config.json`
+//! and for a single visit with `?enable=` and `?disable=` in the address. Unfinished work
+//! can so ship turned off and still be tried out in production.
+
+use std::collections::BTreeMap;
+use web_sys::UrlSearchParams;
+
+use crate::config;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Flag {
+    /// Picking the image on the map, as well as uploading it.
+    Map,
+    Ndvi,
+    Gallery,
+    Workspaces,
+    /// Comparing two results from the history.
+    Compare,
+    /// Comparing a mask against a reviewer's correction.
+    Diff,
+    /// Segmenting many images in one unattended run.
+    Batch,
+    /// Segmenting the same image with several models, side by side.
+    Models,
+    /// Scoring masks against ground-truth masks.
+    Evaluation,
+}
+
+impl Flag {
+    const ALL: [Flag; 9] = [
+        Flag::Map,
+        Flag::Ndvi,
+        Flag::Gallery,
+        Flag::Workspaces,
+        Flag::Compare,
+        Flag::Diff,
+        Flag::Batch,
+        Flag::Models,
+        Flag::Evaluation,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Flag::Map => "map",
+            Flag::Ndvi => "ndvi",
+            Flag::Gallery => "gallery",
+            Flag::Workspaces => "workspaces",
+            Flag::Compare => "compare",
+            Flag::Diff => "diff",
+            Flag::Batch => "batch",
+            Flag::Models => "models",
+            Flag::Evaluation => "evaluation",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Flag::ALL.into_iter().find(|flag| flag.name() == name)
+    }
+
+    /// Whether the flag is on when neither the configuration nor the address say.
+    fn default_on(self) -> bool {
+        true
+    }
+}
+
+/// Names in `flags` that are not flags.
+pub fn unknown(flags: &BTreeMap<String, bool>) -> Vec<String> {
+    flags
+        .keys()
+        .filter(|name| Flag::parse(name).is_none())
+        .cloned()
+        .collect()
+}
+
+/// The comma-separated flag names in the query parameter `param`.
+fn query_flags(param: &str) -> Vec<Flag> {
+    let search = gloo::utils::window()
+        .location()
+        .search()
+        .unwrap_or_default();
+    let Some(names) = UrlSearchParams::new_with_str(&search)
+        .ok()
+        .and_then(|params| params.get(param))
+    else {
+        return Vec::new();
+    };
+    names
+        .split(',')
+        .filter_map(|name| Flag::parse(name.trim()))
+        .collect()
+}
+
+/// Whether `flag` is on: the address overrides the configuration, which overrides the
+/// default.
+pub fn enabled(flag: Flag) -> bool {
+    if query_flags("disable").contains(&flag) {
+        return false;
+    }
+    if query_flags("enable").contains(&flag) {
+        return true;
+    }
+    config::current()
+        .features
+        .get(flag.name())
+        .copied()
+        .unwrap_or_else(|| flag.default_on())
+}
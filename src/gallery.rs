@@ -0,0 +1,271 @@
+//! Thumbnail overview of the stored results, scrolled through as one list of which only
+//! the rows in view are rendered, however many results there are.
+
+use shadow_clone::shadow_clone;
+use std::{ops::Range, rc::Rc};
+use web_sys::{Element, HtmlSelectElement};
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+use yew_hooks::use_event_with_window;
+
+use crate::{
+    codec::EncodeJob,
+    history::{self, HistoryEntry},
+    i18n::{use_language, Language},
+    viewer::use_encoded,
+    FileDetails,
+};
+
+const THUMBNAIL_SIZE: u32 = 160;
+/// Cards in a row of the grid.
+const COLUMNS: usize = 4;
+/// Height of a row of cards, gutter included, in CSS pixels. Cards have a fixed height so
+/// that where each row lies is known without rendering it.
+const ROW_HEIGHT: f64 = 200.0;
+/// Rows rendered above and below those in view, so that scrolling does not show gaps.
+const OVERSCAN_ROWS: usize = 2;
+
+/// The rows of a list of `rows` rows to render, for a viewport of `height` scrolled to
+/// `scroll_top`.
+fn visible_rows(scroll_top: f64, height: f64, rows: usize) -> Range<usize> {
+    let first = (scroll_top.max(0.0) / ROW_HEIGHT) as usize;
+    let last = ((scroll_top.max(0.0) + height) / ROW_HEIGHT).ceil() as usize;
+    first.saturating_sub(OVERSCAN_ROWS).min(rows)..(last + OVERSCAN_ROWS).min(rows)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SortOrder {
+    Newest,
+    Oldest,
+    Name,
+    Server,
+}
+
+impl SortOrder {
+    const ALL: [SortOrder; 4] = [
+        SortOrder::Newest,
+        SortOrder::Oldest,
+        SortOrder::Name,
+        SortOrder::Server,
+    ];
+
+    fn name(self, language: Language) -> String {
+        language.t(match self {
+            SortOrder::Newest => "gallery-newest",
+            SortOrder::Oldest => "gallery-oldest",
+            SortOrder::Name => "gallery-image-name",
+            SortOrder::Server => "gallery-server",
+        })
+    }
+
+    fn sort(self, entries: &mut [Rc<HistoryEntry>]) {
+        match self {
+            SortOrder::Newest => entries.sort_by(|a, b| b.timestamp.total_cmp(&a.timestamp)),
+            SortOrder::Oldest => entries.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp)),
+            SortOrder::Name => entries.sort_by(|a, b| a.image.file_name.cmp(&b.image.file_name)),
+            SortOrder::Server => entries.sort_by(|a, b| a.server.cmp(&b.server)),
+        }
+    }
+}
+
+/// Small PNG of `file`, left out for formats that cannot be decoded here.
+fn thumbnail_job(file: &FileDetails) -> Option<EncodeJob> {
+    Some(EncodeJob::Thumbnail {
+        data: file.data.clone(),
+        size: THUMBNAIL_SIZE,
+    })
+}
+
+#[autoprops_component(GalleryCard)]
+fn gallery_card(entry: Rc<HistoryEntry>, onopen: Callback<Rc<HistoryEntry>>) -> Html {
+    let language = use_language();
+    let image = use_encoded(entry.image.clone(), "thumbnail", thumbnail_job).flatten();
+    let mask = use_encoded(entry.mask.clone(), "thumbnail", thumbnail_job).flatten();
+
+    let onclick = {
+        shadow_clone!(entry, onopen);
+        move |_| onopen.emit(entry.clone())
+    };
+    // Cards are not buttons, so Enter and Space have to be handled here.
+    let onkeydown = {
+        shadow_clone!(entry, onopen);
+        move |e: KeyboardEvent| {
+            if e.key() == "Enter" || e.key() == " " {
+                e.prevent_default();
+                onopen.emit(entry.clone());
+            }
+        }
+    };
+
+    html! {
+        <div class="col">
+            <div class="card h-100" role="button" tabindex="0" {onclick} {onkeydown}>
+                <div class="d-flex">
+                    if let Some(src) = image {
+                        <img
+                            class="w-50"
+                            style="height: 120px; object-fit: contain;"
+                            src={src.to_string()}
+                            alt={entry.image.file_name.clone()}
+                        />
+                    }
+                    if let Some(src) = mask {
+                        <img
+                            class="w-50"
+                            style="height: 120px; object-fit: contain;"
+                            src={src.to_string()}
+                            alt={language.t_args("gallery-mask-of", &[("name", &entry.image.file_name)])}
+                        />
+                    }
+                </div>
+                <div class="card-body p-2">
+                    <small class="d-block text-truncate">{&entry.image.file_name}</small>
+                    <small class="d-block text-body-secondary">{entry.date()}</small>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+/// Stored results as a grid of thumbnails, reloaded whenever `revision` changes.
+#[autoprops_component(GalleryPane)]
+pub fn gallery_pane(revision: i32, onopen: Callback<Rc<HistoryEntry>>) -> Html {
+    let language = use_language();
+    let entries = use_state(|| None::<Result<Vec<Rc<HistoryEntry>>, String>>);
+    let order = use_state(|| SortOrder::Newest);
+    let list_ref = use_node_ref();
+    // Where the list is scrolled to, and how high it is.
+    let viewport = use_state(|| (0.0, 0.0));
+
+    {
+        shadow_clone!(entries);
+        use_effect_with(revision, move |_| {
+            spawn_local(async move {
+                entries.set(Some(
+                    history::list()
+                        .await
+                        .map(|list| list.into_iter().map(Rc::new).collect()),
+                ));
+            });
+        });
+    }
+
+    let sorted = use_memo(((*entries).clone(), *order), |(entries, order)| {
+        let mut entries = entries.clone()?.ok()?;
+        order.sort(&mut entries);
+        Some(entries)
+    });
+
+    let measure = {
+        shadow_clone!(list_ref, viewport);
+        move || {
+            if let Some(list) = list_ref.cast::<Element>() {
+                viewport.set((list.scroll_top() as f64, list.client_height() as f64));
+            }
+        }
+    };
+    // The list only exists once the entries are loaded, and is as high as the window lets it.
+    {
+        shadow_clone!(measure);
+        use_effect_with(sorted.is_some(), move |_| measure());
+    }
+    {
+        shadow_clone!(measure);
+        use_event_with_window("resize", move |_: Event| measure());
+    }
+    let onscroll = move |_| measure();
+
+    let onorder = {
+        shadow_clone!(order, list_ref);
+        move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(&new_order) = SortOrder::ALL.get(select.selected_index() as usize) {
+                order.set(new_order);
+                if let Some(list) = list_ref.cast::<Element>() {
+                    list.set_scroll_top(0);
+                }
+            }
+        }
+    };
+
+    let entries = match (&*entries, &*sorted) {
+        (None, _) => return html!(<span class="spinner-border text-success"></span>),
+        (Some(Err(why)), _) => {
+            return html!(<div class="alert alert-danger" role="alert">{why}</div>)
+        }
+        (_, Some(entries)) if !entries.is_empty() => entries,
+        _ => return html!(<p>{language.t("gallery-empty")}</p>),
+    };
+
+    let rows = entries.len().div_ceil(COLUMNS);
+    let (scroll_top, height) = *viewport;
+    let shown = visible_rows(scroll_top, height, rows);
+    let padding = format!(
+        "padding-top: {}px; padding-bottom: {}px;",
+        shown.start as f64 * ROW_HEIGHT,
+        (rows - shown.end) as f64 * ROW_HEIGHT
+    );
+
+    html! {
+        <div>
+            <div class="d-flex justify-content-between align-items-center mb-2">
+                <select class="form-select form-select-sm w-auto" onchange={onorder}>
+                    {
+                        for SortOrder::ALL.iter().map(|o| html! {
+                            <option selected={*o == *order}>{o.name(language)}</option>
+                        })
+                    }
+                </select>
+                <small class="text-body-secondary">
+                    {language.t_args("gallery-count", &[("count", &entries.len())])}
+                </small>
+            </div>
+            <div ref={list_ref} style="height: 70vh; overflow-y: auto;" {onscroll}>
+                <div style={padding}>
+                {
+                    for entries[shown.start * COLUMNS..(shown.end * COLUMNS).min(entries.len())]
+                        .chunks(COLUMNS)
+                        .map(|row| html! {
+                            <div
+                                class="row row-cols-4 g-2 flex-nowrap"
+                                style={format!("height: {ROW_HEIGHT}px;")}
+                            >
+                            {
+                                for row.iter().map(|entry| html! {
+                                    <GalleryCard
+                                        key={entry.id.unwrap_or_default().to_string()}
+                                        entry={entry.clone()}
+                                        onopen={onopen.clone()}
+                                    />
+                                })
+                            }
+                            </div>
+                        })
+                }
+                </div>
+            </div>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{visible_rows, ROW_HEIGHT};
+
+    #[test]
+    fn only_rows_in_view_are_rendered() {
+        // At the top, the rows in view and those just below.
+        assert_eq!(visible_rows(0.0, 3.0 * ROW_HEIGHT, 1_000), 0..5);
+        // Halfway through a row, that row is in view along with those around it.
+        assert_eq!(
+            visible_rows(10.5 * ROW_HEIGHT, 2.0 * ROW_HEIGHT, 1_000),
+            8..15
+        );
+        // Near the end, never past the last row.
+        assert_eq!(
+            visible_rows(98.0 * ROW_HEIGHT, 5.0 * ROW_HEIGHT, 100),
+            96..100
+        );
+        assert_eq!(visible_rows(0.0, 0.0, 0), 0..0);
+    }
+}
@@ -0,0 +1,69 @@
+//! Clipping of georeferenced images to an area of interest.
+
+use image::{DynamicImage, ImageOutputFormat, Rgba};
+use std::io::Cursor;
+
+use super::{polygon_contains, Crs, Georeference, LatLng};
+
+/// Crops `data` to the bounding box of `area` and blacks out pixels outside the polygon,
+/// like imagery fetched for an AOI. Returns a PNG with its georeference in the same CRS.
+pub fn clip_to_area(
+    data: &[u8],
+    georef: &Georeference,
+    area: &[LatLng],
+) -> Result<(Vec<u8>, Georeference), String> {
+    let crs = Crs::from_epsg(georef.epsg)
+        .ok_or_else(|| format!("EPSG:{} cannot be clipped", georef.epsg))?;
+    let image = image::load_from_memory(data)
+        .map_err(|e| format!("Could not decode image: {e}"))?
+        .to_rgba8();
+
+    let polygon: Vec<_> = area
+        .iter()
+        .map(|p| {
+            let (x, y) = crs.project(*p);
+            georef.crs_to_pixel(x, y)
+        })
+        .collect();
+    let min_x = polygon.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+    let min_y = polygon.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let max_x = polygon
+        .iter()
+        .map(|p| p.0)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let max_y = polygon
+        .iter()
+        .map(|p| p.1)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let min_x = min_x.floor().clamp(0.0, image.width() as f64) as u32;
+    let min_y = min_y.floor().clamp(0.0, image.height() as f64) as u32;
+    let max_x = max_x.ceil().clamp(0.0, image.width() as f64) as u32;
+    let max_y = max_y.ceil().clamp(0.0, image.height() as f64) as u32;
+    if max_x <= min_x || max_y <= min_y {
+        return Err("The area does not overlap the image".to_string());
+    }
+
+    let mut clipped =
+        image::imageops::crop_imm(&image, min_x, min_y, max_x - min_x, max_y - min_y).to_image();
+    for (x, y, pixel) in clipped.enumerate_pixels_mut() {
+        let center = ((min_x + x) as f64 + 0.5, (min_y + y) as f64 + 0.5);
+        if !polygon_contains(&polygon, center) {
+            *pixel = Rgba([0, 0, 0, 255]);
+        }
+    }
+
+    let (c, f) = georef.pixel_to_crs(min_x as f64, min_y as f64);
+    let clipped_georef = Georeference {
+        c,
+        f,
+        width: clipped.width(),
+        height: clipped.height(),
+        ..*georef
+    };
+
+    let mut png = vec![];
+    DynamicImage::ImageRgba8(clipped)
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .map_err(|e| format!("Could not encode clipped image: {e}"))?;
+    Ok((png, clipped_georef))
+}
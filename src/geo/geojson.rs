@@ -0,0 +1,174 @@
+//! Export of georeferenced masks as GeoJSON, for use in desktop GIS.
+
+use serde_json::{json, Value};
+
+use super::{Crs, Georeference};
+
+/// Rectangle of pixels of one class, as `[left, top, right, bottom)` mask coordinates.
+struct Block {
+    class: u8,
+    left: u32,
+    top: u32,
+    right: u32,
+    bottom: u32,
+}
+
+/// Converts `mask` (covering the same extent as `georef`, at any resolution) to a GeoJSON
+/// feature collection in WGS84 with a `MultiPolygon` feature per class, whose `class`
+/// property is the class value. Polygons are rectangles of whole pixels: runs of a class
+/// along a row, merged with the identical runs of the rows below.
+pub fn mask_to_geojson(mask: &[u8], georef: &Georeference) -> Result<String, String> {
+    let crs = Crs::from_epsg(georef.epsg)
+        .ok_or_else(|| format!("EPSG:{} cannot be reprojected", georef.epsg))?;
+    let mask = image::load_from_memory(mask)
+        .map_err(|e| format!("Could not decode mask: {e}"))?
+        .to_luma8();
+    let (scale_x, scale_y) = (
+        georef.width as f64 / mask.width() as f64,
+        georef.height as f64 / mask.height() as f64,
+    );
+
+    let mut blocks: Vec<Block> = vec![];
+    // Blocks reaching the previous row, by their columns and class.
+    let mut open = vec![];
+    for row in 0..mask.height() {
+        let mut reached = vec![];
+        let mut left = 0;
+        while left < mask.width() {
+            let class = mask.get_pixel(left, row)[0];
+            let right = (left..mask.width())
+                .find(|&col| mask.get_pixel(col, row)[0] != class)
+                .unwrap_or(mask.width());
+            let above = open.iter().copied().find(|&i: &usize| {
+                let block = &blocks[i];
+                (block.left, block.right, block.class) == (left, right, class)
+            });
+            match above {
+                Some(i) => {
+                    blocks[i].bottom = row + 1;
+                    reached.push(i);
+                }
+                None => {
+                    reached.push(blocks.len());
+                    blocks.push(Block {
+                        class,
+                        left,
+                        top: row,
+                        right,
+                        bottom: row + 1,
+                    });
+                }
+            }
+            left = right;
+        }
+        open = reached;
+    }
+
+    let position = |col: u32, row: u32| {
+        let (x, y) = georef.pixel_to_crs(col as f64 * scale_x, row as f64 * scale_y);
+        let point = crs.unproject(x, y);
+        json!([point.lng, point.lat])
+    };
+    let mut classes: Vec<u8> = blocks.iter().map(|block| block.class).collect();
+    classes.sort_unstable();
+    classes.dedup();
+    let features = classes
+        .into_iter()
+        .map(|class| {
+            // Counterclockwise for north-up images, as RFC 7946 asks of exterior rings.
+            let polygons = blocks
+                .iter()
+                .filter(|block| block.class == class)
+                .map(|block| {
+                    json!([[
+                        position(block.left, block.top),
+                        position(block.left, block.bottom),
+                        position(block.right, block.bottom),
+                        position(block.right, block.top),
+                        position(block.left, block.top),
+                    ]])
+                })
+                .collect::<Vec<Value>>();
+            json!({
+                "type": "Feature",
+                "properties": { "class": class },
+                "geometry": { "type": "MultiPolygon", "coordinates": polygons },
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({ "type": "FeatureCollection", "features": features }).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::mask_to_geojson;
+    use crate::geo::{Crs, Georeference, LatLng};
+
+    /// An 8×8 mask with a 4×4 square of class 1 and a lone pixel of class 2 at (6, 6).
+    const MASK: &[u8] = include_bytes!("../../fixtures/mask.png");
+
+    fn georef(epsg: u32) -> Georeference {
+        Georeference::from_world_file(include_str!("../../fixtures/image.pgw"), epsg, 8, 8).unwrap()
+    }
+
+    /// Area covered by the polygons of `feature`, in pixels of the 10 m grid.
+    fn pixels(feature: &Value, georef: &Georeference) -> f64 {
+        let crs = Crs::from_epsg(georef.epsg).unwrap();
+        feature["geometry"]["coordinates"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|polygon| {
+                let ring: Vec<(f64, f64)> = polygon[0]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|p| {
+                        let (x, y) = crs.project(LatLng {
+                            lat: p[1].as_f64().unwrap(),
+                            lng: p[0].as_f64().unwrap(),
+                        });
+                        georef.crs_to_pixel(x, y)
+                    })
+                    .collect();
+                // Shoelace formula, positive for counterclockwise rings.
+                ring.windows(2)
+                    .map(|w| w[0].0 * w[1].1 - w[1].0 * w[0].1)
+                    .sum::<f64>()
+                    / -2.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn every_class_becomes_a_feature_covering_its_pixels() {
+        let georef = georef(32633);
+        let geojson: Value =
+            serde_json::from_str(&mask_to_geojson(MASK, &georef).unwrap()).unwrap();
+        assert_eq!(geojson["type"], "FeatureCollection");
+        let features = geojson["features"].as_array().unwrap();
+        let classes: Vec<_> = features.iter().map(|f| &f["properties"]["class"]).collect();
+        assert_eq!(classes, [0, 1, 2]);
+
+        let areas: Vec<f64> = features.iter().map(|f| pixels(f, &georef)).collect();
+        for (area, expected) in areas.iter().zip([64.0 - 16.0 - 1.0, 16.0, 1.0]) {
+            assert!((area - expected).abs() < 1e-3, "{areas:?}");
+        }
+        // The square is merged into a single rectangle.
+        assert_eq!(
+            features[1]["geometry"]["coordinates"]
+                .as_array()
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn unsupported_crss_are_refused() {
+        assert!(mask_to_geojson(MASK, &georef(2056)).is_err());
+    }
+}
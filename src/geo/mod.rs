@@ -0,0 +1,342 @@
+//! Georeferencing of input images and conversions between the supported CRSs.
+
+use serde::{Deserialize, Serialize};
+use std::{f64::consts::PI, io::Cursor};
+use tiff::{decoder::Decoder, tags::Tag};
+
+mod clip;
+mod geojson;
+mod mosaic;
+mod utm;
+mod warp;
+
+pub use clip::clip_to_area;
+pub use geojson::mask_to_geojson;
+pub use mosaic::{mosaic, mosaic_grid, stitch_tiles};
+pub use warp::warp_to_mercator;
+
+/// Half the width of the EPSG:3857 world in metres.
+pub const MERCATOR_EXTENT: f64 = 20037508.342789244;
+
+const GEOGRAPHIC_TYPE_GEO_KEY: u16 = 2048;
+const PROJECTED_CS_TYPE_GEO_KEY: u16 = 3072;
+
+/// Point in WGS84 degrees.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LatLng {
+    pub lat: f64,
+    pub lng: f64,
+}
+
+/// Geographic extent in WGS84 degrees.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Bounds {
+    pub south: f64,
+    pub west: f64,
+    pub north: f64,
+    pub east: f64,
+}
+
+impl Bounds {
+    /// Smallest extent containing all of `points`.
+    pub fn enclosing(points: &[LatLng]) -> Self {
+        Bounds {
+            south: points.iter().map(|p| p.lat).fold(f64::INFINITY, f64::min),
+            west: points.iter().map(|p| p.lng).fold(f64::INFINITY, f64::min),
+            north: points
+                .iter()
+                .map(|p| p.lat)
+                .fold(f64::NEG_INFINITY, f64::max),
+            east: points
+                .iter()
+                .map(|p| p.lng)
+                .fold(f64::NEG_INFINITY, f64::max),
+        }
+    }
+}
+
+/// Even-odd rule point-in-polygon test.
+pub fn polygon_contains(polygon: &[(f64, f64)], (x, y): (f64, f64)) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len() - 1;
+    for i in 0..polygon.len() {
+        let ((xi, yi), (xj, yj)) = (polygon[i], polygon[j]);
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+pub fn lat_lng_to_mercator(point: LatLng) -> (f64, f64) {
+    let x = point.lng / 180.0 * MERCATOR_EXTENT;
+    let y = (PI / 4.0 + point.lat.to_radians() / 2.0).tan().ln() / PI * MERCATOR_EXTENT;
+    (x, y)
+}
+
+pub fn mercator_to_lat_lng(x: f64, y: f64) -> LatLng {
+    LatLng {
+        lat: (2.0 * (y / MERCATOR_EXTENT * PI).exp().atan() - PI / 2.0).to_degrees(),
+        lng: x / MERCATOR_EXTENT * 180.0,
+    }
+}
+
+const WORLD_FILE_EXTENSIONS: [&str; 8] =
+    ["wld", "pgw", "pngw", "jgw", "jpgw", "tfw", "tifw", "gfw"];
+
+pub fn is_world_file(file_name: &str) -> bool {
+    file_name
+        .rsplit_once('.')
+        .is_some_and(|(_, ext)| WORLD_FILE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// Coordinate reference systems that can be converted to and from WGS84 on the client.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Crs {
+    Wgs84,
+    WebMercator,
+    Utm { zone: u8, north: bool },
+}
+
+impl Crs {
+    pub fn from_epsg(epsg: u32) -> Option<Self> {
+        match epsg {
+            4326 => Some(Crs::Wgs84),
+            3857 | 900913 => Some(Crs::WebMercator),
+            // WGS84 / UTM north and south, and ETRS89 / UTM, whose GRS80 ellipsoid is
+            // close enough to WGS84 for display.
+            32601..=32660 => Some(Crs::Utm {
+                zone: (epsg - 32600) as u8,
+                north: true,
+            }),
+            32701..=32760 => Some(Crs::Utm {
+                zone: (epsg - 32700) as u8,
+                north: false,
+            }),
+            25828..=25838 => Some(Crs::Utm {
+                zone: (epsg - 25800) as u8,
+                north: true,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn unproject(self, x: f64, y: f64) -> LatLng {
+        match self {
+            Crs::Wgs84 => LatLng { lat: y, lng: x },
+            Crs::WebMercator => mercator_to_lat_lng(x, y),
+            Crs::Utm { zone, north } => utm::from_utm(x, y, zone, north),
+        }
+    }
+
+    pub fn project(self, point: LatLng) -> (f64, f64) {
+        match self {
+            Crs::Wgs84 => (point.lng, point.lat),
+            Crs::WebMercator => lat_lng_to_mercator(point),
+            Crs::Utm { zone, north } => utm::to_utm(point, zone, north),
+        }
+    }
+}
+
+/// Maps pixel `(col, row)` of an image to coordinates in the `epsg` CRS, using the
+/// six world file parameters: `x = a*col + b*row + c`, `y = d*col + e*row + f`.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Georeference {
+    pub epsg: u32,
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+    pub f: f64,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Georeference {
+    pub fn pixel_to_crs(&self, col: f64, row: f64) -> (f64, f64) {
+        (
+            self.a * col + self.b * row + self.c,
+            self.d * col + self.e * row + self.f,
+        )
+    }
+
+    /// Inverse of [`Georeference::pixel_to_crs`].
+    pub fn crs_to_pixel(&self, x: f64, y: f64) -> (f64, f64) {
+        let det = self.a * self.e - self.b * self.d;
+        let (dx, dy) = (x - self.c, y - self.f);
+        (
+            (self.e * dx - self.b * dy) / det,
+            (self.a * dy - self.d * dx) / det,
+        )
+    }
+
+    /// Converts CRS coordinates to WGS84, if the CRS is supported.
+    pub fn crs_to_lat_lng(&self, x: f64, y: f64) -> Option<LatLng> {
+        Crs::from_epsg(self.epsg).map(|crs| crs.unproject(x, y))
+    }
+
+    /// Inverse of [`Georeference::crs_to_lat_lng`].
+    pub fn lat_lng_to_crs(&self, point: LatLng) -> Option<(f64, f64)> {
+        Crs::from_epsg(self.epsg).map(|crs| crs.project(point))
+    }
+
+    /// Extent of the whole image in WGS84. Points along the edges are sampled as
+    /// well as the corners, since edges are curved in WGS84 for projected CRSs.
+    pub fn bounds(&self) -> Option<Bounds> {
+        let (w, h) = (self.width as f64, self.height as f64);
+        let corners = (0..=8)
+            .map(|i| i as f64 / 8.0)
+            .flat_map(|t| [(t * w, 0.0), (t * w, h), (0.0, t * h), (w, t * h)])
+            .map(|(col, row)| {
+                let (x, y) = self.pixel_to_crs(col, row);
+                self.crs_to_lat_lng(x, y)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Bounds {
+            south: corners.iter().map(|p| p.lat).fold(f64::INFINITY, f64::min),
+            west: corners.iter().map(|p| p.lng).fold(f64::INFINITY, f64::min),
+            north: corners
+                .iter()
+                .map(|p| p.lat)
+                .fold(f64::NEG_INFINITY, f64::max),
+            east: corners
+                .iter()
+                .map(|p| p.lng)
+                .fold(f64::NEG_INFINITY, f64::max),
+        })
+    }
+
+    /// Parses an ESRI world file (`.pgw`, `.jgw`, `.tfw`, `.wld`, ...) describing an
+    /// image of `width` by `height` pixels. World files carry no CRS, so it is given as `epsg`.
+    pub fn from_world_file(text: &str, epsg: u32, width: u32, height: u32) -> Option<Self> {
+        let values = text
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<f64>, _>>()
+            .ok()?;
+        let [a, d, b, e, c, f] = values[..] else {
+            return None;
+        };
+        // World files locate the centre of the top left pixel rather than its corner.
+        Some(Georeference {
+            epsg,
+            a,
+            b,
+            c: c - a / 2.0 - b / 2.0,
+            d,
+            e,
+            f: f - d / 2.0 - e / 2.0,
+            width,
+            height,
+        })
+    }
+
+    /// Reads the georeference of a GeoTIFF, if `data` is one.
+    pub fn from_geotiff(data: &[u8]) -> Option<Self> {
+        let mut decoder = Decoder::new(Cursor::new(data)).ok()?;
+        let (width, height) = decoder.dimensions().ok()?;
+
+        let keys = decoder.get_tag_u16_vec(Tag::GeoKeyDirectoryTag).ok()?;
+        // Projected files also carry their geographic base CRS, so look for the projected one first.
+        let geo_key = |id| {
+            keys.get(4..)?
+                .chunks_exact(4)
+                .find(|key| key[0] == id && key[1] == 0)
+                .map(|key| key[3] as u32)
+        };
+        let epsg =
+            geo_key(PROJECTED_CS_TYPE_GEO_KEY).or_else(|| geo_key(GEOGRAPHIC_TYPE_GEO_KEY))?;
+
+        let (a, b, c, d, e, f) =
+            if let Ok(matrix) = decoder.get_tag_f64_vec(Tag::ModelTransformationTag) {
+                let m = matrix.get(..8)?;
+                (m[0], m[1], m[3], m[4], m[5], m[7])
+            } else {
+                let scale = decoder.get_tag_f64_vec(Tag::ModelPixelScaleTag).ok()?;
+                let tiepoint = decoder.get_tag_f64_vec(Tag::ModelTiepointTag).ok()?;
+                let (scale, tiepoint) = (scale.get(..2)?, tiepoint.get(..5)?);
+                (
+                    scale[0],
+                    0.0,
+                    tiepoint[3] - tiepoint[0] * scale[0],
+                    0.0,
+                    -scale[1],
+                    tiepoint[4] + tiepoint[1] * scale[1],
+                )
+            };
+
+        Some(Georeference {
+            epsg,
+            a,
+            b,
+            c,
+            d,
+            e,
+            f,
+            width,
+            height,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Georeference;
+
+    #[test]
+    fn world_files_locate_the_corner_of_the_top_left_pixel() {
+        let text = include_str!("../../fixtures/image.pgw");
+        assert_eq!(
+            Georeference::from_world_file(text, 32633, 8, 8),
+            Some(Georeference {
+                epsg: 32633,
+                a: 10.0,
+                b: 0.0,
+                c: 500000.0,
+                d: 0.0,
+                e: -10.0,
+                f: 4650000.0,
+                width: 8,
+                height: 8,
+            })
+        );
+        assert_eq!(
+            Georeference::from_world_file("10 0 0 -10", 32633, 8, 8),
+            None
+        );
+        assert_eq!(
+            Georeference::from_world_file("10 0 0 -10 x 0", 32633, 8, 8),
+            None
+        );
+    }
+
+    #[test]
+    fn geotiffs_are_located_by_their_tie_point_in_their_projected_crs() {
+        let georef = Georeference::from_geotiff(include_bytes!("../../fixtures/utm.tif")).unwrap();
+        assert_eq!(
+            georef,
+            Georeference {
+                epsg: 32633,
+                a: 10.0,
+                b: 0.0,
+                c: 500000.0,
+                d: 0.0,
+                e: -10.0,
+                f: 4650000.0,
+                width: 4,
+                height: 2,
+            }
+        );
+        assert_eq!(georef.pixel_to_crs(4.0, 2.0), (500040.0, 4649980.0));
+    }
+
+    #[test]
+    fn other_images_are_not_geotiffs() {
+        assert_eq!(
+            Georeference::from_geotiff(include_bytes!("../../fixtures/image.png")),
+            None
+        );
+    }
+}
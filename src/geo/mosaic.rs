@@ -0,0 +1,145 @@
+//! Mosaicking of several georeferenced chips, or of map tiles, into a single image.
+
+use image::{imageops, DynamicImage, ImageOutputFormat, Rgba, RgbaImage};
+use std::io::Cursor;
+
+use super::{polygon_contains, Georeference};
+
+/// Largest mosaic side in pixels, to keep the browser from running out of memory.
+const MAX_SIDE: u32 = 8192;
+
+/// Georeference of the mosaic of chips georeferenced by `georefs`: north-up, on the grid
+/// of the first chip and covering them all. Cheap, so that chips can be checked before
+/// being decoded.
+pub fn mosaic_grid(georefs: &[Georeference]) -> Result<Georeference, String> {
+    let first = georefs
+        .first()
+        .ok_or_else(|| "No chips to mosaic".to_string())?;
+    if let Some(other) = georefs.iter().find(|g| g.epsg != first.epsg) {
+        return Err(format!(
+            "Chips must share a CRS to be mosaicked, got EPSG:{} and EPSG:{}",
+            first.epsg, other.epsg
+        ));
+    }
+
+    let corners: Vec<_> = georefs
+        .iter()
+        .flat_map(|g| {
+            let (w, h) = (g.width as f64, g.height as f64);
+            [(0.0, 0.0), (w, 0.0), (0.0, h), (w, h)].map(|(col, row)| g.pixel_to_crs(col, row))
+        })
+        .collect();
+    let min_x = corners.iter().map(|p| p.0).fold(f64::INFINITY, f64::min);
+    let max_x = corners
+        .iter()
+        .map(|p| p.0)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let min_y = corners.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let max_y = corners
+        .iter()
+        .map(|p| p.1)
+        .fold(f64::NEG_INFINITY, f64::max);
+
+    let (res_x, res_y) = (first.a.hypot(first.d), first.b.hypot(first.e));
+    let width = ((max_x - min_x) / res_x).ceil().max(1.0) as u32;
+    let height = ((max_y - min_y) / res_y).ceil().max(1.0) as u32;
+    if width > MAX_SIDE || height > MAX_SIDE {
+        return Err(format!(
+            "The mosaic would be {width}x{height} pixels, at most {MAX_SIDE} per side are allowed"
+        ));
+    }
+    Ok(Georeference {
+        epsg: first.epsg,
+        a: res_x,
+        b: 0.0,
+        c: min_x,
+        d: 0.0,
+        e: -res_y,
+        f: max_y,
+        width,
+        height,
+    })
+}
+
+/// Combines `chips` into one north-up PNG on the grid of [`mosaic_grid`], returning it
+/// with its georeference. Where chips overlap, pixels are blended with weights growing
+/// away from each chip's edges so no seam is visible. Uncovered pixels are black.
+pub fn mosaic(chips: &[(&[u8], Georeference)]) -> Result<(Vec<u8>, Georeference), String> {
+    let georef = mosaic_grid(&chips.iter().map(|(_, g)| *g).collect::<Vec<_>>())?;
+    let (width, height) = (georef.width, georef.height);
+    let images = chips
+        .iter()
+        .map(|(data, _)| {
+            image::load_from_memory(data)
+                .map(|img| img.to_rgba8())
+                .map_err(|e| format!("Could not decode chip: {e}"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let canvas = RgbaImage::from_fn(width, height, |col, row| {
+        let (x, y) = georef.pixel_to_crs(col as f64 + 0.5, row as f64 + 0.5);
+        let mut sum = [0.0; 3];
+        let mut total = 0.0;
+        for ((_, chip), image) in chips.iter().zip(&images) {
+            let (src_col, src_row) = chip.crs_to_pixel(x, y);
+            let (w, h) = (image.width() as f64, image.height() as f64);
+            let (src_col, src_row) = (
+                src_col * w / chip.width as f64,
+                src_row * h / chip.height as f64,
+            );
+            let weight = src_col.min(src_row).min(w - src_col).min(h - src_row);
+            if weight <= 0.0 {
+                continue;
+            }
+            let pixel = image.get_pixel(src_col as u32, src_row as u32);
+            // Transparent pixels carry no data.
+            if pixel[3] == 0 {
+                continue;
+            }
+            for (s, v) in sum.iter_mut().zip(pixel.0) {
+                *s += v as f64 * weight;
+            }
+            total += weight;
+        }
+        if total == 0.0 {
+            return Rgba([0, 0, 0, 255]);
+        }
+        let [r, g, b] = sum.map(|s| (s / total).round() as u8);
+        Rgba([r, g, b, 255])
+    });
+
+    let mut png = vec![];
+    DynamicImage::ImageRgba8(canvas)
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .map_err(|e| format!("Could not encode mosaic: {e}"))?;
+    Ok((png, georef))
+}
+
+/// Encoded `tiles`, each placed with its top left corner at the given pixel, stitched into
+/// a PNG of `width` by `height` pixels. Pixels whose centres fall outside `polygon`, in the
+/// same pixels, are blacked out.
+pub fn stitch_tiles(
+    tiles: &[(&[u8], i64, i64)],
+    width: u32,
+    height: u32,
+    polygon: &[(f64, f64)],
+) -> Result<Vec<u8>, String> {
+    let mut canvas = RgbaImage::new(width, height);
+    for (i, (data, x, y)) in tiles.iter().enumerate() {
+        let tile = image::load_from_memory(data)
+            .map_err(|e| format!("Could not decode tile {}: {e}", i + 1))?;
+        imageops::replace(&mut canvas, &tile.to_rgba8(), *x, *y);
+    }
+
+    for (x, y, pixel) in canvas.enumerate_pixels_mut() {
+        if !polygon_contains(polygon, (x as f64 + 0.5, y as f64 + 0.5)) {
+            *pixel = Rgba([0, 0, 0, 255]);
+        }
+    }
+
+    let mut png = vec![];
+    DynamicImage::ImageRgba8(canvas)
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .map_err(|e| format!("Could not encode stitched image: {e}"))?;
+    Ok(png)
+}
@@ -0,0 +1,179 @@
+//! Transverse Mercator projection of the UTM zones on the WGS84 ellipsoid,
+//! following the series expansions in Snyder's "Map Projections: A Working Manual".
+
+use super::LatLng;
+
+const A: f64 = 6378137.0;
+const F: f64 = 1.0 / 298.257223563;
+const K0: f64 = 0.9996;
+const FALSE_EASTING: f64 = 500000.0;
+const FALSE_NORTHING_SOUTH: f64 = 10000000.0;
+
+fn e2() -> f64 {
+    F * (2.0 - F)
+}
+
+fn central_meridian(zone: u8) -> f64 {
+    (zone as f64 * 6.0 - 183.0).to_radians()
+}
+
+/// Distance along the central meridian from the equator to latitude `phi`.
+fn meridian_arc(phi: f64) -> f64 {
+    let e2 = e2();
+    let (e4, e6) = (e2 * e2, e2 * e2 * e2);
+    A * ((1.0 - e2 / 4.0 - 3.0 * e4 / 64.0 - 5.0 * e6 / 256.0) * phi
+        - (3.0 * e2 / 8.0 + 3.0 * e4 / 32.0 + 45.0 * e6 / 1024.0) * (2.0 * phi).sin()
+        + (15.0 * e4 / 256.0 + 45.0 * e6 / 1024.0) * (4.0 * phi).sin()
+        - (35.0 * e6 / 3072.0) * (6.0 * phi).sin())
+}
+
+pub fn to_utm(point: LatLng, zone: u8, north: bool) -> (f64, f64) {
+    let e2 = e2();
+    let ep2 = e2 / (1.0 - e2);
+    let phi = point.lat.to_radians();
+    let (sin, cos, tan) = (phi.sin(), phi.cos(), phi.tan());
+
+    let n = A / (1.0 - e2 * sin * sin).sqrt();
+    let t = tan * tan;
+    let c = ep2 * cos * cos;
+    let a = cos * (point.lng.to_radians() - central_meridian(zone));
+
+    let x = K0
+        * n
+        * (a + (1.0 - t + c) * a.powi(3) / 6.0
+            + (5.0 - 18.0 * t + t * t + 72.0 * c - 58.0 * ep2) * a.powi(5) / 120.0);
+    let y = K0
+        * (meridian_arc(phi)
+            + n * tan
+                * (a * a / 2.0
+                    + (5.0 - t + 9.0 * c + 4.0 * c * c) * a.powi(4) / 24.0
+                    + (61.0 - 58.0 * t + t * t + 600.0 * c - 330.0 * ep2) * a.powi(6) / 720.0));
+
+    (
+        x + FALSE_EASTING,
+        if north { y } else { y + FALSE_NORTHING_SOUTH },
+    )
+}
+
+pub fn from_utm(easting: f64, northing: f64, zone: u8, north: bool) -> LatLng {
+    let e2 = e2();
+    let ep2 = e2 / (1.0 - e2);
+    let x = easting - FALSE_EASTING;
+    let y = if north {
+        northing
+    } else {
+        northing - FALSE_NORTHING_SOUTH
+    };
+
+    let mu = y / K0 / (A * (1.0 - e2 / 4.0 - 3.0 * e2 * e2 / 64.0 - 5.0 * e2.powi(3) / 256.0));
+    let e1 = (1.0 - (1.0 - e2).sqrt()) / (1.0 + (1.0 - e2).sqrt());
+    let phi1 = mu
+        + (3.0 * e1 / 2.0 - 27.0 * e1.powi(3) / 32.0) * (2.0 * mu).sin()
+        + (21.0 * e1 * e1 / 16.0 - 55.0 * e1.powi(4) / 32.0) * (4.0 * mu).sin()
+        + (151.0 * e1.powi(3) / 96.0) * (6.0 * mu).sin()
+        + (1097.0 * e1.powi(4) / 512.0) * (8.0 * mu).sin();
+
+    let (sin, cos, tan) = (phi1.sin(), phi1.cos(), phi1.tan());
+    let c1 = ep2 * cos * cos;
+    let t1 = tan * tan;
+    let n1 = A / (1.0 - e2 * sin * sin).sqrt();
+    let r1 = A * (1.0 - e2) / (1.0 - e2 * sin * sin).powf(1.5);
+    let d = x / (n1 * K0);
+
+    let phi = phi1
+        - (n1 * tan / r1)
+            * (d * d / 2.0
+                - (5.0 + 3.0 * t1 + 10.0 * c1 - 4.0 * c1 * c1 - 9.0 * ep2) * d.powi(4) / 24.0
+                + (61.0 + 90.0 * t1 + 298.0 * c1 + 45.0 * t1 * t1 - 252.0 * ep2 - 3.0 * c1 * c1)
+                    * d.powi(6)
+                    / 720.0);
+    let lambda = central_meridian(zone)
+        + (d - (1.0 + 2.0 * t1 + c1) * d.powi(3) / 6.0
+            + (5.0 - 2.0 * c1 + 28.0 * t1 - 3.0 * c1 * c1 + 8.0 * ep2 + 24.0 * t1 * t1)
+                * d.powi(5)
+                / 120.0)
+            / cos;
+
+    LatLng {
+        lat: phi.to_degrees(),
+        lng: lambda.to_degrees(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_utm, to_utm};
+    use crate::geo::LatLng;
+
+    /// Points with their UTM coordinates from the exact Krüger series, which Snyder's
+    /// expansions match to well under a centimetre this close to the central meridian.
+    const KNOWN: [(LatLng, u8, bool, (f64, f64)); 4] = [
+        (
+            LatLng {
+                lat: 45.0,
+                lng: 9.0,
+            },
+            32,
+            true,
+            (500000.0, 4982950.400),
+        ),
+        (
+            LatLng {
+                lat: 48.8583,
+                lng: 2.2945,
+            },
+            31,
+            true,
+            (448251.898, 5411943.794),
+        ),
+        (
+            LatLng {
+                lat: 55.7558,
+                lng: 37.6173,
+            },
+            37,
+            true,
+            (413224.138, 6179766.954),
+        ),
+        (
+            LatLng {
+                lat: -33.8568,
+                lng: 151.2153,
+            },
+            56,
+            false,
+            (334900.570, 6252288.753),
+        ),
+    ];
+
+    #[test]
+    fn points_are_projected_to_their_known_coordinates() {
+        for (point, zone, north, (easting, northing)) in KNOWN {
+            let (x, y) = to_utm(point, zone, north);
+            assert!((x - easting).abs() < 0.01, "{point:?}: easting {x}");
+            assert!((y - northing).abs() < 0.01, "{point:?}: northing {y}");
+        }
+    }
+
+    #[test]
+    fn known_coordinates_are_unprojected_to_their_points() {
+        for (point, zone, north, (easting, northing)) in KNOWN {
+            let unprojected = from_utm(easting, northing, zone, north);
+            assert!(
+                (unprojected.lat - point.lat).abs() < 1e-7,
+                "{unprojected:?}"
+            );
+            assert!(
+                (unprojected.lng - point.lng).abs() < 1e-7,
+                "{unprojected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn the_equator_is_at_the_false_northing_of_each_hemisphere() {
+        let point = LatLng { lat: 0.0, lng: 3.0 };
+        assert_eq!(to_utm(point, 31, true), (500000.0, 0.0));
+        assert_eq!(to_utm(point, 31, false), (500000.0, 10000000.0));
+    }
+}
@@ -0,0 +1,97 @@
+//! Resampling of georeferenced masks onto the Web Mercator grid used by the basemaps.
+
+use image::{DynamicImage, ImageOutputFormat, Rgba, RgbaImage};
+use std::io::Cursor;
+
+use super::{lat_lng_to_mercator, mercator_to_lat_lng, Bounds, Crs, Georeference, LatLng};
+
+/// Warps `mask` (covering the same extent as `georef`, at any resolution) to EPSG:3857,
+/// returning it as a PNG together with its WGS84 bounds. Pixels are sampled with
+/// nearest neighbour so class values are preserved; uncovered pixels are transparent.
+pub fn warp_to_mercator(mask: &[u8], georef: &Georeference) -> Result<(Vec<u8>, Bounds), String> {
+    let crs = Crs::from_epsg(georef.epsg)
+        .ok_or_else(|| format!("EPSG:{} cannot be reprojected", georef.epsg))?;
+    let bounds = georef
+        .bounds()
+        .ok_or_else(|| "Could not compute the mask extent".to_string())?;
+    let source = image::load_from_memory(mask)
+        .map_err(|e| format!("Could not decode mask: {e}"))?
+        .to_rgba8();
+
+    let (min_x, min_y) = lat_lng_to_mercator(LatLng {
+        lat: bounds.south,
+        lng: bounds.west,
+    });
+    let (max_x, max_y) = lat_lng_to_mercator(LatLng {
+        lat: bounds.north,
+        lng: bounds.east,
+    });
+    let width = source.width().max(1);
+    let height = ((max_y - min_y) / (max_x - min_x) * width as f64)
+        .round()
+        .max(1.0) as u32;
+    let (scale_x, scale_y) = (
+        source.width() as f64 / georef.width as f64,
+        source.height() as f64 / georef.height as f64,
+    );
+
+    let warped = RgbaImage::from_fn(width, height, |col, row| {
+        let x = min_x + (col as f64 + 0.5) / width as f64 * (max_x - min_x);
+        let y = max_y - (row as f64 + 0.5) / height as f64 * (max_y - min_y);
+        let (src_x, src_y) = crs.project(mercator_to_lat_lng(x, y));
+        let (src_col, src_row) = georef.crs_to_pixel(src_x, src_y);
+        let (src_col, src_row) = (src_col * scale_x, src_row * scale_y);
+        if src_col < 0.0 || src_row < 0.0 {
+            return Rgba([0, 0, 0, 0]);
+        }
+        source
+            .get_pixel_checked(src_col as u32, src_row as u32)
+            .copied()
+            .unwrap_or(Rgba([0, 0, 0, 0]))
+    });
+
+    let mut png = vec![];
+    DynamicImage::ImageRgba8(warped)
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .map_err(|e| format!("Could not encode warped mask: {e}"))?;
+    Ok((png, bounds))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::warp_to_mercator;
+    use crate::geo::Georeference;
+
+    const MASK: &[u8] = include_bytes!("../../fixtures/mask.png");
+
+    fn georef(epsg: u32) -> Georeference {
+        Georeference::from_world_file(include_str!("../../fixtures/image.pgw"), epsg, 8, 8).unwrap()
+    }
+
+    #[test]
+    fn warped_masks_keep_their_extent_and_classes() {
+        let georef = georef(32633);
+        let (png, bounds) = warp_to_mercator(MASK, &georef).unwrap();
+        assert_eq!(Some(bounds), georef.bounds());
+
+        let colours = |data: &[u8]| {
+            image::load_from_memory(data)
+                .unwrap()
+                .to_rgba8()
+                .pixels()
+                .filter(|pixel| pixel[3] > 0)
+                .copied()
+                .collect::<HashSet<_>>()
+        };
+        let (warped, source) = (colours(&png), colours(MASK));
+        assert!(warped.is_subset(&source), "{warped:?} not in {source:?}");
+        assert!(warped.len() > 1);
+    }
+
+    #[test]
+    fn unsupported_crss_are_refused() {
+        assert!(warp_to_mercator(MASK, &georef(2056)).is_err());
+    }
+}
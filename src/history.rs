@@ -0,0 +1,848 @@
+//! Segmentation results kept in IndexedDB so they survive page reloads.
+
+use js_sys::{Date, Float64Array, Object, Reflect, Uint8Array};
+use shadow_clone::shadow_clone;
+use std::rc::Rc;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    audit::{self, AuditAction},
+    clock, digest,
+    flags::{self, Flag},
+    geo::Georeference,
+    http,
+    i18n::{use_language, Language},
+    idb,
+    issues::{self, FlagControls},
+    metadata::{self, Decision, HistoryFilter, Metadata, Review},
+    notify, project,
+    rerun::use_rerun,
+    review::ReviewDeck,
+    roles::use_role,
+    route::Route,
+    settings::Settings,
+    sync::{self, SyncMessage},
+    FileDetails,
+};
+
+/// An uploaded image together with the mask the server returned for it.
+#[derive(Clone, PartialEq)]
+pub struct HistoryEntry {
+    /// Key in the database, assigned when the entry is first stored.
+    pub id: Option<f64>,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: f64,
+    /// Segmentation endpoint that produced the mask.
+    pub server: String,
+    pub image: Rc<FileDetails>,
+    pub mask: Rc<FileDetails>,
+    /// Key of the entry whose image was segmented again, with another model, to make this
+    /// one.
+    pub rerun_of: Option<f64>,
+    /// Id the server stored the result under, if it did.
+    pub result_id: Option<String>,
+}
+
+pub fn get(object: &JsValue, key: &str) -> JsValue {
+    Reflect::get(object, &key.into()).unwrap_or(JsValue::UNDEFINED)
+}
+
+pub fn set(object: &Object, key: &str, value: JsValue) {
+    Reflect::set(object, &key.into(), &value).unwrap();
+}
+
+fn georef_to_js(georef: &Georeference) -> JsValue {
+    let Georeference {
+        epsg,
+        a,
+        b,
+        c,
+        d,
+        e,
+        f,
+        width,
+        height,
+    } = *georef;
+    Float64Array::from(&[epsg as f64, a, b, c, d, e, f, width as f64, height as f64][..]).into()
+}
+
+fn georef_from_js(value: JsValue) -> Option<Georeference> {
+    let values = value.dyn_into::<Float64Array>().ok()?.to_vec();
+    let [epsg, a, b, c, d, e, f, width, height] = values[..] else {
+        return None;
+    };
+    Some(Georeference {
+        epsg: epsg as u32,
+        a,
+        b,
+        c,
+        d,
+        e,
+        f,
+        width: width as u32,
+        height: height as u32,
+    })
+}
+
+pub fn file_to_js(file: &FileDetails) -> JsValue {
+    let object = Object::new();
+    set(&object, "file_name", file.file_name.as_str().into());
+    set(&object, "file_type", file.file_type.as_str().into());
+    set(&object, "data", Uint8Array::from(&file.data[..]).into());
+    if let Some(georef) = &file.georef {
+        set(&object, "georef", georef_to_js(georef));
+    }
+    object.into()
+}
+
+pub fn file_from_js(value: &JsValue) -> Option<FileDetails> {
+    Some(FileDetails {
+        file_name: get(value, "file_name").as_string()?,
+        file_type: get(value, "file_type").as_string()?,
+        data: http::shared_bytes(&get(value, "data").dyn_into::<Uint8Array>().ok()?),
+        georef: georef_from_js(get(value, "georef")),
+        auxiliary: None,
+        source: None,
+    })
+}
+
+impl HistoryEntry {
+    pub fn new(image: Rc<FileDetails>, mask: Rc<FileDetails>, server: String) -> Self {
+        HistoryEntry {
+            id: None,
+            timestamp: clock::now(),
+            server,
+            image,
+            mask,
+            rerun_of: None,
+            result_id: None,
+        }
+    }
+
+    fn to_js(&self) -> JsValue {
+        let object = Object::new();
+        if let Some(id) = self.id {
+            set(&object, "id", id.into());
+        }
+        set(&object, "timestamp", self.timestamp.into());
+        set(&object, "server", self.server.as_str().into());
+        set(&object, "image", file_to_js(&self.image));
+        set(&object, "mask", file_to_js(&self.mask));
+        if let Some(rerun_of) = self.rerun_of {
+            set(&object, "rerun_of", rerun_of.into());
+        }
+        if let Some(result_id) = &self.result_id {
+            set(&object, "result_id", result_id.as_str().into());
+        }
+        object.into()
+    }
+
+    fn from_js(value: &JsValue) -> Option<Self> {
+        Some(HistoryEntry {
+            id: get(value, "id").as_f64(),
+            timestamp: get(value, "timestamp").as_f64()?,
+            server: get(value, "server").as_string().unwrap_or_default(),
+            image: Rc::new(file_from_js(&get(value, "image"))?),
+            mask: Rc::new(file_from_js(&get(value, "mask"))?),
+            rerun_of: get(value, "rerun_of").as_f64(),
+            result_id: get(value, "result_id").as_string(),
+        })
+    }
+
+    /// Local date and time the result was produced.
+    pub fn date(&self) -> String {
+        Date::new(&self.timestamp.into())
+            .to_locale_string("default", &JsValue::UNDEFINED)
+            .into()
+    }
+}
+
+/// Stores `entry` along with its class statistics and the digest of its image, returning
+/// its key.
+pub async fn add(entry: &HistoryEntry) -> Result<f64, String> {
+    let db = idb::open().await?;
+    let key = idb::put(&db, idb::RESULTS_STORE, &entry.to_js()).await?;
+    let id = key
+        .as_f64()
+        .ok_or_else(|| "History entry got an unexpected key".to_string())?;
+    let mut metadata = Metadata::new(&entry.mask);
+    metadata.result_id = entry.result_id.clone();
+    // The entry is kept all the same, only to go unrecognized when uploaded again.
+    metadata.digest = digest::sha256(&entry.image.data)
+        .await
+        .inspect_err(|why| log::warn!("{why}"))
+        .ok();
+    metadata::put(id, &metadata).await?;
+    sync::broadcast(SyncMessage::HistoryChanged);
+    Ok(id)
+}
+
+/// Removes the entries the retention settings no longer keep, returning how many.
+pub async fn prune(settings: &Settings) -> Result<usize, String> {
+    if settings.keep_results.is_none() && settings.keep_days.is_none() {
+        return Ok(0);
+    }
+    let oldest_kept = settings
+        .keep_days
+        .map_or(f64::MIN, |days| clock::now() - days as f64 * 86_400_000.0);
+    let keep = settings.keep_results.map_or(usize::MAX, |n| n as usize);
+    let mut pruned = 0;
+    for (i, entry) in list().await?.iter().enumerate() {
+        if i >= keep || entry.timestamp < oldest_kept {
+            if let Some(id) = entry.id {
+                remove(id).await?;
+                pruned += 1;
+            }
+        }
+    }
+    Ok(pruned)
+}
+
+/// The entry stored under `id`, if there is one that can be read.
+pub async fn get_entry(id: f64) -> Result<Option<HistoryEntry>, String> {
+    let db = idb::open().await?;
+    let value = idb::get(&db, idb::RESULTS_STORE, &id.into()).await?;
+    Ok(HistoryEntry::from_js(&value))
+}
+
+/// The newest stored entry for the same image as `image`, byte for byte.
+pub async fn find_image(image: &FileDetails) -> Result<Option<HistoryEntry>, String> {
+    let digest = digest::sha256(&image.data).await?;
+    let mut newest: Option<HistoryEntry> = None;
+    for id in metadata::ids_with_digest(&digest).await? {
+        let Some(entry) = get_entry(id as f64).await? else {
+            continue;
+        };
+        if entry.image.data != image.data {
+            continue;
+        }
+        if newest
+            .as_ref()
+            .is_none_or(|newest| entry.timestamp > newest.timestamp)
+        {
+            newest = Some(entry);
+        }
+    }
+    Ok(newest)
+}
+
+/// Every stored entry, newest first. Entries that cannot be read are skipped.
+pub async fn list() -> Result<Vec<HistoryEntry>, String> {
+    let db = idb::open().await?;
+    let mut entries: Vec<_> = idb::get_all(&db, idb::RESULTS_STORE)
+        .await?
+        .iter()
+        .filter_map(|value| HistoryEntry::from_js(&value))
+        .collect();
+    entries.sort_by(|a, b| b.timestamp.total_cmp(&a.timestamp));
+    Ok(entries)
+}
+
+/// Every stored entry with its metadata, newest first.
+pub async fn list_with_metadata() -> Result<Vec<(Rc<HistoryEntry>, Metadata)>, String> {
+    let mut metadata = metadata::get_all().await?;
+    Ok(list()
+        .await?
+        .into_iter()
+        .map(|entry| {
+            let id = entry.id.map_or(u64::MAX, |id| id as u64);
+            (Rc::new(entry), metadata.remove(&id).unwrap_or_default())
+        })
+        .collect())
+}
+
+pub async fn remove(id: f64) -> Result<(), String> {
+    let db = idb::open().await?;
+    idb::delete(&db, idb::RESULTS_STORE, &id.into()).await?;
+    metadata::remove(id).await?;
+    audit::record(AuditAction::Delete, format!("result {id}"), "");
+    sync::broadcast(SyncMessage::HistoryChanged);
+    Ok(())
+}
+
+/// Stores `review` of the result `id`, or clears it when there is none, reading what else
+/// is known about it afresh so that it is kept.
+pub async fn save_review(id: f64, review: Option<Review>) -> Result<(), String> {
+    let details = match &review {
+        Some(review) => format!("{}: {}", review.decision.name(), review.comment),
+        None => "cleared".to_string(),
+    };
+    let mut stored = metadata::get(id).await?.unwrap_or_default();
+    stored.review = review;
+    metadata::put(id, &stored).await?;
+    audit::record(AuditAction::Review, format!("result {id}"), details);
+    Ok(())
+}
+
+/// Input narrowing the history down, bound to one field of the filter.
+fn filter_input(
+    label: String,
+    input_type: &'static str,
+    value: String,
+    suggestions: Option<&'static str>,
+    filter: &UseStateHandle<HistoryFilter>,
+    update: fn(&mut HistoryFilter, String),
+) -> Html {
+    let onchange = {
+        shadow_clone!(filter);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let mut new_filter = (*filter).clone();
+            update(&mut new_filter, input.value());
+            filter.set(new_filter);
+        }
+    };
+    html! {
+        <div class="input-group input-group-sm">
+            <span class="input-group-text">{label}</span>
+            <input type={input_type} class="form-control" list={suggestions} {value} {onchange} />
+        </div>
+    }
+}
+
+/// `ms` since the Unix epoch as an ISO 8601 date and time, in UTC.
+fn iso_date(ms: f64) -> String {
+    Date::new(&ms.into()).to_iso_string().into()
+}
+
+pub fn decision_label(language: Language, decision: Decision) -> String {
+    language.t(match decision {
+        Decision::Accepted => "decision-accepted",
+        Decision::Rejected => "decision-rejected",
+        Decision::NeedsEdit => "decision-needs-edit",
+    })
+}
+
+pub fn decision_badge(language: Language, decision: Decision) -> Html {
+    let class = match decision {
+        Decision::Accepted => "text-bg-success",
+        Decision::Rejected => "text-bg-danger",
+        Decision::NeedsEdit => "text-bg-warning",
+    };
+    html!(<span class={classes!("badge", "ms-1", class)}>{decision_label(language, decision)}</span>)
+}
+
+/// Marking a result accepted, rejected or in need of editing, with a comment. The comment
+/// can be written before deciding, and changing it later keeps the decision.
+#[autoprops_component(ReviewControls)]
+fn review_controls(review: Option<Review>, onreview: Callback<Review>) -> Html {
+    let language = use_language();
+    let comment = use_state(|| {
+        review
+            .as_ref()
+            .map(|review| review.comment.clone())
+            .unwrap_or_default()
+    });
+    // Rows are reused for other results as the list is filtered.
+    {
+        shadow_clone!(comment);
+        use_effect_with(review.clone(), move |review| {
+            if let Some(review) = review {
+                comment.set(review.comment.clone());
+            }
+        });
+    }
+    let decide = |decision: Decision| {
+        shadow_clone!(comment, onreview);
+        move |_| {
+            onreview.emit(Review {
+                decision,
+                comment: (*comment).clone(),
+                timestamp: clock::now(),
+            })
+        }
+    };
+    let oncomment = {
+        shadow_clone!(comment, review, onreview);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            comment.set(input.value());
+            if let Some(review) = &review {
+                onreview.emit(Review {
+                    comment: input.value(),
+                    timestamp: clock::now(),
+                    ..review.clone()
+                });
+            }
+        }
+    };
+    let current = review.as_ref().map(|review| review.decision);
+    html! {
+        <div class="input-group input-group-sm mt-1">
+            {for Decision::ALL.into_iter().map(|decision| html! {
+                <button
+                    class={classes!(
+                        "btn",
+                        if current == Some(decision) { "btn-secondary" } else { "btn-outline-secondary" },
+                    )}
+                    aria-pressed={(current == Some(decision)).to_string()}
+                    onclick={decide(decision)}
+                >
+                    {decision_label(language, decision)}
+                </button>
+            })}
+            <input
+                class="form-control"
+                placeholder={language.t("history-review-comment")}
+                value={(*comment).clone()}
+                onchange={oncomment}
+            />
+        </div>
+    }
+}
+
+/// Stored results, reloaded whenever `revision` changes, with their tags and notes and a
+/// search over them. Any two results can be picked to be compared, older one first. In
+/// review mode, each result can be accepted, rejected or marked as needing edits, also from
+/// the keyboard alone, and the decisions exported as a CSV log, as can the comments on the
+/// results. Results can be segmented again with another model, and the new results
+/// compared with those they were re-run from.
+#[autoprops_component(HistoryPane)]
+pub fn history_pane(
+    revision: i32,
+    onopen: Callback<Rc<HistoryEntry>>,
+    #[prop_or_default] oncompare: Callback<(Rc<HistoryEntry>, Rc<HistoryEntry>)>,
+) -> Html {
+    let language = use_language();
+    let entries = use_state(|| None::<Result<Vec<(Rc<HistoryEntry>, Metadata)>, String>>);
+    let filter = use_state(HistoryFilter::default);
+    // Results picked for comparison, at most two, most recently picked last.
+    let selected = use_state(Vec::<Rc<HistoryEntry>>::new);
+    // Results carrying the filter's tag, from the tags index.
+    let tagged = use_state(|| None::<Vec<u64>>);
+    let reviewing = use_state(|| false);
+    let rerun = use_rerun();
+    let can_edit = use_role().can_edit();
+
+    let reload = {
+        shadow_clone!(entries);
+        move || {
+            shadow_clone!(entries);
+            spawn_local(async move { entries.set(Some(list_with_metadata().await)) });
+        }
+    };
+
+    {
+        shadow_clone!(reload);
+        use_effect_with(revision, move |_| reload());
+    }
+
+    {
+        shadow_clone!(tagged);
+        use_effect_with(filter.tag.trim().to_string(), move |tag| {
+            if tag.is_empty() {
+                tagged.set(None);
+                return;
+            }
+            shadow_clone!(tag);
+            spawn_local(async move {
+                match metadata::ids_with_tag(&tag).await {
+                    Ok(ids) => tagged.set(Some(ids)),
+                    Err(why) => notify::error(language.t("history-tag-filter-failed"), why),
+                }
+            });
+        });
+    }
+
+    let oncompareclick = {
+        shadow_clone!(selected, oncompare);
+        move |_| {
+            if let [a, b] = &selected[..] {
+                let (before, after) = if a.timestamp <= b.timestamp {
+                    (a, b)
+                } else {
+                    (b, a)
+                };
+                oncompare.emit((before.clone(), after.clone()));
+            }
+        }
+    };
+
+    let onreviewing = {
+        shadow_clone!(reviewing);
+        move |_| reviewing.set(!*reviewing)
+    };
+    let onreviewfilter = {
+        shadow_clone!(filter);
+        move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            filter.set(HistoryFilter {
+                review: select.value(),
+                ..(*filter).clone()
+            });
+        }
+    };
+    let onexportlog = {
+        shadow_clone!(entries);
+        move |_| {
+            if let Some(Ok(entries)) = &*entries {
+                let log = metadata::decision_log(entries, iso_date);
+                project::download("decisions.csv", "text/csv", log.as_bytes());
+            }
+        }
+    };
+    let onexportcomments = {
+        shadow_clone!(entries);
+        move |_| {
+            if let Some(Ok(entries)) = &*entries {
+                let log = metadata::comment_log(entries, iso_date);
+                project::download("comments.csv", "text/csv", log.as_bytes());
+            }
+        }
+    };
+
+    let filters = html! {
+        <div class="row row-cols-4 g-1 mb-2">
+            <div class="col">
+                {filter_input(language.t("history-filter-name"), "search", filter.file_name.clone(), None, &filter, |f, v| f.file_name = v)}
+            </div>
+            <div class="col">
+                {filter_input(language.t("history-filter-tag"), "search", filter.tag.clone(), Some("history-tags"), &filter, |f, v| f.tag = v)}
+            </div>
+            <div class="col">
+                {filter_input(language.t("history-filter-server"), "search", filter.server.clone(), None, &filter, |f, v| f.server = v)}
+            </div>
+            <div class="col">
+                {filter_input(
+                    language.t("history-filter-class"),
+                    "number",
+                    filter.class.map(|c| c.to_string()).unwrap_or_default(),
+                    None,
+                    &filter,
+                    |f, v| f.class = v.parse().ok(),
+                )}
+            </div>
+            <div class="col">
+                {filter_input(language.t("history-filter-from"), "date", filter.from.clone(), None, &filter, |f, v| f.from = v)}
+            </div>
+            <div class="col">
+                {filter_input(language.t("history-filter-to"), "date", filter.to.clone(), None, &filter, |f, v| f.to = v)}
+            </div>
+            <div class="col">
+                {filter_input(
+                    language.t("history-filter-min-share"),
+                    "number",
+                    (filter.min_share * 100.0).to_string(),
+                    None,
+                    &filter,
+                    |f, v| f.min_share = v.parse::<f64>().unwrap_or(0.0).clamp(0.0, 100.0) / 100.0,
+                )}
+            </div>
+            <div class="col">
+                <div class="input-group input-group-sm">
+                    <span class="input-group-text">{language.t("history-filter-review")}</span>
+                    <select class="form-select" onchange={onreviewfilter}>
+                        <option value="" selected={filter.review.is_empty()}>{language.t("history-filter-any")}</option>
+                        <option value="pending" selected={filter.review == "pending"}>{language.t("history-filter-pending")}</option>
+                        {for Decision::ALL.into_iter().map(|decision| html! {
+                            <option value={decision.name()} selected={filter.review == decision.name()}>
+                                {decision_label(language, decision)}
+                            </option>
+                        })}
+                    </select>
+                </div>
+            </div>
+        </div>
+    };
+
+    let entries = match &*entries {
+        None => return html!(<span class="spinner-border text-success"></span>),
+        Some(Err(why)) => return html!(<div class="alert alert-danger" role="alert">{why}</div>),
+        Some(Ok(entries)) if entries.is_empty() => {
+            return html!(<p>{language.t("history-empty")}</p>)
+        }
+        Some(Ok(entries)) => entries,
+    };
+
+    let reviewed = entries.iter().any(|(_, m)| m.review.is_some());
+    let commented = entries.iter().any(|(_, m)| !m.comments.is_empty());
+    let mut known_tags: Vec<&String> = entries.iter().flat_map(|(_, m)| &m.tags).collect();
+    known_tags.sort();
+    known_tags.dedup();
+
+    let shown: Vec<_> = entries
+        .iter()
+        .filter(|(entry, metadata)| {
+            let id = entry.id.map(|id| id as u64);
+            let has_tag = match &*tagged {
+                Some(ids) => id.is_some_and(|id| ids.contains(&id)),
+                None => true,
+            };
+            has_tag && filter.matches(entry, Some(metadata))
+        })
+        .collect();
+
+    let onrerundone = {
+        shadow_clone!(reload);
+        Callback::from(move |_| reload())
+    };
+    let onsavereview = {
+        shadow_clone!(reload);
+        Callback::from(move |(id, _, review): (f64, Metadata, Review)| {
+            shadow_clone!(reload);
+            spawn_local(async move {
+                if let Err(why) = save_review(id, Some(review)).await {
+                    notify::error(language.t("history-review-failed"), why);
+                }
+                reload();
+            });
+        })
+    };
+    let rerun_controls = rerun.controls(
+        shown.iter().map(|(entry, _)| entry.clone()).collect(),
+        onrerundone.clone(),
+    );
+
+    html! {
+        <div>
+            {filters}
+            <datalist id="history-tags">
+                {for known_tags.iter().map(|tag| html!(<option value={(*tag).clone()} />))}
+            </datalist>
+            <div class="d-flex align-items-center gap-2 mb-2">
+                if can_edit {
+                    <div class="form-check form-switch mb-0">
+                        <input
+                            id="history-reviewing"
+                            class="form-check-input"
+                            type="checkbox"
+                            role="switch"
+                            checked={*reviewing}
+                            onchange={onreviewing}
+                        />
+                        <label class="form-check-label" for="history-reviewing">{language.t("history-review-mode")}</label>
+                    </div>
+                }
+                <button class="btn btn-sm btn-outline-secondary" disabled={!reviewed} onclick={onexportlog}>
+                    {language.t("history-export-decisions")}
+                </button>
+                <button class="btn btn-sm btn-outline-secondary" disabled={!commented} onclick={onexportcomments}>
+                    {language.t("history-export-comments")}
+                </button>
+            </div>
+            if can_edit {
+                {rerun_controls}
+            }
+            if flags::enabled(Flag::Compare) {
+                <div class="d-flex align-items-center gap-2 mb-2">
+                    <button
+                        class="btn btn-sm btn-outline-primary"
+                        disabled={selected.len() != 2}
+                        onclick={oncompareclick}
+                    >
+                        {language.t("history-compare-selected")}
+                    </button>
+                    <small class="text-body-secondary">
+                        {language.t_args("history-selected", &[("count", &selected.len())])}
+                    </small>
+                </div>
+            }
+            if shown.is_empty() {
+                <p>{language.t("history-no-match")}</p>
+            } else if *reviewing && can_edit {
+                <ReviewDeck
+                    entries={Rc::new(shown.iter().map(|(entry, m)| (entry.clone(), m.clone())).collect::<Vec<_>>())}
+                    onreview={onsavereview.clone()}
+                />
+            }
+            <ul class="list-group">
+            {
+                for shown.into_iter().map(|(entry, entry_metadata)| {
+                    let onclick = {
+                        shadow_clone!(onopen, entry);
+                        move |_| onopen.emit(entry.clone())
+                    };
+                    let ondelete = {
+                        shadow_clone!(reload);
+                        let id = entry.id;
+                        move |_| {
+                            let Some(id) = id else {
+                                return;
+                            };
+                            shadow_clone!(reload);
+                            spawn_local(async move {
+                                if let Err(why) = remove(id).await {
+                                    notify::error(language.t("history-delete-failed"), why);
+                                }
+                                reload();
+                            });
+                        }
+                    };
+                    let is_selected = selected.iter().any(|s| s.id == entry.id);
+                    let onselect = {
+                        shadow_clone!(selected, entry);
+                        move |_| {
+                            let mut new_selected: Vec<_> =
+                                selected.iter().filter(|s| s.id != entry.id).cloned().collect();
+                            if !is_selected {
+                                if new_selected.len() == 2 {
+                                    new_selected.remove(0);
+                                }
+                                new_selected.push(entry.clone());
+                            }
+                            selected.set(new_selected);
+                        }
+                    };
+                    let onreview = {
+                        shadow_clone!(onsavereview, entry_metadata);
+                        let id = entry.id;
+                        move |review: Review| {
+                            if let Some(id) = id {
+                                onsavereview.emit((id, entry_metadata.clone(), review));
+                            }
+                        }
+                    };
+                    let onflag = {
+                        shadow_clone!(reload, entry, entry_metadata);
+                        move |issue| {
+                            let Some(id) = entry.id else {
+                                return;
+                            };
+                            let new_metadata = Metadata {
+                                issue,
+                                ..entry_metadata.clone()
+                            };
+                            shadow_clone!(reload, entry);
+                            spawn_local(async move {
+                                if let Err(why) = issues::save(id, &entry.server, new_metadata).await {
+                                    notify::error(language.t("history-flag-failed"), why);
+                                }
+                                reload();
+                            });
+                        }
+                    };
+                    let onrerun = {
+                        shadow_clone!(rerun, entry, onrerundone);
+                        move |_| rerun.start(vec![entry.clone()], onrerundone.clone())
+                    };
+                    // The result this one was re-run from, if it is still stored.
+                    let original = entry.rerun_of.and_then(|id| {
+                        entries.iter().find(|(e, _)| e.id == Some(id)).map(|(e, _)| e.clone())
+                    });
+                    let oncompareoriginal = {
+                        shadow_clone!(oncompare, entry, original);
+                        move |_| {
+                            if let Some(original) = &original {
+                                oncompare.emit((original.clone(), entry.clone()));
+                            }
+                        }
+                    };
+                    let update = |change: fn(&mut Metadata, String)| {
+                        shadow_clone!(reload, entry_metadata);
+                        let id = entry.id;
+                        move |e: Event| {
+                            let Some(id) = id else {
+                                return;
+                            };
+                            let input: HtmlInputElement = e.target_unchecked_into();
+                            let mut new_metadata = entry_metadata.clone();
+                            change(&mut new_metadata, input.value());
+                            shadow_clone!(reload);
+                            spawn_local(async move {
+                                match metadata::put(id, &new_metadata).await {
+                                    Ok(()) => audit::record(
+                                        AuditAction::Edit,
+                                        format!("result {id}"),
+                                        "tags and notes",
+                                    ),
+                                    Err(why) => notify::error(language.t("history-tags-failed"), why),
+                                }
+                                reload();
+                            });
+                        }
+                    };
+                    html! {
+                        <li class="list-group-item">
+                            <div class="d-flex justify-content-between align-items-center">
+                                <span>
+                                    <input
+                                        type="checkbox"
+                                        class="form-check-input me-2"
+                                        title={language.t("history-select")}
+                                        checked={is_selected}
+                                        onchange={onselect}
+                                    />
+                                    <strong>{&entry.image.file_name}</strong>
+                                    <small class="text-body-secondary ms-2">
+                                        {format!("{} · {} · {}", entry.date(), entry.mask.file_name, entry.server)}
+                                    </small>
+                                    if let Some(review) = &entry_metadata.review {
+                                        {decision_badge(language, review.decision)}
+                                    }
+                                    {for entry_metadata.tags.iter().map(|tag| html!(
+                                        <span class="badge text-bg-secondary ms-1">{tag}</span>
+                                    ))}
+                                </span>
+                                <div class="btn-group btn-group-sm">
+                                    <button class="btn btn-outline-primary" {onclick}>{language.t("history-open")}</button>
+                                    if can_edit {
+                                        <button
+                                            class="btn btn-outline-secondary"
+                                            disabled={rerun.running()}
+                                            onclick={onrerun}
+                                        >
+                                            {language.t("history-rerun")}
+                                        </button>
+                                    }
+                                    if let Some(id) = entry.id {
+                                        <a class="btn btn-outline-secondary" href={Route::Report(id as u64).href()}>
+                                            {language.t("history-report")}
+                                        </a>
+                                    }
+                                    if can_edit {
+                                        <button class="btn btn-outline-danger" onclick={ondelete}>{language.t("history-delete")}</button>
+                                    }
+                                </div>
+                            </div>
+                            if entry.rerun_of.is_some() {
+                                <div class="d-flex align-items-center gap-2 mt-1">
+                                    <small class="text-body-secondary">
+                                        {match &original {
+                                            Some(original) => language.t_args(
+                                                "history-rerun-of",
+                                                &[("date", &original.date()), ("server", &original.server)],
+                                            ),
+                                            None => language.t("history-rerun-of-removed"),
+                                        }}
+                                    </small>
+                                    if original.is_some() && flags::enabled(Flag::Compare) {
+                                        <button class="btn btn-sm btn-link p-0" onclick={oncompareoriginal}>
+                                            {language.t("history-compare-original")}
+                                        </button>
+                                    }
+                                </div>
+                            }
+                            if can_edit {
+                                <div class="input-group input-group-sm mt-1">
+                                    <span class="input-group-text">{language.t("history-tags")}</span>
+                                    <input
+                                        class="form-control"
+                                        placeholder={language.t("history-tags-placeholder")}
+                                        value={entry_metadata.tags.join(", ")}
+                                        onchange={update(|m, v| m.tags = metadata::parse_tags(&v))}
+                                    />
+                                    <span class="input-group-text">{language.t("history-notes")}</span>
+                                    <input
+                                        class="form-control"
+                                        value={entry_metadata.notes.clone()}
+                                        onchange={update(|m, v| m.notes = v)}
+                                    />
+                                </div>
+                            } else if !entry_metadata.notes.is_empty() {
+                                <p class="small mb-0 mt-1">{&entry_metadata.notes}</p>
+                            }
+                            if *reviewing && can_edit {
+                                <ReviewControls review={entry_metadata.review.clone()} {onreview} />
+                            }
+                            <FlagControls issue={entry_metadata.issue.clone()} {onflag} />
+                        </li>
+                    }
+                })
+            }
+            </ul>
+        </div>
+    }
+}
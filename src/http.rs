@@ -0,0 +1,375 @@
+//! Requests over the browser's own `fetch` and `XMLHttpRequest`. Unlike a general HTTP
+//! client they can be aborted, report how much of an upload was sent and read answers as
+//! they stream in, and they add next to nothing to the bundle.
+
+use gloo::{
+    events::EventListener,
+    net::http::{Request, RequestBuilder, Response},
+};
+use js_sys::{Array, Promise, Reflect, Uint8Array};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{cell::Cell, fmt, rc::Rc};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    AbortController, AbortSignal, Blob, BlobPropertyBag, EventTarget, FormData, ProgressEvent,
+    ReadableStreamDefaultReader, XmlHttpRequest,
+};
+use yew::Callback;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Error {
+    /// No answer came, for the reason the browser gives.
+    Network(String),
+    /// No answer came within the time allowed.
+    Timeout,
+    /// The request was aborted through its [`AbortHandle`].
+    Aborted,
+    /// The server answered with an error status.
+    Status { status: u16, message: String },
+    /// The answer did not have the expected form.
+    Decode(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Network(message) | Error::Decode(message) => f.write_str(message),
+            Error::Timeout => f.write_str("timed out"),
+            Error::Aborted => f.write_str("aborted"),
+            Error::Status { status, message } => write!(f, "status {status}: {message}"),
+        }
+    }
+}
+
+impl From<gloo::net::Error> for Error {
+    fn from(e: gloo::net::Error) -> Self {
+        match e {
+            gloo::net::Error::JsError(e) if e.name == "AbortError" => Error::Aborted,
+            gloo::net::Error::SerdeError(e) => Error::Decode(e.to_string()),
+            e => Error::Network(e.to_string()),
+        }
+    }
+}
+
+/// How much of a body was sent or received, in bytes.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Progress {
+    pub loaded: u64,
+    /// The size of the whole body, when it is known.
+    pub total: Option<u64>,
+}
+
+impl Progress {
+    /// How much was done, from 0 to 100, when the size is known.
+    pub fn percent(self) -> Option<u8> {
+        let total = self.total.filter(|&total| total > 0)?;
+        Some((self.loaded.min(total) * 100 / total) as u8)
+    }
+}
+
+/// Aborts the requests it was given to. Clones abort the same requests.
+#[derive(Clone)]
+pub struct AbortHandle(AbortController);
+
+impl AbortHandle {
+    pub fn new() -> Self {
+        Self(AbortController::new().expect("Browsers able to run the app support aborting."))
+    }
+
+    pub fn abort(&self) {
+        self.0.abort();
+    }
+
+    fn signal(&self) -> AbortSignal {
+        self.0.signal()
+    }
+}
+
+impl Default for AbortHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sends `request`, abortable through `abort`, failing on error statuses.
+pub async fn send(request: RequestBuilder, abort: Option<&AbortHandle>) -> Result<Response, Error> {
+    let signal = abort.map(AbortHandle::signal);
+    checked(request.abort_signal(signal.as_ref()).send().await?).await
+}
+
+/// Whether anything answers at `url` when the answer need not be readable, which tells a
+/// server refusing requests from other origins from one that cannot be reached at all.
+pub async fn answers_opaquely(url: &str) -> bool {
+    Request::get(url)
+        .mode(web_sys::RequestMode::NoCors)
+        .send()
+        .await
+        .is_ok()
+}
+
+/// Sends `request` with `body` as JSON, like [`send`].
+pub async fn send_json<T: Serialize + ?Sized>(
+    request: RequestBuilder,
+    body: &T,
+    abort: Option<&AbortHandle>,
+) -> Result<Response, Error> {
+    let signal = abort.map(AbortHandle::signal);
+    let request = request.abort_signal(signal.as_ref()).json(body)?;
+    checked(request.send().await?).await
+}
+
+async fn checked(response: Response) -> Result<Response, Error> {
+    if !response.ok() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(Error::Status {
+            status: response.status(),
+            message: status_message(&body, response.status_text()),
+        });
+    }
+    Ok(response)
+}
+
+pub async fn json<T: DeserializeOwned>(response: Response) -> Result<T, Error> {
+    Ok(response.json().await?)
+}
+
+/// The body of `response`, read as it streams in and reported through `onprogress`.
+pub async fn bytes(response: Response, onprogress: &Callback<Progress>) -> Result<Vec<u8>, Error> {
+    let total = response
+        .headers()
+        .get("content-length")
+        .and_then(|length| length.parse().ok());
+    let Some(body) = response.body() else {
+        return Ok(Vec::new());
+    };
+    let reader: ReadableStreamDefaultReader = body.get_reader().unchecked_into();
+    let mut data = Vec::with_capacity(total.unwrap_or(0) as usize);
+    loop {
+        let chunk = JsFuture::from(reader.read()).await.map_err(js_error)?;
+        if Reflect::get(&chunk, &"done".into()).map_or(true, |done| done.is_truthy()) {
+            break;
+        }
+        let value = Reflect::get(&chunk, &"value".into()).map_err(js_error)?;
+        data.extend(value.unchecked_into::<Uint8Array>().to_vec());
+        onprogress.emit(Progress {
+            loaded: data.len() as u64,
+            total,
+        });
+    }
+    Ok(data)
+}
+
+/// The bytes of `array`, copied once straight into the buffer that keeps them.
+pub fn shared_bytes(array: &Uint8Array) -> Rc<[u8]> {
+    // Collected from an iterator of known length, the buffer is allocated once, in place.
+    let mut data: Rc<[u8]> = std::iter::repeat_n(0, array.length() as usize).collect();
+    array.copy_to(Rc::get_mut(&mut data).expect("The buffer was just made."));
+    data
+}
+
+/// An answer read as the bytes that came, without going through a string.
+pub struct Answer {
+    /// Header names, in lowercase, and values.
+    pub headers: Vec<(String, String)>,
+    pub body: Rc<[u8]>,
+}
+
+impl Answer {
+    /// The value of header `name`, whatever its case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The type of the body, without parameters such as its charset.
+    pub fn content_type(&self) -> Option<&str> {
+        self.header("content-type")
+            .map(|value| value.split(';').next().unwrap_or_default().trim())
+    }
+}
+
+/// Headers as `XMLHttpRequest` lists them, one `name: value` per line.
+fn parse_headers(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect()
+}
+
+/// A file part of a multipart form.
+pub fn file_blob(data: &[u8], file_type: &str) -> Result<Blob, Error> {
+    let options = BlobPropertyBag::new();
+    options.set_type(file_type);
+    // SAFETY: the blob copies the bytes as it is made, and nothing allocates in wasm memory
+    // between making the view and that copy, so the view cannot be invalidated meanwhile.
+    // This spares a copy of the whole image on the JavaScript side.
+    let view = unsafe { Uint8Array::view(data) };
+    let parts = Array::of1(&view);
+    Blob::new_with_u8_array_sequence_and_options(&parts, &options).map_err(js_error)
+}
+
+/// Posts `form` to `url` with `XMLHttpRequest`, the only way for browsers to report how much
+/// of an upload was sent. `onanswer` is called once the server starts answering.
+pub async fn upload(
+    url: &str,
+    form: &FormData,
+    headers: &[(&str, String)],
+    timeout_ms: u32,
+    abort: Option<&AbortHandle>,
+    onupload: &Callback<Progress>,
+    onanswer: &Callback<()>,
+) -> Result<Answer, Error> {
+    if abort.is_some_and(|abort| abort.signal().aborted()) {
+        return Err(Error::Aborted);
+    }
+    let xhr = XmlHttpRequest::new().map_err(js_error)?;
+    xhr.open("POST", url).map_err(js_error)?;
+    for (name, value) in headers {
+        xhr.set_request_header(name, value).map_err(js_error)?;
+    }
+    xhr.set_timeout(timeout_ms);
+    Reflect::set(&xhr, &"responseType".into(), &"arraybuffer".into()).map_err(js_error)?;
+
+    let progress = |e: &web_sys::Event| {
+        let e: &ProgressEvent = e.unchecked_ref();
+        Progress {
+            loaded: e.loaded() as u64,
+            total: e.length_computable().then_some(e.total() as u64),
+        }
+    };
+    let upload: EventTarget = xhr.upload().map_err(js_error)?.into();
+    let mut listeners = vec![
+        EventListener::new(&upload, "progress", {
+            let onupload = onupload.clone();
+            move |e| onupload.emit(progress(e))
+        }),
+        EventListener::new(&xhr, "readystatechange", {
+            let (xhr, onanswer) = (xhr.clone(), onanswer.clone());
+            let answered = Cell::new(false);
+            move |_| {
+                if xhr.ready_state() >= XmlHttpRequest::HEADERS_RECEIVED && !answered.replace(true)
+                {
+                    onanswer.emit(());
+                }
+            }
+        }),
+    ];
+    if let Some(abort) = abort {
+        let xhr = xhr.clone();
+        listeners.push(EventListener::once(&abort.signal(), "abort", move |_| {
+            let _ = xhr.abort();
+        }));
+    }
+    // Settles with the name of the event that ended the request.
+    let finished = Promise::new(&mut |resolve, _| {
+        for event in ["load", "error", "timeout", "abort"] {
+            let resolve = resolve.clone();
+            listeners.push(EventListener::once(&xhr, event, move |_| {
+                let _ = resolve.call1(&JsValue::NULL, &event.into());
+            }));
+        }
+    });
+    xhr.send_with_opt_form_data(Some(form)).map_err(js_error)?;
+    let ended = JsFuture::from(finished).await.map_err(js_error)?;
+    drop(listeners);
+
+    match ended.as_string().as_deref() {
+        Some("load") => {}
+        Some("timeout") => return Err(Error::Timeout),
+        Some("abort") => return Err(Error::Aborted),
+        _ => return Err(Error::Network(format!("Could not reach {url}"))),
+    }
+    let status = xhr.status().map_err(js_error)?;
+    let body = xhr.response().map_err(js_error)?;
+    let body = shared_bytes(&Uint8Array::new(&body));
+    if !(200..300).contains(&status) {
+        return Err(Error::Status {
+            status,
+            message: status_message(
+                &String::from_utf8_lossy(&body),
+                xhr.status_text().unwrap_or_default(),
+            ),
+        });
+    }
+    Ok(Answer {
+        headers: parse_headers(&xhr.get_all_response_headers().map_err(js_error)?),
+        body,
+    })
+}
+
+/// What the server said of an error status in `body`: the `error`, `detail` or `message` of
+/// a JSON body, followed by its `hint`, or the body itself. Falls back to `status_text` for
+/// empty bodies and HTML error pages, which proxies answer with.
+fn status_message(body: &str, status_text: String) -> String {
+    let body = body.trim();
+    if body.is_empty() || body.starts_with('<') {
+        return status_text;
+    }
+    let Ok(serde_json::Value::Object(fields)) = serde_json::from_str(body) else {
+        return body.to_string();
+    };
+    let text = |name| fields.get(name).and_then(|value| value.as_str());
+    let Some(error) = ["error", "detail", "message"].into_iter().find_map(text) else {
+        return body.to_string();
+    };
+    match text("hint") {
+        Some(hint) => format!("{error} ({hint})"),
+        None => error.to_string(),
+    }
+}
+
+fn js_error(e: JsValue) -> Error {
+    Error::Network(
+        e.dyn_ref::<js_sys::Error>()
+            .map(|e| String::from(e.message()))
+            .unwrap_or_else(|| format!("{e:?}")),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_headers, status_message, Answer, Progress};
+
+    #[test]
+    fn answers_are_read_with_their_headers() {
+        let answer = Answer {
+            headers: parse_headers(
+                "Content-Type: image/png; charset=binary\r\nX-Result-Id: a:b\r\n",
+            ),
+            body: b"{}"[..].into(),
+        };
+        assert_eq!(answer.content_type(), Some("image/png"));
+        assert_eq!(answer.header("x-result-id"), Some("a:b"));
+        assert_eq!(answer.header("X-File-Name"), None);
+    }
+
+    #[test]
+    fn progress_is_a_percentage_of_a_known_size() {
+        let progress = |loaded, total| Progress { loaded, total }.percent();
+        assert_eq!(progress(512, Some(2048)), Some(25));
+        assert_eq!(progress(2048, Some(2048)), Some(100));
+        assert_eq!(progress(4096, Some(2048)), Some(100));
+        assert_eq!(progress(512, None), None);
+        assert_eq!(progress(0, Some(0)), None);
+    }
+
+    #[test]
+    fn error_bodies_are_shown_as_the_server_put_them() {
+        let message = |body| status_message(body, "Unprocessable Entity".to_string());
+        assert_eq!(
+            message(r#"{"error": "Image too large", "hint": "Send at most 8192 pixels a side"}"#),
+            "Image too large (Send at most 8192 pixels a side)"
+        );
+        assert_eq!(message(r#"{"detail": "Not a GeoTIFF"}"#), "Not a GeoTIFF");
+        assert_eq!(message(r#"{"code": 7}"#), r#"{"code": 7}"#);
+        assert_eq!(message("Model not loaded\n"), "Model not loaded");
+        assert_eq!(
+            message("<html><body>Bad gateway</body></html>"),
+            "Unprocessable Entity"
+        );
+        assert_eq!(message(""), "Unprocessable Entity");
+    }
+}
@@ -0,0 +1,273 @@
+//! Translations of the user interface, kept as Fluent resources in `locales/`. Only the
+//! part of Fluent the app needs is read: messages, indented continuation lines, comments
+//! and `{ $name }` placeables.
+
+use gloo::storage::{LocalStorage, Storage};
+use std::{cell::Cell, collections::HashMap};
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+/// Key of the language users picked, shared by all workspaces.
+const STORAGE_KEY: &str = "language";
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Language {
+    En,
+    Ru,
+}
+
+impl Language {
+    const ALL: [Language; 2] = [Language::En, Language::Ru];
+
+    fn code(self) -> &'static str {
+        match self {
+            Language::En => "en",
+            Language::Ru => "ru",
+        }
+    }
+
+    /// The language's name in itself, as offered in the switcher.
+    fn name(self) -> &'static str {
+        match self {
+            Language::En => "English",
+            Language::Ru => "Русский",
+        }
+    }
+
+    fn resource(self) -> &'static str {
+        match self {
+            Language::En => include_str!("../locales/en.ftl"),
+            Language::Ru => include_str!("../locales/ru.ftl"),
+        }
+    }
+
+    /// The language of a BCP 47 tag such as `ru-RU`, if it is one the app speaks.
+    fn parse(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_']).next()?.to_lowercase();
+        Language::ALL.into_iter().find(|l| l.code() == primary)
+    }
+
+    /// The message `key`, falling back to English and then to the key itself.
+    pub fn t(self, key: &str) -> String {
+        self.t_args(key, &[])
+    }
+
+    /// The message `key` with its placeables replaced by `args`.
+    pub fn t_args(self, key: &str, args: &[(&str, &dyn ToString)]) -> String {
+        let message = BUNDLES.with(|bundles| {
+            [self, Language::En]
+                .into_iter()
+                .find_map(|language| bundles[&language].get(key).cloned())
+        });
+        let Some(message) = message else {
+            log::warn!("No translation for {key:?}");
+            return key.to_string();
+        };
+        format_message(&message, args)
+    }
+}
+
+/// The messages of a Fluent resource by their key.
+fn parse_resource(resource: &'static str) -> HashMap<&'static str, String> {
+    let mut messages = HashMap::new();
+    let mut last = None;
+    for line in resource.lines() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with(' ') {
+            if let Some(value) = last.and_then(|key| messages.get_mut(key)) {
+                let value: &mut String = value;
+                value.push('\n');
+                value.push_str(line.trim());
+            }
+        } else if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim();
+            messages.insert(key, value.trim().to_string());
+            last = Some(key);
+        }
+    }
+    messages
+}
+
+/// `message` with every `{ $name }` replaced by the argument of that name. Placeables
+/// without an argument are kept as they are.
+fn format_message(message: &str, args: &[(&str, &dyn ToString)]) -> String {
+    let mut formatted = String::new();
+    let mut rest = message;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        formatted.push_str(&rest[..start]);
+        let name = rest[start + 1..end].trim().trim_start_matches('$');
+        match args.iter().find(|(arg, _)| *arg == name) {
+            Some((_, value)) => formatted.push_str(&value.to_string()),
+            None => formatted.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    formatted.push_str(rest);
+    formatted
+}
+
+thread_local! {
+    static BUNDLES: HashMap<Language, HashMap<&'static str, String>> = Language::ALL
+        .into_iter()
+        .map(|language| (language, parse_resource(language.resource())))
+        .collect();
+    static CURRENT: Cell<Language> = const { Cell::new(Language::En) };
+}
+
+/// The language picked earlier, or else the first of the browser's languages the app
+/// speaks, or English.
+fn detect() -> Language {
+    if let Some(language) = LocalStorage::get::<String>(STORAGE_KEY)
+        .ok()
+        .and_then(|code| Language::parse(&code))
+    {
+        return language;
+    }
+    let navigator = gloo::utils::window().navigator();
+    navigator
+        .languages()
+        .iter()
+        .filter_map(|tag| tag.as_string())
+        .chain(navigator.language())
+        .find_map(|tag| Language::parse(&tag))
+        .unwrap_or(Language::En)
+}
+
+fn apply(language: Language) {
+    CURRENT.set(language);
+    if let Some(root) = gloo::utils::document().document_element() {
+        let _ = root.set_attribute("lang", language.code());
+    }
+}
+
+/// Detects the language to use. Called once at startup, before rendering.
+pub fn init() {
+    apply(detect());
+}
+
+pub fn current() -> Language {
+    CURRENT.get()
+}
+
+/// The message `key` in the current language, for code outside of components.
+pub fn t(key: &str) -> String {
+    current().t(key)
+}
+
+pub fn t_args(key: &str, args: &[(&str, &dyn ToString)]) -> String {
+    current().t_args(key, args)
+}
+
+pub type LanguageContext = UseStateHandle<Language>;
+
+/// The current language, re-rendering the component when users switch to another one.
+#[hook]
+pub fn use_language() -> Language {
+    use_context::<LanguageContext>().map_or_else(current, |language| *language)
+}
+
+/// Provides the current language to `children`, so that they re-render when it changes.
+#[autoprops_component(LanguageProvider)]
+pub fn language_provider(children: Children) -> Html {
+    let language = use_state(current);
+    html! {
+        <ContextProvider<LanguageContext> context={language}>
+            {children.clone()}
+        </ContextProvider<LanguageContext>>
+    }
+}
+
+/// A choice of the languages the app speaks, remembered for the next visits.
+#[function_component(LanguageSwitcher)]
+pub fn language_switcher() -> Html {
+    let context = use_context::<LanguageContext>();
+    let language = use_language();
+    let onchange = move |e: Event| {
+        let select: HtmlSelectElement = e.target_unchecked_into();
+        let Some(picked) = Language::parse(&select.value()) else {
+            return;
+        };
+        if let Err(why) = LocalStorage::set(STORAGE_KEY, picked.code()) {
+            log::warn!("Could not save the language: {why}");
+        }
+        apply(picked);
+        if let Some(context) = &context {
+            context.set(picked);
+        }
+    };
+
+    html! {
+        <select
+            class="form-select form-select-sm w-auto"
+            aria-label={language.t("language")}
+            {onchange}
+        >
+            {
+                for Language::ALL.into_iter().map(|option| html! {
+                    <option value={option.code()} selected={option == language}>
+                        {option.name()}
+                    </option>
+                })
+            }
+        </select>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::{format_message, parse_resource, Language};
+
+    #[test]
+    fn every_language_has_every_message() {
+        let keys = |language: Language| {
+            parse_resource(language.resource())
+                .into_keys()
+                .collect::<BTreeSet<_>>()
+        };
+        for language in Language::ALL {
+            assert_eq!(keys(language), keys(Language::En), "{language:?}");
+        }
+    }
+
+    #[test]
+    fn continuation_lines_join_the_message() {
+        let messages = parse_resource("# A comment\nkey = First\n    second\nother = Value\n");
+        assert_eq!(messages["key"], "First\nsecond");
+        assert_eq!(messages["other"], "Value");
+    }
+
+    #[test]
+    fn placeables_take_their_arguments() {
+        assert_eq!(
+            format_message(
+                "{ $url } answered { $status }",
+                &[("url", &"a"), ("status", &404)]
+            ),
+            "a answered 404"
+        );
+        assert_eq!(
+            format_message("Keep { $missing }", &[]),
+            "Keep { $missing }"
+        );
+    }
+
+    #[test]
+    fn tags_are_matched_by_their_primary_language() {
+        assert_eq!(Language::parse("ru-RU"), Some(Language::Ru));
+        assert_eq!(Language::parse("en_GB"), Some(Language::En));
+        assert_eq!(Language::parse("de"), None);
+    }
+
+    #[test]
+    fn missing_messages_fall_back_to_english_then_the_key() {
+        assert_eq!(Language::Ru.t("no-such-message"), "no-such-message");
+    }
+}
@@ -0,0 +1,208 @@
+//! Promise-based helpers over the callback-style IndexedDB API, and the object stores
+//! the app keeps in its database.
+
+use js_sys::{Array, Promise};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    IdbDatabase, IdbIndexParameters, IdbObjectStore, IdbObjectStoreParameters, IdbRequest,
+    IdbTransactionMode,
+};
+
+use crate::workspace;
+
+/// Name of the default workspace's database; other workspaces have their own.
+pub const DB_NAME: &str = "segmentation-history";
+const DB_VERSION: u32 = 7;
+/// Segmentation results, keyed by an auto-incremented `id` field.
+pub const RESULTS_STORE: &str = "results";
+/// Autosaved session, under out-of-line keys.
+pub const SESSION_STORE: &str = "session";
+/// Tags, notes and class statistics of results, keyed by the result's `id`.
+pub const METADATA_STORE: &str = "metadata";
+/// Index of [`METADATA_STORE`] with an entry for every tag of every result.
+pub const TAGS_INDEX: &str = "tags";
+/// Index of [`METADATA_STORE`] by the digest of each result's image.
+pub const DIGEST_INDEX: &str = "digest";
+/// Index of [`METADATA_STORE`] by the id the server stored each result under.
+pub const RESULT_ID_INDEX: &str = "result_id";
+/// Segmentation requests made while offline, keyed by an auto-incremented `id` field.
+pub const OUTBOX_STORE: &str = "outbox";
+/// What users did, keyed by an auto-incremented `id` field.
+pub const AUDIT_STORE: &str = "audit";
+
+fn js_error(what: &str, error: JsValue) -> String {
+    match error.dyn_ref::<js_sys::Error>() {
+        Some(error) => format!("{what}: {}", error.message()),
+        None => format!("{what}: {error:?}"),
+    }
+}
+
+/// Resolves once `request` succeeds, with its result.
+async fn complete(request: &IdbRequest) -> Result<JsValue, String> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    let outcome = JsFuture::from(promise).await;
+    request.set_onsuccess(None);
+    request.set_onerror(None);
+    if outcome.is_err() {
+        let message = match request.error() {
+            Ok(Some(error)) => error.message(),
+            _ => "unknown error".to_string(),
+        };
+        return Err(format!("IndexedDB request failed: {message}"));
+    }
+    request
+        .result()
+        .map_err(|e| js_error("Could not read IndexedDB result", e))
+}
+
+/// Opens the database, creating any object store missing from an older version.
+pub async fn open() -> Result<IdbDatabase, String> {
+    let factory = web_sys::window()
+        .and_then(|window| window.indexed_db().ok().flatten())
+        .ok_or_else(|| "IndexedDB is not available in this browser".to_string())?;
+    let request = factory
+        .open_with_u32(&workspace::key(DB_NAME), DB_VERSION)
+        .map_err(|e| js_error("Could not open the database", e))?;
+
+    let onupgrade = {
+        let request = request.clone();
+        Closure::<dyn FnMut(JsValue)>::new(move |_| {
+            let Ok(db) = request.result() else {
+                return;
+            };
+            let db: IdbDatabase = db.unchecked_into();
+            let existing = db.object_store_names();
+            for (name, what) in [
+                (RESULTS_STORE, "history"),
+                (OUTBOX_STORE, "outbox"),
+                (AUDIT_STORE, "audit"),
+            ] {
+                if existing.contains(name) {
+                    continue;
+                }
+                let parameters = IdbObjectStoreParameters::new();
+                parameters.set_auto_increment(true);
+                parameters.set_key_path(&"id".into());
+                if let Err(e) = db.create_object_store_with_optional_parameters(name, &parameters) {
+                    log::error!(
+                        "{}",
+                        js_error(&format!("Could not create the {what} store"), e)
+                    );
+                }
+            }
+            if !existing.contains(SESSION_STORE) {
+                if let Err(e) = db.create_object_store(SESSION_STORE) {
+                    log::error!("{}", js_error("Could not create the session store", e));
+                }
+            }
+            if !existing.contains(METADATA_STORE) {
+                let parameters = IdbObjectStoreParameters::new();
+                parameters.set_key_path(&"id".into());
+                let index_parameters = IdbIndexParameters::new();
+                index_parameters.set_multi_entry(true);
+                let created = db
+                    .create_object_store_with_optional_parameters(METADATA_STORE, &parameters)
+                    .and_then(|store| {
+                        store.create_index_with_str_and_optional_parameters(
+                            TAGS_INDEX,
+                            "tags",
+                            &index_parameters,
+                        )
+                    });
+                if let Err(e) = created {
+                    log::error!("{}", js_error("Could not create the metadata store", e));
+                }
+            }
+            // Stores from older versions are changed through the upgrade's own transaction.
+            let metadata = request
+                .transaction()
+                .ok_or_else(|| JsValue::from_str("no upgrade transaction"))
+                .and_then(|transaction| transaction.object_store(METADATA_STORE));
+            for (index, key_path) in [(DIGEST_INDEX, "digest"), (RESULT_ID_INDEX, "result_id")] {
+                let created = metadata.clone().and_then(|store| {
+                    if store.index_names().contains(index) {
+                        return Ok(());
+                    }
+                    store.create_index_with_str(index, key_path).map(|_| ())
+                });
+                if let Err(e) = created {
+                    log::error!(
+                        "{}",
+                        js_error(&format!("Could not create the {index} index"), e)
+                    );
+                }
+            }
+        })
+    };
+    request.set_onupgradeneeded(Some(onupgrade.as_ref().unchecked_ref()));
+    let db = complete(&request).await;
+    request.set_onupgradeneeded(None);
+    Ok(db?.unchecked_into())
+}
+
+fn store(db: &IdbDatabase, name: &str, mode: IdbTransactionMode) -> Result<IdbObjectStore, String> {
+    db.transaction_with_str_and_mode(name, mode)
+        .and_then(|transaction| transaction.object_store(name))
+        .map_err(|e| js_error(&format!("Could not access the {name} store"), e))
+}
+
+/// Stores `value`, returning the key it was given.
+pub async fn put(db: &IdbDatabase, name: &str, value: &JsValue) -> Result<JsValue, String> {
+    let request = store(db, name, IdbTransactionMode::Readwrite)?
+        .put(value)
+        .map_err(|e| js_error("Could not save to the database", e))?;
+    complete(&request).await
+}
+
+/// Stores `value` under `key`, replacing whatever was there.
+pub async fn put_with_key(
+    db: &IdbDatabase,
+    name: &str,
+    value: &JsValue,
+    key: &JsValue,
+) -> Result<(), String> {
+    let request = store(db, name, IdbTransactionMode::Readwrite)?
+        .put_with_key(value, key)
+        .map_err(|e| js_error("Could not save to the database", e))?;
+    complete(&request).await.map(|_| ())
+}
+
+/// Value stored under `key`, `undefined` when there is none.
+pub async fn get(db: &IdbDatabase, name: &str, key: &JsValue) -> Result<JsValue, String> {
+    let request = store(db, name, IdbTransactionMode::Readonly)?
+        .get(key)
+        .map_err(|e| js_error("Could not read from the database", e))?;
+    complete(&request).await
+}
+
+pub async fn get_all(db: &IdbDatabase, name: &str) -> Result<Array, String> {
+    let request = store(db, name, IdbTransactionMode::Readonly)?
+        .get_all()
+        .map_err(|e| js_error("Could not read from the database", e))?;
+    Ok(complete(&request).await?.unchecked_into())
+}
+
+/// Primary keys of the records whose `index` contains `key`.
+pub async fn keys_in_index(
+    db: &IdbDatabase,
+    name: &str,
+    index: &str,
+    key: &JsValue,
+) -> Result<Array, String> {
+    let request = store(db, name, IdbTransactionMode::Readonly)?
+        .index(index)
+        .and_then(|index| index.get_all_keys_with_key(key))
+        .map_err(|e| js_error("Could not read from the database", e))?;
+    Ok(complete(&request).await?.unchecked_into())
+}
+
+pub async fn delete(db: &IdbDatabase, name: &str, key: &JsValue) -> Result<(), String> {
+    let request = store(db, name, IdbTransactionMode::Readwrite)?
+        .delete(key)
+        .map_err(|e| js_error("Could not delete from the database", e))?;
+    complete(&request).await.map(|_| ())
+}
@@ -0,0 +1,94 @@
+//! Installing the app to run in its own window, offered with a button once the browser
+//! tells that it can be installed.
+
+use gloo::events::{EventListener, EventListenerOptions};
+use js_sys::{Function, Promise, Reflect};
+use std::cell::RefCell;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use yew::{platform::spawn_local, prelude::*};
+
+use crate::i18n::use_language;
+
+thread_local! {
+    /// The `beforeinstallprompt` event the browser's own prompt was deferred with, until
+    /// it is used or the app installed.
+    static DEFERRED: RefCell<Option<JsValue>> = const { RefCell::new(None) };
+}
+
+fn deferred() -> Option<JsValue> {
+    DEFERRED.with(|deferred| deferred.borrow().clone())
+}
+
+/// Keeps the install prompt for the button instead of letting the browser show it. Called
+/// before rendering, as the browser may offer it while the app is still starting.
+pub fn listen() {
+    let window = gloo::utils::window();
+    EventListener::new_with_options(
+        &window,
+        "beforeinstallprompt",
+        EventListenerOptions::enable_prevent_default(),
+        |e| {
+            e.prevent_default();
+            DEFERRED.with(|deferred| *deferred.borrow_mut() = Some(e.clone().into()));
+        },
+    )
+    .forget();
+    EventListener::new(&window, "appinstalled", |_| {
+        DEFERRED.with(|deferred| *deferred.borrow_mut() = None)
+    })
+    .forget();
+}
+
+/// Shows the browser's install prompt deferred as `event`, which can only be done once.
+async fn prompt(event: JsValue) -> Result<(), JsValue> {
+    DEFERRED.with(|deferred| *deferred.borrow_mut() = None);
+    let prompt: Function = Reflect::get(&event, &"prompt".into())?.dyn_into()?;
+    JsFuture::from(Promise::from(prompt.call0(&event)?)).await?;
+    Ok(())
+}
+
+/// A button installing the app, shown while the browser allows it.
+#[function_component(InstallButton)]
+pub fn install_button() -> Html {
+    let language = use_language();
+    let installable = use_state(|| deferred().is_some());
+
+    {
+        let installable = installable.clone();
+        use_effect_with((), move |_| {
+            // Registered after the listeners of `listen`, so they see what those stored.
+            let window = gloo::utils::window();
+            let listeners = ["beforeinstallprompt", "appinstalled"].map(|name| {
+                let installable = installable.clone();
+                EventListener::new(&window, name, move |_| {
+                    installable.set(deferred().is_some())
+                })
+            });
+            move || drop(listeners)
+        });
+    }
+
+    if !*installable {
+        return html!();
+    }
+    let onclick = {
+        let installable = installable.clone();
+        move |_| {
+            let Some(event) = deferred() else {
+                return;
+            };
+            installable.set(false);
+            spawn_local(async move {
+                if let Err(e) = prompt(event).await {
+                    log::warn!("Could not show the install prompt: {e:?}");
+                }
+            });
+        }
+    };
+    html! {
+        <button class="btn btn-sm btn-outline-secondary" {onclick}>
+            {language.t("install-app")}
+        </button>
+    }
+}
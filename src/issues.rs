@@ -0,0 +1,367 @@
+//! Results flagged by users as showing a problem of the model, such as clouds taken for
+//! buildings, gathered in a queue to be triaged, and reported to the endpoint named in
+//! `config.json`, if any.
+
+use gloo::net::http::Request;
+use serde::Serialize;
+use shadow_clone::shadow_clone;
+use std::{collections::BTreeMap, rc::Rc};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    audit::{self, AuditAction},
+    clock, config,
+    history::{self, HistoryEntry},
+    http,
+    i18n::{self, use_language, Language},
+    metadata::{self, Issue, IssueReason, Metadata},
+    notify,
+    roles::use_role,
+};
+
+pub fn reason_label(language: Language, reason: IssueReason) -> String {
+    language.t(match reason {
+        IssueReason::CloudCover => "issue-cloud-cover",
+        IssueReason::Misclassification => "issue-misclassification",
+        IssueReason::Offset => "issue-offset",
+        IssueReason::Other => "issue-other",
+    })
+}
+
+/// What the issue endpoint is told about a flagged result: why it was flagged and what
+/// tells the image and the model apart, but not the image, its name or where it lies.
+#[derive(Serialize, PartialEq, Debug)]
+struct IssueReport<'a> {
+    app_version: &'static str,
+    result_id: f64,
+    /// Segmentation endpoint that produced the mask, standing in for the model.
+    server: &'a str,
+    /// SHA-256 digest of the image, by which the server may find it among those it kept.
+    image_digest: Option<&'a str>,
+    reason: &'static str,
+    comment: &'a str,
+    flagged_at: f64,
+    class_shares: &'a BTreeMap<u8, f64>,
+}
+
+impl<'a> IssueReport<'a> {
+    fn new(id: f64, server: &'a str, metadata: &'a Metadata, issue: &'a Issue) -> Self {
+        IssueReport {
+            app_version: env!("CARGO_PKG_VERSION"),
+            result_id: id,
+            server,
+            image_digest: metadata.digest.as_deref(),
+            reason: issue.reason.name(),
+            comment: &issue.comment,
+            flagged_at: issue.timestamp,
+            class_shares: &metadata.class_shares,
+        }
+    }
+}
+
+/// Sends the issue of the result `id` to the issue endpoint, answering whether there is one.
+async fn report(id: f64, server: &str, metadata: &Metadata) -> Result<bool, String> {
+    let (Some(url), Some(issue)) = (&config::current().issue_report_url, &metadata.issue) else {
+        return Ok(false);
+    };
+    let report = IssueReport::new(id, server, metadata, issue);
+    http::send_json(Request::post(url), &report, None)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Stores `metadata` of the result `id`, produced by `server`, reporting its issue first if
+/// it was not yet. Issues that could not be reported are kept all the same, to be reported
+/// again from the queue.
+pub async fn save(id: f64, server: &str, mut metadata: Metadata) -> Result<(), String> {
+    let details = match &metadata.issue {
+        Some(issue) => issue.reason.name(),
+        None => "resolved",
+    };
+    if metadata.issue.as_ref().is_some_and(|issue| !issue.reported) {
+        match report(id, server, &metadata).await {
+            Ok(reported) => {
+                if let Some(issue) = &mut metadata.issue {
+                    issue.reported = reported;
+                }
+            }
+            Err(why) => notify::warn(i18n::t("issue-report-failed"), why),
+        }
+    }
+    metadata::put(id, &metadata).await?;
+    audit::record(AuditAction::Flag, format!("result {id}"), details);
+    Ok(())
+}
+
+/// Flagging a result with a reason and a comment, or taking the flag back.
+#[autoprops_component(FlagControls)]
+pub fn flag_controls(issue: Option<Issue>, onflag: Callback<Option<Issue>>) -> Html {
+    let language = use_language();
+    let open = use_state(|| false);
+    let reason = use_state(|| IssueReason::Misclassification);
+    let comment = use_state(String::new);
+    let role = use_role();
+
+    if let Some(issue) = issue {
+        let onunflag = {
+            shadow_clone!(onflag);
+            move |_| onflag.emit(None)
+        };
+        return html! {
+            <div class="d-flex align-items-center gap-2 mt-1">
+                <span class="badge text-bg-danger">
+                    {language.t_args("issue-flagged", &[("reason", &reason_label(language, issue.reason))])}
+                </span>
+                if !issue.comment.is_empty() {
+                    <small class="text-body-secondary">{&issue.comment}</small>
+                }
+                if role.can_edit() {
+                    <button class="btn btn-sm btn-link p-0" onclick={onunflag}>{language.t("issue-unflag")}</button>
+                }
+            </div>
+        };
+    }
+    if !role.can_edit() {
+        return html!();
+    }
+
+    let ontoggle = {
+        shadow_clone!(open);
+        move |_| open.set(!*open)
+    };
+    if !*open {
+        return html! {
+            <button class="btn btn-sm btn-link p-0 mt-1" onclick={ontoggle}>
+                {language.t("issue-flag-for-review")}
+            </button>
+        };
+    }
+    let onreason = {
+        shadow_clone!(reason);
+        move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(new_reason) = IssueReason::parse(&select.value()) {
+                reason.set(new_reason);
+            }
+        }
+    };
+    let oncomment = {
+        shadow_clone!(comment);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            comment.set(input.value());
+        }
+    };
+    let onsubmit = {
+        shadow_clone!(open, reason, comment, onflag);
+        move |e: SubmitEvent| {
+            e.prevent_default();
+            onflag.emit(Some(Issue {
+                reason: *reason,
+                comment: (*comment).clone(),
+                timestamp: clock::now(),
+                reported: false,
+            }));
+            open.set(false);
+        }
+    };
+    html! {
+        <form class="input-group input-group-sm mt-1" {onsubmit}>
+            <select class="form-select" style="max-width: 14em;" onchange={onreason}>
+                {for IssueReason::ALL.into_iter().map(|r| html! {
+                    <option value={r.name()} selected={r == *reason}>{reason_label(language, r)}</option>
+                })}
+            </select>
+            <input
+                class="form-control"
+                placeholder={language.t("issue-comment")}
+                value={(*comment).clone()}
+                onchange={oncomment}
+            />
+            <button class="btn btn-outline-danger" type="submit">{language.t("issue-flag")}</button>
+            <button class="btn btn-outline-secondary" type="button" onclick={ontoggle}>
+                {language.t("action-cancel")}
+            </button>
+        </form>
+    }
+}
+
+/// The flagged results, most recently flagged first, reloaded whenever `revision` changes.
+/// Each can be opened, resolved, which takes the flag back, or reported again when that
+/// failed.
+#[autoprops_component(IssueQueuePane)]
+pub fn issue_queue_pane(revision: i32, onopen: Callback<Rc<HistoryEntry>>) -> Html {
+    let language = use_language();
+    let entries = use_state(|| None::<Result<Vec<(Rc<HistoryEntry>, Metadata)>, String>>);
+    let shown_reason = use_state(|| None::<IssueReason>);
+    let can_edit = use_role().can_edit();
+
+    let reload = {
+        shadow_clone!(entries);
+        move || {
+            shadow_clone!(entries);
+            spawn_local(async move {
+                let loaded = history::list_with_metadata().await.map(|entries| {
+                    let mut flagged: Vec<_> = entries
+                        .into_iter()
+                        .filter(|(_, metadata)| metadata.issue.is_some())
+                        .collect();
+                    let flagged_at = |metadata: &Metadata| {
+                        metadata.issue.as_ref().map_or(0.0, |issue| issue.timestamp)
+                    };
+                    flagged.sort_by(|(_, a), (_, b)| flagged_at(b).total_cmp(&flagged_at(a)));
+                    flagged
+                });
+                entries.set(Some(loaded));
+            });
+        }
+    };
+
+    {
+        shadow_clone!(reload);
+        use_effect_with(revision, move |_| reload());
+    }
+
+    let entries = match &*entries {
+        None => return html!(<span class="spinner-border text-success"></span>),
+        Some(Err(why)) => return html!(<div class="alert alert-danger" role="alert">{why}</div>),
+        Some(Ok(entries)) if entries.is_empty() => {
+            return html!(<p>{language.t("issue-queue-empty")}</p>)
+        }
+        Some(Ok(entries)) => entries,
+    };
+
+    let onreasonfilter = {
+        shadow_clone!(shown_reason);
+        move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            shown_reason.set(IssueReason::parse(&select.value()));
+        }
+    };
+    let count = |reason: IssueReason| {
+        entries
+            .iter()
+            .filter(|(_, m)| m.issue.as_ref().is_some_and(|issue| issue.reason == reason))
+            .count()
+    };
+    let can_report = config::current().issue_report_url.is_some();
+
+    html! {
+        <div>
+            <div class="input-group input-group-sm mb-2 w-auto">
+                <span class="input-group-text">{language.t("issue-reason")}</span>
+                <select class="form-select" onchange={onreasonfilter}>
+                    <option value="" selected={shown_reason.is_none()}>
+                        {language.t_args("issue-any", &[("count", &entries.len())])}
+                    </option>
+                    {for IssueReason::ALL.into_iter().map(|reason| html! {
+                        <option value={reason.name()} selected={*shown_reason == Some(reason)}>
+                            {format!("{} ({})", reason_label(language, reason), count(reason))}
+                        </option>
+                    })}
+                </select>
+            </div>
+            <ul class="list-group">
+            {
+                for entries.iter().filter_map(|(entry, entry_metadata)| {
+                    let issue = entry_metadata.issue.as_ref()?;
+                    if shown_reason.is_some_and(|reason| reason != issue.reason) {
+                        return None;
+                    }
+                    let id = entry.id?;
+                    let onclick = {
+                        shadow_clone!(onopen, entry);
+                        move |_| onopen.emit(entry.clone())
+                    };
+                    let change = |issue: Option<Issue>| {
+                        shadow_clone!(reload, entry, entry_metadata);
+                        move |_| {
+                            let new_metadata = Metadata {
+                                issue: issue.clone(),
+                                ..entry_metadata.clone()
+                            };
+                            shadow_clone!(reload, entry);
+                            spawn_local(async move {
+                                if let Err(why) = save(id, &entry.server, new_metadata).await {
+                                    notify::error(language.t("history-flag-failed"), why);
+                                }
+                                reload();
+                            });
+                        }
+                    };
+                    Some(html! {
+                        <li class="list-group-item">
+                            <div class="d-flex justify-content-between align-items-center">
+                                <span>
+                                    <strong>{&entry.image.file_name}</strong>
+                                    <span class="badge text-bg-danger ms-2">{reason_label(language, issue.reason)}</span>
+                                    <small class="text-body-secondary ms-2">
+                                        {format!("{} · {}", entry.date(), entry.server)}
+                                    </small>
+                                    if can_report && !issue.reported {
+                                        <small class="text-warning ms-2">{language.t("issue-not-reported")}</small>
+                                    }
+                                </span>
+                                <div class="btn-group btn-group-sm">
+                                    <button class="btn btn-outline-primary" {onclick}>{language.t("history-open")}</button>
+                                    if can_edit && can_report && !issue.reported {
+                                        <button class="btn btn-outline-secondary" onclick={change(Some(issue.clone()))}>
+                                            {language.t("issue-report-again")}
+                                        </button>
+                                    }
+                                    if can_edit {
+                                        <button class="btn btn-outline-success" onclick={change(None)}>
+                                            {language.t("issue-resolve")}
+                                        </button>
+                                    }
+                                </div>
+                            </div>
+                            if !issue.comment.is_empty() {
+                                <p class="mb-0 mt-1">{&issue.comment}</p>
+                            }
+                        </li>
+                    })
+                })
+            }
+            </ul>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::IssueReport;
+    use crate::metadata::{Issue, IssueReason, Metadata};
+
+    #[test]
+    fn reports_tell_the_model_and_the_reason_but_not_the_image() {
+        let metadata = Metadata {
+            notes: "private notes".to_string(),
+            class_shares: BTreeMap::from([(0, 0.75), (3, 0.25)]),
+            digest: Some("ab12".to_string()),
+            ..Default::default()
+        };
+        let issue = Issue {
+            reason: IssueReason::CloudCover,
+            comment: "clouds as roofs".to_string(),
+            timestamp: 1_700_000_000_000.0,
+            reported: false,
+        };
+
+        let report = IssueReport::new(42.0, "http://localhost:5000/segment", &metadata, &issue);
+        let json = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(json["result_id"], 42.0);
+        assert_eq!(json["server"], "http://localhost:5000/segment");
+        assert_eq!(json["image_digest"], "ab12");
+        assert_eq!(json["reason"], "cloud-cover");
+        assert_eq!(json["comment"], "clouds as roofs");
+        assert_eq!(json["class_shares"]["3"], 0.25);
+        assert!(!json.to_string().contains("private notes"));
+    }
+}
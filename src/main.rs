@@ -1,240 +1,487 @@
-use base64::engine::general_purpose::STANDARD;
-use base64::Engine;
-use gloo::file::callbacks::FileReader;
-use gloo::file::File;
-use serde::{Deserialize, Deserializer};
+use gloo::timers::callback::Timeout;
 use shadow_clone::shadow_clone;
-use std::{borrow::Borrow, collections::HashMap, rc::Rc, sync::Arc};
-use web_sys::{Event, FileList, HtmlInputElement};
-use yew::{
-    prelude::*,
-    suspense::{use_future, use_future_with},
-};
-use yew_autoprops::autoprops_component;
-use yew_hooks::prelude::*;
-
-#[derive(Deserialize, PartialEq, Clone)]
-struct FileDetails {
-    file_name: String,
-    file_type: String,
-    #[serde(deserialize_with = "deserialize_file_data")]
-    data: Vec<u8>,
-}
+use std::rc::Rc;
+use yew::{platform::spawn_local, prelude::*};
 
-fn deserialize_file_data<'de, D>(d: D) -> Result<Vec<u8>, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    Deserialize::deserialize(d).map(|v: String| STANDARD.decode(v.into_bytes()).unwrap())
-}
+mod aoi;
+mod api;
+mod archive;
+mod audit;
+mod batch;
+mod change;
+mod clock;
+mod codec;
+mod collab;
+mod comments;
+mod config;
+mod crash;
+mod debuglog;
+mod decoded;
+mod diff;
+mod digest;
+mod error;
+mod evaluation;
+#[cfg(test)]
+mod fixtures;
+mod flags;
+mod gallery;
+mod geo;
+mod history;
+mod http;
+mod i18n;
+mod idb;
+mod install;
+mod issues;
+mod map;
+mod memory;
+mod metadata;
+mod models;
+mod ndvi;
+mod notify;
+mod offline;
+mod permalink;
+mod postprocess;
+mod project;
+mod raster;
+mod report;
+mod rerun;
+mod review;
+mod roles;
+mod route;
+mod schema;
+mod segments;
+mod session;
+mod settings;
+mod shortcuts;
+mod state;
+mod status;
+mod storage;
+mod sync;
+mod telemetry;
+mod theme;
+mod throttle;
+mod uploader;
+mod viewer;
+mod workers;
+mod workspace;
+
+use api::use_api;
+use audit::AuditLogPane;
+use batch::BatchPane;
+use change::ChangePane;
+use comments::CommentsPane;
+use crash::ErrorBoundary;
+use debuglog::DebugLogPanel;
+use error::AppError;
+use flags::Flag;
+use gallery::GalleryPane;
+use history::{HistoryEntry, HistoryPane};
+use i18n::{use_language, LanguageProvider, LanguageSwitcher};
+use install::InstallButton;
+use issues::IssueQueuePane;
+use memory::MemoryPane;
+use models::ModelComparisonPane;
+use notify::NotificationToasts;
+use postprocess::PostProcessPane;
+use project::Project;
+use report::ReportPane;
+use roles::use_role;
+use route::{use_route, Route, RouteTabs};
+use schema::FileDetails;
+use segments::CurrentSegmentsPane;
+use session::{AutosaveStatus, AUTOSAVE_DELAY_MS};
+use settings::{Settings, SettingsPane};
+use shortcuts::ShortcutHelp;
+use state::{use_app_state, AppAction, AppStateProvider};
+use status::{ResultToolbar, SegmentationStatusBadge, StatusBar};
+use storage::{entry_size, StoragePane};
+use sync::SyncMessage;
+use theme::ThemeSwitcher;
+use uploader::ImageUploader;
+use workspace::WorkspaceSwitcher;
 
 #[function_component(App)]
 fn app() -> Html {
-    let src_image_state = use_state(|| Rc::new(None));
+    html! {
+        <LanguageProvider>
+            <AppStateProvider>
+                <Layout />
+                <NotificationToasts />
+                <ShortcutHelp />
+            </AppStateProvider>
+        </LanguageProvider>
+    }
+}
+
+#[function_component(Layout)]
+fn layout() -> Html {
+    let state = use_app_state();
+    let language = use_language();
+    let history_error = use_state(|| None::<AttrValue>);
+    let autosave_status = use_state(|| AutosaveStatus::Idle);
+    let permalink_error = use_state(|| None::<AppError>);
+    let route = use_route();
+    let api = use_api();
+    let role = use_role();
 
-    let onupload = {
-        shadow_clone!(src_image_state);
-        move |newdata| {
-            src_image_state.set(newdata);
+    let onhistoryopen = {
+        shadow_clone!(state);
+        move |entry: Rc<HistoryEntry>| {
+            state.dispatch(AppAction::Open {
+                image: Rc::new(Some((*entry.image).clone())),
+                mask: Some(entry.mask.clone()),
+                result: entry.id.map(|id| id as u64),
+            });
+            match entry.id {
+                Some(id) => Route::Result(id as u64).push(),
+                None => Route::Upload.push(),
+            }
         }
     };
 
-    html! {
-        <div class="row justify-content-evenly">
-            <div class="col-4">
-                <h1>{"Satellite image"}</h1>
-                <UploadPane {onupload} />
-            </div>
-            <div class="col-4">
-                <h1>{"Segments"}</h1>
-                <SegmentsPane image_data={(*src_image_state).clone()} />
-            </div>
-        </div>
+    // Results opened from a link or the back button are read from the history.
+    {
+        shadow_clone!(state);
+        use_effect_with(route, move |route| {
+            let Route::Result(id) = *route else {
+                return;
+            };
+            if state.opened_result == Some(id) {
+                return;
+            }
+            spawn_local(async move {
+                match history::get_entry(id as f64).await {
+                    Ok(Some(entry)) => state.dispatch(AppAction::Open {
+                        image: Rc::new(Some((*entry.image).clone())),
+                        mask: Some(entry.mask.clone()),
+                        result: Some(id),
+                    }),
+                    Ok(None) => notify::warn(
+                        language.t("error-result-unopened"),
+                        language.t_args("error-result-missing", &[("id", &id)]),
+                    ),
+                    Err(why) => notify::error(language.t("error-result-unopened"), why),
+                }
+            });
+        });
     }
-}
 
-#[autoprops_component(SegmentsPane)]
-fn segments_pane(image_data: Rc<Option<FileDetails>>) -> Html {
-    let mask_image: UseStateHandle<Rc<Option<FileDetails>>> = use_state(|| Rc::new(None));
-
-    let fallback = html!(
-        <h1>{"Processing image..."} <span class="spinner-border text-success"></span></h1>
-    );
+    {
+        shadow_clone!(state, history_error);
+        use_effect_with(state.mask.clone(), move |mask| {
+            if let (Some(image), Some(mask), None) =
+                ((*state.image).as_ref(), mask, &state.restored_mask)
+            {
+                let settings = state.settings.clone();
+                let mut entry =
+                    HistoryEntry::new(Rc::new(image.clone()), mask.clone(), settings.segment_url());
+                entry.result_id = state.result_id.clone();
+                spawn_local(async move {
+                    if let Some(warning) = storage::check_before_write(entry_size(&entry)).await {
+                        log::warn!("{warning}");
+                    }
+                    match history::add(&entry).await {
+                        Ok(_) => {
+                            history_error.set(None);
+                            if let Err(why) = history::prune(&settings).await {
+                                log::warn!("Could not prune the history: {why}");
+                            }
+                        }
+                        Err(why) => {
+                            log::error!("Could not save result to history: {why}");
+                            history_error.set(Some(AttrValue::from(why)));
+                        }
+                    }
+                    state.dispatch(AppAction::HistoryChanged);
+                });
+            }
+        });
+    }
 
-    html!(
-        <Suspense {fallback}>
-            <SegmentsInnerPane src_image={image_data} />
-        </Suspense>
-    )
-}
+    // A `?result=<id>` address opens that stored result in place of anything else.
+    {
+        shadow_clone!(state, api, permalink_error);
+        use_effect_with((), move |_| {
+            if let Some(result_id) = permalink::result_id() {
+                spawn_local(async move {
+                    match api.fetch_result(&result_id).await {
+                        Ok(result) => state.dispatch(AppAction::Open {
+                            image: Rc::new(Some(result.image)),
+                            mask: Some(Rc::new(result.mask)),
+                            result: None,
+                        }),
+                        Err(error) => permalink_error.set(Some(error)),
+                    }
+                });
+            }
+        });
+    }
 
-#[derive(Properties, PartialEq)]
-struct SegmentsInnerPaneProps {
-    src_image: Rc<Option<FileDetails>>,
-}
+    // Changes saved by other tabs are picked up here too.
+    {
+        shadow_clone!(state);
+        use_effect_with((), move |_| {
+            let listener = sync::subscribe(Callback::from(move |message| match message {
+                SyncMessage::HistoryChanged => state.dispatch(AppAction::HistoryChanged),
+                SyncMessage::SettingsChanged => {
+                    state.dispatch(AppAction::SetSettings(Rc::new(Settings::load())))
+                }
+            }));
+            move || drop(listener)
+        });
+    }
 
-#[function_component(SegmentsInnerPane)]
-fn segments_inner_pane(props: &SegmentsInnerPaneProps) -> HtmlResult {
-    let res = use_future_with(props.src_image.clone(), |deps| async move {
-        if deps.is_none() {
-            return None;
+    let comparison = use_state(|| None::<(Rc<HistoryEntry>, Rc<HistoryEntry>)>);
+    let oncompare = {
+        shadow_clone!(comparison);
+        move |results| {
+            comparison.set(Some(results));
+            Route::Compare.push();
         }
-        let FileDetails {
-            file_name,
-            file_type,
-            data,
-        } = (**deps).clone().unwrap();
-        let client = reqwest::Client::new();
-        let body = reqwest::multipart::Form::new().part(
-            "f[]",
-            reqwest::multipart::Part::bytes(data)
-                .file_name(file_name)
-                .mime_str(&file_type)
-                .unwrap(),
-        );
-        let reqwest = client
-            .post(format!("{}/segment", env!("SERVER_URL")))
-            .multipart(body)
-            .send()
-            .await;
-        let result = match reqwest {
-            Ok(resp) => match resp.error_for_status() {
-                Ok(mask) => match mask.json::<FileDetails>().await {
-                    Ok(json) => Ok(json),
-                    Err(e) => Err(format!("Error in receiving json: {e}")),
-                },
-                Err(e) => Err(format!("Error code in sending imaget to server: {e}")),
-            },
-            Err(e) => Err(format!("Error sending image to server: {e}")),
-        };
-
-        Some(result)
-    })?;
-
-    let answer = match *res {
-        Some(ref res) => match res {
-            Ok(file) => html! {
-                <div>
-                    <h2>{&file.file_name}</h2>
-                    <img
-                        width={"100%"}
-                        src={
-                            format!("data:{};base64,{}",
-                            file.file_type,
-                            STANDARD.encode(&file.data))
-                        }
-                    />
-                </div>
-            },
-            Err(why) => html!(
-                <div class="alert alert-danger">
-                    {"Could not fetch answer: "}{why}
-                </div>
-            ),
-        },
-        None => html!({ "No image uploaded yet..." }),
     };
 
-    Ok(answer)
-}
-
-#[autoprops_component(UploadPane)]
-fn upload_pane(#[prop_or_default] onupload: Callback<Rc<Option<FileDetails>>>) -> Html {
-    let src_image_state = use_state(|| Rc::new(None));
-    let readers = use_map(HashMap::new());
-
-    let on_complete_read = {
-        shadow_clone!(src_image_state, readers, onupload);
-        move |file_name, file_type, data| {
-            readers.remove(&file_name);
-
-            log::info!("Finished reading {file_name}");
-            let src_img = Rc::new(Some(FileDetails {
-                file_name,
-                file_type,
-                data,
-            }));
+    let onhistorychange = {
+        shadow_clone!(state);
+        move |_| state.dispatch(AppAction::HistoryChanged)
+    };
 
-            src_image_state.set(src_img.clone());
+    let onsettingschange = {
+        shadow_clone!(state);
+        move |new_settings: Rc<Settings>| {
+            new_settings.store();
+            state.dispatch(AppAction::SetSettings(new_settings));
+        }
+    };
 
-            onupload.emit(src_img);
+    let onprojectopen = {
+        shadow_clone!(state);
+        move |project: Rc<Project>| {
+            let new_settings = Rc::new(project.settings().clone());
+            new_settings.store();
+            state.dispatch(AppAction::SetSettings(new_settings));
+            state.dispatch(AppAction::Open {
+                image: Rc::new(project.image()),
+                mask: project.mask().map(Rc::new),
+                result: None,
+            });
         }
     };
 
-    let onupload = {
-        shadow_clone!(src_image_state, readers);
-        move |e: Event| {
-            let input: HtmlInputElement = e.target_unchecked_into();
-            let files = input.files();
-            let files = match files {
-                Some(f) => f,
-                None => return,
-            };
-            let files = js_sys::try_iter(&files)
-                .unwrap()
-                .unwrap()
-                .map(|v| web_sys::File::from(v.unwrap()))
-                .map(File::from)
-                .collect::<Vec<_>>();
-
-            log::info!("New image: {files:?}");
-            for file in files.into_iter() {
-                let file_name = file.name();
-                let file_type = file.raw_mime_type();
-
-                let task = {
-                    let file_name = file_name.clone();
-
-                    gloo::file::callbacks::read_as_bytes(&file, {
-                        shadow_clone!(on_complete_read);
-                        move |res| {
-                            on_complete_read(
-                                file_name,
-                                file_type,
-                                res.expect("Failed to read file."),
-                            )
-                        }
+    // Changes in quick succession are saved once, after they settle.
+    {
+        shadow_clone!(autosave_status);
+        let settings = state.settings.clone();
+        use_effect_with(
+            (state.image.clone(), state.mask.clone()),
+            move |(image, mask)| {
+                let timeout = (**image).as_ref().map(|image| {
+                    let project = Project::new(Some(image), mask.as_deref(), &settings);
+                    autosave_status.set(AutosaveStatus::Pending);
+                    Timeout::new(AUTOSAVE_DELAY_MS, move || {
+                        autosave_status.set(AutosaveStatus::Saving);
+                        spawn_local(async move {
+                            autosave_status.set(match session::autosave(&project).await {
+                                Ok(()) => AutosaveStatus::Saved(clock::now()),
+                                Err(why) => AutosaveStatus::Failed(why),
+                            });
+                        });
                     })
-                };
-                readers.insert(file_name, task);
+                });
+                move || drop(timeout)
+            },
+        );
+    }
+
+    let onprojectsave = move |_| {
+        spawn_local(async {
+            if let Err(why) = session::clear().await {
+                log::warn!("Could not clear the autosaved session: {why}");
             }
-        }
+        })
     };
 
-    html!(
-        <>
-        {
-            if let Some(file) = (*src_image_state).borrow() {
-                html! {
-                    <div>
-                        <h2>{&file.file_name}</h2>
-                        <img
-                            width={"100%"}
-                            src={
-                                format!("data:{};base64,{}",
-                                file.file_type,
-                                STANDARD.encode(&file.data))
-                            }
+    let config = config::current();
+
+    html! {
+        <div class="row justify-content-evenly">
+            <div class="col-12 d-print-none">
+                <div class="d-flex align-items-center gap-2 my-2">
+                    if let Some(logo_url) = &config.branding.logo_url {
+                        <img src={logo_url.clone()} alt="" height="40" />
+                        <span class="fs-4">{&config.branding.title}</span>
+                    }
+                    <div class="ms-auto d-flex gap-2">
+                        <InstallButton />
+                        <ThemeSwitcher />
+                        <LanguageSwitcher />
+                    </div>
+                </div>
+                if flags::enabled(Flag::Workspaces) {
+                    <WorkspaceSwitcher />
+                }
+                <StatusBar
+                    permalink_error={(*permalink_error).clone()}
+                    history_error={(*history_error).clone()}
+                    onrestore={onprojectopen.clone()}
+                />
+                // Kept at the bottom of the screen on phones and tablets, within reach.
+                if role.can_edit() {
+                    <div class="mobile-toolbar">
+                        <ResultToolbar
+                            autosave_status={(*autosave_status).clone()}
+                            onopen={onprojectopen}
+                            onsave={onprojectsave}
                         />
                     </div>
                 }
-            } else {
-                html! {
-                    <p>{"No file uploaded."}</p>
+            </div>
+            <div class="col-12 d-print-none">
+                <RouteTabs current={route} />
+            </div>
+            {
+                match route {
+                    route if !role.allows(route) => html! {
+                        <div class="col-12 col-lg-8">
+                            <div class="alert alert-warning" role="alert">
+                                {language.t_args(
+                                    "role-screen-unavailable",
+                                    &[("role", &language.t(&format!("role-{}", role.name())))],
+                                )}
+                            </div>
+                        </div>
+                    },
+                    // Viewers look at results in their report, which changes nothing.
+                    Route::Result(id) if !role.can_edit() => html! {
+                        <ErrorBoundary class="col-12" name={language.t("heading-report")}>
+                            <ReportPane {id} />
+                        </ErrorBoundary>
+                    },
+                    Route::Upload if !role.can_edit() => html! {
+                        <div class="col-12 col-lg-8">
+                            <p>{language.t("role-viewer-results")}</p>
+                            <a class="btn btn-outline-primary" href={Route::History.href()}>
+                                {language.t("tab-history")}
+                            </a>
+                        </div>
+                    },
+                    Route::Upload | Route::Result(_) => html! {
+                        <>
+                            <ErrorBoundary class="col-12 col-lg-4" name={language.t("region-image")}>
+                                <ImageUploader />
+                            </ErrorBoundary>
+                            <ErrorBoundary class="col-12 col-lg-4" name={language.t("heading-segments")}>
+                                <h1>{language.t("heading-segments")}</h1>
+                                <SegmentationStatusBadge />
+                                <CurrentSegmentsPane />
+                            </ErrorBoundary>
+                            if let Route::Result(id) = route {
+                                <ErrorBoundary class="col-12 col-lg-8" name={language.t("heading-comments")}>
+                                    <h2>{language.t("heading-comments")}</h2>
+                                    <CommentsPane result={id} />
+                                </ErrorBoundary>
+                            }
+                        </>
+                    },
+                    Route::Compare => html! {
+                        <ErrorBoundary class="col-12 col-lg-8" name={language.t("heading-compare")}>
+                            <h1>{language.t("heading-compare")}</h1>
+                            <ChangePane results={(*comparison).clone()} />
+                        </ErrorBoundary>
+                    },
+                    Route::History => html! {
+                        <ErrorBoundary class="col-12 col-lg-8" name={language.t("heading-history")}>
+                            <details open={true}>
+                                <summary class="h1">{language.t("heading-history")}</summary>
+                                <HistoryPane
+                                    revision={state.history_revision}
+                                    onopen={onhistoryopen.clone()}
+                                    {oncompare}
+                                />
+                            </details>
+                            if flags::enabled(Flag::Gallery) {
+                                <details>
+                                    <summary class="h1">{language.t("heading-gallery")}</summary>
+                                    <GalleryPane revision={state.history_revision} onopen={onhistoryopen} />
+                                </details>
+                            }
+                            if role.can_administer() {
+                                <details>
+                                    <summary class="h1">{language.t("heading-storage")}</summary>
+                                    <StoragePane revision={state.history_revision} onchange={onhistorychange} />
+                                    <MemoryPane />
+                                </details>
+                                <details>
+                                    <summary class="h1">{language.t("heading-audit")}</summary>
+                                    <AuditLogPane revision={state.history_revision} />
+                                </details>
+                            }
+                        </ErrorBoundary>
+                    },
+                    Route::Flagged => html! {
+                        <ErrorBoundary class="col-12 col-lg-8" name={language.t("heading-flagged")}>
+                            <h1>{language.t("heading-flagged")}</h1>
+                            <IssueQueuePane revision={state.history_revision} onopen={onhistoryopen.clone()} />
+                        </ErrorBoundary>
+                    },
+                    Route::Models => html! {
+                        <ErrorBoundary class="col-12" name={language.t("heading-models")}>
+                            <h1>{language.t("heading-models")}</h1>
+                            <ModelComparisonPane />
+                        </ErrorBoundary>
+                    },
+                    Route::Report(id) => html! {
+                        <ErrorBoundary class="col-12" name={language.t("heading-report")}>
+                            <ReportPane {id} />
+                        </ErrorBoundary>
+                    },
+                    // Shown below, as it stays mounted on every screen.
+                    Route::Batch => html!(),
+                    Route::Settings => html! {
+                        <ErrorBoundary class="col-12 col-lg-8" name={language.t("heading-settings")}>
+                            <h1>{language.t("heading-settings")}</h1>
+                            <SettingsPane settings={state.settings.clone()} onchange={onsettingschange.clone()} />
+                            if role.can_administer() {
+                                <h2 class="mt-3">{language.t("heading-postprocess")}</h2>
+                                <PostProcessPane settings={state.settings.clone()} onchange={onsettingschange} />
+                            }
+                        </ErrorBoundary>
+                    },
                 }
             }
-        }
-        <input
-            type="file"
-            accept="image/*"
-            multiple={false}
-            onchange={onupload}
-        />
-        </>
-    )
+            // Kept mounted while other screens are shown, so that a run goes on meanwhile.
+            if flags::enabled(Flag::Batch) && role.allows(Route::Batch) {
+                <ErrorBoundary
+                    class={classes!("col-12", "col-lg-8", (route != Route::Batch).then_some("d-none"))}
+                    name={language.t("heading-batch")}
+                >
+                    <h1>{language.t("heading-batch")}</h1>
+                    <BatchPane />
+                </ErrorBoundary>
+            }
+            <div class="col-12 d-print-none">
+                <DebugLogPanel />
+            </div>
+        </div>
+    }
 }
 
 fn main() {
-    wasm_logger::init(wasm_logger::Config::default());
-    yew::Renderer::<App>::new().render();
+    debuglog::init();
+    crash::install();
+    clock::init();
+    offline::register_service_worker();
+    install::listen();
+    workspace::purge_deleted();
+    // The configuration is needed by nearly everything, so it is read before rendering.
+    spawn_local(async {
+        let loaded = config::load().await;
+        i18n::init();
+        if let Err(why) = loaded {
+            notify::warn(i18n::t("error-config-ignored"), why);
+        }
+        theme::init();
+        gloo::utils::document().set_title(&config::current().branding.title);
+        telemetry::start();
+        yew::Renderer::<App>::new().render();
+    });
 }
@@ -0,0 +1,121 @@
+//! Place search against a Nominatim-compatible geocoder.
+
+use gloo::net::http::Request;
+use serde::Deserialize;
+use shadow_clone::shadow_clone;
+use web_sys::HtmlInputElement;
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    geo::Bounds,
+    http,
+    i18n::{self, use_language},
+};
+
+/// Search endpoint, overridable at build time through the `GEOCODER_URL` environment variable.
+const GEOCODER_URL: &str = match option_env!("GEOCODER_URL") {
+    Some(url) => url,
+    None => "https://nominatim.openstreetmap.org/search",
+};
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Place {
+    display_name: String,
+    /// `[south, north, west, east]` as decimal strings.
+    boundingbox: [String; 4],
+}
+
+impl Place {
+    fn bounds(&self) -> Option<Bounds> {
+        let [south, north, west, east] = &self.boundingbox;
+        Some(Bounds {
+            south: south.parse().ok()?,
+            west: west.parse().ok()?,
+            north: north.parse().ok()?,
+            east: east.parse().ok()?,
+        })
+    }
+}
+
+async fn search(query: String) -> Result<Vec<Place>, String> {
+    let request =
+        Request::get(GEOCODER_URL).query([("format", "json"), ("limit", "5"), ("q", &query)]);
+    let response = http::send(request, None)
+        .await
+        .map_err(|e| i18n::t_args("geocoder-failed", &[("query", &query), ("error", &e)]))?;
+    http::json(response)
+        .await
+        .map_err(|e| i18n::t_args("geocoder-unreadable", &[("error", &e)]))
+}
+
+#[autoprops_component(GeocoderSearch)]
+pub fn geocoder_search(onselect: Callback<Bounds>) -> Html {
+    let language = use_language();
+    let query = use_state(String::new);
+    let results = use_state(|| None::<Result<Vec<Place>, String>>);
+
+    let oninput = {
+        shadow_clone!(query);
+        move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            query.set(input.value());
+        }
+    };
+
+    let onsubmit = {
+        shadow_clone!(query, results);
+        move |e: SubmitEvent| {
+            e.prevent_default();
+            if query.trim().is_empty() {
+                return;
+            }
+            shadow_clone!(results);
+            let query = (*query).clone();
+            spawn_local(async move { results.set(Some(search(query).await)) });
+        }
+    };
+
+    let places = match &*results {
+        None => html!(),
+        Some(Err(why)) => html!(<div class="alert alert-danger" role="alert">{why}</div>),
+        Some(Ok(places)) if places.is_empty() => html!(<p>{language.t("geocoder-none")}</p>),
+        Some(Ok(places)) => html! {
+            <div class="list-group mb-2">
+            {
+                for places.iter().filter_map(|place| {
+                    let bounds = place.bounds()?;
+                    let onclick = {
+                        shadow_clone!(onselect, results);
+                        move |_| {
+                            onselect.emit(bounds);
+                            results.set(None);
+                        }
+                    };
+                    Some(html! {
+                        <button class="list-group-item list-group-item-action" {onclick}>
+                            {&place.display_name}
+                        </button>
+                    })
+                })
+            }
+            </div>
+        },
+    };
+
+    html! {
+        <>
+            <form class="input-group mb-2" {onsubmit}>
+                <input
+                    type="search"
+                    class="form-control"
+                    placeholder={language.t("geocoder-placeholder")}
+                    value={(*query).clone()}
+                    {oninput}
+                />
+                <button type="submit" class="btn btn-outline-primary">{language.t("geocoder-search")}</button>
+            </form>
+            {places}
+        </>
+    }
+}
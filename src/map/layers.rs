@@ -0,0 +1,202 @@
+//! Ordering, visibility and opacity of the layers drawn on the map.
+
+use shadow_clone::shadow_clone;
+use std::rc::Rc;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    i18n::{use_language, Language},
+    throttle::use_throttled,
+};
+
+/// Least time between opacity changes applied to the map while a slider is dragged.
+const OPACITY_INTERVAL_MS: u32 = 50;
+
+/// Kinds of content drawn on the map. Each kind is drawn in its own Leaflet pane or
+/// OpenLayers layer group, so its settings apply to every layer it holds.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum LayerKind {
+    Basemap,
+    Image,
+    Mask,
+    /// The ground truth of the mask, or the map of the mask's errors against it.
+    GroundTruth,
+    Aoi,
+}
+
+impl LayerKind {
+    pub const ALL: [LayerKind; 5] = [
+        LayerKind::Basemap,
+        LayerKind::Image,
+        LayerKind::Mask,
+        LayerKind::GroundTruth,
+        LayerKind::Aoi,
+    ];
+
+    pub fn name(self, language: Language) -> String {
+        language.t(match self {
+            LayerKind::Basemap => "layer-basemap",
+            LayerKind::Image => "layer-image",
+            LayerKind::Mask => "layer-mask",
+            LayerKind::GroundTruth => "layer-ground-truth",
+            LayerKind::Aoi => "layer-aoi",
+        })
+    }
+
+    /// Name of the Leaflet pane holding layers of this kind.
+    pub fn pane(self) -> &'static str {
+        match self {
+            LayerKind::Basemap => "basemapPane",
+            LayerKind::Image => "imagePane",
+            LayerKind::Mask => "maskPane",
+            LayerKind::GroundTruth => "truthPane",
+            LayerKind::Aoi => "aoiPane",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct LayerSettings {
+    pub kind: LayerKind,
+    pub visible: bool,
+    pub opacity: f64,
+}
+
+/// Layer settings ordered from the bottom of the map to the top.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LayerStack {
+    pub layers: Vec<LayerSettings>,
+}
+
+impl LayerStack {
+    /// Every layer visible, in the order of [`LayerKind::ALL`], masks at `mask_opacity`.
+    pub fn new(mask_opacity: f64) -> Self {
+        LayerStack {
+            layers: LayerKind::ALL
+                .into_iter()
+                .map(|kind| LayerSettings {
+                    kind,
+                    visible: true,
+                    opacity: if matches!(kind, LayerKind::Mask | LayerKind::GroundTruth) {
+                        mask_opacity
+                    } else {
+                        1.0
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+pub enum LayerAction {
+    Toggle(LayerKind),
+    Opacity(LayerKind, f64),
+    Raise(LayerKind),
+    Lower(LayerKind),
+}
+
+impl Reducible for LayerStack {
+    type Action = LayerAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut stack = (*self).clone();
+        let (LayerAction::Toggle(kind)
+        | LayerAction::Opacity(kind, _)
+        | LayerAction::Raise(kind)
+        | LayerAction::Lower(kind)) = action;
+        let Some(i) = stack.layers.iter().position(|l| l.kind == kind) else {
+            return self;
+        };
+        match action {
+            LayerAction::Toggle(_) => stack.layers[i].visible = !stack.layers[i].visible,
+            LayerAction::Opacity(_, opacity) => stack.layers[i].opacity = opacity.clamp(0.0, 1.0),
+            LayerAction::Raise(_) if i + 1 < stack.layers.len() => stack.layers.swap(i, i + 1),
+            LayerAction::Lower(_) if i > 0 => stack.layers.swap(i, i - 1),
+            LayerAction::Raise(_) | LayerAction::Lower(_) => return self,
+        }
+        stack.into()
+    }
+}
+
+/// Layer list with the topmost layer first.
+#[autoprops_component(LayersPane)]
+pub fn layers_pane(layers: LayerStack, onaction: Callback<LayerAction>) -> Html {
+    let language = use_language();
+    let count = layers.layers.len();
+    let onopacityaction = use_throttled(onaction.clone(), OPACITY_INTERVAL_MS);
+
+    html! {
+        <ul class="list-group">
+        {
+            for layers.layers.iter().enumerate().rev().map(|(i, layer)| {
+                let kind = layer.kind;
+                let name = kind.name(language);
+                let ontoggle = {
+                    shadow_clone!(onaction);
+                    move |_| onaction.emit(LayerAction::Toggle(kind))
+                };
+                let onopacity = {
+                    shadow_clone!(onopacityaction);
+                    move |e: InputEvent| {
+                        let input: HtmlInputElement = e.target_unchecked_into();
+                        if let Ok(value) = input.value().parse() {
+                            onopacityaction.emit(LayerAction::Opacity(kind, value));
+                        }
+                    }
+                };
+                let onraise = {
+                    shadow_clone!(onaction);
+                    move |_| onaction.emit(LayerAction::Raise(kind))
+                };
+                let onlower = {
+                    shadow_clone!(onaction);
+                    move |_| onaction.emit(LayerAction::Lower(kind))
+                };
+                html! {
+                    <li class="list-group-item d-flex gap-2 align-items-center">
+                        <input
+                            type="checkbox"
+                            class="form-check-input"
+                            checked={layer.visible}
+                            aria-label={language.t_args("layer-show", &[("layer", &name)])}
+                            onchange={ontoggle}
+                        />
+                        <span style="width: 10em;">{&name}</span>
+                        <input
+                            type="range"
+                            class="form-range"
+                            min="0"
+                            max="1"
+                            step="0.05"
+                            value={layer.opacity.to_string()}
+                            aria-label={language.t_args("layer-opacity", &[("layer", &name)])}
+                            disabled={!layer.visible}
+                            oninput={onopacity}
+                        />
+                        <div class="btn-group btn-group-sm">
+                            <button
+                                class="btn btn-outline-secondary"
+                                disabled={i + 1 == count}
+                                aria-label={language.t_args("layer-raise", &[("layer", &name)])}
+                                onclick={onraise}
+                            >
+                                {"▲"}
+                            </button>
+                            <button
+                                class="btn btn-outline-secondary"
+                                disabled={i == 0}
+                                aria-label={language.t_args("layer-lower", &[("layer", &name)])}
+                                onclick={onlower}
+                            >
+                                {"▼"}
+                            </button>
+                        </div>
+                    </li>
+                }
+            })
+        }
+        </ul>
+    }
+}
@@ -0,0 +1,64 @@
+//! Minimal bindings to the subset of the Leaflet API loaded from `index.html`.
+
+use wasm_bindgen::prelude::*;
+use web_sys::HtmlElement;
+
+#[wasm_bindgen]
+extern "C" {
+    #[derive(Clone)]
+    pub type Map;
+
+    #[wasm_bindgen(js_namespace = L, js_name = map)]
+    pub fn new_map(element: &HtmlElement, options: &JsValue) -> Map;
+
+    #[wasm_bindgen(method)]
+    pub fn on(this: &Map, event: &str, handler: &Closure<dyn FnMut(JsValue)>);
+
+    #[wasm_bindgen(method, js_name = fitBounds)]
+    pub fn fit_bounds(this: &Map, bounds: &JsValue);
+
+    #[wasm_bindgen(method)]
+    pub fn remove(this: &Map);
+
+    #[wasm_bindgen(method, js_name = createPane)]
+    pub fn create_pane(this: &Map, name: &str) -> HtmlElement;
+
+    #[wasm_bindgen(method, js_name = getPane)]
+    pub fn get_pane(this: &Map, name: &str) -> HtmlElement;
+
+    pub type Layer;
+
+    #[wasm_bindgen(js_namespace = L, js_name = tileLayer)]
+    pub fn tile_layer(url_template: &str, options: &JsValue) -> Layer;
+
+    #[wasm_bindgen(js_namespace = ["L", "tileLayer"], js_name = wms)]
+    pub fn tile_layer_wms(url: &str, options: &JsValue) -> Layer;
+
+    #[wasm_bindgen(thread_local_v2, js_namespace = L, js_name = CRS)]
+    pub static CRS: JsValue;
+
+    #[wasm_bindgen(js_namespace = L, js_name = imageOverlay)]
+    pub fn image_overlay(url: &str, bounds: &JsValue, options: &JsValue) -> Layer;
+
+    #[wasm_bindgen(js_namespace = L)]
+    pub fn polygon(latlngs: &JsValue, options: &JsValue) -> Layer;
+
+    #[wasm_bindgen(method, js_name = addTo)]
+    pub fn add_to(this: &Layer, map: &Map) -> Layer;
+
+    #[wasm_bindgen(method, js_name = remove)]
+    pub fn remove_layer(this: &Layer);
+
+    pub type MouseEvent;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn latlng(this: &MouseEvent) -> LatLng;
+
+    pub type LatLng;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn lat(this: &LatLng) -> f64;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn lng(this: &LatLng) -> f64;
+}
@@ -0,0 +1,880 @@
+use js_sys::{Array, Object, Reflect};
+use shadow_clone::shadow_clone;
+use std::{collections::HashMap, rc::Rc};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{HtmlElement, HtmlInputElement, HtmlSelectElement};
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    aoi::{SavedAoi, SavedAoisPane},
+    evaluation::ErrorLegend,
+    geo::{lat_lng_to_mercator, Bounds, Georeference, LatLng},
+    i18n::use_language,
+    settings::Settings,
+    shortcuts::use_shortcut,
+    viewer::{describe_position, BlobUrl},
+    FileDetails,
+};
+
+mod geocoder;
+mod layers;
+mod leaflet;
+mod openlayers;
+mod sources;
+mod stac;
+mod tiles;
+
+pub use sources::{SourceKind, TileSource, WMS_CRSS};
+
+use geocoder::GeocoderSearch;
+use layers::{LayerAction, LayerKind, LayerStack, LayersPane};
+use sources::SourcesPane;
+use stac::StacBrowser;
+
+/// Library used to draw the basemap.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MapRenderer {
+    Leaflet,
+    OpenLayers,
+}
+
+impl MapRenderer {
+    const ALL: [MapRenderer; 2] = [MapRenderer::Leaflet, MapRenderer::OpenLayers];
+
+    /// Renderer picked at build time through the `MAP_RENDERER` environment variable.
+    pub fn build_default() -> Self {
+        match option_env!("MAP_RENDERER") {
+            Some("openlayers") => MapRenderer::OpenLayers,
+            _ => MapRenderer::Leaflet,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            MapRenderer::Leaflet => "Leaflet",
+            MapRenderer::OpenLayers => "OpenLayers",
+        }
+    }
+}
+
+/// An image stretched over a geographic extent of the map.
+#[derive(Clone, PartialEq)]
+pub struct MapOverlay {
+    pub url: BlobUrl,
+    pub bounds: Bounds,
+}
+
+enum MapKind {
+    Leaflet(leaflet::Map),
+    OpenLayers(openlayers::Map),
+}
+
+struct BaseMap {
+    kind: MapKind,
+    listeners: Vec<Closure<dyn FnMut(JsValue)>>,
+    /// OpenLayers group holding the layers of each kind. Leaflet uses panes instead.
+    groups: HashMap<LayerKind, openlayers::LayerGroup>,
+}
+
+#[derive(Clone, Copy)]
+enum MapEvent {
+    Click,
+    PointerMove,
+}
+
+/// A layer added on top of the basemap, removed with [`MapLayer::remove`].
+enum MapLayer {
+    Leaflet(leaflet::Layer),
+    OpenLayers(openlayers::Collection, JsValue),
+}
+
+impl MapLayer {
+    fn remove(&self) {
+        match self {
+            MapLayer::Leaflet(layer) => layer.remove_layer(),
+            MapLayer::OpenLayers(group, layer) => group.remove(layer),
+        }
+    }
+}
+
+impl BaseMap {
+    fn new(renderer: MapRenderer, element: &HtmlElement) -> Self {
+        match renderer {
+            MapRenderer::Leaflet => {
+                let map = leaflet::new_map(
+                    element,
+                    &js_object(&[
+                        ("center", Array::of2(&20.0.into(), &0.0.into()).into()),
+                        ("zoom", 2.into()),
+                    ]),
+                );
+                for kind in LayerKind::ALL {
+                    map.create_pane(kind.pane());
+                }
+
+                BaseMap {
+                    kind: MapKind::Leaflet(map),
+                    listeners: vec![],
+                    groups: HashMap::new(),
+                }
+            }
+            MapRenderer::OpenLayers => {
+                let view = openlayers::View::new(&js_object(&[
+                    (
+                        "center",
+                        openlayers::from_lon_lat(&Array::of2(&0.0.into(), &20.0.into())),
+                    ),
+                    ("zoom", 2.into()),
+                ]));
+                let map = openlayers::Map::new(&js_object(&[
+                    ("target", element.into()),
+                    ("view", view.into()),
+                ]));
+                let groups = LayerKind::ALL
+                    .into_iter()
+                    .map(|kind| {
+                        let group = openlayers::LayerGroup::new(&js_object(&[]));
+                        map.add_layer(&group);
+                        (kind, group)
+                    })
+                    .collect();
+
+                BaseMap {
+                    kind: MapKind::OpenLayers(map),
+                    listeners: vec![],
+                    groups,
+                }
+            }
+        }
+    }
+
+    /// Reports the position of every `event` on the map to `callback`.
+    fn listen(&mut self, event: MapEvent, callback: Callback<LatLng>) {
+        let listener = match &self.kind {
+            MapKind::Leaflet(map) => {
+                let listener = Closure::new(move |event: JsValue| {
+                    let latlng = event.unchecked_into::<leaflet::MouseEvent>().latlng();
+                    callback.emit(LatLng {
+                        lat: latlng.lat(),
+                        lng: latlng.lng(),
+                    });
+                });
+                let name = match event {
+                    MapEvent::Click => "click",
+                    MapEvent::PointerMove => "mousemove",
+                };
+                map.on(name, &listener);
+                listener
+            }
+            MapKind::OpenLayers(map) => {
+                let listener = Closure::new(move |event: JsValue| {
+                    let coordinate = event
+                        .unchecked_into::<openlayers::MapBrowserEvent>()
+                        .coordinate();
+                    let lon_lat = openlayers::to_lon_lat(&coordinate);
+                    callback.emit(LatLng {
+                        lat: lon_lat.get(1).as_f64().unwrap_or_default(),
+                        lng: lon_lat.get(0).as_f64().unwrap_or_default(),
+                    });
+                });
+                let name = match event {
+                    MapEvent::Click => "click",
+                    MapEvent::PointerMove => "pointermove",
+                };
+                map.on(name, &listener);
+                listener
+            }
+        };
+        self.listeners.push(listener);
+    }
+
+    /// Adds `layer` to the OpenLayers group of `kind`.
+    fn add_to_group(&self, kind: LayerKind, layer: JsValue) -> MapLayer {
+        let group = self.groups[&kind].get_layers();
+        group.push(&layer);
+        MapLayer::OpenLayers(group, layer)
+    }
+
+    /// Applies the order, visibility and opacity of `stack` to the panes or groups.
+    fn arrange(&self, stack: &LayerStack) {
+        for (i, layer) in stack.layers.iter().enumerate() {
+            match &self.kind {
+                MapKind::Leaflet(map) => {
+                    let style = map.get_pane(layer.kind.pane()).style();
+                    // Leaflet's own panes start at 200 for tiles and 600 for markers.
+                    let _ = style.set_property("z-index", &(200 + 10 * i).to_string());
+                    let _ = style.set_property("opacity", &layer.opacity.to_string());
+                    let _ = style.set_property("display", if layer.visible { "" } else { "none" });
+                }
+                MapKind::OpenLayers(_) => {
+                    let group = &self.groups[&layer.kind];
+                    group.set_z_index(i as i32);
+                    group.set_opacity(layer.opacity);
+                    group.set_visible(layer.visible);
+                }
+            }
+        }
+    }
+
+    fn show_basemap(&self, source: &TileSource) -> MapLayer {
+        match &self.kind {
+            MapKind::Leaflet(map) => {
+                let options = js_object(&[
+                    ("pane", LayerKind::Basemap.pane().into()),
+                    ("maxZoom", 19.into()),
+                    ("attribution", source.name.as_str().into()),
+                ]);
+                let layer = match &source.kind {
+                    SourceKind::Xyz { url_template } => {
+                        leaflet::tile_layer(&source.authorized(url_template), &options)
+                    }
+                    SourceKind::Wms { url, layers, crs } => {
+                        let crs = leaflet::CRS
+                            .with(|all| Reflect::get(all, &crs.replace(':', "").into()))
+                            .unwrap_or(JsValue::UNDEFINED);
+                        Reflect::set(&options, &"layers".into(), &layers.as_str().into()).unwrap();
+                        Reflect::set(&options, &"version".into(), &"1.3.0".into()).unwrap();
+                        if !crs.is_undefined() {
+                            Reflect::set(&options, &"crs".into(), &crs).unwrap();
+                        }
+                        leaflet::tile_layer_wms(&source.authorized(url), &options)
+                    }
+                };
+                MapLayer::Leaflet(layer.add_to(map))
+            }
+            MapKind::OpenLayers(_) => {
+                let tile_source: JsValue = match &source.kind {
+                    SourceKind::Xyz { url_template } => openlayers::XyzSource::new(&js_object(&[
+                        ("url", source.authorized(url_template).into()),
+                        ("attributions", source.name.as_str().into()),
+                    ]))
+                    .into(),
+                    SourceKind::Wms { url, layers, crs } => {
+                        openlayers::TileWmsSource::new(&js_object(&[
+                            ("url", source.authorized(url).into()),
+                            (
+                                "params",
+                                js_object(&[
+                                    ("LAYERS", layers.as_str().into()),
+                                    ("TILED", true.into()),
+                                ]),
+                            ),
+                            ("projection", crs.as_str().into()),
+                            ("attributions", source.name.as_str().into()),
+                        ]))
+                        .into()
+                    }
+                };
+                let layer = openlayers::TileLayer::new(&js_object(&[("source", tile_source)]));
+                self.add_to_group(LayerKind::Basemap, layer.into())
+            }
+        }
+    }
+
+    fn show_image(&self, kind: LayerKind, overlay: &MapOverlay) -> MapLayer {
+        match &self.kind {
+            MapKind::Leaflet(map) => MapLayer::Leaflet(
+                leaflet::image_overlay(
+                    &overlay.url,
+                    &leaflet_bounds(overlay.bounds),
+                    &js_object(&[("pane", kind.pane().into())]),
+                )
+                .add_to(map),
+            ),
+            MapKind::OpenLayers(_) => {
+                let source = openlayers::ImageStaticSource::new(&js_object(&[
+                    ("url", (*overlay.url).into()),
+                    ("imageExtent", mercator_extent(overlay.bounds)),
+                    ("projection", "EPSG:3857".into()),
+                ]));
+                let layer = openlayers::ImageLayer::new(&js_object(&[("source", source.into())]));
+                self.add_to_group(kind, layer.into())
+            }
+        }
+    }
+
+    fn fit(&self, bounds: Bounds) {
+        match &self.kind {
+            MapKind::Leaflet(map) => map.fit_bounds(&leaflet_bounds(bounds)),
+            MapKind::OpenLayers(map) => map.get_view().fit(&mercator_extent(bounds)),
+        }
+    }
+
+    fn show_polygon(&self, points: &[LatLng]) -> MapLayer {
+        match &self.kind {
+            MapKind::Leaflet(map) => {
+                let latlngs: Array = points
+                    .iter()
+                    .map(|p| Array::of2(&p.lat.into(), &p.lng.into()))
+                    .collect();
+                MapLayer::Leaflet(
+                    leaflet::polygon(
+                        &latlngs,
+                        &js_object(&[
+                            ("pane", LayerKind::Aoi.pane().into()),
+                            ("color", "#ffc107".into()),
+                        ]),
+                    )
+                    .add_to(map),
+                )
+            }
+            MapKind::OpenLayers(_) => {
+                let ring: Array = points
+                    .iter()
+                    .chain(points.first())
+                    .map(|p| openlayers::from_lon_lat(&Array::of2(&p.lng.into(), &p.lat.into())))
+                    .collect();
+                let feature =
+                    openlayers::Feature::new(&openlayers::Polygon::new(&Array::of1(&ring)));
+                let source = openlayers::VectorSource::new(&js_object(&[(
+                    "features",
+                    Array::of1(&feature).into(),
+                )]));
+                let layer = openlayers::VectorLayer::new(&js_object(&[("source", source.into())]));
+                self.add_to_group(LayerKind::Aoi, layer.into())
+            }
+        }
+    }
+
+    fn remove(&self) {
+        match &self.kind {
+            MapKind::Leaflet(map) => map.remove(),
+            MapKind::OpenLayers(map) => map.set_target(&JsValue::UNDEFINED),
+        }
+    }
+}
+
+/// `[[south, west], [north, east]]`, the shape Leaflet expects.
+fn leaflet_bounds(bounds: Bounds) -> JsValue {
+    Array::of2(
+        &Array::of2(&bounds.south.into(), &bounds.west.into()),
+        &Array::of2(&bounds.north.into(), &bounds.east.into()),
+    )
+    .into()
+}
+
+/// `[min_x, min_y, max_x, max_y]` in EPSG:3857, the shape OpenLayers expects.
+fn mercator_extent(bounds: Bounds) -> JsValue {
+    let (min_x, min_y) = lat_lng_to_mercator(LatLng {
+        lat: bounds.south,
+        lng: bounds.west,
+    });
+    let (max_x, max_y) = lat_lng_to_mercator(LatLng {
+        lat: bounds.north,
+        lng: bounds.east,
+    });
+    [min_x, min_y, max_x, max_y]
+        .into_iter()
+        .map(JsValue::from)
+        .collect::<Array>()
+        .into()
+}
+
+fn labeled_input(label: &str, input_type: &'static str, value: &UseStateHandle<String>) -> Html {
+    let onchange = {
+        shadow_clone!(value);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            value.set(input.value());
+        }
+    };
+    html! {
+        <div class="input-group input-group-sm mb-1">
+            <span class="input-group-text" style="width: 8em;">{label}</span>
+            <input type={input_type} class="form-control" value={(**value).clone()} {onchange} />
+        </div>
+    }
+}
+
+fn js_object(entries: &[(&str, JsValue)]) -> JsValue {
+    let object = Object::new();
+    for (key, value) in entries {
+        Reflect::set(&object, &(*key).into(), value).unwrap();
+    }
+    object.into()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum AoiShape {
+    Rectangle,
+    Polygon,
+}
+
+/// Area of interest being drawn on the map by clicking its vertices.
+#[derive(Clone, PartialEq, Default)]
+struct AoiDraft {
+    drawing: Option<AoiShape>,
+    points: Vec<LatLng>,
+}
+
+enum AoiAction {
+    Start(AoiShape),
+    Click(LatLng),
+    Finish,
+    Load(Vec<LatLng>),
+}
+
+impl Reducible for AoiDraft {
+    type Action = AoiAction;
+
+    fn reduce(self: Rc<Self>, action: Self::Action) -> Rc<Self> {
+        let mut draft = (*self).clone();
+        match action {
+            AoiAction::Start(shape) => {
+                draft.drawing = Some(shape);
+                draft.points.clear();
+            }
+            AoiAction::Click(point) => match draft.drawing {
+                None => return self,
+                Some(AoiShape::Rectangle) if draft.points.len() == 1 => {
+                    let corner = draft.points[0];
+                    draft.points = vec![
+                        corner,
+                        LatLng {
+                            lat: corner.lat,
+                            lng: point.lng,
+                        },
+                        point,
+                        LatLng {
+                            lat: point.lat,
+                            lng: corner.lng,
+                        },
+                    ];
+                    draft.drawing = None;
+                }
+                Some(_) => draft.points.push(point),
+            },
+            AoiAction::Finish => {
+                draft.drawing = None;
+                if draft.points.len() < 3 {
+                    draft.points.clear();
+                }
+            }
+            AoiAction::Load(points) => {
+                draft.drawing = None;
+                draft.points = points;
+            }
+        }
+        draft.into()
+    }
+}
+
+impl AoiDraft {
+    fn area(&self) -> Option<&[LatLng]> {
+        (self.drawing.is_none() && self.points.len() >= 3).then_some(&self.points)
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        self.area().map(Bounds::enclosing)
+    }
+}
+
+#[autoprops_component(MapPane)]
+pub fn map_pane(
+    #[prop_or_default] onupload: Callback<Rc<Option<FileDetails>>>,
+    sources: Rc<Vec<TileSource>>,
+    #[prop_or_default] onsourceschange: Callback<Rc<Vec<TileSource>>>,
+    #[prop_or_default] image: Option<MapOverlay>,
+    #[prop_or_default] mask: Option<MapOverlay>,
+    /// The ground truth of `mask`, in its own colours.
+    #[prop_or_default]
+    truth: Option<MapOverlay>,
+    /// The errors of `mask` against its ground truth.
+    #[prop_or_default]
+    errors: Option<MapOverlay>,
+    #[prop_or_default] georef: Option<Georeference>,
+) -> Html {
+    let language = use_language();
+    let container = use_node_ref();
+    let renderer = use_state(MapRenderer::build_default);
+    let basemap = use_state(|| 0usize);
+    let imagery = use_state(|| 0usize);
+    let basemap_source = sources.get(*basemap).or(sources.first()).cloned();
+    let imagery_source = sources.get(*imagery).or(sources.first()).cloned();
+    let map = use_mut_ref(|| None::<BaseMap>);
+    let aoi = use_reducer(AoiDraft::default);
+    let zoom = use_state(|| 16u8);
+    let fetching = use_state(|| false);
+    let fetch_error = use_state(|| None::<String>);
+    let settings = use_context::<Rc<Settings>>().unwrap_or_default();
+    let layers = use_reducer(|| LayerStack::new(settings.mask_opacity));
+    let pointer = use_state(|| None::<LatLng>);
+    // Whether the ground-truth layer shows the errors of the mask rather than the truth.
+    let show_errors = use_state(|| true);
+    let truth_layer = if *show_errors {
+        errors.clone()
+    } else {
+        truth.clone()
+    };
+
+    let mask_opacity_by = |step: f64| {
+        shadow_clone!(layers);
+        Callback::from(move |_| {
+            if let Some(mask) = layers.layers.iter().find(|l| l.kind == LayerKind::Mask) {
+                layers.dispatch(LayerAction::Opacity(LayerKind::Mask, mask.opacity + step));
+            }
+        })
+    };
+    use_shortcut(&["m", "M"], "shortcut-toggle-mask", {
+        shadow_clone!(layers);
+        Callback::from(move |_| layers.dispatch(LayerAction::Toggle(LayerKind::Mask)))
+    });
+    use_shortcut(
+        &["+", "="],
+        "shortcut-mask-opacity-up",
+        mask_opacity_by(0.1),
+    );
+    use_shortcut(&["-"], "shortcut-mask-opacity-down", mask_opacity_by(-0.1));
+
+    {
+        shadow_clone!(container, map, aoi, pointer);
+        use_effect_with(*renderer, move |renderer| {
+            let element = container.cast::<HtmlElement>().unwrap();
+            let mut new_map = BaseMap::new(*renderer, &element);
+            new_map.listen(
+                MapEvent::Click,
+                Callback::from(move |point| aoi.dispatch(AoiAction::Click(point))),
+            );
+            new_map.listen(
+                MapEvent::PointerMove,
+                Callback::from(move |point| pointer.set(Some(point))),
+            );
+            *map.borrow_mut() = Some(new_map);
+
+            move || {
+                if let Some(map) = map.borrow_mut().take() {
+                    map.remove();
+                }
+            }
+        });
+    }
+
+    {
+        shadow_clone!(map);
+        use_effect_with((*renderer, basemap_source), move |(_, source)| {
+            let layer = match (&*map.borrow(), source) {
+                (Some(map), Some(source)) => Some(map.show_basemap(source)),
+                _ => None,
+            };
+
+            move || {
+                if let Some(layer) = layer {
+                    layer.remove();
+                }
+            }
+        });
+    }
+
+    {
+        shadow_clone!(map);
+        use_effect_with((*renderer, aoi.points.clone()), move |(_, points)| {
+            let layer = map.borrow().as_ref().map(|map| map.show_polygon(points));
+
+            move || {
+                if let Some(layer) = layer {
+                    layer.remove();
+                }
+            }
+        });
+    }
+
+    {
+        shadow_clone!(map);
+        use_effect_with((*renderer, image.clone()), move |(_, overlay)| {
+            let layer = match (&*map.borrow(), overlay) {
+                (Some(map), Some(overlay)) => Some(map.show_image(LayerKind::Image, overlay)),
+                _ => None,
+            };
+
+            move || {
+                if let Some(layer) = layer {
+                    layer.remove();
+                }
+            }
+        });
+    }
+
+    {
+        shadow_clone!(map);
+        use_effect_with((*renderer, mask.clone()), move |(_, overlay)| {
+            let layer = match (&*map.borrow(), overlay) {
+                (Some(map), Some(overlay)) => Some(map.show_image(LayerKind::Mask, overlay)),
+                _ => None,
+            };
+
+            move || {
+                if let Some(layer) = layer {
+                    layer.remove();
+                }
+            }
+        });
+    }
+
+    {
+        shadow_clone!(map);
+        use_effect_with((*renderer, truth_layer), move |(_, overlay)| {
+            let layer = match (&*map.borrow(), overlay) {
+                (Some(map), Some(overlay)) => Some(map.show_image(LayerKind::GroundTruth, overlay)),
+                _ => None,
+            };
+
+            move || {
+                if let Some(layer) = layer {
+                    layer.remove();
+                }
+            }
+        });
+    }
+
+    {
+        shadow_clone!(map);
+        use_effect_with(
+            (
+                *renderer,
+                image.as_ref().or(mask.as_ref()).map(|o| o.bounds),
+            ),
+            move |(_, bounds)| {
+                if let (Some(map), Some(bounds)) = (&*map.borrow(), bounds) {
+                    map.fit(*bounds);
+                }
+            },
+        );
+    }
+
+    {
+        shadow_clone!(map);
+        use_effect_with((*renderer, (*layers).clone()), move |(_, layers)| {
+            if let Some(map) = &*map.borrow() {
+                map.arrange(layers);
+            }
+        });
+    }
+
+    let onchange = {
+        shadow_clone!(renderer);
+        move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(selected) = MapRenderer::ALL
+                .into_iter()
+                .find(|r| r.name() == select.value())
+            {
+                renderer.set(selected);
+            }
+        }
+    };
+
+    let draw_button = |shape: AoiShape, title: &str| {
+        let onclick = {
+            shadow_clone!(aoi);
+            move |_| aoi.dispatch(AoiAction::Start(shape))
+        };
+        html! {
+            <button
+                class={classes!("btn", "btn-outline-warning", (aoi.drawing == Some(shape)).then_some("active"))}
+                {onclick}
+            >
+                {title}
+            </button>
+        }
+    };
+
+    let onfinish = {
+        shadow_clone!(aoi);
+        move |_| aoi.dispatch(AoiAction::Finish)
+    };
+
+    let onzoom = {
+        shadow_clone!(zoom);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(value) = input.value().parse::<u8>() {
+                zoom.set(value.clamp(1, 19));
+            }
+        }
+    };
+
+    let source_select = |selected: &UseStateHandle<usize>| {
+        let onchange = {
+            shadow_clone!(selected);
+            move |e: Event| {
+                let select: HtmlSelectElement = e.target_unchecked_into();
+                if let Ok(index) = select.value().parse() {
+                    selected.set(index);
+                }
+            }
+        };
+        html! {
+            <select class="form-select" {onchange}>
+            {
+                for sources.iter().enumerate().map(|(i, source)| html! {
+                    <option value={i.to_string()} selected={i == **selected}>{&source.name}</option>
+                })
+            }
+            </select>
+        }
+    };
+
+    let onplace = {
+        shadow_clone!(map);
+        move |bounds| {
+            if let Some(map) = &*map.borrow() {
+                map.fit(bounds);
+            }
+        }
+    };
+
+    let onsavedaoi = {
+        shadow_clone!(map, aoi);
+        move |saved: Rc<SavedAoi>| {
+            aoi.dispatch(AoiAction::Load(saved.points.clone()));
+            if let Some(map) = &*map.borrow() {
+                map.fit(Bounds::enclosing(&saved.points));
+            }
+        }
+    };
+
+    let onlayer = {
+        shadow_clone!(layers);
+        move |action| layers.dispatch(action)
+    };
+
+    let onsegment = {
+        shadow_clone!(aoi, zoom, fetching, fetch_error, onupload, imagery_source);
+        move |_| {
+            let (Some(area), Some(source)) =
+                (aoi.area().map(<[LatLng]>::to_vec), imagery_source.clone())
+            else {
+                return;
+            };
+            let zoom = *zoom;
+            fetching.set(true);
+            fetch_error.set(None);
+            shadow_clone!(fetching, fetch_error, onupload);
+            spawn_local(async move {
+                match tiles::fetch_area(&source, &area, zoom).await {
+                    Ok((data, georef)) => onupload.emit(Rc::new(Some(FileDetails {
+                        file_name: format!("aoi-z{zoom}.png"),
+                        file_type: "image/png".to_string(),
+                        data: data.into(),
+                        georef: Some(georef),
+                        auxiliary: None,
+                        source: None,
+                    }))),
+                    Err(why) => fetch_error.set(Some(why)),
+                }
+                fetching.set(false);
+            });
+        }
+    };
+
+    html!(
+        <>
+            <div class="d-flex gap-2 mb-2">
+                <select class="form-select" {onchange}>
+                {
+                    for MapRenderer::ALL.into_iter().map(|r| html! {
+                        <option value={r.name()} selected={r == *renderer}>{r.name()}</option>
+                    })
+                }
+                </select>
+                <div class="input-group">
+                    <span class="input-group-text">{language.t("map-basemap")}</span>
+                    {source_select(&basemap)}
+                </div>
+                <div class="input-group">
+                    <span class="input-group-text">{language.t("map-aoi-imagery")}</span>
+                    {source_select(&imagery)}
+                </div>
+            </div>
+            <GeocoderSearch onselect={onplace} />
+            <div class="d-flex gap-2 mb-2">
+                <div class="btn-group">
+                    {draw_button(AoiShape::Rectangle, &language.t("map-draw-box"))}
+                    {draw_button(AoiShape::Polygon, &language.t("map-draw-polygon"))}
+                </div>
+                if aoi.drawing == Some(AoiShape::Polygon) {
+                    <button class="btn btn-outline-secondary" onclick={onfinish}>{language.t("map-finish")}</button>
+                }
+                <div class="input-group" style="width: 10em;">
+                    <span class="input-group-text">{language.t("map-zoom")}</span>
+                    <input
+                        type="number"
+                        class="form-control"
+                        min="1"
+                        max="19"
+                        value={zoom.to_string()}
+                        onchange={onzoom}
+                    />
+                </div>
+                <button
+                    class="btn btn-success"
+                    disabled={aoi.area().is_none() || *fetching}
+                    onclick={onsegment}
+                >
+                    {language.t("map-segment-area")}
+                    if *fetching {
+                        {" "}<span class="spinner-border spinner-border-sm"></span>
+                    }
+                </button>
+            </div>
+            if let Some(why) = &*fetch_error {
+                <div class="alert alert-danger" role="alert">{why}</div>
+            }
+            // Keyed by renderer so each library starts from a fresh container.
+            <div>
+                <div key={renderer.name()} ref={container} style="height: 600px;"></div>
+            </div>
+            <small class="font-monospace text-body-secondary">
+            {
+                match *pointer {
+                    Some(point) => describe_position(point, georef.as_ref()),
+                    None => language.t("map-hover"),
+                }
+            }
+            </small>
+            <details class="mt-2" open=true>
+                <summary>{language.t("map-layers")}</summary>
+                <LayersPane layers={(*layers).clone()} onaction={onlayer} />
+                if truth.is_some() || errors.is_some() {
+                    <div class="btn-group btn-group-sm mt-2" role="group" aria-label={language.t("map-truth-layer")}>
+                        {for [(false, "layer-ground-truth"), (true, "map-error-map")].into_iter().map(|(option, label)| {
+                            let onclick = {
+                                shadow_clone!(show_errors);
+                                move |_| show_errors.set(option)
+                            };
+                            html! {
+                                <button
+                                    class={classes!("btn", "btn-outline-secondary", (*show_errors == option).then_some("active"))}
+                                    aria-pressed={(*show_errors == option).to_string()}
+                                    {onclick}
+                                >
+                                    {language.t(label)}
+                                </button>
+                            }
+                        })}
+                    </div>
+                    if *show_errors {
+                        <ErrorLegend />
+                    }
+                }
+            </details>
+            <details class="mt-2">
+                <summary>{language.t("map-saved-aois")}</summary>
+                <SavedAoisPane
+                    area={aoi.area().map(|area| Rc::new(area.to_vec()))}
+                    select_label={language.t("map-use-aoi")}
+                    onselect={onsavedaoi}
+                />
+            </details>
+            <details class="mt-2">
+                <summary>{language.t("map-stac")}</summary>
+                <StacBrowser area={aoi.bounds()} onupload={onupload.clone()} />
+            </details>
+            <details class="mt-2">
+                <summary>{language.t("map-sources")}</summary>
+                <SourcesPane sources={sources.clone()} onchange={onsourceschange} />
+            </details>
+        </>
+    )
+}
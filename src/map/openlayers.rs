@@ -0,0 +1,133 @@
+//! Minimal bindings to the subset of the OpenLayers API loaded from `index.html`.
+
+use js_sys::Array;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ol)]
+    #[derive(Clone)]
+    pub type Map;
+
+    #[wasm_bindgen(constructor, js_namespace = ol)]
+    pub fn new(options: &JsValue) -> Map;
+
+    #[wasm_bindgen(method, js_name = setTarget)]
+    pub fn set_target(this: &Map, target: &JsValue);
+
+    #[wasm_bindgen(method)]
+    pub fn on(this: &Map, event: &str, handler: &Closure<dyn FnMut(JsValue)>);
+
+    #[wasm_bindgen(method, js_name = addLayer)]
+    pub fn add_layer(this: &Map, layer: &JsValue);
+
+    #[wasm_bindgen(method, js_name = removeLayer)]
+    pub fn remove_layer(this: &Map, layer: &JsValue);
+
+    #[wasm_bindgen(method, js_name = getView)]
+    pub fn get_view(this: &Map) -> View;
+
+    #[wasm_bindgen(js_namespace = ol)]
+    pub type View;
+
+    #[wasm_bindgen(constructor, js_namespace = ol)]
+    pub fn new(options: &JsValue) -> View;
+
+    #[wasm_bindgen(method)]
+    pub fn fit(this: &View, extent: &JsValue);
+
+    #[wasm_bindgen(js_namespace = ["ol", "layer"], js_name = Tile)]
+    pub type TileLayer;
+
+    #[wasm_bindgen(constructor, js_namespace = ["ol", "layer"], js_class = "Tile")]
+    pub fn new(options: &JsValue) -> TileLayer;
+
+    #[wasm_bindgen(js_namespace = ["ol", "layer"], js_name = Group)]
+    #[derive(Clone)]
+    pub type LayerGroup;
+
+    #[wasm_bindgen(constructor, js_namespace = ["ol", "layer"], js_class = "Group")]
+    pub fn new(options: &JsValue) -> LayerGroup;
+
+    #[wasm_bindgen(method, js_name = getLayers)]
+    pub fn get_layers(this: &LayerGroup) -> Collection;
+
+    #[wasm_bindgen(method, js_name = setZIndex)]
+    pub fn set_z_index(this: &LayerGroup, z_index: i32);
+
+    #[wasm_bindgen(method, js_name = setOpacity)]
+    pub fn set_opacity(this: &LayerGroup, opacity: f64);
+
+    #[wasm_bindgen(method, js_name = setVisible)]
+    pub fn set_visible(this: &LayerGroup, visible: bool);
+
+    #[wasm_bindgen(js_namespace = ol)]
+    #[derive(Clone)]
+    pub type Collection;
+
+    #[wasm_bindgen(method)]
+    pub fn push(this: &Collection, element: &JsValue);
+
+    #[wasm_bindgen(method)]
+    pub fn remove(this: &Collection, element: &JsValue);
+
+    #[wasm_bindgen(js_namespace = ["ol", "layer"], js_name = Vector)]
+    #[derive(Clone)]
+    pub type VectorLayer;
+
+    #[wasm_bindgen(constructor, js_namespace = ["ol", "layer"], js_class = "Vector")]
+    pub fn new(options: &JsValue) -> VectorLayer;
+
+    #[wasm_bindgen(js_namespace = ["ol", "layer"], js_name = Image)]
+    pub type ImageLayer;
+
+    #[wasm_bindgen(constructor, js_namespace = ["ol", "layer"], js_class = "Image")]
+    pub fn new(options: &JsValue) -> ImageLayer;
+
+    #[wasm_bindgen(js_namespace = ["ol", "source"], js_name = ImageStatic)]
+    pub type ImageStaticSource;
+
+    #[wasm_bindgen(constructor, js_namespace = ["ol", "source"], js_class = "ImageStatic")]
+    pub fn new(options: &JsValue) -> ImageStaticSource;
+
+    #[wasm_bindgen(js_namespace = ["ol", "source"], js_name = XYZ)]
+    pub type XyzSource;
+
+    #[wasm_bindgen(constructor, js_namespace = ["ol", "source"], js_class = "XYZ")]
+    pub fn new(options: &JsValue) -> XyzSource;
+
+    #[wasm_bindgen(js_namespace = ["ol", "source"], js_name = TileWMS)]
+    pub type TileWmsSource;
+
+    #[wasm_bindgen(constructor, js_namespace = ["ol", "source"], js_class = "TileWMS")]
+    pub fn new(options: &JsValue) -> TileWmsSource;
+
+    #[wasm_bindgen(js_namespace = ["ol", "source"], js_name = Vector)]
+    pub type VectorSource;
+
+    #[wasm_bindgen(constructor, js_namespace = ["ol", "source"], js_class = "Vector")]
+    pub fn new(options: &JsValue) -> VectorSource;
+
+    #[wasm_bindgen(js_namespace = ol)]
+    pub type Feature;
+
+    #[wasm_bindgen(constructor, js_namespace = ol)]
+    pub fn new(geometry: &JsValue) -> Feature;
+
+    #[wasm_bindgen(js_namespace = ["ol", "geom"], js_name = Polygon)]
+    pub type Polygon;
+
+    #[wasm_bindgen(constructor, js_namespace = ["ol", "geom"], js_class = "Polygon")]
+    pub fn new(rings: &JsValue) -> Polygon;
+
+    pub type MapBrowserEvent;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn coordinate(this: &MapBrowserEvent) -> JsValue;
+
+    #[wasm_bindgen(js_namespace = ["ol", "proj"], js_name = fromLonLat)]
+    pub fn from_lon_lat(coordinate: &JsValue) -> JsValue;
+
+    #[wasm_bindgen(js_namespace = ["ol", "proj"], js_name = toLonLat)]
+    pub fn to_lon_lat(coordinate: &JsValue) -> Array;
+}
@@ -0,0 +1,211 @@
+//! User-registered imagery sources used as basemaps and for fetching AOI imagery.
+
+use serde::{Deserialize, Serialize};
+use shadow_clone::shadow_clone;
+use std::rc::Rc;
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use super::labeled_input;
+use crate::{
+    geo::{mercator_to_lat_lng, MERCATOR_EXTENT},
+    i18n::use_language,
+};
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SourceKind {
+    /// Template containing `{z}`, `{x}` and `{y}` placeholders.
+    Xyz { url_template: String },
+    Wms {
+        url: String,
+        layers: String,
+        crs: String,
+    },
+}
+
+/// CRSs WMS tiles can be asked for in, as their bounding boxes are only worked out in
+/// these two.
+pub const WMS_CRSS: [&str; 2] = ["EPSG:3857", "EPSG:4326"];
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct TileSource {
+    pub name: String,
+    pub kind: SourceKind,
+    /// Query parameter name and value carrying an API key, appended to every request.
+    pub auth: Option<(String, String)>,
+}
+
+impl TileSource {
+    pub fn osm() -> Self {
+        TileSource {
+            name: "OpenStreetMap".to_string(),
+            kind: SourceKind::Xyz {
+                url_template: "https://tile.openstreetmap.org/{z}/{x}/{y}.png".to_string(),
+            },
+            auth: None,
+        }
+    }
+
+    /// `url` with the auth parameter appended, if any.
+    pub fn authorized(&self, url: &str) -> String {
+        match &self.auth {
+            Some((param, key)) => {
+                let separator = if url.contains('?') { '&' } else { '?' };
+                format!("{url}{separator}{param}={key}")
+            }
+            None => url.to_string(),
+        }
+    }
+
+    /// URL of the 256px tile at `x`, `y` in the Web Mercator tile grid.
+    pub fn tile_url(&self, x: u32, y: u32, zoom: u8) -> String {
+        match &self.kind {
+            SourceKind::Xyz { url_template } => self.authorized(
+                &url_template
+                    .replace("{z}", &zoom.to_string())
+                    .replace("{x}", &x.to_string())
+                    .replace("{y}", &y.to_string()),
+            ),
+            SourceKind::Wms { url, layers, crs } => {
+                let tile_size = 2.0 * MERCATOR_EXTENT / (1u32 << zoom) as f64;
+                let min_x = -MERCATOR_EXTENT + x as f64 * tile_size;
+                let max_y = MERCATOR_EXTENT - y as f64 * tile_size;
+                let (max_x, min_y) = (min_x + tile_size, max_y - tile_size);
+                let bbox = if crs == "EPSG:4326" {
+                    // WMS 1.3.0 uses latitude-first axis order for EPSG:4326.
+                    let south_west = mercator_to_lat_lng(min_x, min_y);
+                    let north_east = mercator_to_lat_lng(max_x, max_y);
+                    format!(
+                        "{},{},{},{}",
+                        south_west.lat, south_west.lng, north_east.lat, north_east.lng
+                    )
+                } else {
+                    format!("{min_x},{min_y},{max_x},{max_y}")
+                };
+                let separator = if url.contains('?') { '&' } else { '?' };
+                self.authorized(&format!(
+                    "{url}{separator}SERVICE=WMS&VERSION=1.3.0&REQUEST=GetMap&LAYERS={layers}&STYLES=&CRS={crs}&BBOX={bbox}&WIDTH=256&HEIGHT=256&FORMAT=image/png"
+                ))
+            }
+        }
+    }
+}
+
+#[autoprops_component(SourcesPane)]
+pub fn sources_pane(sources: Rc<Vec<TileSource>>, onchange: Callback<Rc<Vec<TileSource>>>) -> Html {
+    let language = use_language();
+    let is_wms = use_state(|| false);
+    let name = use_state(String::new);
+    let url = use_state(String::new);
+    let layers = use_state(String::new);
+    let crs = use_state(|| "EPSG:3857".to_string());
+    let key_param = use_state(String::new);
+    let key = use_state(String::new);
+
+    let onkind = {
+        shadow_clone!(is_wms);
+        move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            is_wms.set(select.value() == "wms");
+        }
+    };
+
+    let oncrs = {
+        shadow_clone!(crs);
+        move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            crs.set(select.value());
+        }
+    };
+
+    let onadd = {
+        shadow_clone!(sources, onchange, is_wms, name, url, layers, crs, key_param, key);
+        move |_| {
+            if name.is_empty() || url.is_empty() {
+                return;
+            }
+            let kind = if *is_wms {
+                SourceKind::Wms {
+                    url: (*url).clone(),
+                    layers: (*layers).clone(),
+                    crs: (*crs).clone(),
+                }
+            } else {
+                SourceKind::Xyz {
+                    url_template: (*url).clone(),
+                }
+            };
+            let auth = (!key_param.is_empty()).then(|| ((*key_param).clone(), (*key).clone()));
+            let mut new_sources = (*sources).clone();
+            new_sources.push(TileSource {
+                name: (*name).clone(),
+                kind,
+                auth,
+            });
+            onchange.emit(Rc::new(new_sources));
+            name.set(String::new());
+            url.set(String::new());
+        }
+    };
+
+    html! {
+        <div>
+            <ul class="list-group mb-2">
+            {
+                for sources.iter().enumerate().map(|(i, source)| {
+                    let onremove = {
+                        shadow_clone!(sources, onchange);
+                        move |_| {
+                            let mut new_sources = (*sources).clone();
+                            new_sources.remove(i);
+                            onchange.emit(Rc::new(new_sources));
+                        }
+                    };
+                    let description = match &source.kind {
+                        SourceKind::Xyz { url_template } => format!("XYZ {url_template}"),
+                        SourceKind::Wms { url, layers, crs } => format!("WMS {url} ({layers}, {crs})"),
+                    };
+                    html! {
+                        <li class="list-group-item d-flex justify-content-between align-items-center">
+                            <span>
+                                <strong>{&source.name}</strong>
+                                <small class="text-body-secondary ms-2">{description}</small>
+                            </span>
+                            // The last source cannot be removed so a basemap is always available.
+                            <button
+                                class="btn btn-sm btn-outline-danger"
+                                disabled={sources.len() == 1}
+                                onclick={onremove}
+                            >
+                                {language.t("sources-remove")}
+                            </button>
+                        </li>
+                    }
+                })
+            }
+            </ul>
+            <select class="form-select form-select-sm mb-1" onchange={onkind}>
+                <option value="xyz" selected={!*is_wms}>{language.t("sources-xyz")}</option>
+                <option value="wms" selected={*is_wms}>{"WMS"}</option>
+            </select>
+            {labeled_input(&language.t("sources-name"), "text", &name)}
+            {labeled_input(&language.t(if *is_wms { "sources-endpoint" } else { "sources-url-template" }), "text", &url)}
+            if *is_wms {
+                {labeled_input(&language.t("sources-layers"), "text", &layers)}
+                <div class="input-group input-group-sm mb-1">
+                    <span class="input-group-text" style="width: 8em;">{"CRS"}</span>
+                    <select class="form-select" onchange={oncrs}>
+                        {for WMS_CRSS.iter().map(|option| html! {
+                            <option value={*option} selected={*crs == *option}>{option}</option>
+                        })}
+                    </select>
+                </div>
+            }
+            {labeled_input(&language.t("sources-key-parameter"), "text", &key_param)}
+            {labeled_input(&language.t("sources-key"), "password", &key)}
+            <button class="btn btn-sm btn-primary" onclick={onadd}>{language.t("sources-add")}</button>
+        </div>
+    }
+}
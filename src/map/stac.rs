@@ -0,0 +1,337 @@
+//! Scene search against a STAC API, sending scene previews to segmentation.
+
+use gloo::net::http::Request;
+use image::{DynamicImage, ImageOutputFormat};
+use serde::{Deserialize, Serialize};
+use shadow_clone::shadow_clone;
+use std::{collections::HashMap, io::Cursor, rc::Rc};
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+
+use super::labeled_input;
+use crate::{
+    geo::{Bounds, Georeference},
+    http,
+    i18n::{self, use_language},
+    FileDetails,
+};
+
+const DEFAULT_CATALOG: &str = "https://earth-search.aws.element84.com/v1";
+const DEFAULT_COLLECTION: &str = "sentinel-2-l2a";
+/// Assets tried, in order, as the image sent to segmentation.
+const PREVIEW_ASSETS: [&str; 3] = ["preview", "overview", "thumbnail"];
+
+#[derive(Serialize)]
+struct SearchRequest {
+    collections: Vec<String>,
+    bbox: [f64; 4],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    datetime: Option<String>,
+    limit: u32,
+    query: CloudCoverQuery,
+}
+
+#[derive(Serialize)]
+struct CloudCoverQuery {
+    #[serde(rename = "eo:cloud_cover")]
+    cloud_cover: LessThan,
+}
+
+#[derive(Serialize)]
+struct LessThan {
+    lt: f64,
+}
+
+#[derive(Deserialize)]
+struct ItemCollection {
+    features: Vec<Item>,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Item {
+    id: String,
+    bbox: Vec<f64>,
+    properties: ItemProperties,
+    assets: HashMap<String, Asset>,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct ItemProperties {
+    datetime: Option<String>,
+    #[serde(rename = "eo:cloud_cover")]
+    cloud_cover: Option<f64>,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Asset {
+    href: String,
+    #[serde(rename = "type")]
+    media_type: Option<String>,
+}
+
+impl Item {
+    fn thumbnail(&self) -> Option<&str> {
+        self.assets.get("thumbnail").map(|a| a.href.as_str())
+    }
+
+    /// A browser-decodable rendering of the whole scene.
+    fn preview(&self) -> Option<&Asset> {
+        PREVIEW_ASSETS
+            .iter()
+            .filter_map(|key| self.assets.get(*key))
+            .chain(self.assets.values())
+            .find(|asset| {
+                matches!(
+                    asset.media_type.as_deref(),
+                    Some("image/png" | "image/jpeg" | "image/jpg")
+                )
+            })
+    }
+
+    fn bounds(&self) -> Option<Bounds> {
+        let [west, south, east, north] = self.bbox.get(..4)?.try_into().ok()?;
+        Some(Bounds {
+            south,
+            west,
+            north,
+            east,
+        })
+    }
+}
+
+async fn search(catalog: &str, request: SearchRequest) -> Result<Vec<Item>, String> {
+    let url = format!("{}/search", catalog.trim_end_matches('/'));
+    let response = http::send_json(Request::post(&url), &request, None)
+        .await
+        .map_err(|e| i18n::t_args("stac-search-failed", &[("error", &e)]))?;
+    http::json::<ItemCollection>(response)
+        .await
+        .map(|collection| collection.features)
+        .map_err(|e| i18n::t_args("stac-results-unreadable", &[("error", &e)]))
+}
+
+/// Downloads the scene preview, clipped to `area` when given. Since previews carry no
+/// georeference of their own, the scene's bounding box is assumed to span the whole image.
+async fn fetch_scene(item: &Item, area: Option<Bounds>) -> Result<FileDetails, String> {
+    let preview = item
+        .preview()
+        .ok_or_else(|| i18n::t_args("stac-no-preview", &[("scene", &item.id)]))?;
+    let scene = item
+        .bounds()
+        .ok_or_else(|| i18n::t_args("stac-no-bounds", &[("scene", &item.id)]))?;
+    let response = http::send(Request::get(&preview.href), None)
+        .await
+        .map_err(|e| i18n::t_args("stac-preview-failed", &[("error", &e)]))?;
+    let bytes = http::bytes(response, &Callback::noop())
+        .await
+        .map_err(|e| i18n::t_args("stac-preview-failed", &[("error", &e)]))?;
+    let mut image = image::load_from_memory(&bytes)
+        .map_err(|e| i18n::t_args("stac-preview-undecodable", &[("error", &e)]))?;
+
+    let (width, height) = (image.width() as f64, image.height() as f64);
+    let lng_per_px = (scene.east - scene.west) / width;
+    let lat_per_px = (scene.north - scene.south) / height;
+    let mut window = scene;
+    if let Some(area) = area {
+        let left = ((area.west - scene.west) / lng_per_px)
+            .clamp(0.0, width)
+            .floor();
+        let right = ((area.east - scene.west) / lng_per_px)
+            .clamp(0.0, width)
+            .ceil();
+        let top = ((scene.north - area.north) / lat_per_px)
+            .clamp(0.0, height)
+            .floor();
+        let bottom = ((scene.north - area.south) / lat_per_px)
+            .clamp(0.0, height)
+            .ceil();
+        if right <= left || bottom <= top {
+            return Err(i18n::t("stac-no-overlap"));
+        }
+        image = DynamicImage::ImageRgba8(
+            image
+                .crop_imm(
+                    left as u32,
+                    top as u32,
+                    (right - left) as u32,
+                    (bottom - top) as u32,
+                )
+                .to_rgba8(),
+        );
+        window = Bounds {
+            south: scene.north - bottom * lat_per_px,
+            west: scene.west + left * lng_per_px,
+            north: scene.north - top * lat_per_px,
+            east: scene.west + right * lng_per_px,
+        };
+    }
+
+    let georef = Georeference {
+        epsg: 4326,
+        a: lng_per_px,
+        b: 0.0,
+        c: window.west,
+        d: 0.0,
+        e: -lat_per_px,
+        f: window.north,
+        width: image.width(),
+        height: image.height(),
+    };
+    let mut png = vec![];
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .map_err(|e| i18n::t_args("stac-unencodable", &[("error", &e)]))?;
+
+    Ok(FileDetails {
+        file_name: format!("{}.png", item.id),
+        file_type: "image/png".to_string(),
+        data: png.into(),
+        georef: Some(georef),
+        auxiliary: None,
+        source: None,
+    })
+}
+
+#[autoprops_component(StacBrowser)]
+pub fn stac_browser(
+    area: Option<Bounds>,
+    #[prop_or_default] onupload: Callback<Rc<Option<FileDetails>>>,
+) -> Html {
+    let language = use_language();
+    let catalog = use_state(|| DEFAULT_CATALOG.to_string());
+    let collection = use_state(|| DEFAULT_COLLECTION.to_string());
+    let start = use_state(String::new);
+    let end = use_state(String::new);
+    let max_cloud_cover = use_state(|| "20".to_string());
+    let clip = use_state(|| true);
+    let results = use_state(|| None::<Result<Vec<Item>, String>>);
+    let busy = use_state(|| false);
+
+    let onsearch = {
+        shadow_clone!(
+            catalog,
+            collection,
+            start,
+            end,
+            max_cloud_cover,
+            results,
+            busy
+        );
+        move |_| {
+            let Some(area) = area else {
+                return;
+            };
+            let datetime = match (start.is_empty(), end.is_empty()) {
+                (true, true) => None,
+                (false, true) => Some(format!("{}T00:00:00Z/..", *start)),
+                (true, false) => Some(format!("../{}T23:59:59Z", *end)),
+                (false, false) => Some(format!("{}T00:00:00Z/{}T23:59:59Z", *start, *end)),
+            };
+            let request = SearchRequest {
+                collections: vec![(*collection).clone()],
+                bbox: [area.west, area.south, area.east, area.north],
+                datetime,
+                limit: 10,
+                query: CloudCoverQuery {
+                    cloud_cover: LessThan {
+                        lt: max_cloud_cover.parse().unwrap_or(100.0),
+                    },
+                },
+            };
+            busy.set(true);
+            shadow_clone!(catalog, results, busy);
+            spawn_local(async move {
+                results.set(Some(search(&catalog, request).await));
+                busy.set(false);
+            });
+        }
+    };
+
+    let onclip = {
+        shadow_clone!(clip);
+        move |_| clip.set(!*clip)
+    };
+
+    let scenes = match &*results {
+        None => html!(),
+        Some(Err(why)) => html!(<div class="alert alert-danger" role="alert">{why}</div>),
+        Some(Ok(items)) if items.is_empty() => html!(<p>{language.t("stac-none")}</p>),
+        Some(Ok(items)) => html! {
+            <div class="row row-cols-2 g-2">
+            {
+                for items.iter().map(|item| {
+                    let onsegment = {
+                        shadow_clone!(onupload, results, busy, item);
+                        let area = area.filter(|_| *clip);
+                        move |_| {
+                            busy.set(true);
+                            shadow_clone!(onupload, results, busy, item);
+                            spawn_local(async move {
+                                match fetch_scene(&item, area).await {
+                                    Ok(file) => onupload.emit(Rc::new(Some(file))),
+                                    Err(why) => results.set(Some(Err(why))),
+                                }
+                                busy.set(false);
+                            });
+                        }
+                    };
+                    html! {
+                        <div class="col">
+                            <div class="card h-100">
+                                if let Some(thumbnail) = item.thumbnail() {
+                                    <img
+                                        class="card-img-top"
+                                        src={thumbnail.to_string()}
+                                        alt={language.t_args("stac-thumbnail", &[("scene", &item.id)])}
+                                    />
+                                }
+                                <div class="card-body p-2">
+                                    <small class="d-block text-truncate">{&item.id}</small>
+                                    <small class="d-block text-body-secondary">
+                                        {item.properties.datetime.clone().unwrap_or_default()}
+                                        if let Some(cloud_cover) = item.properties.cloud_cover {
+                                            {", "}{language.t_args("stac-clouds", &[("percent", &format!("{cloud_cover:.0}"))])}
+                                        }
+                                    </small>
+                                    <button
+                                        class="btn btn-sm btn-success mt-1"
+                                        disabled={*busy || item.preview().is_none()}
+                                        onclick={onsegment}
+                                    >
+                                        {language.t("stac-segment")}
+                                    </button>
+                                </div>
+                            </div>
+                        </div>
+                    }
+                })
+            }
+            </div>
+        },
+    };
+
+    html! {
+        <div>
+            {labeled_input(&language.t("stac-catalog"), "url", &catalog)}
+            {labeled_input(&language.t("stac-collection"), "text", &collection)}
+            {labeled_input(&language.t("stac-from"), "date", &start)}
+            {labeled_input(&language.t("stac-to"), "date", &end)}
+            {labeled_input(&language.t("stac-max-clouds"), "number", &max_cloud_cover)}
+            <div class="form-check mb-1">
+                <input class="form-check-input" type="checkbox" checked={*clip} onchange={onclip} />
+                <label class="form-check-label">{language.t("stac-clip")}</label>
+            </div>
+            <button
+                class="btn btn-sm btn-primary mb-2"
+                disabled={area.is_none() || *busy}
+                onclick={onsearch}
+            >
+                {language.t("stac-search")}
+                if *busy {
+                    {" "}<span class="spinner-border spinner-border-sm"></span>
+                }
+            </button>
+            {scenes}
+        </div>
+    }
+}
@@ -0,0 +1,113 @@
+//! Fetching and stitching of XYZ imagery tiles covering an area of interest.
+
+use gloo::net::http::Request;
+use std::rc::Rc;
+
+use super::sources::TileSource;
+use crate::{
+    codec::EncodeJob,
+    geo::{lat_lng_to_mercator, Georeference, LatLng, MERCATOR_EXTENT},
+    http, i18n,
+    workers::encode,
+};
+
+const TILE_SIZE: u32 = 256;
+const MAX_TILES: u32 = 64;
+
+/// Size of a pixel in EPSG:3857 metres at `zoom`.
+fn resolution(zoom: u8) -> f64 {
+    2.0 * MERCATOR_EXTENT / (TILE_SIZE << zoom) as f64
+}
+
+/// Position in Web Mercator pixel space at `zoom`.
+fn to_pixel(point: LatLng, zoom: u8) -> (f64, f64) {
+    let (x, y) = lat_lng_to_mercator(point);
+    let resolution = resolution(zoom);
+    (
+        (x + MERCATOR_EXTENT) / resolution,
+        (MERCATOR_EXTENT - y) / resolution,
+    )
+}
+
+async fn fetch_tile(url: &str) -> Result<Rc<[u8]>, String> {
+    let response = http::send(Request::get(url), None)
+        .await
+        .map_err(|e| i18n::t_args("tiles-failed", &[("url", &url), ("error", &e)]))?;
+    http::bytes(response, &yew::Callback::noop())
+        .await
+        .map(Rc::from)
+        .map_err(|e| i18n::t_args("tiles-failed", &[("url", &url), ("error", &e)]))
+}
+
+/// Downloads the tiles under `area` at `zoom` and returns them stitched into a PNG
+/// cropped to the area's bounding box, along with its EPSG:3857 georeference.
+/// Pixels outside the polygon are blacked out. Tiles are decoded and stitched in a worker.
+pub async fn fetch_area(
+    source: &TileSource,
+    area: &[LatLng],
+    zoom: u8,
+) -> Result<(Vec<u8>, Georeference), String> {
+    let polygon: Vec<_> = area.iter().map(|p| to_pixel(*p, zoom)).collect();
+    let min_x = polygon
+        .iter()
+        .map(|p| p.0)
+        .fold(f64::INFINITY, f64::min)
+        .floor() as u32;
+    let min_y = polygon
+        .iter()
+        .map(|p| p.1)
+        .fold(f64::INFINITY, f64::min)
+        .floor() as u32;
+    let max_x = polygon.iter().map(|p| p.0).fold(0.0, f64::max).ceil() as u32;
+    let max_y = polygon.iter().map(|p| p.1).fold(0.0, f64::max).ceil() as u32;
+    if max_x <= min_x || max_y <= min_y {
+        return Err(i18n::t("tiles-empty-area"));
+    }
+
+    let tiles_x = min_x / TILE_SIZE..=(max_x - 1) / TILE_SIZE;
+    let tiles_y = min_y / TILE_SIZE..=(max_y - 1) / TILE_SIZE;
+    let tile_count = tiles_x.clone().count() as u32 * tiles_y.clone().count() as u32;
+    if tile_count > MAX_TILES {
+        return Err(i18n::t_args(
+            "tiles-too-many",
+            &[("count", &tile_count), ("zoom", &zoom), ("max", &MAX_TILES)],
+        ));
+    }
+
+    let mut tiles = vec![];
+    for ty in tiles_y {
+        for tx in tiles_x.clone() {
+            tiles.push((
+                fetch_tile(&source.tile_url(tx, ty, zoom)).await?,
+                (tx * TILE_SIZE) as i64 - min_x as i64,
+                (ty * TILE_SIZE) as i64 - min_y as i64,
+            ));
+        }
+    }
+    let (width, height) = (max_x - min_x, max_y - min_y);
+    let polygon = polygon
+        .iter()
+        .map(|(x, y)| (x - min_x as f64, y - min_y as f64))
+        .collect();
+    let png = encode(EncodeJob::Stitch {
+        tiles,
+        width,
+        height,
+        polygon,
+    })
+    .await?;
+
+    let resolution = resolution(zoom);
+    let georef = Georeference {
+        epsg: 3857,
+        a: resolution,
+        b: 0.0,
+        c: min_x as f64 * resolution - MERCATOR_EXTENT,
+        d: 0.0,
+        e: -resolution,
+        f: MERCATOR_EXTENT - min_y as f64 * resolution,
+        width,
+        height,
+    };
+    Ok((png, georef))
+}
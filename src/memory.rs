@@ -0,0 +1,140 @@
+//! Memory the app holds, so that long sessions can be freed before the tab runs out of it.
+//! The wasm heap only ever grows, so what is freed is reused by the app rather than given
+//! back to the browser.
+
+use gloo::timers::callback::Interval;
+use js_sys::Reflect;
+use shadow_clone::shadow_clone;
+use yew::prelude::*;
+
+use crate::{decoded, i18n::use_language, storage::format_size, viewer};
+
+/// How often memory use is checked.
+const POLL_INTERVAL_MS: u32 = 5_000;
+
+/// Heap size past which users are warned. Wasm heaps can grow to 4 GB, but browsers,
+/// on phones especially, end tabs well before.
+const WARNING_HEAP: u64 = 1536 << 20;
+
+#[derive(Clone, Copy, PartialEq, Default)]
+struct Usage {
+    /// Bytes of the wasm heap.
+    heap: u64,
+    /// Bytes of the masks kept decoded.
+    decoded: u64,
+    /// Blob URLs of images kept to be shown again.
+    urls: usize,
+}
+
+impl Usage {
+    fn now() -> Self {
+        Usage {
+            heap: heap_bytes(),
+            decoded: decoded::usage() as u64,
+            urls: viewer::cached_urls(),
+        }
+    }
+}
+
+fn heap_bytes() -> u64 {
+    let buffer = Reflect::get(&wasm_bindgen::memory(), &"buffer".into()).unwrap_or_default();
+    Reflect::get(&buffer, &"byteLength".into())
+        .ok()
+        .and_then(|length| length.as_f64())
+        .unwrap_or_default() as u64
+}
+
+/// Drops the decoded masks and the blob URLs kept for images no longer shown. Images on
+/// screen keep their URLs until they are closed.
+fn free() {
+    decoded::clear();
+    viewer::forget_urls();
+}
+
+/// Whether a heap of `heap` bytes is worth a warning, given the size it had when memory was
+/// last freed, since the heap does not shrink afterwards.
+fn should_warn(heap: u64, freed_at: Option<u64>) -> bool {
+    heap > WARNING_HEAP && freed_at.is_none_or(|freed_at| heap > freed_at)
+}
+
+#[hook]
+fn use_usage() -> UseStateHandle<Usage> {
+    let usage = use_state(Usage::now);
+    {
+        shadow_clone!(usage);
+        use_effect_with((), move |_| {
+            let interval = Interval::new(POLL_INTERVAL_MS, move || usage.set(Usage::now()));
+            move || drop(interval)
+        });
+    }
+    usage
+}
+
+/// Memory the app holds, and freeing what it keeps only to be faster.
+#[function_component(MemoryPane)]
+pub fn memory_pane() -> Html {
+    let language = use_language();
+    let usage = use_usage();
+    let onfree = {
+        shadow_clone!(usage);
+        move |_| {
+            free();
+            usage.set(Usage::now());
+        }
+    };
+
+    html! {
+        <p>
+            {language.t_args("memory-usage", &[
+                ("heap", &format_size(usage.heap)),
+                ("decoded", &format_size(usage.decoded)),
+                ("urls", &usage.urls),
+            ])}{" "}
+            <button class="btn btn-sm btn-outline-secondary" onclick={onfree}>
+                {language.t("memory-free")}
+            </button>
+        </p>
+    }
+}
+
+/// Warning shown when the app holds enough memory for the tab to risk being ended.
+#[function_component(MemoryBanner)]
+pub fn memory_banner() -> Html {
+    let language = use_language();
+    let usage = use_usage();
+    let freed_at = use_state(|| None::<u64>);
+    let onfree = {
+        shadow_clone!(usage, freed_at);
+        move |_| {
+            free();
+            freed_at.set(Some(usage.heap));
+            usage.set(Usage::now());
+        }
+    };
+
+    if !should_warn(usage.heap, *freed_at) {
+        return html!();
+    }
+
+    html! {
+        <div class="alert alert-warning d-flex justify-content-between align-items-center">
+            <span>{language.t_args("memory-warning", &[("heap", &format_size(usage.heap))])}</span>
+            <button class="btn btn-sm btn-outline-dark" onclick={onfree}>
+                {language.t("memory-free")}
+            </button>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{should_warn, WARNING_HEAP};
+
+    #[test]
+    fn warnings_wait_for_the_heap_to_grow_past_where_it_was_freed() {
+        assert!(!should_warn(WARNING_HEAP, None));
+        assert!(should_warn(WARNING_HEAP + 1, None));
+        assert!(!should_warn(WARNING_HEAP + 1, Some(WARNING_HEAP + 1)));
+        assert!(should_warn(WARNING_HEAP + 2, Some(WARNING_HEAP + 1)));
+    }
+}
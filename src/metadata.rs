@@ -0,0 +1,747 @@
+//! Tags, notes, review decisions, flagged issues and class statistics kept alongside the
+//! stored results, and searching by them.
+
+use js_sys::{Array, Date, Object, Reflect};
+use std::{
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::{
+    decoded,
+    history::HistoryEntry,
+    i18n::Language,
+    idb, raster,
+    sync::{self, SyncMessage},
+    FileDetails,
+};
+
+/// What a reviewer made of a result.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Decision {
+    Accepted,
+    Rejected,
+    /// Usable once the mask is corrected.
+    NeedsEdit,
+}
+
+impl Decision {
+    pub const ALL: [Decision; 3] = [Decision::Accepted, Decision::Rejected, Decision::NeedsEdit];
+
+    /// The name stored and written to decision logs.
+    pub fn name(self) -> &'static str {
+        match self {
+            Decision::Accepted => "accepted",
+            Decision::Rejected => "rejected",
+            Decision::NeedsEdit => "needs-edit",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Decision::ALL.into_iter().find(|d| d.name() == name)
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Review {
+    pub decision: Decision,
+    pub comment: String,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: f64,
+}
+
+/// What is wrong with a result flagged for review.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum IssueReason {
+    CloudCover,
+    Misclassification,
+    /// The mask is shifted against the image.
+    Offset,
+    Other,
+}
+
+impl IssueReason {
+    pub const ALL: [IssueReason; 4] = [
+        IssueReason::CloudCover,
+        IssueReason::Misclassification,
+        IssueReason::Offset,
+        IssueReason::Other,
+    ];
+
+    /// The name stored and sent in reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            IssueReason::CloudCover => "cloud-cover",
+            IssueReason::Misclassification => "misclassification",
+            IssueReason::Offset => "offset",
+            IssueReason::Other => "other",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        IssueReason::ALL.into_iter().find(|r| r.name() == name)
+    }
+}
+
+/// A result flagged as showing a problem of the model, waiting to be triaged.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Issue {
+    pub reason: IssueReason,
+    pub comment: String,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: f64,
+    /// Whether the issue reached the deployment's issue endpoint.
+    pub reported: bool,
+}
+
+/// Where on the image a comment points, in fractions of its width and height.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Anchor {
+    Point {
+        x: f64,
+        y: f64,
+    },
+    Area {
+        left: f64,
+        top: f64,
+        right: f64,
+        bottom: f64,
+    },
+}
+
+impl Anchor {
+    /// Drags shorter than this, in fractions of the image, are taken for clicks.
+    const CLICK: f64 = 0.01;
+
+    /// The point clicked, or the area dragged over from `start` to `end`, kept on the image.
+    pub fn from_drag(start: (f64, f64), end: (f64, f64)) -> Self {
+        let clamp = |v: f64| v.clamp(0.0, 1.0);
+        let (start, end) = (
+            (clamp(start.0), clamp(start.1)),
+            (clamp(end.0), clamp(end.1)),
+        );
+        if (end.0 - start.0).abs() < Self::CLICK && (end.1 - start.1).abs() < Self::CLICK {
+            return Anchor::Point {
+                x: start.0,
+                y: start.1,
+            };
+        }
+        Anchor::Area {
+            left: start.0.min(end.0),
+            top: start.1.min(end.1),
+            right: start.0.max(end.0),
+            bottom: start.1.max(end.1),
+        }
+    }
+
+    /// Where the anchor is, in percent of the image's width and height.
+    pub fn describe(&self, language: Language) -> String {
+        let percent = |v: f64| format!("{:.0}%", v * 100.0);
+        match *self {
+            Anchor::Point { x, y } => {
+                language.t_args("comments-point", &[("x", &percent(x)), ("y", &percent(y))])
+            }
+            Anchor::Area {
+                left,
+                top,
+                right,
+                bottom,
+            } => language.t_args(
+                "comments-area",
+                &[
+                    ("left", &percent(left)),
+                    ("right", &percent(right)),
+                    ("top", &percent(top)),
+                    ("bottom", &percent(bottom)),
+                ],
+            ),
+        }
+    }
+
+    fn to_js(self) -> JsValue {
+        let object = Object::new();
+        let set = |key: &str, value: f64| {
+            Reflect::set(&object, &key.into(), &value.into()).unwrap();
+        };
+        match self {
+            Anchor::Point { x, y } => {
+                set("x", x);
+                set("y", y);
+            }
+            Anchor::Area {
+                left,
+                top,
+                right,
+                bottom,
+            } => {
+                set("left", left);
+                set("top", top);
+                set("right", right);
+                set("bottom", bottom);
+            }
+        }
+        object.into()
+    }
+
+    fn from_js(value: &JsValue) -> Option<Self> {
+        let get = |key: &str| Reflect::get(value, &key.into()).ok()?.as_f64();
+        match (get("x"), get("y")) {
+            (Some(x), Some(y)) => Some(Anchor::Point { x, y }),
+            _ => Some(Anchor::Area {
+                left: get("left")?,
+                top: get("top")?,
+                right: get("right")?,
+                bottom: get("bottom")?,
+            }),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Reply {
+    pub author: String,
+    pub text: String,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: f64,
+}
+
+/// A thread of comments on a part of the image, such as a misclassified building.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Comment {
+    /// When the thread was started, in milliseconds since the Unix epoch, telling it apart
+    /// from the other threads of the result.
+    pub timestamp: f64,
+    pub anchor: Anchor,
+    pub author: String,
+    pub text: String,
+    pub replies: Vec<Reply>,
+    pub resolved: bool,
+}
+
+impl Comment {
+    fn to_js(&self) -> JsValue {
+        let object = Object::new();
+        let set = |object: &Object, key: &str, value: JsValue| {
+            Reflect::set(object, &key.into(), &value).unwrap();
+        };
+        set(&object, "timestamp", self.timestamp.into());
+        set(&object, "anchor", self.anchor.to_js());
+        set(&object, "author", self.author.as_str().into());
+        set(&object, "text", self.text.as_str().into());
+        set(&object, "resolved", self.resolved.into());
+        let replies = self
+            .replies
+            .iter()
+            .map(|reply| {
+                let fields = Object::new();
+                set(&fields, "author", reply.author.as_str().into());
+                set(&fields, "text", reply.text.as_str().into());
+                set(&fields, "timestamp", reply.timestamp.into());
+                JsValue::from(fields)
+            })
+            .collect::<Array>();
+        set(&object, "replies", replies.into());
+        object.into()
+    }
+
+    fn from_js(value: &JsValue) -> Option<Self> {
+        let get = |value: &JsValue, key: &str| {
+            Reflect::get(value, &key.into()).unwrap_or(JsValue::UNDEFINED)
+        };
+        Some(Comment {
+            timestamp: get(value, "timestamp").as_f64()?,
+            anchor: Anchor::from_js(&get(value, "anchor"))?,
+            author: get(value, "author").as_string().unwrap_or_default(),
+            text: get(value, "text").as_string()?,
+            resolved: get(value, "resolved").as_bool().unwrap_or(false),
+            replies: Array::from(&get(value, "replies"))
+                .iter()
+                .filter_map(|reply| {
+                    Some(Reply {
+                        author: get(&reply, "author").as_string().unwrap_or_default(),
+                        text: get(&reply, "text").as_string()?,
+                        timestamp: get(&reply, "timestamp").as_f64()?,
+                    })
+                })
+                .collect(),
+        })
+    }
+}
+
+/// What the user and the app know about a stored result beyond the files themselves.
+#[derive(Clone, Default, PartialEq)]
+pub struct Metadata {
+    pub tags: Vec<String>,
+    pub notes: String,
+    /// The latest review of the result, if it was reviewed.
+    pub review: Option<Review>,
+    /// The problem the result was flagged for, until it is resolved.
+    pub issue: Option<Issue>,
+    /// Share of the mask's pixels in each class, from 0 to 1.
+    pub class_shares: BTreeMap<u8, f64>,
+    /// SHA-256 digest of the image, in hexadecimal, for results stored since digests are
+    /// taken.
+    pub digest: Option<String>,
+    /// Threads of comments on parts of the image, oldest first.
+    pub comments: Vec<Comment>,
+    /// Id the server stored the result under, for results it stored.
+    pub result_id: Option<String>,
+}
+
+impl Metadata {
+    pub fn new(mask: &FileDetails) -> Self {
+        Metadata {
+            class_shares: class_shares(mask),
+            ..Default::default()
+        }
+    }
+
+    fn to_js(&self, id: f64) -> JsValue {
+        let object = Object::new();
+        let set = |key: &str, value: JsValue| Reflect::set(&object, &key.into(), &value).unwrap();
+        set("id", id.into());
+        set(
+            "tags",
+            self.tags
+                .iter()
+                .map(|tag| JsValue::from(tag.as_str()))
+                .collect::<Array>()
+                .into(),
+        );
+        set("notes", self.notes.as_str().into());
+        if let Some(review) = &self.review {
+            let fields = Object::new();
+            let field = |key: &str, value: JsValue| {
+                Reflect::set(&fields, &key.into(), &value).unwrap();
+            };
+            field("decision", review.decision.name().into());
+            field("comment", review.comment.as_str().into());
+            field("timestamp", review.timestamp.into());
+            set("review", fields.into());
+        }
+        if let Some(issue) = &self.issue {
+            let fields = Object::new();
+            let field = |key: &str, value: JsValue| {
+                Reflect::set(&fields, &key.into(), &value).unwrap();
+            };
+            field("reason", issue.reason.name().into());
+            field("comment", issue.comment.as_str().into());
+            field("timestamp", issue.timestamp.into());
+            field("reported", issue.reported.into());
+            set("issue", fields.into());
+        }
+        let shares = Object::new();
+        for (class, share) in &self.class_shares {
+            Reflect::set(&shares, &class.to_string().into(), &(*share).into()).unwrap();
+        }
+        set("class_shares", shares.into());
+        if let Some(digest) = &self.digest {
+            set("digest", digest.as_str().into());
+        }
+        if let Some(result_id) = &self.result_id {
+            set("result_id", result_id.as_str().into());
+        }
+        set(
+            "comments",
+            self.comments
+                .iter()
+                .map(Comment::to_js)
+                .collect::<Array>()
+                .into(),
+        );
+        object.into()
+    }
+
+    fn from_js(value: &JsValue) -> Option<(f64, Self)> {
+        let get = |key: &str| Reflect::get(value, &key.into()).unwrap_or(JsValue::UNDEFINED);
+        let id = get("id").as_f64()?;
+        let tags = Array::from(&get("tags"))
+            .iter()
+            .filter_map(|tag| tag.as_string())
+            .collect();
+        let shares = get("class_shares");
+        let class_shares = match shares.dyn_ref::<Object>() {
+            Some(shares) => Object::entries(shares)
+                .iter()
+                .filter_map(|entry| {
+                    let entry = Array::from(&entry);
+                    Some((
+                        entry.get(0).as_string()?.parse().ok()?,
+                        entry.get(1).as_f64()?,
+                    ))
+                })
+                .collect(),
+            None => BTreeMap::new(),
+        };
+        let review = get("review");
+        let field = |key: &str| Reflect::get(&review, &key.into()).unwrap_or(JsValue::UNDEFINED);
+        let review = (!review.is_undefined())
+            .then(|| {
+                Some(Review {
+                    decision: Decision::parse(&field("decision").as_string()?)?,
+                    comment: field("comment").as_string().unwrap_or_default(),
+                    timestamp: field("timestamp").as_f64()?,
+                })
+            })
+            .flatten();
+        let issue = get("issue");
+        let field = |key: &str| Reflect::get(&issue, &key.into()).unwrap_or(JsValue::UNDEFINED);
+        let issue = (!issue.is_undefined())
+            .then(|| {
+                Some(Issue {
+                    reason: IssueReason::parse(&field("reason").as_string()?)?,
+                    comment: field("comment").as_string().unwrap_or_default(),
+                    timestamp: field("timestamp").as_f64()?,
+                    reported: field("reported").as_bool().unwrap_or(false),
+                })
+            })
+            .flatten();
+        Some((
+            id,
+            Metadata {
+                tags,
+                notes: get("notes").as_string().unwrap_or_default(),
+                review,
+                issue,
+                class_shares,
+                digest: get("digest").as_string(),
+                comments: Array::from(&get("comments"))
+                    .iter()
+                    .filter_map(|comment| Comment::from_js(&comment))
+                    .collect(),
+                result_id: get("result_id").as_string(),
+            },
+        ))
+    }
+}
+
+/// Share of `mask`'s pixels in each class, empty when the mask cannot be decoded.
+pub fn class_shares(mask: &FileDetails) -> BTreeMap<u8, f64> {
+    let Ok(mask) = decoded::mask(&mask.data) else {
+        return BTreeMap::new();
+    };
+    let total = mask.as_raw().len().max(1) as f64;
+    (0..=u8::MAX)
+        .zip(raster::class_counts(&mask))
+        .filter(|(_, count)| *count > 0)
+        .map(|(class, count)| (class, count as f64 / total))
+        .collect()
+}
+
+/// Splits comma-separated user input into trimmed, non-empty, distinct tags.
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = vec![];
+    for tag in text.split(',').map(str::trim).filter(|tag| !tag.is_empty()) {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+pub async fn put(id: f64, metadata: &Metadata) -> Result<(), String> {
+    let db = idb::open().await?;
+    idb::put(&db, idb::METADATA_STORE, &metadata.to_js(id)).await?;
+    sync::broadcast(SyncMessage::HistoryChanged);
+    Ok(())
+}
+
+/// Metadata of the result `id`, if it has some.
+pub async fn get(id: f64) -> Result<Option<Metadata>, String> {
+    let db = idb::open().await?;
+    let value = idb::get(&db, idb::METADATA_STORE, &id.into()).await?;
+    Ok(Metadata::from_js(&value).map(|(_, metadata)| metadata))
+}
+
+/// Metadata of every result that has some, by result id.
+pub async fn get_all() -> Result<HashMap<u64, Metadata>, String> {
+    let db = idb::open().await?;
+    Ok(idb::get_all(&db, idb::METADATA_STORE)
+        .await?
+        .iter()
+        .filter_map(|value| Metadata::from_js(&value))
+        .map(|(id, metadata)| (id as u64, metadata))
+        .collect())
+}
+
+/// Ids of the results tagged with `tag`, looked up through the tags index.
+pub async fn ids_with_tag(tag: &str) -> Result<Vec<u64>, String> {
+    let db = idb::open().await?;
+    Ok(
+        idb::keys_in_index(&db, idb::METADATA_STORE, idb::TAGS_INDEX, &tag.into())
+            .await?
+            .iter()
+            .filter_map(|key| key.as_f64())
+            .map(|id| id as u64)
+            .collect(),
+    )
+}
+
+/// Ids of the results whose image has `digest`, looked up through the digest index.
+pub async fn ids_with_digest(digest: &str) -> Result<Vec<u64>, String> {
+    let db = idb::open().await?;
+    Ok(
+        idb::keys_in_index(&db, idb::METADATA_STORE, idb::DIGEST_INDEX, &digest.into())
+            .await?
+            .iter()
+            .filter_map(|key| key.as_f64())
+            .map(|id| id as u64)
+            .collect(),
+    )
+}
+
+/// Ids of the results the server stored as `result_id`, looked up through the result id
+/// index.
+pub async fn ids_with_result_id(result_id: &str) -> Result<Vec<u64>, String> {
+    let db = idb::open().await?;
+    Ok(idb::keys_in_index(
+        &db,
+        idb::METADATA_STORE,
+        idb::RESULT_ID_INDEX,
+        &result_id.into(),
+    )
+    .await?
+    .iter()
+    .filter_map(|key| key.as_f64())
+    .map(|id| id as u64)
+    .collect())
+}
+
+pub async fn remove(id: f64) -> Result<(), String> {
+    let db = idb::open().await?;
+    idb::delete(&db, idb::METADATA_STORE, &id.into()).await
+}
+
+/// `text` as a CSV field, quoted when it has to be.
+pub fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Reviews of `entries` as CSV, one line per reviewed result, with times written by `date`.
+pub fn decision_log(entries: &[(Rc<HistoryEntry>, Metadata)], date: fn(f64) -> String) -> String {
+    let mut log = "reviewed,result,image,server,decision,comment\n".to_string();
+    for (entry, metadata) in entries {
+        let Some(review) = &metadata.review else {
+            continue;
+        };
+        log += &format!(
+            "{},{},{},{},{},{}\n",
+            date(review.timestamp),
+            entry.id.map_or(String::new(), |id| id.to_string()),
+            csv_field(&entry.image.file_name),
+            csv_field(&entry.server),
+            review.decision.name(),
+            csv_field(&review.comment),
+        );
+    }
+    log
+}
+
+/// The comment threads of `entries` as CSV, a line for each comment and reply, with the
+/// thread it belongs to and where on the image it points.
+pub fn comment_log(entries: &[(Rc<HistoryEntry>, Metadata)], date: fn(f64) -> String) -> String {
+    let mut log = "commented,result,image,thread,anchor,resolved,author,comment\n".to_string();
+    for (entry, metadata) in entries {
+        for comment in &metadata.comments {
+            let messages = std::iter::once((comment.timestamp, &comment.author, &comment.text))
+                .chain(
+                    comment
+                        .replies
+                        .iter()
+                        .map(|reply| (reply.timestamp, &reply.author, &reply.text)),
+                );
+            for (timestamp, author, text) in messages {
+                log += &format!(
+                    "{},{},{},{},{},{},{},{}\n",
+                    date(timestamp),
+                    entry.id.map_or(String::new(), |id| id.to_string()),
+                    csv_field(&entry.image.file_name),
+                    date(comment.timestamp),
+                    csv_field(&comment.anchor.describe(Language::En)),
+                    comment.resolved,
+                    csv_field(author),
+                    csv_field(text),
+                );
+            }
+        }
+    }
+    log
+}
+
+/// Criteria the history is narrowed down by. Empty fields match everything.
+#[derive(Clone, Default, PartialEq)]
+pub struct HistoryFilter {
+    /// Part of the image's file name, in any case.
+    pub file_name: String,
+    pub tag: String,
+    /// Name of the review decision, `pending` for results not reviewed yet.
+    pub review: String,
+    /// Part of the segmentation endpoint, standing in for the model that produced the mask.
+    pub server: String,
+    /// Earliest and latest day, as `YYYY-MM-DD`.
+    pub from: String,
+    pub to: String,
+    /// Class whose share of the mask must be at least `min_share`.
+    pub class: Option<u8>,
+    pub min_share: f64,
+}
+
+/// Milliseconds since the Unix epoch at the start of `day`, given as `YYYY-MM-DD`.
+fn day_start(day: &str) -> Option<f64> {
+    if day.is_empty() {
+        return None;
+    }
+    let ms = Date::parse(day);
+    (!ms.is_nan()).then_some(ms)
+}
+
+impl HistoryFilter {
+    /// Whether `entry` passes every criterion except the tag, which is looked up in the
+    /// tags index instead.
+    pub fn matches(&self, entry: &HistoryEntry, metadata: Option<&Metadata>) -> bool {
+        let contains =
+            |haystack: &str, needle: &str| haystack.to_lowercase().contains(&needle.to_lowercase());
+        if !contains(&entry.image.file_name, &self.file_name)
+            || !contains(&entry.server, &self.server)
+        {
+            return false;
+        }
+        let review = metadata.and_then(|metadata| metadata.review.as_ref());
+        match (self.review.as_str(), review) {
+            ("", _) | ("pending", None) => {}
+            (name, Some(review)) if review.decision.name() == name => {}
+            _ => return false,
+        }
+        if day_start(&self.from).is_some_and(|from| entry.timestamp < from) {
+            return false;
+        }
+        if day_start(&self.to).is_some_and(|to| entry.timestamp >= to + 86_400_000.0) {
+            return false;
+        }
+        if let Some(class) = self.class {
+            let share = match metadata {
+                Some(metadata) if !metadata.class_shares.is_empty() => {
+                    metadata.class_shares.get(&class).copied()
+                }
+                _ => class_shares(&entry.mask).get(&class).copied(),
+            };
+            if share.unwrap_or(0.0) < self.min_share {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::{
+        comment_log, decision_log, Anchor, Comment, Decision, HistoryFilter, Metadata, Reply,
+        Review,
+    };
+    use crate::{
+        clock::{self, Replay},
+        fixtures,
+        history::HistoryEntry,
+    };
+
+    fn reviewed(name: &str, review: Option<(Decision, &str)>) -> (Rc<HistoryEntry>, Metadata) {
+        clock::install(Replay::new(0.0, 1.0));
+        let mut entry = HistoryEntry::new(
+            Rc::new(fixtures::png(name, fixtures::IMAGE)),
+            Rc::new(fixtures::png("mask.png", fixtures::MASK)),
+            "http://localhost:5000/segment".to_string(),
+        );
+        entry.id = Some(1.0);
+        let metadata = Metadata {
+            review: review.map(|(decision, comment)| Review {
+                decision,
+                comment: comment.to_string(),
+                timestamp: 0.0,
+            }),
+            ..Default::default()
+        };
+        (Rc::new(entry), metadata)
+    }
+
+    #[test]
+    fn logs_list_reviewed_results_only() {
+        let entries = [
+            reviewed(
+                "a.png",
+                Some((Decision::NeedsEdit, "road missing, see \"north\"")),
+            ),
+            reviewed("b.png", None),
+        ];
+
+        assert_eq!(
+            decision_log(&entries, |ms| ms.to_string()),
+            "reviewed,result,image,server,decision,comment\n\
+             0,1,a.png,http://localhost:5000/segment,needs-edit,\"road missing, see \"\"north\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn results_are_filtered_by_their_review() {
+        let (accepted, accepted_metadata) = reviewed("a.png", Some((Decision::Accepted, "")));
+        let (pending, pending_metadata) = reviewed("b.png", None);
+        let filter = |review: &str| HistoryFilter {
+            review: review.to_string(),
+            ..Default::default()
+        };
+
+        assert!(filter("accepted").matches(&accepted, Some(&accepted_metadata)));
+        assert!(!filter("rejected").matches(&accepted, Some(&accepted_metadata)));
+        assert!(!filter("pending").matches(&accepted, Some(&accepted_metadata)));
+        assert!(filter("pending").matches(&pending, Some(&pending_metadata)));
+        assert!(filter("").matches(&pending, None));
+    }
+
+    #[test]
+    fn short_drags_anchor_points_and_longer_ones_areas() {
+        assert_eq!(
+            Anchor::from_drag((0.5, 0.5), (0.505, 0.5)),
+            Anchor::Point { x: 0.5, y: 0.5 }
+        );
+        assert_eq!(
+            Anchor::from_drag((0.75, 0.5), (0.25, 1.5)),
+            Anchor::Area {
+                left: 0.25,
+                top: 0.5,
+                right: 0.75,
+                bottom: 1.0
+            }
+        );
+    }
+
+    #[test]
+    fn comment_logs_list_every_reply_under_its_thread() {
+        let (entry, mut metadata) = reviewed("a.png", None);
+        metadata.comments = vec![Comment {
+            timestamp: 10.0,
+            anchor: Anchor::Point { x: 0.25, y: 0.5 },
+            author: "Ana".to_string(),
+            text: "Shadow taken for a road".to_string(),
+            replies: vec![Reply {
+                author: "ML".to_string(),
+                text: "Added to the next training set, thanks".to_string(),
+                timestamp: 20.0,
+            }],
+            resolved: true,
+        }];
+
+        assert_eq!(
+            comment_log(&[(entry, metadata)], |ms| ms.to_string()),
+            "commented,result,image,thread,anchor,resolved,author,comment\n\
+             10,1,a.png,10,\"point 25%, 50%\",true,Ana,Shadow taken for a road\n\
+             20,1,a.png,10,\"point 25%, 50%\",true,ML,\"Added to the next training set, thanks\"\n"
+        );
+    }
+}
@@ -0,0 +1,408 @@
+//! Segmenting the same image with several candidate models, each a segmentation server, and
+//! laying their masks out side by side, zoomed and scrolled together, to choose between them.
+
+use shadow_clone::shadow_clone;
+use std::{cell::Cell, collections::HashMap, rc::Rc};
+use web_sys::{Element, HtmlInputElement, HtmlSelectElement};
+use yew::{platform::spawn_local, prelude::*};
+use yew_hooks::prelude::*;
+
+use crate::{
+    api::{Api, RequestControl},
+    config::{self, ModelConfig},
+    decoded,
+    diff::{diff_masks, DiffSummary},
+    i18n::{self, use_language},
+    postprocess,
+    settings::Settings,
+    state::use_app_state,
+    uploader::UploadPane,
+    viewer::FileViewer,
+    FileDetails,
+};
+
+/// Zoom levels the grid can be shown at.
+const ZOOMS: [u32; 4] = [1, 2, 4, 8];
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Candidate {
+    pub name: String,
+    pub server_url: String,
+}
+
+/// The servers to choose from, each once: those in the configuration, the one in the
+/// settings, and those added here, named after their address.
+pub fn candidates(configured: &[ModelConfig], current: &str, added: &[String]) -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = Vec::new();
+    let named = configured
+        .iter()
+        .map(|model| (model.name.clone(), model.server_url.clone()))
+        .chain([(i18n::t("models-from-settings"), current.to_string())])
+        .chain(added.iter().map(|url| (url.clone(), url.clone())));
+    for (name, server_url) in named {
+        let server_url = server_url.trim_end_matches('/').to_string();
+        if !candidates.iter().any(|c| c.server_url == server_url) {
+            candidates.push(Candidate { name, server_url });
+        }
+    }
+    candidates
+}
+
+#[derive(Clone, PartialEq)]
+enum Outcome {
+    Running,
+    Done(Rc<FileDetails>),
+    Failed(String),
+}
+
+/// Scrolls every cell but `from` to where `from` is scrolled, so that they show the same
+/// part of their masks. Cells already there are left alone, which stops the scroll events
+/// this causes from going round.
+fn scroll_together(cells: &[NodeRef], from: usize) {
+    let Some(source) = cells[from].cast::<Element>() else {
+        return;
+    };
+    for (i, cell) in cells.iter().enumerate() {
+        let Some(cell) = cell.cast::<Element>().filter(|_| i != from) else {
+            continue;
+        };
+        if cell.scroll_left() != source.scroll_left() {
+            cell.set_scroll_left(source.scroll_left());
+        }
+        if cell.scroll_top() != source.scroll_top() {
+            cell.set_scroll_top(source.scroll_top());
+        }
+    }
+}
+
+/// The image and the masks the chosen models made of it, in a grid, each with the
+/// difference between two of them on request. The image starts as the one being
+/// segmented, if any.
+#[function_component(ModelComparisonPane)]
+pub fn model_comparison_pane() -> Html {
+    let language = use_language();
+    let state = use_app_state();
+    let settings = state.settings.clone();
+    let image = use_state(|| state.image.clone());
+    let added = use_state(Vec::<String>::new);
+    let candidates = candidates(&config::current().models, &settings.server_url, &added);
+    // Chosen server addresses, all of them to begin with.
+    let chosen = use_state(|| {
+        candidates
+            .iter()
+            .map(|c| c.server_url.clone())
+            .collect::<Vec<_>>()
+    });
+    let outcomes = use_map(HashMap::<String, Outcome>::new());
+    // Answers to an earlier run are dropped once another one starts.
+    let run = use_mut_ref(|| Rc::new(Cell::new(true)));
+    let zoom = use_state(|| 1);
+    let compared = use_state(|| None::<(String, String)>);
+
+    let onupload = {
+        shadow_clone!(image, outcomes, compared);
+        move |file| {
+            image.set(file);
+            outcomes.clear();
+            compared.set(None);
+        }
+    };
+
+    let new_server = use_node_ref();
+    let onadd = {
+        shadow_clone!(added, chosen, new_server);
+        move |e: SubmitEvent| {
+            e.prevent_default();
+            let Some(input) = new_server.cast::<HtmlInputElement>() else {
+                return;
+            };
+            let url = input.value().trim().trim_end_matches('/').to_string();
+            if !(url.starts_with("http://") || url.starts_with("https://")) {
+                return;
+            }
+            if !chosen.contains(&url) {
+                chosen.set([(*chosen).clone(), vec![url.clone()]].concat());
+            }
+            added.set([(*added).clone(), vec![url]].concat());
+            input.set_value("");
+        }
+    };
+
+    let onrun = {
+        shadow_clone!(image, chosen, outcomes, run, settings, compared);
+        let candidates = candidates.clone();
+        move |_| {
+            let Some(image) = (**image).clone() else {
+                return;
+            };
+            let current = Rc::new(Cell::new(true));
+            run.replace(current.clone()).set(false);
+            outcomes.clear();
+            compared.set(None);
+            for candidate in candidates.iter().filter(|c| chosen.contains(&c.server_url)) {
+                let url = candidate.server_url.clone();
+                outcomes.insert(url.clone(), Outcome::Running);
+                let api = Api::http(Rc::new(Settings {
+                    server_url: url.clone(),
+                    ..(*settings).clone()
+                }));
+                shadow_clone!(image, outcomes, settings, current);
+                spawn_local(async move {
+                    let mask = match api.segment(image, RequestControl::default()).await {
+                        Ok(response) => postprocess::apply(&settings.postprocess, response.mask),
+                        Err(error) => Err(error.to_string()),
+                    };
+                    if current.get() {
+                        outcomes.insert(
+                            url,
+                            match mask {
+                                Ok(mask) => Outcome::Done(Rc::new(mask)),
+                                Err(why) => Outcome::Failed(why),
+                            },
+                        );
+                    }
+                });
+            }
+        }
+    };
+
+    let onzoom = {
+        shadow_clone!(zoom);
+        move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            zoom.set(select.value().parse().unwrap_or(1));
+        }
+    };
+
+    let done: Vec<(Candidate, Rc<FileDetails>)> = candidates
+        .iter()
+        .filter_map(|c| match outcomes.current().get(&c.server_url) {
+            Some(Outcome::Done(mask)) => Some((c.clone(), mask.clone())),
+            _ => None,
+        })
+        .collect();
+
+    let diff = use_memo((*compared).clone(), {
+        let masks: HashMap<_, _> = done
+            .iter()
+            .map(|(c, mask)| (c.server_url.clone(), mask.clone()))
+            .collect();
+        move |compared| {
+            let (a, b) = compared.as_ref()?;
+            let (a, b) = (masks.get(a)?, masks.get(b)?);
+            Some(
+                decoded::mask(&a.data)
+                    .and_then(|a| decoded::mask(&b.data).map(|b| (a, b)))
+                    .and_then(|(a, b)| diff_masks(&a, &b))
+                    .map(Rc::new),
+            )
+        }
+    });
+
+    // One cell for the image and one for every chosen model.
+    let shown: Vec<_> = candidates
+        .iter()
+        .filter(|c| outcomes.current().contains_key(&c.server_url))
+        .cloned()
+        .collect();
+    let cells = use_memo(shown.len() + 1, |count| {
+        (0..*count).map(|_| NodeRef::default()).collect::<Vec<_>>()
+    });
+    let cell = |i: usize, title: String, content: Html| {
+        let onscroll = {
+            shadow_clone!(cells);
+            move |_| scroll_together(&cells, i)
+        };
+        html! {
+            <div class="col">
+                <h3 class="h6 text-truncate" title={title.clone()}>{title}</h3>
+                <div
+                    ref={cells[i].clone()}
+                    class="border overflow-auto"
+                    style="height: 320px;"
+                    {onscroll}
+                >
+                    <div style={format!("width: {}%;", *zoom * 100)}>{content}</div>
+                </div>
+            </div>
+        }
+    };
+
+    let compare_select = |which: usize| {
+        let onchange = {
+            shadow_clone!(compared, done);
+            move |e: Event| {
+                let select: HtmlSelectElement = e.target_unchecked_into();
+                let (mut a, mut b) = (*compared).clone().unwrap_or_else(|| {
+                    (done[0].0.server_url.clone(), done[1].0.server_url.clone())
+                });
+                if which == 0 {
+                    a = select.value();
+                } else {
+                    b = select.value();
+                }
+                compared.set(Some((a, b)));
+            }
+        };
+        let current = compared
+            .as_ref()
+            .map(|(a, b)| if which == 0 { a } else { b });
+        html! {
+            <select class="form-select" {onchange}>
+                <option value="" selected={current.is_none()} disabled={true}>{language.t("models-choose")}</option>
+                {for done.iter().map(|(c, _)| html! {
+                    <option value={c.server_url.clone()} selected={current == Some(&c.server_url)}>
+                        {&c.name}
+                    </option>
+                })}
+            </select>
+        }
+    };
+
+    let names: HashMap<_, _> = candidates
+        .iter()
+        .map(|c| (c.server_url.clone(), c.name.clone()))
+        .collect();
+
+    html! {
+        <div>
+            <UploadPane {onupload} />
+            <fieldset class="my-2">
+                <legend class="h6">{language.t("models-legend")}</legend>
+                {for candidates.iter().map(|candidate| {
+                    let url = candidate.server_url.clone();
+                    let checked = chosen.contains(&url);
+                    let onchange = {
+                        shadow_clone!(chosen, url);
+                        move |_| {
+                            let mut new_chosen: Vec<_> =
+                                chosen.iter().filter(|u| **u != url).cloned().collect();
+                            if !checked {
+                                new_chosen.push(url.clone());
+                            }
+                            chosen.set(new_chosen);
+                        }
+                    };
+                    let id = format!("model-{url}");
+                    html! {
+                        <div class="form-check">
+                            <input class="form-check-input" type="checkbox" id={id.clone()} {checked} {onchange} />
+                            <label class="form-check-label" for={id}>
+                                {&candidate.name}
+                                <small class="text-body-secondary ms-2">{&candidate.server_url}</small>
+                            </label>
+                        </div>
+                    }
+                })}
+                <form class="input-group input-group-sm mt-1" onsubmit={onadd}>
+                    <input ref={new_server} class="form-control" type="url" placeholder="https://another-server.example" />
+                    <button class="btn btn-outline-secondary" type="submit">{language.t("models-add-server")}</button>
+                </form>
+            </fieldset>
+            <div class="d-flex flex-wrap align-items-center gap-2 mb-2">
+                <button
+                    class="btn btn-primary"
+                    disabled={image.is_none() || chosen.is_empty()}
+                    onclick={onrun}
+                >
+                    {language.t_args("models-run", &[("count", &chosen.len())])}
+                </button>
+                <div class="input-group w-auto">
+                    <label class="input-group-text" for="models-zoom">{language.t("map-zoom")}</label>
+                    <select id="models-zoom" class="form-select" onchange={onzoom}>
+                        {for ZOOMS.into_iter().map(|z| html! {
+                            <option value={z.to_string()} selected={z == *zoom}>{format!("{z}×")}</option>
+                        })}
+                    </select>
+                </div>
+            </div>
+            if let Some(file) = (*image).as_ref().as_ref().filter(|_| !shown.is_empty()) {
+                <div class="row row-cols-2 row-cols-xl-3 g-2 mb-3">
+                    {cell(0, language.t("report-image"), html!(<FileViewer file={Rc::new(file.clone())} />))}
+                    {for shown.iter().enumerate().map(|(i, candidate)| {
+                        let content = match outcomes.current().get(&candidate.server_url) {
+                            Some(Outcome::Done(mask)) => html! {
+                                <FileViewer
+                                    file={mask.clone()}
+                                    alt={AttrValue::from(language.t_args("models-mask-by", &[("name", &candidate.name)]))}
+                                />
+                            },
+                            Some(Outcome::Failed(why)) => html! {
+                                <div class="alert alert-danger m-2" role="alert">{why}</div>
+                            },
+                            _ => html! {
+                                <div class="d-flex align-items-center gap-2 p-3" aria-busy="true">
+                                    <span class="spinner-border spinner-border-sm" aria-hidden="true"></span>
+                                    {language.t("batch-running")}
+                                </div>
+                            },
+                        };
+                        cell(i + 1, candidate.name.clone(), content)
+                    })}
+                </div>
+            }
+            if done.len() >= 2 {
+                <h2 class="h5">{language.t("models-difference")}</h2>
+                <div class="input-group mb-2">
+                    {compare_select(0)}
+                    <span class="input-group-text">{language.t("models-and")}</span>
+                    {compare_select(1)}
+                </div>
+                {
+                    match (&*diff, &*compared) {
+                        (Some(Ok(diff)), Some((a, b))) => html! {
+                            <DiffSummary
+                                diff={diff.clone()}
+                                before={names.get(a).cloned().unwrap_or_default()}
+                                after={names.get(b).cloned().unwrap_or_default()}
+                            />
+                        },
+                        (Some(Err(why)), _) => html! {
+                            <div class="alert alert-danger" role="alert">{why}</div>
+                        },
+                        _ => html!(),
+                    }
+                }
+            }
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{candidates, Candidate};
+    use crate::config::ModelConfig;
+
+    #[test]
+    fn every_server_is_offered_once() {
+        let configured = [
+            ModelConfig {
+                name: "Production".to_string(),
+                server_url: "https://a.example".to_string(),
+            },
+            ModelConfig {
+                name: "Candidate".to_string(),
+                server_url: "https://b.example".to_string(),
+            },
+        ];
+        let added = [
+            "https://b.example/".to_string(),
+            "https://c.example".to_string(),
+        ];
+
+        let offered = candidates(&configured, "https://a.example", &added);
+
+        let candidate = |name: &str, server_url: &str| Candidate {
+            name: name.to_string(),
+            server_url: server_url.to_string(),
+        };
+        assert_eq!(
+            offered,
+            [
+                candidate("Production", "https://a.example"),
+                candidate("Candidate", "https://b.example"),
+                candidate("https://c.example", "https://c.example"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,294 @@
+//! NDVI preview for four-band (red, green, blue, near infrared) TIFF inputs.
+
+use image::{DynamicImage, GrayImage, ImageOutputFormat, Luma, Rgb, RgbImage};
+use shadow_clone::shadow_clone;
+use std::{io::Cursor, rc::Rc};
+use tiff::{decoder::Decoder, decoder::DecodingResult, ColorType};
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use crate::{i18n::use_language, throttle::use_throttled, viewer::BlobUrl, FileDetails};
+
+/// Least time between thresholds the vegetation mask is made for while the slider is
+/// dragged.
+const THRESHOLD_INTERVAL_MS: u32 = 150;
+
+/// Pixel-interleaved samples of a multiband image.
+#[derive(PartialEq)]
+pub struct Bands {
+    pub width: u32,
+    pub height: u32,
+    pub count: usize,
+    pub samples: Vec<f32>,
+}
+
+/// Decodes a TIFF with four samples per pixel. Other images have no near infrared band.
+pub fn read_bands(data: &[u8]) -> Option<Bands> {
+    let mut decoder = Decoder::new(Cursor::new(data)).ok()?;
+    let count = match decoder.colortype().ok()? {
+        ColorType::RGBA(_) | ColorType::CMYK(_) => 4,
+        _ => return None,
+    };
+    let (width, height) = decoder.dimensions().ok()?;
+    let samples = match decoder.read_image().ok()? {
+        DecodingResult::U8(v) => v.into_iter().map(f32::from).collect(),
+        DecodingResult::U16(v) => v.into_iter().map(f32::from).collect(),
+        DecodingResult::U32(v) => v.into_iter().map(|s| s as f32).collect(),
+        DecodingResult::U64(v) => v.into_iter().map(|s| s as f32).collect(),
+        DecodingResult::F32(v) => v,
+        DecodingResult::F64(v) => v.into_iter().map(|s| s as f32).collect(),
+        DecodingResult::I8(v) => v.into_iter().map(f32::from).collect(),
+        DecodingResult::I16(v) => v.into_iter().map(f32::from).collect(),
+        DecodingResult::I32(v) => v.into_iter().map(|s| s as f32).collect(),
+        DecodingResult::I64(v) => v.into_iter().map(|s| s as f32).collect(),
+    };
+    Some(Bands {
+        width,
+        height,
+        count,
+        samples,
+    })
+}
+
+/// `(nir - red) / (nir + red)` for every pixel, with zero where both bands are empty.
+pub fn ndvi(bands: &Bands, red: usize, nir: usize) -> Vec<f32> {
+    bands
+        .samples
+        .chunks_exact(bands.count)
+        .map(|px| {
+            let (red, nir) = (px[red], px[nir]);
+            if red + nir == 0.0 {
+                0.0
+            } else {
+                (nir - red) / (nir + red)
+            }
+        })
+        .collect()
+}
+
+/// Red-yellow-green ramp over [-1, 1].
+fn colormap(value: f32) -> Rgb<u8> {
+    const STOPS: [(f32, [f32; 3]); 3] = [
+        (-1.0, [165.0, 0.0, 38.0]),
+        (0.0, [255.0, 255.0, 191.0]),
+        (1.0, [0.0, 104.0, 55.0]),
+    ];
+    let value = value.clamp(-1.0, 1.0);
+    let (lo, hi) = if value < 0.0 {
+        (STOPS[0], STOPS[1])
+    } else {
+        (STOPS[1], STOPS[2])
+    };
+    let t = (value - lo.0) / (hi.0 - lo.0);
+    Rgb([0, 1, 2].map(|i| (lo.1[i] + (hi.1[i] - lo.1[i]) * t) as u8))
+}
+
+fn encode_png(image: DynamicImage) -> Vec<u8> {
+    let mut png = vec![];
+    image
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .expect("Encoding PNG in memory cannot fail.");
+    png
+}
+
+pub fn colorized(ndvi: &[f32], width: u32, height: u32) -> Vec<u8> {
+    encode_png(DynamicImage::ImageRgb8(RgbImage::from_fn(
+        width,
+        height,
+        |x, y| colormap(ndvi[(y * width + x) as usize]),
+    )))
+}
+
+/// NDVI scaled from [-1, 1] to an 8-bit band.
+pub fn band(ndvi: &[f32], width: u32, height: u32) -> Vec<u8> {
+    encode_png(DynamicImage::ImageLuma8(GrayImage::from_fn(
+        width,
+        height,
+        |x, y| Luma([((ndvi[(y * width + x) as usize].clamp(-1.0, 1.0) + 1.0) * 127.5) as u8]),
+    )))
+}
+
+/// Vegetation mask: 255 where NDVI exceeds `threshold`, 0 elsewhere.
+pub fn thresholded(ndvi: &[f32], width: u32, height: u32, threshold: f32) -> Vec<u8> {
+    encode_png(DynamicImage::ImageLuma8(GrayImage::from_fn(
+        width,
+        height,
+        |x, y| {
+            Luma([if ndvi[(y * width + x) as usize] > threshold {
+                255
+            } else {
+                0
+            }])
+        },
+    )))
+}
+
+fn band_input(label: String, value: &UseStateHandle<usize>, count: usize) -> Html {
+    let onchange = {
+        shadow_clone!(value);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(band) = input.value().parse::<usize>() {
+                value.set(band.clamp(1, count) - 1);
+            }
+        }
+    };
+    html! {
+        <div class="input-group input-group-sm">
+            <span class="input-group-text">{label}</span>
+            <input
+                type="number"
+                class="form-control"
+                min="1"
+                max={count.to_string()}
+                value={(**value + 1).to_string()}
+                {onchange}
+            />
+        </div>
+    }
+}
+
+/// NDVI preview of `file`, offering to attach the NDVI band or the vegetation mask
+/// as an auxiliary input for segmentation.
+#[autoprops_component(NdviPane)]
+pub fn ndvi_pane(file: Rc<FileDetails>, onattach: Callback<Option<Rc<FileDetails>>>) -> Html {
+    let language = use_language();
+    let bands = use_memo(file.clone(), |file| read_bands(&file.data));
+    let red = use_state(|| 0usize);
+    let nir = use_state(|| 3usize);
+    let threshold = use_state(|| 0.3f32);
+
+    // Pictures are only made again when what they show changes, not on every render.
+    let preview = {
+        let bands = bands.clone();
+        use_memo((file.clone(), *red, *nir), move |(_, red, nir)| {
+            let bands = (*bands).as_ref()?;
+            let values = ndvi(bands, *red, *nir);
+            let url = BlobUrl::new(&colorized(&values, bands.width, bands.height), "image/png");
+            Some((values, url))
+        })
+    };
+    let mask = {
+        let (bands, preview) = (bands.clone(), preview.clone());
+        use_memo(
+            (file.clone(), *red, *nir, *threshold),
+            move |(_, _, _, threshold)| {
+                let (bands, (values, _)) = ((*bands).as_ref()?, (*preview).as_ref()?);
+                let png: Rc<[u8]> =
+                    thresholded(values, bands.width, bands.height, *threshold).into();
+                let url = BlobUrl::new(&png, "image/png");
+                let vegetated = values.iter().filter(|v| **v > *threshold).count();
+                Some((png, url, vegetated))
+            },
+        )
+    };
+
+    // The mask is made again for every value set, so only some are while dragging.
+    let set_threshold = {
+        shadow_clone!(threshold);
+        use_throttled(
+            Callback::from(move |value| threshold.set(value)),
+            THRESHOLD_INTERVAL_MS,
+        )
+    };
+
+    let (Some(bands), Some((values, preview_url)), Some((mask, mask_url, vegetated))) =
+        (&*bands, &*preview, &*mask)
+    else {
+        return html!();
+    };
+    let (width, height) = (bands.width, bands.height);
+
+    let onthreshold = {
+        shadow_clone!(set_threshold);
+        move |e: InputEvent| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(value) = input.value().parse() {
+                set_threshold.emit(value);
+            }
+        }
+    };
+
+    let attach = onattach.reform(|(file_name, data): (&str, Rc<[u8]>)| {
+        Some(Rc::new(FileDetails {
+            file_name: file_name.to_string(),
+            file_type: "image/png".to_string(),
+            data,
+            georef: None,
+            auxiliary: None,
+            source: None,
+        }))
+    });
+    // The band is only encoded when it is sent.
+    let onattachband = {
+        shadow_clone!(attach, preview);
+        move |_| {
+            if let Some((values, _)) = &*preview {
+                attach.emit(("ndvi.png", band(values, width, height).into()));
+            }
+        }
+    };
+    let onattachmask = {
+        shadow_clone!(attach, mask);
+        move |_| attach.emit(("vegetation.png", mask.clone()))
+    };
+    let ondetach = {
+        shadow_clone!(onattach);
+        move |_| onattach.emit(None)
+    };
+
+    html! {
+        <div class="mt-2">
+            <h3>{"NDVI"}</h3>
+            <div class="d-flex gap-2 mb-2">
+                {band_input(language.t("ndvi-red-band"), &red, bands.count)}
+                {band_input(language.t("ndvi-nir-band"), &nir, bands.count)}
+            </div>
+            <img
+                width={"100%"}
+                src={preview_url.to_string()}
+                alt={language.t("ndvi-preview")}
+            />
+            <div class="input-group input-group-sm my-2">
+                <span class="input-group-text">{language.t_args("ndvi-threshold", &[("threshold", &format!("{:.2}", *threshold))])}</span>
+                <input
+                    type="range"
+                    class="form-range form-control"
+                    min="-1"
+                    max="1"
+                    step="0.05"
+                    value={threshold.to_string()}
+                    oninput={onthreshold}
+                />
+            </div>
+            <img
+                width={"100%"}
+                src={mask_url.to_string()}
+                alt={language.t("ndvi-mask")}
+            />
+            <p>
+                {language.t_args(
+                    "ndvi-vegetated",
+                    &[(
+                        "percent",
+                        &format!("{:.1}", 100.0 * *vegetated as f64 / values.len().max(1) as f64),
+                    )],
+                )}
+            </p>
+            <div class="btn-group btn-group-sm">
+                <button class="btn btn-outline-success" onclick={onattachband}>
+                    {language.t("ndvi-send-band")}
+                </button>
+                <button class="btn btn-outline-success" onclick={onattachmask}>
+                    {language.t("ndvi-send-mask")}
+                </button>
+                if file.auxiliary.is_some() {
+                    <button class="btn btn-outline-secondary" onclick={ondetach}>
+                        {language.t("ndvi-send-image")}
+                    </button>
+                }
+            </div>
+        </div>
+    }
+}
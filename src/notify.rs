@@ -0,0 +1,215 @@
+//! Messages for the user, shown as toasts in a corner of the page. Any module can push
+//! them; they are logged to the console as well.
+
+use gloo::timers::callback::Timeout;
+use shadow_clone::shadow_clone;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use crate::i18n::{use_language, Language};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn class(self) -> &'static str {
+        match self {
+            Severity::Info => "text-bg-info",
+            Severity::Success => "text-bg-success",
+            Severity::Warning => "text-bg-warning",
+            Severity::Error => "text-bg-danger",
+        }
+    }
+
+    fn title(self, language: Language) -> String {
+        language.t(match self {
+            Severity::Info => "notify-info",
+            Severity::Success => "notify-success",
+            Severity::Warning => "notify-warning",
+            Severity::Error => "notify-error",
+        })
+    }
+
+    /// How long toasts of this severity stay up, in milliseconds.
+    fn duration(self) -> u32 {
+        match self {
+            Severity::Info | Severity::Success => 5_000,
+            Severity::Warning => 10_000,
+            Severity::Error => 20_000,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Notification {
+    id: u32,
+    pub severity: Severity,
+    pub message: String,
+    /// Technical detail, shown on request.
+    pub detail: Option<String>,
+}
+
+thread_local! {
+    static NEXT_ID: Cell<u32> = const { Cell::new(0) };
+    static LISTENER: RefCell<Option<Callback<Notification>>> = const { RefCell::new(None) };
+    // Notifications pushed before the toasts were shown, handed over once they are.
+    static PENDING: RefCell<Vec<Notification>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Shows `message` to the user, with `detail` behind a toggle.
+pub fn notify(severity: Severity, message: impl Into<String>, detail: Option<String>) {
+    let message = message.into();
+    match (severity, &detail) {
+        (Severity::Error, Some(detail)) => log::error!("{message}: {detail}"),
+        (Severity::Error, None) => log::error!("{message}"),
+        (Severity::Warning, Some(detail)) => log::warn!("{message}: {detail}"),
+        (Severity::Warning, None) => log::warn!("{message}"),
+        (_, Some(detail)) => log::info!("{message}: {detail}"),
+        (_, None) => log::info!("{message}"),
+    }
+    let notification = Notification {
+        id: NEXT_ID.with(|id| id.replace(id.get() + 1)),
+        severity,
+        message,
+        detail,
+    };
+    let listener = LISTENER.with(|listener| listener.borrow().clone());
+    match listener {
+        Some(listener) => listener.emit(notification),
+        None => PENDING.with(|pending| pending.borrow_mut().push(notification)),
+    }
+}
+
+pub fn error(message: impl Into<String>, detail: impl ToString) {
+    notify(Severity::Error, message, Some(detail.to_string()));
+}
+
+pub fn warn(message: impl Into<String>, detail: impl ToString) {
+    notify(Severity::Warning, message, Some(detail.to_string()));
+}
+
+pub fn info(message: impl Into<String>) {
+    notify(Severity::Info, message, None);
+}
+
+pub fn success(message: impl Into<String>) {
+    notify(Severity::Success, message, None);
+}
+
+enum ToastAction {
+    Push(Notification),
+    Dismiss(u32),
+}
+
+#[derive(Default, PartialEq)]
+struct Toasts(Vec<Notification>);
+
+impl Reducible for Toasts {
+    type Action = ToastAction;
+
+    fn reduce(self: Rc<Self>, action: ToastAction) -> Rc<Self> {
+        let mut toasts = self.0.clone();
+        match action {
+            ToastAction::Push(notification) => toasts.push(notification),
+            ToastAction::Dismiss(id) => toasts.retain(|toast| toast.id != id),
+        }
+        Rc::new(Toasts(toasts))
+    }
+}
+
+/// Every pending notification, newest last, each dismissed after a while unless its
+/// detail is open.
+#[function_component(NotificationToasts)]
+pub fn notification_toasts() -> Html {
+    let toasts = use_reducer(Toasts::default);
+
+    {
+        shadow_clone!(toasts);
+        use_effect_with((), move |_| {
+            let push = {
+                shadow_clone!(toasts);
+                Callback::from(move |notification| toasts.dispatch(ToastAction::Push(notification)))
+            };
+            for notification in PENDING.with(|pending| pending.take()) {
+                push.emit(notification);
+            }
+            LISTENER.with(|listener| *listener.borrow_mut() = Some(push));
+            || LISTENER.with(|listener| *listener.borrow_mut() = None)
+        });
+    }
+
+    let ondismiss = {
+        shadow_clone!(toasts);
+        Callback::from(move |id| toasts.dispatch(ToastAction::Dismiss(id)))
+    };
+
+    html! {
+        <div class="toast-container position-fixed bottom-0 end-0 p-3" aria-live="polite">
+            {
+                for toasts.0.iter().map(|notification| html! {
+                    <Toast
+                        key={notification.id}
+                        notification={notification.clone()}
+                        ondismiss={ondismiss.clone()}
+                    />
+                })
+            }
+        </div>
+    }
+}
+
+#[autoprops_component(Toast)]
+fn toast(notification: Notification, ondismiss: Callback<u32>) -> Html {
+    let language = use_language();
+    let expanded = use_state(|| false);
+    let id = notification.id;
+
+    {
+        shadow_clone!(ondismiss);
+        let duration = notification.severity.duration();
+        use_effect_with(*expanded, move |expanded| {
+            let timeout = (!*expanded).then(|| Timeout::new(duration, move || ondismiss.emit(id)));
+            move || drop(timeout)
+        });
+    }
+
+    let onclose = {
+        shadow_clone!(ondismiss);
+        move |_| ondismiss.emit(id)
+    };
+    let ontoggle = {
+        shadow_clone!(expanded);
+        move |_| expanded.set(!*expanded)
+    };
+
+    html! {
+        <div class={classes!("toast", "show", notification.severity.class())} role="alert">
+            <div class="toast-header">
+                <strong class="me-auto">{notification.severity.title(language)}</strong>
+                <button type="button" class="btn-close" aria-label={language.t("notify-close")} onclick={onclose}></button>
+            </div>
+            <div class="toast-body">
+                {&notification.message}
+                if let Some(detail) = &notification.detail {
+                    <div>
+                        <button class="btn btn-link btn-sm p-0 text-reset" onclick={ontoggle}>
+                            {language.t(if *expanded { "notify-hide-details" } else { "notify-show-details" })}
+                        </button>
+                    </div>
+                    if *expanded {
+                        <pre class="small mb-0 text-wrap">{detail}</pre>
+                    }
+                }
+            </div>
+        </div>
+    }
+}
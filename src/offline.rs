@@ -0,0 +1,431 @@
+//! Working without a connection: the service worker caching the app, and the outbox of
+//! segmentation requests made while offline.
+
+use futures::{stream, Stream, StreamExt};
+use gloo::{events::EventListener, timers::callback::Interval};
+use js_sys::{Object, Reflect};
+use shadow_clone::shadow_clone;
+use std::{cell::Cell, pin::pin, rc::Rc};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    api::{use_api, RequestControl, SegmentationApi},
+    audit::{self, AuditAction},
+    clock,
+    history::{self, file_from_js, file_to_js, get, set, HistoryEntry},
+    i18n::{self, use_language},
+    idb, notify,
+    postprocess::{self, PipelineStep},
+    settings::Settings,
+    storage::format_size,
+    FileDetails,
+};
+
+/// Registers `service-worker.js`, which caches the app so it still loads offline.
+pub fn register_service_worker() {
+    let container = gloo::utils::window().navigator().service_worker();
+    let promise = container.register("./service-worker.js");
+    spawn_local(async move {
+        if let Err(e) = JsFuture::from(promise).await {
+            log::warn!("Could not register the service worker: {e:?}");
+        }
+    });
+}
+
+/// How often queued images are sent again while the server cannot be reached although the
+/// browser thinks it is online, as no event tells when such a connection returns.
+pub const RETRY_INTERVAL_MS: u32 = 30_000;
+
+thread_local! {
+    /// Whether the server answered since the app started.
+    static REACHED: Cell<bool> = const { Cell::new(false) };
+    /// Whether the server could not be reached since it last answered.
+    static LOST: Cell<bool> = const { Cell::new(false) };
+}
+
+pub fn is_online() -> bool {
+    gloo::utils::window().navigator().on_line()
+}
+
+/// Whether requests are expected to get through: the browser is online and the server
+/// answered the last request that tried to reach it.
+pub fn is_connected() -> bool {
+    is_online() && !LOST.get()
+}
+
+/// Records that the server answered.
+pub fn reached() {
+    REACHED.set(true);
+    LOST.set(false);
+}
+
+/// Records that a request could not reach the server, and answers whether the connection is
+/// to blame rather than the settings.
+pub fn unreachable() -> bool {
+    let lost = connection_lost(is_online(), REACHED.get());
+    LOST.set(LOST.get() || lost);
+    lost
+}
+
+/// Whether a request that could not reach the server failed because the connection is down:
+/// the browser says it is offline, or the same server answered before. Browsers on flaky
+/// mobile networks often still say they are online.
+fn connection_lost(online: bool, reached_before: bool) -> bool {
+    !online || reached_before
+}
+
+/// Keeps `image` to be segmented once the app is back online.
+pub async fn queue(image: &FileDetails) -> Result<(), String> {
+    let object = Object::new();
+    set(&object, "queued", clock::now().into());
+    set(&object, "image", file_to_js(image));
+    if let Some(auxiliary) = &image.auxiliary {
+        set(&object, "auxiliary", file_to_js(auxiliary));
+    }
+    let db = idb::open().await?;
+    idb::put(&db, idb::OUTBOX_STORE, &object.into())
+        .await
+        .map(|_| ())
+}
+
+/// An image waiting in the outbox.
+struct Queued {
+    key: JsValue,
+    image: FileDetails,
+    /// Why the last attempt to send the image failed, if it was sent already.
+    error: Option<String>,
+}
+
+/// Queued images, oldest first.
+async fn queued() -> Result<Vec<Queued>, String> {
+    let db = idb::open().await?;
+    Ok(idb::get_all(&db, idb::OUTBOX_STORE)
+        .await?
+        .iter()
+        .filter_map(|value| {
+            let mut image = file_from_js(&get(&value, "image"))?;
+            image.auxiliary = file_from_js(&get(&value, "auxiliary")).map(Rc::new);
+            Some(Queued {
+                key: get(&value, "id"),
+                image,
+                error: get(&value, "error").as_string(),
+            })
+        })
+        .collect())
+}
+
+/// Keeps why sending the queued image under `key` failed, so that it can be retried later
+/// from the bytes already read.
+async fn mark_failed(key: &JsValue, error: &str) -> Result<(), String> {
+    let db = idb::open().await?;
+    let object = idb::get(&db, idb::OUTBOX_STORE, key).await?;
+    Reflect::set(&object, &"error".into(), &error.into())
+        .map_err(|e| format!("Could not mark a queued image as failed: {e:?}"))?;
+    idb::put(&db, idb::OUTBOX_STORE, &object).await.map(|_| ())
+}
+
+/// `images` segmented and their masks post-processed by `steps`, with up to `limit` requests
+/// sent at once, so that the server already works on the next images while an answer is
+/// handled. Answers, masks or why there are none, come in the order of `images`.
+fn segment_all<'a, K: 'a>(
+    api: &'a dyn SegmentationApi,
+    images: Vec<(K, FileDetails)>,
+    steps: &'a [PipelineStep],
+    limit: usize,
+) -> impl Stream<Item = (K, FileDetails, Result<FileDetails, String>)> + 'a {
+    stream::iter(images)
+        .map(move |(key, image)| async move {
+            let mask = async {
+                let response = api
+                    .segment(image.clone(), RequestControl::default())
+                    .await?;
+                postprocess::apply(steps, response.mask)
+            }
+            .await;
+            (key, image, mask)
+        })
+        .buffered(limit.max(1))
+}
+
+/// Segments the queued images, saving the results to the history, along with those that
+/// failed before when `retry_failed`. Images that fail, whether segmenting or saving them,
+/// stay queued with their error without stopping the others. Returns how many were sent and
+/// how many failed.
+async fn submit_queued(
+    api: &dyn SegmentationApi,
+    settings: &Settings,
+    retry_failed: bool,
+) -> Result<(usize, usize), String> {
+    let (mut sent, mut failed) = (0, 0);
+    let images = queued()
+        .await?
+        .into_iter()
+        .filter(|queued| retry_failed || queued.error.is_none())
+        .map(|queued| (queued.key, queued.image))
+        .collect();
+    let limit = settings.parallel_requests as usize;
+    let mut answers = pin!(segment_all(api, images, &settings.postprocess, limit));
+    while let Some((key, image, mask)) = answers.next().await {
+        let saved = match mask {
+            Ok(mask) => {
+                reached();
+                save(settings, &key, image, mask).await
+            }
+            Err(why) => Err(why),
+        };
+        match saved {
+            Ok(()) => sent += 1,
+            Err(why) => {
+                if let Err(e) = mark_failed(&key, &why).await {
+                    log::warn!("Could not keep why queued image {key:?} failed: {e}");
+                }
+                failed += 1;
+            }
+        }
+    }
+    history::prune(settings).await?;
+    Ok((sent, failed))
+}
+
+/// Saves `mask` of `image` to the history and takes the image, queued under `key`, out of
+/// the outbox.
+async fn save(
+    settings: &Settings,
+    key: &JsValue,
+    image: FileDetails,
+    mask: FileDetails,
+) -> Result<(), String> {
+    let (file_name, size) = (image.file_name.clone(), image.data.len() as u64);
+    let entry = HistoryEntry::new(Rc::new(image), Rc::new(mask), settings.segment_url());
+    let result = history::add(&entry).await?;
+    audit::record(
+        AuditAction::Upload,
+        file_name,
+        format!(
+            "{} queued while offline, result {result}",
+            format_size(size)
+        ),
+    );
+    let db = idb::open().await?;
+    idb::delete(&db, idb::OUTBOX_STORE, key).await
+}
+
+/// What became of sending queued images, as told once they were all sent.
+fn summary(sent: usize, failed: usize) -> String {
+    match (sent, failed) {
+        (sent, 0) => i18n::t_args("offline-sent", &[("sent", &sent)]),
+        (0, failed) => i18n::t_args("offline-all-failed", &[("failed", &failed)]),
+        (sent, failed) => i18n::t_args(
+            "offline-some-failed",
+            &[("sent", &sent), ("failed", &failed)],
+        ),
+    }
+}
+
+/// Connection status and queued requests, sent automatically when the connection returns,
+/// with those that failed kept to be retried. The count is reloaded whenever `revision`
+/// changes.
+#[autoprops_component(OutboxBanner)]
+pub fn outbox_banner(revision: i32, onsubmitted: Callback<()>) -> Html {
+    let language = use_language();
+    let settings = use_context::<Rc<Settings>>().unwrap_or_default();
+    let api = use_api();
+    let online = use_state(is_connected);
+    let count = use_state(|| 0);
+    // The names of the queued images that failed, and why.
+    let failures = use_state(Vec::<(String, String)>::new);
+    let sending = use_state(|| false);
+
+    let reload = {
+        shadow_clone!(online, count, failures);
+        move || {
+            shadow_clone!(count, failures);
+            online.set(is_connected());
+            spawn_local(async move {
+                match queued().await {
+                    Ok(queued) => {
+                        count.set(queued.len());
+                        failures.set(
+                            queued
+                                .into_iter()
+                                .filter_map(|q| Some((q.image.file_name, q.error?)))
+                                .collect(),
+                        );
+                    }
+                    Err(why) => log::warn!("Could not read the outbox: {why}"),
+                }
+            });
+        }
+    };
+
+    let submit = {
+        shadow_clone!(settings, api, sending, reload, onsubmitted);
+        move |retry_failed: bool| {
+            shadow_clone!(settings, api, sending, reload, onsubmitted);
+            sending.set(true);
+            spawn_local(async move {
+                match submit_queued(&*api, &settings, retry_failed).await {
+                    Ok((sent, failed)) => {
+                        if sent > 0 {
+                            onsubmitted.emit(());
+                        }
+                        if failed > 0 {
+                            notify::warn(summary(sent, failed), language.t("offline-retry-hint"));
+                        } else if sent > 0 {
+                            notify::success(summary(sent, failed));
+                        }
+                    }
+                    Err(why) => notify::warn(language.t("offline-send-failed"), why),
+                }
+                sending.set(false);
+                reload();
+            });
+        }
+    };
+
+    {
+        shadow_clone!(reload);
+        use_effect_with(revision, move |_| reload());
+    }
+
+    {
+        shadow_clone!(online, submit);
+        use_effect_with((), move |_| {
+            if is_online() {
+                submit(false);
+            }
+            let window = gloo::utils::window();
+            let ononline = {
+                shadow_clone!(online);
+                // Images that failed while the connection was down are worth another try.
+                EventListener::new(&window, "online", move |_| {
+                    online.set(true);
+                    notify::info(i18n::t("offline-back-online"));
+                    submit(true);
+                })
+            };
+            let onoffline = EventListener::new(&window, "offline", move |_| online.set(false));
+            move || drop((ononline, onoffline))
+        });
+    }
+
+    {
+        shadow_clone!(submit);
+        use_effect_with((*online, *count), move |&(online, count)| {
+            // The browser says it is online but the server cannot be reached, so no event
+            // will tell when to send the queued images.
+            let retrying = (!online && is_online() && count > 0)
+                .then(|| Interval::new(RETRY_INTERVAL_MS, move || submit(true)));
+            move || drop(retrying)
+        });
+    }
+
+    if *online && *count == 0 {
+        return html!();
+    }
+
+    let failed = failures.len();
+    let waiting = *count - failed;
+    let onsend = {
+        shadow_clone!(submit);
+        move |_| submit(false)
+    };
+    let onretry = move |_| submit(true);
+
+    html! {
+        <div class="alert alert-warning d-flex flex-wrap align-items-center gap-2">
+            if !*online {
+                <strong>{language.t("offline")}</strong>
+            }
+            if *count > 0 {
+                if waiting > 0 {
+                    {language.t_args("offline-queued", &[("count", &waiting)])}
+                }
+                if failed > 0 {
+                    <details>
+                        <summary>{language.t_args("offline-failed", &[("count", &failed)])}</summary>
+                        <ul class="mb-0 small">
+                            {for failures.iter().map(|(name, error)| html! {
+                                <li><strong>{name}</strong>{": "}{error}</li>
+                            })}
+                        </ul>
+                    </details>
+                }
+                if *sending {
+                    <span class="spinner-border spinner-border-sm"></span>
+                } else if *online {
+                    if waiting > 0 {
+                        <button class="btn btn-sm btn-outline-dark" onclick={onsend}>
+                            {language.t("offline-send-now")}
+                        </button>
+                    }
+                    if failed > 0 {
+                        <button class="btn btn-sm btn-outline-dark" onclick={onretry}>
+                            {language.t("offline-retry-failed")}
+                        </button>
+                    }
+                } else {
+                    {language.t("offline-sent-later")}
+                }
+            } else {
+                {language.t("offline-queue-hint")}
+            }
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use super::{connection_lost, segment_all, summary};
+    use crate::{
+        api::mock::{block_on, MockApi},
+        error::AppError,
+        fixtures,
+        schema::SegmentResponse,
+    };
+
+    #[test]
+    fn answers_come_in_turn_failed_or_not() {
+        let answer = |name| {
+            Ok(SegmentResponse {
+                mask: fixtures::png(name, fixtures::MASK),
+                result_id: None,
+            })
+        };
+        let api = MockApi::answering([
+            answer("0"),
+            Err(AppError::Offline(String::new())),
+            answer("2"),
+        ]);
+        let images = (0..3).map(|i| (i, fixtures::png(&i.to_string(), fixtures::IMAGE)));
+
+        let answers = block_on(segment_all(&api, images.collect(), &[], 2).collect::<Vec<_>>());
+
+        let keys: Vec<_> = answers
+            .iter()
+            .map(|(key, _, mask)| (*key, mask.is_ok()))
+            .collect();
+        assert_eq!(keys, [(0, true), (1, false), (2, true)]);
+        assert_eq!(*api.sent.borrow(), ["0", "1", "2"]);
+    }
+
+    #[test]
+    fn unreachable_servers_mean_no_connection_once_they_answered() {
+        assert!(connection_lost(false, false));
+        assert!(connection_lost(true, true));
+        // A server never reached may as well be misconfigured.
+        assert!(!connection_lost(true, false));
+    }
+
+    #[test]
+    fn summaries_tell_successes_from_failures() {
+        assert_eq!(summary(3, 0), "Segmented 3 queued images");
+        assert_eq!(summary(47, 3), "47 queued images succeeded, 3 failed");
+        assert_eq!(summary(0, 2), "All 2 queued images failed");
+    }
+}
@@ -0,0 +1,65 @@
+//! Links to results stored by the server, opened from a `?result=<id>` URL.
+
+use shadow_clone::shadow_clone;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::UrlSearchParams;
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+
+use crate::{i18n::use_language, notify};
+
+const RESULT_PARAM: &str = "result";
+
+/// Id of the result the page was opened for, if any.
+pub fn result_id() -> Option<String> {
+    let search = gloo::utils::window().location().search().ok()?;
+    UrlSearchParams::new_with_str(&search)
+        .ok()?
+        .get(RESULT_PARAM)
+        .filter(|id| !id.is_empty())
+}
+
+/// Address of this page opening the result stored as `result_id`.
+pub fn link(result_id: &str) -> String {
+    let location = gloo::utils::window().location();
+    format!(
+        "{}{}?{RESULT_PARAM}={}",
+        location.origin().unwrap_or_default(),
+        location.pathname().unwrap_or_default(),
+        String::from(js_sys::encode_uri_component(result_id))
+    )
+}
+
+#[autoprops_component(CopyLinkButton)]
+pub fn copy_link_button(result_id: AttrValue) -> Html {
+    let language = use_language();
+    let copied = use_state(|| false);
+
+    {
+        shadow_clone!(copied);
+        use_effect_with(result_id.clone(), move |_| copied.set(false));
+    }
+
+    let onclick = {
+        shadow_clone!(copied, result_id);
+        move |_| {
+            let promise = gloo::utils::window()
+                .navigator()
+                .clipboard()
+                .write_text(&link(&result_id));
+            shadow_clone!(copied);
+            spawn_local(async move {
+                match JsFuture::from(promise).await {
+                    Ok(_) => copied.set(true),
+                    Err(e) => notify::warn(language.t("permalink-copy-failed"), format!("{e:?}")),
+                }
+            });
+        }
+    };
+
+    html! {
+        <button class="btn btn-sm btn-outline-secondary mb-2" {onclick}>
+            {language.t(if *copied { "permalink-copied" } else { "permalink-copy" })}
+        </button>
+    }
+}
@@ -0,0 +1,493 @@
+//! Clean-up steps applied on the client to every mask the server answers with, before it
+//! is shown or saved. Each step is a [`PostProcessor`]; which ones run, and in what order,
+//! is part of the settings.
+
+use image::{DynamicImage, GrayImage, ImageOutputFormat};
+use serde::{Deserialize, Serialize};
+use shadow_clone::shadow_clone;
+use std::{io::Cursor, rc::Rc};
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    diff::{decode_mask, label_regions},
+    i18n::{use_language, Language},
+    raster,
+    settings::Settings,
+    FileDetails,
+};
+
+/// A step of the pipeline, changing a mask of class values in place.
+pub trait PostProcessor {
+    /// The step and its parameters, as listed in the pipeline.
+    fn describe(&self, language: Language) -> String;
+    fn apply(&self, mask: &mut GrayImage);
+}
+
+/// Turns the mask into a binary one: 255 from `level` up, 0 below.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Threshold {
+    pub level: u8,
+}
+
+impl PostProcessor for Threshold {
+    fn describe(&self, language: Language) -> String {
+        language.t_args("postprocess-threshold-at", &[("level", &self.level)])
+    }
+
+    fn apply(&self, mask: &mut GrayImage) {
+        raster::threshold(mask, self.level);
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MorphologyOp {
+    /// Removes specks and thin protrusions.
+    Open,
+    /// Fills small holes and gaps.
+    Close,
+}
+
+impl MorphologyOp {
+    fn label(self, language: Language) -> String {
+        language.t(match self {
+            MorphologyOp::Open => "postprocess-opening",
+            MorphologyOp::Close => "postprocess-closing",
+        })
+    }
+}
+
+/// Opening or closing over a square of `radius` pixels around every pixel. Higher class
+/// values count as foreground, so it suits binary masks best.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Morphology {
+    pub op: MorphologyOp,
+    pub radius: u32,
+}
+
+impl PostProcessor for Morphology {
+    fn describe(&self, language: Language) -> String {
+        language.t_args(
+            "postprocess-morphology-with",
+            &[("op", &self.op.label(language)), ("radius", &self.radius)],
+        )
+    }
+
+    fn apply(&self, mask: &mut GrayImage) {
+        type Filter = fn(&[u8], usize, usize) -> Vec<u8>;
+        let (first, then): (Filter, Filter) = match self.op {
+            MorphologyOp::Open => (raster::erode, raster::dilate),
+            MorphologyOp::Close => (raster::dilate, raster::erode),
+        };
+        let (width, radius) = (mask.width() as usize, self.radius as usize);
+        let filtered = then(&first(mask, width, radius), width, radius);
+        mask.copy_from_slice(&filtered);
+    }
+}
+
+/// Sets regions of fewer than `min_pixels` pixels to the background class.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct RemoveSmallRegions {
+    pub min_pixels: u32,
+}
+
+impl PostProcessor for RemoveSmallRegions {
+    fn describe(&self, language: Language) -> String {
+        language.t_args("postprocess-regions-under", &[("pixels", &self.min_pixels)])
+    }
+
+    fn apply(&self, mask: &mut GrayImage) {
+        let (labels, classes) = label_regions(mask);
+        let mut sizes = vec![0u32; classes.len()];
+        for label in &labels {
+            sizes[*label as usize] += 1;
+        }
+        for (value, label) in mask.iter_mut().zip(labels) {
+            if sizes[label as usize] < self.min_pixels {
+                *value = 0;
+            }
+        }
+    }
+}
+
+/// Gives the pixels of class `from` class `to` instead.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Recolor {
+    pub from: u8,
+    pub to: u8,
+}
+
+impl PostProcessor for Recolor {
+    fn describe(&self, language: Language) -> String {
+        language.t_args(
+            "postprocess-class-becomes",
+            &[("from", &self.from), ("to", &self.to)],
+        )
+    }
+
+    fn apply(&self, mask: &mut GrayImage) {
+        raster::recolor(mask, self.from, self.to);
+    }
+}
+
+/// Every kind of step, as stored in the settings. New steps are added here.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Step {
+    Threshold(Threshold),
+    Morphology(Morphology),
+    RemoveSmallRegions(RemoveSmallRegions),
+    Recolor(Recolor),
+}
+
+impl Step {
+    /// One step of every kind, with the parameters they start with when added.
+    fn defaults() -> [Step; 4] {
+        [
+            Step::Threshold(Threshold { level: 128 }),
+            Step::Morphology(Morphology {
+                op: MorphologyOp::Open,
+                radius: 1,
+            }),
+            Step::RemoveSmallRegions(RemoveSmallRegions { min_pixels: 16 }),
+            Step::Recolor(Recolor { from: 1, to: 0 }),
+        ]
+    }
+
+    fn name(&self, language: Language) -> String {
+        language.t(match self {
+            Step::Threshold(_) => "postprocess-threshold",
+            Step::Morphology(_) => "postprocess-morphology",
+            Step::RemoveSmallRegions(_) => "postprocess-remove-small-regions",
+            Step::Recolor(_) => "postprocess-recolor",
+        })
+    }
+
+    fn processor(&self) -> &dyn PostProcessor {
+        match self {
+            Step::Threshold(step) => step,
+            Step::Morphology(step) => step,
+            Step::RemoveSmallRegions(step) => step,
+            Step::Recolor(step) => step,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct PipelineStep {
+    pub enabled: bool,
+    pub step: Step,
+}
+
+/// `mask` after the enabled `steps`, in order, as a PNG. Returned as is when no step is
+/// enabled.
+pub fn apply(steps: &[PipelineStep], mask: FileDetails) -> Result<FileDetails, String> {
+    let mut enabled = steps.iter().filter(|step| step.enabled).peekable();
+    if enabled.peek().is_none() {
+        return Ok(mask);
+    }
+    let mut image = decode_mask(&mask.data)?;
+    for step in enabled {
+        step.step.processor().apply(&mut image);
+    }
+    let mut png = vec![];
+    DynamicImage::ImageLuma8(image)
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .map_err(|e| format!("Could not encode the post-processed mask: {e}"))?;
+    let stem = mask
+        .file_name
+        .rsplit_once('.')
+        .map_or(mask.file_name.as_str(), |(stem, _)| stem);
+    Ok(FileDetails {
+        file_name: format!("{stem}.png"),
+        file_type: "image/png".to_string(),
+        data: png.into(),
+        ..mask
+    })
+}
+
+/// Inputs for the parameters of `step`, emitting it changed through `onchange`.
+fn step_parameters(language: Language, step: &Step, onchange: Callback<Step>) -> Html {
+    let input = |label: &str, value: u32, max: u32, set: fn(&mut Step, u32)| {
+        let onchange = {
+            shadow_clone!(step, onchange);
+            move |e: Event| {
+                let input: HtmlInputElement = e.target_unchecked_into();
+                if let Ok(value) = input.value().parse::<u32>() {
+                    let mut step = step.clone();
+                    set(&mut step, value.min(max));
+                    onchange.emit(step);
+                }
+            }
+        };
+        html! {
+            <>
+                <span class="input-group-text">{language.t(label)}</span>
+                <input
+                    type="number"
+                    class="form-control"
+                    min="0"
+                    max={max.to_string()}
+                    value={value.to_string()}
+                    {onchange}
+                />
+            </>
+        }
+    };
+
+    match step {
+        Step::Threshold(Threshold { level }) => {
+            input("postprocess-level", (*level).into(), 255, |step, v| {
+                if let Step::Threshold(s) = step {
+                    s.level = v as u8;
+                }
+            })
+        }
+        Step::Morphology(Morphology { op, radius }) => {
+            let onop = {
+                shadow_clone!(step, onchange);
+                move |e: Event| {
+                    let select: HtmlSelectElement = e.target_unchecked_into();
+                    if let Step::Morphology(mut morphology) = step.clone() {
+                        morphology.op = match select.value().as_str() {
+                            "close" => MorphologyOp::Close,
+                            _ => MorphologyOp::Open,
+                        };
+                        onchange.emit(Step::Morphology(morphology));
+                    }
+                }
+            };
+            html! {
+                <>
+                    <select class="form-select" onchange={onop}>
+                        <option value="open" selected={*op == MorphologyOp::Open}>{MorphologyOp::Open.label(language)}</option>
+                        <option value="close" selected={*op == MorphologyOp::Close}>{MorphologyOp::Close.label(language)}</option>
+                    </select>
+                    {input("postprocess-radius", *radius, 16, |step, v| {
+                        if let Step::Morphology(s) = step {
+                            s.radius = v;
+                        }
+                    })}
+                </>
+            }
+        }
+        Step::RemoveSmallRegions(RemoveSmallRegions { min_pixels }) => input(
+            "postprocess-minimum-pixels",
+            *min_pixels,
+            u32::MAX,
+            |step, v| {
+                if let Step::RemoveSmallRegions(s) = step {
+                    s.min_pixels = v;
+                }
+            },
+        ),
+        Step::Recolor(Recolor { from, to }) => html! {
+            <>
+                {input("postprocess-from", (*from).into(), 255, |step, v| {
+                    if let Step::Recolor(s) = step {
+                        s.from = v as u8;
+                    }
+                })}
+                {input("postprocess-to", (*to).into(), 255, |step, v| {
+                    if let Step::Recolor(s) = step {
+                        s.to = v as u8;
+                    }
+                })}
+            </>
+        },
+    }
+}
+
+/// The post-processing steps of `settings`, to be turned on or off, reordered, tuned,
+/// removed and added to.
+#[autoprops_component(PostProcessPane)]
+pub fn post_process_pane(settings: Rc<Settings>, onchange: Callback<Rc<Settings>>) -> Html {
+    let language = use_language();
+    let update = {
+        shadow_clone!(settings, onchange);
+        move |change: &dyn Fn(&mut Vec<PipelineStep>)| {
+            let mut new_settings = (*settings).clone();
+            change(&mut new_settings.postprocess);
+            onchange.emit(Rc::new(new_settings));
+        }
+    };
+    let steps = &settings.postprocess;
+
+    html! {
+        <div style="max-width: 40em;">
+            if steps.is_empty() {
+                <p class="text-body-secondary">{language.t("postprocess-none")}</p>
+            }
+            <ul class="list-group mb-2">
+            {
+                for steps.iter().enumerate().map(|(i, step)| {
+                    let ontoggle = {
+                        shadow_clone!(update);
+                        move |_| update(&|steps| steps[i].enabled = !steps[i].enabled)
+                    };
+                    let onparameters = {
+                        shadow_clone!(update);
+                        Callback::from(move |new_step: Step| {
+                            update(&|steps| steps[i].step = new_step.clone())
+                        })
+                    };
+                    let onup = {
+                        shadow_clone!(update);
+                        move |_| update(&|steps| steps.swap(i - 1, i))
+                    };
+                    let ondown = {
+                        shadow_clone!(update);
+                        move |_| update(&|steps| steps.swap(i, i + 1))
+                    };
+                    let onremove = {
+                        shadow_clone!(update);
+                        move |_| update(&|steps| { steps.remove(i); })
+                    };
+                    html! {
+                        <li class="list-group-item">
+                            <div class="d-flex justify-content-between align-items-center">
+                                <span>
+                                    <input
+                                        type="checkbox"
+                                        class="form-check-input me-2"
+                                        title={language.t("postprocess-apply")}
+                                        checked={step.enabled}
+                                        onchange={ontoggle}
+                                    />
+                                    <strong>{step.step.name(language)}</strong>
+                                    <small class="text-body-secondary ms-2">
+                                        {step.step.processor().describe(language)}
+                                    </small>
+                                </span>
+                                <div class="btn-group btn-group-sm">
+                                    <button class="btn btn-outline-secondary" disabled={i == 0} onclick={onup}>
+                                        {"↑"}
+                                    </button>
+                                    <button
+                                        class="btn btn-outline-secondary"
+                                        disabled={i + 1 == steps.len()}
+                                        onclick={ondown}
+                                    >
+                                        {"↓"}
+                                    </button>
+                                    <button class="btn btn-outline-danger" onclick={onremove}>{language.t("postprocess-remove")}</button>
+                                </div>
+                            </div>
+                            <div class="input-group input-group-sm mt-1">
+                                {step_parameters(language, &step.step, onparameters)}
+                            </div>
+                        </li>
+                    }
+                })
+            }
+            </ul>
+            <div class="btn-group btn-group-sm">
+            {
+                for Step::defaults().into_iter().map(|step| {
+                    let name = step.name(language);
+                    let onclick = {
+                        shadow_clone!(update);
+                        move |_| {
+                            update(&|steps| steps.push(PipelineStep {
+                                enabled: true,
+                                step: step.clone(),
+                            }))
+                        }
+                    };
+                    html! {
+                        <button class="btn btn-outline-primary" {onclick}>{format!("+ {name}")}</button>
+                    }
+                })
+            }
+            </div>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::GrayImage;
+
+    use super::*;
+    use crate::fixtures;
+
+    fn mask() -> GrayImage {
+        decode_mask(fixtures::MASK).unwrap()
+    }
+
+    fn count(mask: &GrayImage, class: u8) -> usize {
+        mask.iter().filter(|value| **value == class).count()
+    }
+
+    #[test]
+    fn threshold_makes_binary_masks() {
+        let mut mask = mask();
+        Threshold { level: 2 }.apply(&mut mask);
+        assert_eq!(count(&mask, 255), 1);
+        assert_eq!(count(&mask, 0), 63);
+    }
+
+    #[test]
+    fn opening_removes_specks_and_keeps_larger_shapes() {
+        let mut mask = mask();
+        Morphology {
+            op: MorphologyOp::Open,
+            radius: 1,
+        }
+        .apply(&mut mask);
+        assert_eq!(count(&mask, 2), 0);
+        assert_eq!(count(&mask, 1), 16);
+    }
+
+    #[test]
+    fn small_regions_become_background() {
+        let mut mask = mask();
+        RemoveSmallRegions { min_pixels: 2 }.apply(&mut mask);
+        assert_eq!(mask, decode_mask(fixtures::MASK_CORRECTED).unwrap());
+    }
+
+    #[test]
+    fn recolor_changes_one_class() {
+        let mut mask = mask();
+        Recolor { from: 1, to: 3 }.apply(&mut mask);
+        assert_eq!(count(&mask, 1), 0);
+        assert_eq!(count(&mask, 3), 16);
+        assert_eq!(count(&mask, 2), 1);
+    }
+
+    #[test]
+    fn masks_are_left_alone_without_enabled_steps() {
+        let steps = [PipelineStep {
+            enabled: false,
+            step: Step::Recolor(Recolor { from: 1, to: 3 }),
+        }];
+        let mask = fixtures::png("mask.tif", fixtures::MASK);
+        let processed = apply(&steps, mask.clone()).unwrap();
+        assert!(processed == mask);
+    }
+
+    #[test]
+    fn processed_masks_are_pngs() {
+        let steps = [PipelineStep {
+            enabled: true,
+            step: Step::Recolor(Recolor { from: 1, to: 3 }),
+        }];
+        let processed = apply(&steps, fixtures::png("mask.tif", fixtures::MASK)).unwrap();
+        assert_eq!(processed.file_name, "mask.png");
+        assert_eq!(processed.file_type, "image/png");
+        assert_eq!(count(&decode_mask(&processed.data).unwrap(), 3), 16);
+    }
+
+    #[test]
+    fn pipelines_round_trip_through_the_settings() {
+        let steps = Step::defaults().map(|step| PipelineStep {
+            enabled: true,
+            step,
+        });
+        let json = serde_json::to_string(&steps).unwrap();
+        let read: Vec<PipelineStep> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read, steps);
+    }
+}
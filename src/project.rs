@@ -0,0 +1,219 @@
+//! Saving the current session to a JSON project file and opening it again.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use gloo::{
+    file::{callbacks::FileReader, Blob, File, ObjectUrl},
+    timers::callback::Timeout,
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use shadow_clone::shadow_clone;
+use std::rc::Rc;
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlElement, HtmlInputElement};
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    audit::{self, AuditAction},
+    geo::Georeference,
+    i18n::{self, use_language},
+    notify,
+    settings::Settings,
+    FileDetails,
+};
+
+const VERSION: u32 = 1;
+
+fn serialize_base64<S: Serializer>(data: &[u8], s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&STANDARD.encode(data))
+}
+
+fn deserialize_base64<'de, D: Deserializer<'de>>(d: D) -> Result<Rc<[u8]>, D::Error> {
+    let text = String::deserialize(d)?;
+    STANDARD
+        .decode(text)
+        .map(Rc::from)
+        .map_err(serde::de::Error::custom)
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct ProjectFile {
+    file_name: String,
+    file_type: String,
+    #[serde(
+        serialize_with = "serialize_base64",
+        deserialize_with = "deserialize_base64"
+    )]
+    data: Rc<[u8]>,
+    georef: Option<Georeference>,
+}
+
+impl From<&FileDetails> for ProjectFile {
+    fn from(file: &FileDetails) -> Self {
+        ProjectFile {
+            file_name: file.file_name.clone(),
+            file_type: file.file_type.clone(),
+            data: file.data.clone(),
+            georef: file.georef,
+        }
+    }
+}
+
+impl From<ProjectFile> for FileDetails {
+    fn from(file: ProjectFile) -> Self {
+        FileDetails {
+            file_name: file.file_name,
+            file_type: file.file_type,
+            data: file.data,
+            georef: file.georef,
+            auxiliary: None,
+            source: None,
+        }
+    }
+}
+
+/// Everything needed to pick a session up again: the image, its mask and the settings.
+#[derive(Serialize, Deserialize)]
+pub struct Project {
+    version: u32,
+    image: Option<ProjectFile>,
+    mask: Option<ProjectFile>,
+    settings: Settings,
+}
+
+impl Project {
+    pub fn new(
+        image: Option<&FileDetails>,
+        mask: Option<&FileDetails>,
+        settings: &Settings,
+    ) -> Self {
+        Project {
+            version: VERSION,
+            image: image.map(ProjectFile::from),
+            mask: mask.map(ProjectFile::from),
+            settings: settings.clone(),
+        }
+    }
+
+    pub fn from_json(data: &[u8]) -> Result<Self, String> {
+        let project: Project = serde_json::from_slice(data)
+            .map_err(|e| i18n::t_args("project-invalid", &[("error", &e)]))?;
+        if project.version > VERSION {
+            return Err(i18n::t_args(
+                "project-too-new",
+                &[("version", &project.version)],
+            ));
+        }
+        Ok(project)
+    }
+
+    pub fn image(&self) -> Option<FileDetails> {
+        self.image.clone().map(FileDetails::from)
+    }
+
+    pub fn mask(&self) -> Option<FileDetails> {
+        self.mask.clone().map(FileDetails::from)
+    }
+
+    pub fn image_name(&self) -> Option<&str> {
+        self.image.as_ref().map(|image| image.file_name.as_str())
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+}
+
+/// How long the URL of a download is kept, enough for the browser to have read it.
+const DOWNLOAD_URL_LIFETIME_MS: u32 = 60_000;
+
+/// Offers `data` to the user as a file download, recorded in the audit trail once it was
+/// offered.
+pub fn download(file_name: &str, mime_type: &str, data: &[u8]) {
+    let document = gloo::utils::document();
+    let Ok(link) = document.create_element("a") else {
+        return;
+    };
+    let url = ObjectUrl::from(Blob::new_with_options(data, Some(mime_type)));
+    let _ = link.set_attribute("href", &url);
+    let _ = link.set_attribute("download", file_name);
+    link.unchecked_into::<HtmlElement>().click();
+    audit::record(AuditAction::Export, file_name, mime_type);
+    // The download starts once the click is handled, so the URL is kept a while after it.
+    Timeout::new(DOWNLOAD_URL_LIFETIME_MS, move || drop(url)).forget();
+}
+
+#[autoprops_component(ProjectPane)]
+pub fn project_pane(
+    image: Rc<Option<FileDetails>>,
+    mask: Option<Rc<FileDetails>>,
+    settings: Rc<Settings>,
+    onopen: Callback<Rc<Project>>,
+    #[prop_or_default] onsave: Callback<()>,
+) -> Html {
+    let language = use_language();
+    let reader = use_state(|| None::<FileReader>);
+    let error = use_state(|| None::<String>);
+
+    let onsaveclick = {
+        shadow_clone!(image, mask, settings, onsave);
+        move |_| {
+            let project = Project::new((*image).as_ref(), mask.as_deref(), &settings);
+            match serde_json::to_vec(&project) {
+                Ok(json) => {
+                    let stem = (*image).as_ref().map_or("project", |image| {
+                        image
+                            .file_name
+                            .rsplit_once('.')
+                            .map_or(image.file_name.as_str(), |(stem, _)| stem)
+                    });
+                    download(&format!("{stem}.segproj.json"), "application/json", &json);
+                    onsave.emit(());
+                }
+                Err(e) => notify::error(language.t("project-save-failed"), e),
+            }
+        }
+    };
+
+    let onfile = {
+        shadow_clone!(reader, error, onopen);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let Some(file) = input.files().and_then(|files| files.get(0)) else {
+                return;
+            };
+            shadow_clone!(error, onopen);
+            reader.set(Some(gloo::file::callbacks::read_as_bytes(
+                &File::from(file),
+                move |res| match res
+                    .map_err(|e| language.t_args("project-unreadable", &[("error", &e)]))
+                    .and_then(|data| Project::from_json(&data))
+                {
+                    Ok(project) => {
+                        error.set(None);
+                        onopen.emit(Rc::new(project));
+                    }
+                    Err(why) => error.set(Some(why)),
+                },
+            )));
+        }
+    };
+
+    html! {
+        <div class="mb-3">
+            <div class="d-flex gap-2 align-items-center">
+                <button class="btn btn-sm btn-outline-primary" onclick={onsaveclick}>
+                    {language.t("project-save")}
+                </button>
+                <label class="btn btn-sm btn-outline-primary mb-0">
+                    {language.t("project-open")}
+                    <input type="file" accept=".json" class="d-none" onchange={onfile} />
+                </label>
+            </div>
+            if let Some(why) = &*error {
+                <div class="alert alert-danger mt-2" role="alert">{why}</div>
+            }
+        </div>
+    }
+}
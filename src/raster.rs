@@ -0,0 +1,249 @@
+//! Operations over the raw class values of whole masks, behind the post-processing steps
+//! and class statistics, kept fast enough for large masks to be edited stroke by stroke.
+//! Builds for wasm with `simd128`, enabled in `.cargo/config.toml` and so required of
+//! browsers, take 16 values at a time; other builds, and whatever is left past the last
+//! 16, go one value at a time. The tests run on both, see `.github/workflows/ci.yml`.
+
+/// Each function handles the leading values it can and answers how many, leaving the rest
+/// to the scalar code.
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod lanes {
+    use core::arch::wasm32::*;
+
+    const LANES: usize = 16;
+
+    /// Calls `f` with every whole vector of `values` and answers how many values they hold.
+    fn each(values: &mut [u8], f: impl Fn(v128) -> v128) -> usize {
+        let len = values.len();
+        let mut chunks = values.chunks_exact_mut(LANES);
+        for chunk in &mut chunks {
+            // SAFETY: chunks hold 16 bytes, and v128 loads and stores need no alignment.
+            unsafe {
+                v128_store(
+                    chunk.as_mut_ptr().cast(),
+                    f(v128_load(chunk.as_ptr().cast())),
+                )
+            };
+        }
+        len - chunks.into_remainder().len()
+    }
+
+    pub fn threshold(values: &mut [u8], level: u8) -> usize {
+        let level = u8x16_splat(level);
+        // Comparisons answer 255 in the lanes where they hold and 0 elsewhere.
+        each(values, |v| u8x16_ge(v, level))
+    }
+
+    pub fn recolor(values: &mut [u8], from: u8, to: u8) -> usize {
+        let (from, to) = (u8x16_splat(from), u8x16_splat(to));
+        each(values, |v| v128_bitselect(to, v, u8x16_eq(v, from)))
+    }
+
+    fn combine(dst: &mut [u8], src: &[u8], pick: fn(v128, v128) -> v128) -> usize {
+        let len = dst.len().min(src.len()) / LANES * LANES;
+        for (dst, src) in dst[..len]
+            .chunks_exact_mut(LANES)
+            .zip(src.chunks_exact(LANES))
+        {
+            // SAFETY: as in `each`.
+            unsafe {
+                let v = pick(
+                    v128_load(dst.as_ptr().cast()),
+                    v128_load(src.as_ptr().cast()),
+                );
+                v128_store(dst.as_mut_ptr().cast(), v);
+            }
+        }
+        len
+    }
+
+    pub fn min_with(dst: &mut [u8], src: &[u8]) -> usize {
+        combine(dst, src, |a, b| u8x16_min(a, b))
+    }
+
+    pub fn max_with(dst: &mut [u8], src: &[u8]) -> usize {
+        combine(dst, src, |a, b| u8x16_max(a, b))
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+mod lanes {
+    pub fn threshold(_: &mut [u8], _: u8) -> usize {
+        0
+    }
+
+    pub fn recolor(_: &mut [u8], _: u8, _: u8) -> usize {
+        0
+    }
+
+    pub fn min_with(_: &mut [u8], _: &[u8]) -> usize {
+        0
+    }
+
+    pub fn max_with(_: &mut [u8], _: &[u8]) -> usize {
+        0
+    }
+}
+
+/// 255 from `level` up, 0 below.
+pub fn threshold(values: &mut [u8], level: u8) {
+    let done = lanes::threshold(values, level);
+    for value in &mut values[done..] {
+        *value = if *value >= level { 255 } else { 0 };
+    }
+}
+
+/// Class `to` wherever class `from` was.
+pub fn recolor(values: &mut [u8], from: u8, to: u8) {
+    let done = lanes::recolor(values, from, to);
+    for value in values[done..].iter_mut().filter(|value| **value == from) {
+        *value = to;
+    }
+}
+
+/// Each of `dst` lowered to the value at the same place in `src`, where that is lower.
+pub fn min_with(dst: &mut [u8], src: &[u8]) {
+    let done = lanes::min_with(dst, src);
+    for (dst, src) in dst[done..].iter_mut().zip(&src[done..]) {
+        *dst = (*dst).min(*src);
+    }
+}
+
+/// Each of `dst` raised to the value at the same place in `src`, where that is higher.
+pub fn max_with(dst: &mut [u8], src: &[u8]) {
+    let done = lanes::max_with(dst, src);
+    for (dst, src) in dst[done..].iter_mut().zip(&src[done..]) {
+        *dst = (*dst).max(*src);
+    }
+}
+
+/// How many of `values` are of each class. Counted into several tables in turn, so that
+/// runs of one class do not wait on the same counter, as there are no vector instructions
+/// for this.
+pub fn class_counts(values: &[u8]) -> [u64; 256] {
+    let mut tables = [[0u64; 256]; 4];
+    let mut chunks = values.chunks_exact(4);
+    for chunk in &mut chunks {
+        for (table, value) in tables.iter_mut().zip(chunk) {
+            table[*value as usize] += 1;
+        }
+    }
+    for value in chunks.remainder() {
+        tables[0][*value as usize] += 1;
+    }
+    let mut counts = [0u64; 256];
+    for table in &tables {
+        for (count, n) in counts.iter_mut().zip(table) {
+            *count += n;
+        }
+    }
+    counts
+}
+
+/// `values`, rows of `width`, with every value combined by `pick` with those up to
+/// `radius` away along its row, then along its column: over the square around it.
+fn square_filter(
+    values: &[u8],
+    width: usize,
+    radius: usize,
+    pick: fn(&mut [u8], &[u8]),
+) -> Vec<u8> {
+    if width == 0 {
+        return values.to_vec();
+    }
+    let height = values.len() / width;
+    let mut rows = values.to_vec();
+    for (out, row) in rows.chunks_exact_mut(width).zip(values.chunks_exact(width)) {
+        for d in 1..=radius.min(width - 1) {
+            pick(&mut out[..width - d], &row[d..]);
+            pick(&mut out[d..], &row[..width - d]);
+        }
+    }
+    let mut squares = rows.clone();
+    let row = |y: usize| &rows[y * width..(y + 1) * width];
+    for (y, out) in squares.chunks_exact_mut(width).enumerate() {
+        for d in 1..=radius.min(height - 1) {
+            if y >= d {
+                pick(out, row(y - d));
+            }
+            if y + d < height {
+                pick(out, row(y + d));
+            }
+        }
+    }
+    squares
+}
+
+/// The lowest value over the square of `radius` around every value of `values`, rows of
+/// `width`.
+pub fn erode(values: &[u8], width: usize, radius: usize) -> Vec<u8> {
+    square_filter(values, width, radius, min_with)
+}
+
+/// The highest value over the square of `radius` around every value of `values`, rows of
+/// `width`.
+pub fn dilate(values: &[u8], width: usize, radius: usize) -> Vec<u8> {
+    square_filter(values, width, radius, max_with)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Varied values, enough to leave some past the last whole vector.
+    fn values() -> Vec<u8> {
+        (0..37u32).map(|i| (i * 97 % 256) as u8).collect()
+    }
+
+    #[test]
+    fn values_are_thresholded_and_recolored_to_the_end() {
+        let mut thresholded = values();
+        threshold(&mut thresholded, 128);
+        let expected: Vec<u8> = values()
+            .iter()
+            .map(|v| if *v >= 128 { 255 } else { 0 })
+            .collect();
+        assert_eq!(thresholded, expected);
+
+        let mut recolored = values();
+        let last = *recolored.last().unwrap();
+        recolor(&mut recolored, last, 0);
+        assert_eq!(*recolored.last().unwrap(), 0);
+        assert_eq!(class_counts(&recolored)[last as usize], 0);
+    }
+
+    #[test]
+    fn classes_are_counted() {
+        let values = [1, 1, 2, 0, 1, 2, 1];
+        let counts = class_counts(&values);
+        assert_eq!(&counts[..3], [1, 4, 2]);
+        assert_eq!(counts.iter().sum::<u64>(), values.len() as u64);
+    }
+
+    #[test]
+    fn squares_match_looking_at_every_neighbour() {
+        // Rows wider than a vector, so that SIMD builds use it.
+        let (width, height) = (21, 5);
+        let values: Vec<u8> = (0..width * height).map(|i| (i * 53 % 11) as u8).collect();
+        for radius in [0, 1, 2, 9] {
+            let naive = |pick: fn(u8, u8) -> u8| -> Vec<u8> {
+                let r = radius as i64;
+                (0..height as i64)
+                    .flat_map(|y| (0..width as i64).map(move |x| (x, y)))
+                    .map(|(x, y)| {
+                        (-r..=r)
+                            .flat_map(|dy| (-r..=r).map(move |dx| (x + dx, y + dy)))
+                            .filter(|&(x, y)| {
+                                x >= 0 && y >= 0 && x < width as i64 && y < height as i64
+                            })
+                            .map(|(x, y)| values[y as usize * width + x as usize])
+                            .reduce(pick)
+                            .unwrap()
+                    })
+                    .collect()
+            };
+            assert_eq!(erode(&values, width, radius), naive(u8::min), "{radius}");
+            assert_eq!(dilate(&values, width, radius), naive(u8::max), "{radius}");
+        }
+    }
+}
@@ -0,0 +1,356 @@
+//! A stored result laid out on a single A4 page to be printed or saved as a PDF: the
+//! image, the mask over it, its legend and class statistics, what is known about it and
+//! the comments on it.
+
+use image::{GrayImage, Rgba, RgbaImage};
+use shadow_clone::shadow_clone;
+use std::rc::Rc;
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    decoded,
+    evaluation::{self, percent},
+    history::{self, decision_label, HistoryEntry},
+    i18n::{self, use_language},
+    issues::reason_label,
+    metadata::{self, Metadata},
+    raster,
+    roles::use_role,
+    route::Route,
+    storage::format_size,
+    viewer::{BlobUrl, FileViewer},
+    FileDetails,
+};
+
+/// Colours of the classes of masks in reports, cycled through, chosen to stay apart when
+/// printed in colour and over photographs. The background is left clear.
+const CLASS_COLORS: [Rgba<u8>; 6] = [
+    Rgba([230, 25, 75, 255]),
+    Rgba([60, 180, 75, 255]),
+    Rgba([0, 130, 200, 255]),
+    Rgba([245, 130, 48, 255]),
+    Rgba([145, 30, 180, 255]),
+    Rgba([255, 225, 25, 255]),
+];
+
+/// The colour of `class` in reports, or `None` for the background.
+fn class_color(class: u8) -> Option<Rgba<u8>> {
+    (class > 0).then(|| CLASS_COLORS[usize::from(class - 1) % CLASS_COLORS.len()])
+}
+
+/// `mask` in the colours of its classes, over a clear background.
+fn class_colors(mask: &GrayImage) -> RgbaImage {
+    RgbaImage::from_fn(mask.width(), mask.height(), |x, y| {
+        class_color(mask.get_pixel(x, y).0[0]).unwrap_or(Rgba([0, 0, 0, 0]))
+    })
+}
+
+/// A line of the class table of a report.
+#[derive(Clone, PartialEq, Debug)]
+struct ClassRow {
+    class: u8,
+    pixels: u64,
+    /// Share of the mask's pixels, from 0 to 1.
+    share: f64,
+}
+
+/// The classes found in a mask with `counts` pixels of each, in class order.
+fn class_rows(counts: &[u64; 256]) -> Vec<ClassRow> {
+    let total = counts.iter().sum::<u64>().max(1) as f64;
+    (0..=u8::MAX)
+        .filter(|&class| counts[usize::from(class)] > 0)
+        .map(|class| ClassRow {
+            class,
+            pixels: counts[usize::from(class)],
+            share: counts[usize::from(class)] as f64 / total,
+        })
+        .collect()
+}
+
+/// Everything a report shows of a result.
+#[derive(Clone, PartialEq)]
+struct Report {
+    entry: Rc<HistoryEntry>,
+    metadata: Metadata,
+    /// The mask in the colours of its classes, to be laid over the image.
+    overlay: Option<BlobUrl>,
+    classes: Vec<ClassRow>,
+}
+
+async fn load(id: u64) -> Result<Report, String> {
+    let entry = history::get_entry(id as f64)
+        .await?
+        .ok_or_else(|| i18n::t_args("report-missing", &[("id", &id)]))?;
+    let metadata = metadata::get(id as f64).await?.unwrap_or_default();
+    let (overlay, classes) = match decoded::mask(&entry.mask.data) {
+        Ok(mask) => {
+            let overlay = evaluation::png(
+                class_colors(&mask),
+                i18n::t_args("report-classes-of", &[("name", &entry.mask.file_name)]),
+                &entry.mask,
+            )?;
+            (
+                Some(BlobUrl::new(&overlay.data, &overlay.file_type)),
+                class_rows(&raster::class_counts(&mask)),
+            )
+        }
+        Err(why) => {
+            log::warn!("Could not read the mask of result {id}: {why}");
+            (None, vec![])
+        }
+    };
+    Ok(Report {
+        entry: Rc::new(entry),
+        metadata,
+        overlay,
+        classes,
+    })
+}
+
+fn describe_file(file: &FileDetails) -> String {
+    format!(
+        "{} ({}, {})",
+        file.file_name,
+        file.file_type,
+        format_size(file.data.len() as u64)
+    )
+}
+
+/// The result stored as `id`, laid out to be printed, with buttons to print it and go back
+/// that are left out of the print.
+#[autoprops_component(ReportPane)]
+pub fn report_pane(id: u64) -> Html {
+    let language = use_language();
+    let report = use_state(|| None::<Result<Report, String>>);
+    let can_edit = use_role().can_edit();
+
+    {
+        shadow_clone!(report);
+        use_effect_with(id, move |&id| {
+            report.set(None);
+            spawn_local(async move { report.set(Some(load(id).await)) });
+        });
+    }
+
+    let onprint = |_| {
+        if let Err(e) = gloo::utils::window().print() {
+            log::error!("Could not print: {e:?}");
+        }
+    };
+
+    let report = match &*report {
+        None => return html!(<span class="spinner-border text-success"></span>),
+        Some(Err(why)) => return html!(<div class="alert alert-danger" role="alert">{why}</div>),
+        Some(Ok(report)) => report,
+    };
+    let Report {
+        entry,
+        metadata,
+        overlay,
+        classes,
+    } = report;
+    let georef = entry.image.georef.or(entry.mask.georef);
+
+    html! {
+        <article class="report">
+            <div class="d-flex gap-2 mb-2 d-print-none">
+                <button class="btn btn-primary" onclick={onprint}>{language.t("report-print")}</button>
+                // Viewers are shown the report when opening the result anyway.
+                if can_edit {
+                    <a class="btn btn-outline-secondary" href={Route::Result(id).href()}>{language.t("report-open")}</a>
+                }
+                <a class="btn btn-outline-secondary" href={Route::History.href()}>{language.t("report-back")}</a>
+            </div>
+            <header class="mb-2">
+                <h1 class="h3 mb-0">{&entry.image.file_name}</h1>
+                <small class="text-body-secondary">
+                    {language.t_args("report-subtitle", &[("id", &id), ("date", &entry.date()), ("server", &entry.server)])}
+                </small>
+            </header>
+            <div class="row g-2">
+                <figure class="col-6 mb-0">
+                    <FileViewer file={entry.image.clone()} />
+                    <figcaption class="small text-body-secondary">{language.t("report-image")}</figcaption>
+                </figure>
+                <figure class="col-6 mb-0">
+                    <div class="position-relative">
+                        <FileViewer file={entry.image.clone()} />
+                        if let Some(overlay) = overlay {
+                            <img
+                                class="position-absolute top-0 start-0 w-100"
+                                style="opacity: 0.6; pointer-events: none;"
+                                src={overlay.to_string()}
+                                alt={language.t_args("report-classes-of", &[("name", &entry.mask.file_name)])}
+                            />
+                        }
+                    </div>
+                    <figcaption class="small text-body-secondary">
+                        {language.t_args("report-mask-over-image", &[("name", &entry.mask.file_name)])}
+                    </figcaption>
+                </figure>
+            </div>
+            <div class="row g-2 mt-1">
+                <section class="col-6">
+                    <h2 class="h5">{language.t("report-classes")}</h2>
+                    if classes.is_empty() {
+                        <p>{language.t("report-mask-unreadable")}</p>
+                    } else {
+                        <table class="table table-sm">
+                            <thead>
+                                <tr>
+                                    <th scope="col">{language.t("report-class")}</th>
+                                    <th scope="col" class="text-end">{language.t("report-pixels")}</th>
+                                    <th scope="col" class="text-end">{language.t("report-share")}</th>
+                                </tr>
+                            </thead>
+                            <tbody>
+                            {for classes.iter().map(|row| {
+                                let swatch = match class_color(row.class) {
+                                    Some(Rgba([r, g, b, _])) => format!("background: rgb({r}, {g}, {b});"),
+                                    None => "background: transparent;".to_string(),
+                                };
+                                html! {
+                                    <tr>
+                                        <td>
+                                            <span
+                                                class="d-inline-block me-1 border align-middle"
+                                                style={format!("width: 1em; height: 1em; {swatch}")}
+                                                aria-hidden="true"
+                                            ></span>
+                                            if row.class == 0 {
+                                                {language.t("report-background")}
+                                            } else {
+                                                {row.class}
+                                            }
+                                        </td>
+                                        <td class="text-end">{row.pixels}</td>
+                                        <td class="text-end">{percent(Some(row.share))}</td>
+                                    </tr>
+                                }
+                            })}
+                            </tbody>
+                        </table>
+                    }
+                </section>
+                <section class="col-6">
+                    <h2 class="h5">{language.t("report-details")}</h2>
+                    <dl class="row small mb-0">
+                        <dt class="col-4">{language.t("report-image")}</dt>
+                        <dd class="col-8 text-break">{describe_file(&entry.image)}</dd>
+                        <dt class="col-4">{language.t("report-mask")}</dt>
+                        <dd class="col-8 text-break">{describe_file(&entry.mask)}</dd>
+                        if let Some(georef) = georef {
+                            <dt class="col-4">{language.t("report-projection")}</dt>
+                            <dd class="col-8">
+                                {language.t_args("report-projection-value", &[
+                                    ("epsg", &georef.epsg),
+                                    ("width", &georef.width),
+                                    ("height", &georef.height),
+                                ])}
+                            </dd>
+                            if let Some(bounds) = georef.bounds() {
+                                <dt class="col-4">{language.t("report-bounds")}</dt>
+                                <dd class="col-8">
+                                    {language.t_args("report-bounds-value", &[
+                                        ("south", &format!("{:.6}", bounds.south)),
+                                        ("north", &format!("{:.6}", bounds.north)),
+                                        ("west", &format!("{:.6}", bounds.west)),
+                                        ("east", &format!("{:.6}", bounds.east)),
+                                    ])}
+                                </dd>
+                            }
+                        }
+                        if let Some(digest) = &metadata.digest {
+                            <dt class="col-4">{"SHA-256"}</dt>
+                            <dd class="col-8 font-monospace text-break">{digest}</dd>
+                        }
+                        if !metadata.tags.is_empty() {
+                            <dt class="col-4">{language.t("history-tags")}</dt>
+                            <dd class="col-8">{metadata.tags.join(", ")}</dd>
+                        }
+                        if !metadata.notes.is_empty() {
+                            <dt class="col-4">{language.t("history-notes")}</dt>
+                            <dd class="col-8">{&metadata.notes}</dd>
+                        }
+                        if let Some(review) = &metadata.review {
+                            <dt class="col-4">{language.t("report-review")}</dt>
+                            <dd class="col-8">
+                                {decision_label(language, review.decision)}
+                                if !review.comment.is_empty() {
+                                    {format!(": {}", review.comment)}
+                                }
+                            </dd>
+                        }
+                        if let Some(issue) = &metadata.issue {
+                            <dt class="col-4">{language.t("report-flagged")}</dt>
+                            <dd class="col-8">
+                                {reason_label(language, issue.reason)}
+                                if !issue.comment.is_empty() {
+                                    {format!(": {}", issue.comment)}
+                                }
+                            </dd>
+                        }
+                    </dl>
+                </section>
+            </div>
+            if !metadata.comments.is_empty() {
+                <section class="mt-2">
+                    <h2 class="h5">{language.t("heading-comments")}</h2>
+                    <ol class="small">
+                        {for metadata.comments.iter().map(|comment| html! {
+                            <li>
+                                <span class="text-body-secondary">{language.t_args("report-comment-on", &[("anchor", &comment.anchor.describe(language))])}</span>
+                                if comment.resolved {
+                                    {" "}{language.t("report-resolved")}
+                                }
+                                <br />
+                                <strong>{&comment.author}</strong>{format!(": {}", comment.text)}
+                                {for comment.replies.iter().map(|reply| html! {
+                                    <div class="ms-3"><strong>{&reply.author}</strong>{format!(": {}", reply.text)}</div>
+                                })}
+                            </li>
+                        })}
+                    </ol>
+                </section>
+            }
+        </article>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{GrayImage, Rgba};
+
+    use super::{class_colors, class_rows, ClassRow, CLASS_COLORS};
+
+    #[test]
+    fn classes_are_listed_in_order_with_their_share() {
+        let mut counts = [0; 256];
+        counts[0] = 6;
+        counts[3] = 2;
+        assert_eq!(
+            class_rows(&counts),
+            [
+                ClassRow {
+                    class: 0,
+                    pixels: 6,
+                    share: 0.75
+                },
+                ClassRow {
+                    class: 3,
+                    pixels: 2,
+                    share: 0.25
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn the_background_is_left_clear_and_colours_cycle() {
+        let mask = GrayImage::from_raw(3, 1, vec![0, 1, 1 + CLASS_COLORS.len() as u8]).unwrap();
+        let colored = class_colors(&mask);
+        assert_eq!(*colored.get_pixel(0, 0), Rgba([0, 0, 0, 0]));
+        assert_eq!(colored.get_pixel(1, 0), colored.get_pixel(2, 0));
+    }
+}
@@ -0,0 +1,250 @@
+//! Segmenting images of the history again with another model, one at a time or all those
+//! shown at once, such as after every release of a model. New results are stored next to
+//! the ones they were re-run from, to be compared with them.
+
+use std::{cell::Cell, rc::Rc};
+use web_sys::HtmlSelectElement;
+use yew::{platform::spawn_local, prelude::*};
+
+use crate::{
+    api::{Api, RequestControl},
+    audit::{self, AuditAction},
+    config,
+    history::{self, HistoryEntry},
+    i18n::{use_language, Language},
+    models::{self, Candidate},
+    notify, postprocess,
+    settings::Settings,
+    state::use_app_state,
+};
+
+/// How far a run over several results has got.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct RerunProgress {
+    pub total: usize,
+    pub done: usize,
+    pub failed: usize,
+}
+
+impl RerunProgress {
+    fn describe(&self, language: Language) -> String {
+        let current = (self.done + self.failed + 1).min(self.total);
+        if self.failed > 0 {
+            language.t_args(
+                "rerun-progress-failed",
+                &[
+                    ("current", &current),
+                    ("total", &self.total),
+                    ("failed", &self.failed),
+                ],
+            )
+        } else {
+            language.t_args(
+                "rerun-progress",
+                &[("current", &current), ("total", &self.total)],
+            )
+        }
+    }
+}
+
+/// Segments the image of `entry` with `model`, post-processed by the settings' steps, and
+/// stores the result as re-run from `entry`, answering with its key.
+async fn rerun(
+    model: &Candidate,
+    settings: &Settings,
+    entry: &HistoryEntry,
+) -> Result<f64, String> {
+    let settings = Rc::new(Settings {
+        server_url: model.server_url.clone(),
+        ..settings.clone()
+    });
+    let response = Api::http(settings.clone())
+        .segment((*entry.image).clone(), RequestControl::default())
+        .await
+        .map_err(|error| error.to_string())?;
+    let mask = postprocess::apply(&settings.postprocess, response.mask)?;
+    let mut rerun = HistoryEntry::new(entry.image.clone(), Rc::new(mask), settings.segment_url());
+    rerun.rerun_of = entry.id;
+    let result = history::add(&rerun).await?;
+    audit::record(
+        AuditAction::Upload,
+        entry.image.file_name.clone(),
+        format!("re-run with {}, result {result}", model.name),
+    );
+    Ok(result)
+}
+
+/// The model results are re-run with and the run under way, if any.
+#[derive(Clone)]
+pub struct Rerun {
+    language: Language,
+    candidates: Rc<Vec<Candidate>>,
+    settings: Rc<Settings>,
+    /// Address of the chosen model.
+    model: UseStateHandle<String>,
+    progress: UseStateHandle<Option<RerunProgress>>,
+    /// Set to stop the run under way after the result being segmented.
+    stopped: UseStateHandle<Rc<Cell<bool>>>,
+}
+
+#[hook]
+pub fn use_rerun() -> Rerun {
+    let state = use_app_state();
+    let settings = state.settings.clone();
+    let candidates = Rc::new(models::candidates(
+        &config::current().models,
+        &settings.server_url,
+        &[],
+    ));
+    let first = candidates
+        .first()
+        .map(|c| c.server_url.clone())
+        .unwrap_or_default();
+    Rerun {
+        language: use_language(),
+        candidates,
+        settings,
+        model: use_state(|| first),
+        progress: use_state(|| None),
+        stopped: use_state(|| Rc::new(Cell::new(false))),
+    }
+}
+
+impl Rerun {
+    pub fn running(&self) -> bool {
+        self.progress.is_some()
+    }
+
+    fn model(&self) -> Option<Candidate> {
+        self.candidates
+            .iter()
+            .find(|c| c.server_url == *self.model)
+            .cloned()
+    }
+
+    /// Re-runs `entries` in turn with the chosen model, calling `ondone` once all are
+    /// stored. Only one run goes at a time.
+    pub fn start(&self, entries: Vec<Rc<HistoryEntry>>, ondone: Callback<()>) {
+        let Some(model) = self.model() else {
+            return;
+        };
+        if self.running() || entries.is_empty() {
+            return;
+        }
+        let stopped = Rc::new(Cell::new(false));
+        self.stopped.set(stopped.clone());
+        let mut progress = RerunProgress {
+            total: entries.len(),
+            ..Default::default()
+        };
+        self.progress.set(Some(progress));
+        let handle = self.progress.clone();
+        let settings = self.settings.clone();
+        let language = self.language;
+        spawn_local(async move {
+            let mut last_error = None;
+            for entry in entries {
+                if stopped.get() {
+                    break;
+                }
+                match rerun(&model, &settings, &entry).await {
+                    Ok(_) => progress.done += 1,
+                    Err(why) => {
+                        log::warn!("Could not re-run {}: {why}", entry.image.file_name);
+                        progress.failed += 1;
+                        last_error = Some(why);
+                    }
+                }
+                handle.set(Some(progress));
+            }
+            handle.set(None);
+            if let Some(why) = last_error {
+                notify::warn(
+                    language.t_args(
+                        "rerun-failed",
+                        &[
+                            ("failed", &progress.failed),
+                            ("total", &progress.total),
+                            ("model", &model.name),
+                        ],
+                    ),
+                    why,
+                );
+            }
+            ondone.emit(());
+        });
+    }
+
+    /// The choice of model, the button re-running all `shown` results, and the progress of
+    /// the run under way.
+    pub fn controls(&self, shown: Vec<Rc<HistoryEntry>>, ondone: Callback<()>) -> Html {
+        let onmodel = {
+            let model = self.model.clone();
+            move |e: Event| {
+                let select: HtmlSelectElement = e.target_unchecked_into();
+                model.set(select.value());
+            }
+        };
+        let count = shown.len();
+        let onall = {
+            let rerun = self.clone();
+            move |_| rerun.start(shown.clone(), ondone.clone())
+        };
+        let onstop = {
+            let stopped = (*self.stopped).clone();
+            move |_| stopped.set(true)
+        };
+        html! {
+            <div class="d-flex align-items-center gap-2 mb-2">
+                <div class="input-group input-group-sm w-auto">
+                    <span class="input-group-text">{self.language.t("rerun-with")}</span>
+                    <select class="form-select" onchange={onmodel} disabled={self.running()}>
+                        {for self.candidates.iter().map(|c| html! {
+                            <option value={c.server_url.clone()} selected={c.server_url == *self.model}>
+                                {&c.name}
+                            </option>
+                        })}
+                    </select>
+                    <button
+                        class="btn btn-outline-primary"
+                        disabled={self.running() || count == 0}
+                        onclick={onall}
+                    >
+                        {self.language.t_args("rerun-all", &[("count", &count)])}
+                    </button>
+                </div>
+                if let Some(progress) = *self.progress {
+                    <span role="status">
+                        <span class="spinner-border spinner-border-sm me-1" aria-hidden="true"></span>
+                        {progress.describe(self.language)}
+                    </span>
+                    <button class="btn btn-sm btn-outline-secondary" onclick={onstop}>{self.language.t("rerun-stop")}</button>
+                }
+            </div>
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RerunProgress;
+    use crate::i18n::Language;
+
+    #[test]
+    fn progress_counts_the_result_under_way() {
+        let progress = RerunProgress {
+            total: 3,
+            done: 1,
+            failed: 1,
+        };
+        assert_eq!(
+            progress.describe(Language::En),
+            "Re-running 3 of 3, 1 failed"
+        );
+        let started = RerunProgress {
+            total: 2,
+            ..Default::default()
+        };
+        assert_eq!(started.describe(Language::En), "Re-running 1 of 2");
+    }
+}
@@ -0,0 +1,210 @@
+//! Reviewing the results shown in the history from the keyboard alone, one at a time:
+//! arrows move between them, `M` shows the mask or the image, number keys decide and
+//! `Enter` goes on to the next result not reviewed yet.
+
+use std::rc::Rc;
+use web_sys::{HtmlElement, KeyboardEvent};
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    clock,
+    history::{decision_badge, decision_label, HistoryEntry},
+    i18n::use_language,
+    metadata::{Decision, Metadata, Review},
+    viewer::FileViewer,
+};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ReviewKey {
+    Previous,
+    Next,
+    /// The next result not reviewed yet.
+    NextPending,
+    ToggleMask,
+    Decide(Decision),
+}
+
+impl ReviewKey {
+    /// What the key `key`, a value of `KeyboardEvent.key`, does in the review.
+    fn parse(key: &str) -> Option<Self> {
+        Some(match key {
+            "ArrowUp" | "ArrowLeft" | "k" => ReviewKey::Previous,
+            "ArrowDown" | "ArrowRight" | "j" => ReviewKey::Next,
+            "Enter" => ReviewKey::NextPending,
+            "m" | "M" => ReviewKey::ToggleMask,
+            "1" => ReviewKey::Decide(Decision::Accepted),
+            "2" => ReviewKey::Decide(Decision::Rejected),
+            "3" => ReviewKey::Decide(Decision::NeedsEdit),
+            _ => return None,
+        })
+    }
+}
+
+/// The first result after `from` that is not reviewed, going round to the start, or the
+/// one after `from` when all are.
+fn next_pending(reviewed: &[bool], from: usize) -> usize {
+    let count = reviewed.len();
+    (1..=count)
+        .map(|step| (from + step) % count)
+        .find(|&i| !reviewed[i])
+        .unwrap_or((from + 1).min(count.saturating_sub(1)))
+}
+
+/// The results in `entries`, one at a time, for a reviewer to go through with the keyboard
+/// once the deck has focus, which it takes when it appears. Keys the deck uses go no
+/// further, so that the number keys decide here rather than switch tabs.
+#[autoprops_component(ReviewDeck)]
+pub fn review_deck(
+    entries: Rc<Vec<(Rc<HistoryEntry>, Metadata)>>,
+    onreview: Callback<(f64, Metadata, Review)>,
+) -> Html {
+    let language = use_language();
+    // The result shown, by its key, so that it stays shown as the list is reloaded.
+    let current = use_state(|| None::<f64>);
+    let show_mask = use_state(|| true);
+    let deck = use_node_ref();
+
+    {
+        let deck = deck.clone();
+        use_effect_with((), move |_| {
+            if let Some(deck) = deck.cast::<HtmlElement>() {
+                let _ = deck.focus();
+            }
+        });
+    }
+
+    let index = (*current)
+        .and_then(|id| entries.iter().position(|(entry, _)| entry.id == Some(id)))
+        .unwrap_or(0);
+
+    let onkeydown = {
+        let (entries, current, show_mask, onreview) = (
+            entries.clone(),
+            current.clone(),
+            show_mask.clone(),
+            onreview.clone(),
+        );
+        move |e: KeyboardEvent| {
+            if e.ctrl_key() || e.meta_key() || e.alt_key() || entries.is_empty() {
+                return;
+            }
+            let Some(key) = ReviewKey::parse(&e.key()) else {
+                return;
+            };
+            e.prevent_default();
+            e.stop_propagation();
+            let go = |i: usize| current.set(entries[i].0.id);
+            match key {
+                ReviewKey::Previous => go(index.saturating_sub(1)),
+                ReviewKey::Next => go((index + 1).min(entries.len() - 1)),
+                ReviewKey::NextPending => {
+                    let reviewed: Vec<bool> =
+                        entries.iter().map(|(_, m)| m.review.is_some()).collect();
+                    go(next_pending(&reviewed, index));
+                }
+                ReviewKey::ToggleMask => show_mask.set(!*show_mask),
+                ReviewKey::Decide(decision) => {
+                    let (entry, metadata) = &entries[index];
+                    let Some(id) = entry.id else {
+                        return;
+                    };
+                    current.set(Some(id));
+                    onreview.emit((
+                        id,
+                        metadata.clone(),
+                        Review {
+                            decision,
+                            comment: metadata
+                                .review
+                                .as_ref()
+                                .map(|review| review.comment.clone())
+                                .unwrap_or_default(),
+                            timestamp: clock::now(),
+                        },
+                    ));
+                }
+            }
+        }
+    };
+
+    let reviewed = entries.iter().filter(|(_, m)| m.review.is_some()).count();
+    let shown = entries.get(index).map(|(entry, metadata)| {
+        let file = if *show_mask {
+            entry.mask.clone()
+        } else {
+            entry.image.clone()
+        };
+        html! {
+            <>
+                <div class="d-flex align-items-center gap-2 mb-1">
+                    <strong>{&entry.image.file_name}</strong>
+                    <small class="text-body-secondary">
+                        {language.t_args("review-position", &[
+                            ("index", &(index + 1)),
+                            ("count", &entries.len()),
+                            ("date", &entry.date()),
+                            ("server", &entry.server),
+                        ])}
+                    </small>
+                    if let Some(review) = &metadata.review {
+                        {decision_badge(language, review.decision)}
+                    }
+                </div>
+                <div style="max-width: 32em;">
+                    <FileViewer {file} />
+                </div>
+            </>
+        }
+    });
+
+    html! {
+        <section
+            class="border rounded p-2 mb-2"
+            tabindex="0"
+            ref={deck}
+            aria-label={language.t("review-deck")}
+            {onkeydown}
+        >
+            <p class="small text-body-secondary mb-1">
+                {language.t_args("review-progress", &[("reviewed", &reviewed), ("count", &entries.len())])}{" "}
+                <kbd>{"↑"}</kbd>{" "}<kbd>{"↓"}</kbd>{" "}{language.t("review-key-move")}{", "}
+                <kbd>{"M"}</kbd>{" "}{language.t(if *show_mask { "review-key-image" } else { "review-key-mask" })}{", "}
+                {for Decision::ALL.into_iter().enumerate().map(|(i, decision)| html! {
+                    <>
+                        <kbd>{i + 1}</kbd>{format!(" {}, ", decision_label(language, decision).to_lowercase())}
+                    </>
+                })}
+                <kbd>{"Enter"}</kbd>{" "}{language.t("review-key-next-pending")}
+            </p>
+            {shown.unwrap_or_else(|| html!(<p>{language.t("review-none")}</p>))}
+        </section>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{next_pending, ReviewKey};
+    use crate::metadata::Decision;
+
+    #[test]
+    fn number_keys_decide_in_the_order_decisions_are_listed() {
+        assert_eq!(
+            ReviewKey::parse("2"),
+            Some(ReviewKey::Decide(Decision::Rejected))
+        );
+        assert_eq!(ReviewKey::parse("ArrowDown"), Some(ReviewKey::Next));
+        assert_eq!(ReviewKey::parse("4"), None);
+    }
+
+    #[test]
+    fn enter_skips_to_results_not_reviewed_yet() {
+        let reviewed = [false, true, true, false, true];
+        assert_eq!(next_pending(&reviewed, 0), 3);
+        // Going round to the start.
+        assert_eq!(next_pending(&reviewed, 3), 0);
+        // Once all are reviewed, the next one.
+        assert_eq!(next_pending(&[true, true, true], 0), 1);
+        assert_eq!(next_pending(&[true, true, true], 2), 2);
+    }
+}
@@ -0,0 +1,137 @@
+//! What users may do, so that the same deployment can serve stakeholders who only look at
+//! results next to those who produce and correct them. The role is named by the `role`
+//! claim of the access token when it is a JWT, and by `config.json` otherwise.
+//!
+//! This only decides what the interface offers: the token is not verified here, so the
+//! servers remain the ones enforcing who may do what.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde_json::Value;
+use yew::prelude::*;
+
+use crate::{config, route::Route, state::use_app_state};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Role {
+    /// Looks at results, their reports and comments without changing anything.
+    Viewer,
+    /// Segments images and edits, reviews, flags and comments on results.
+    Editor,
+    /// Also changes the settings and manages what is stored.
+    Admin,
+}
+
+impl Role {
+    const ALL: [Role; 3] = [Role::Viewer, Role::Editor, Role::Admin];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Admin => "admin",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Role::ALL.into_iter().find(|role| role.name() == name)
+    }
+
+    /// Whether images may be segmented and results changed.
+    pub fn can_edit(self) -> bool {
+        self >= Role::Editor
+    }
+
+    /// Whether the settings and the stored results may be managed.
+    pub fn can_administer(self) -> bool {
+        self == Role::Admin
+    }
+
+    /// Whether `route` is offered. Viewers still get the settings screen, reduced to the
+    /// access token, so that they can sign in with another one.
+    pub fn allows(self, route: Route) -> bool {
+        match route {
+            Route::Batch | Route::Models => self.can_edit(),
+            _ => true,
+        }
+    }
+}
+
+/// The claims of `token`, when it is a JWT.
+fn claims(token: &str) -> Option<Value> {
+    let payload = token.split('.').nth(1)?;
+    serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()
+}
+
+/// The role claimed by `token`, a JWT with a `role` claim or a `roles` claim listing
+/// several, of which the highest is taken.
+fn claimed(token: &str) -> Option<Role> {
+    let claims = claims(token)?;
+    match (&claims["role"], &claims["roles"]) {
+        (Value::String(role), _) => Role::parse(role),
+        (_, Value::Array(roles)) => roles.iter().filter_map(|r| Role::parse(r.as_str()?)).max(),
+        _ => None,
+    }
+}
+
+/// Who `token` was issued to, from its `sub` claim.
+pub fn subject(token: &str) -> Option<String> {
+    Some(claims(token)?["sub"].as_str()?.to_string()).filter(|sub| !sub.is_empty())
+}
+
+/// The role of users with `token`, that of the deployment when the token names none. A
+/// role that cannot be told gives the least rights rather than the most.
+pub fn of(token: Option<&str>) -> Role {
+    token.and_then(claimed).unwrap_or_else(|| {
+        let role = &config::current().role;
+        Role::parse(role).unwrap_or_else(|| {
+            log::warn!("Unknown role {role:?}, taken as viewer");
+            Role::Viewer
+        })
+    })
+}
+
+/// The role of the current user, changing with their access token.
+#[hook]
+pub fn use_role() -> Role {
+    let state = use_app_state();
+    of(state.settings.token.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+    use super::{claimed, subject, Role};
+
+    fn jwt(claims: &str) -> String {
+        format!(
+            "eyJhbGciOiJIUzI1NiJ9.{}.c2lnbmF0dXJl",
+            URL_SAFE_NO_PAD.encode(claims)
+        )
+    }
+
+    #[test]
+    fn the_role_is_read_from_the_token_claims() {
+        assert_eq!(
+            claimed(&jwt(r#"{"sub": "ana", "role": "viewer"}"#)),
+            Some(Role::Viewer)
+        );
+        assert_eq!(
+            claimed(&jwt(r#"{"roles": ["viewer", "admin", "auditor"]}"#)),
+            Some(Role::Admin)
+        );
+        assert_eq!(claimed(&jwt(r#"{"role": "owner"}"#)), None);
+        assert_eq!(claimed(&jwt(r#"{"sub": "ana"}"#)), None);
+        assert_eq!(claimed("an-opaque-token"), None);
+
+        assert_eq!(subject(&jwt(r#"{"sub": "ana"}"#)).as_deref(), Some("ana"));
+        assert_eq!(subject(&jwt(r#"{"role": "viewer"}"#)), None);
+    }
+
+    #[test]
+    fn higher_roles_can_do_more() {
+        assert!(!Role::Viewer.can_edit());
+        assert!(Role::Editor.can_edit() && !Role::Editor.can_administer());
+        assert!(Role::Admin.can_edit() && Role::Admin.can_administer());
+    }
+}
@@ -0,0 +1,159 @@
+//! Routes kept in the URL's hash, so the back button, deep links and bookmarks work
+//! without any server-side configuration.
+
+use gloo::events::EventListener;
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    flags::{self, Flag},
+    i18n::use_language,
+    roles::use_role,
+    shortcuts::use_shortcut,
+};
+
+/// A screen of the app.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Route {
+    Upload,
+    History,
+    Compare,
+    Settings,
+    /// Segmenting many images in one run.
+    Batch,
+    /// Results flagged for review.
+    Flagged,
+    /// The same image segmented by several models.
+    Models,
+    /// A result from the history, by its id, opened in the upload screen.
+    Result(u64),
+    /// A result from the history, by its id, laid out to be printed.
+    Report(u64),
+}
+
+impl Route {
+    const TABS: [(Route, &'static str); 7] = [
+        (Route::Upload, "tab-segment"),
+        (Route::History, "tab-history"),
+        (Route::Compare, "tab-compare"),
+        (Route::Settings, "tab-settings"),
+        (Route::Batch, "tab-batch"),
+        (Route::Flagged, "tab-flagged"),
+        (Route::Models, "tab-models"),
+    ];
+
+    /// Whether the screen is turned on by its feature flag, if it has one.
+    fn is_available(self) -> bool {
+        match self {
+            Route::Compare => flags::enabled(Flag::Compare),
+            Route::Batch => flags::enabled(Flag::Batch),
+            Route::Models => flags::enabled(Flag::Models),
+            _ => true,
+        }
+    }
+
+    pub fn path(self) -> String {
+        match self {
+            Route::Upload => "/".to_string(),
+            Route::History => "/history".to_string(),
+            Route::Compare => "/compare".to_string(),
+            Route::Batch => "/batch".to_string(),
+            Route::Flagged => "/flagged".to_string(),
+            Route::Models => "/models".to_string(),
+            Route::Settings => "/settings".to_string(),
+            Route::Result(id) => format!("/results/{id}"),
+            Route::Report(id) => format!("/results/{id}/report"),
+        }
+    }
+
+    /// The route at `path`, falling back to the upload screen for unknown paths.
+    pub fn parse(path: &str) -> Self {
+        let segments: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+        match segments[..] {
+            ["history"] => Route::History,
+            ["compare"] if flags::enabled(Flag::Compare) => Route::Compare,
+            ["batch"] if flags::enabled(Flag::Batch) => Route::Batch,
+            ["settings"] => Route::Settings,
+            ["flagged"] => Route::Flagged,
+            ["models"] if flags::enabled(Flag::Models) => Route::Models,
+            ["results", id] => id.parse().map_or(Route::Upload, Route::Result),
+            ["results", id, "report"] => id.parse().map_or(Route::Upload, Route::Report),
+            _ => Route::Upload,
+        }
+    }
+
+    pub fn current() -> Self {
+        let hash = gloo::utils::window().location().hash().unwrap_or_default();
+        Route::parse(hash.trim_start_matches('#'))
+    }
+
+    /// Goes to this route, adding it to the browser history.
+    pub fn push(self) {
+        if let Err(e) = gloo::utils::window().location().set_hash(&self.path()) {
+            log::error!("Could not navigate to {}: {e:?}", self.path());
+        }
+    }
+
+    pub fn href(self) -> String {
+        format!("#{}", self.path())
+    }
+}
+
+/// The current route, updated when the URL's hash changes.
+#[hook]
+pub fn use_route() -> Route {
+    let route = use_state(Route::current);
+    {
+        let route = route.clone();
+        use_effect_with((), move |_| {
+            let listener = EventListener::new(&gloo::utils::window(), "hashchange", move |_| {
+                route.set(Route::current())
+            });
+            move || drop(listener)
+        });
+    }
+    *route
+}
+
+#[autoprops_component(RouteTabs)]
+pub fn route_tabs(current: Route) -> Html {
+    let language = use_language();
+    let role = use_role();
+    let go = |route: Route| {
+        Callback::from(move |_| {
+            if route.is_available() && role.allows(route) {
+                route.push();
+            }
+        })
+    };
+    use_shortcut(&["1"], "shortcut-tab-segment", go(Route::Upload));
+    use_shortcut(&["2"], "shortcut-tab-history", go(Route::History));
+    use_shortcut(&["3"], "shortcut-tab-compare", go(Route::Compare));
+    use_shortcut(&["4"], "shortcut-tab-settings", go(Route::Settings));
+    use_shortcut(&["5"], "shortcut-tab-batch", go(Route::Batch));
+    use_shortcut(&["6"], "shortcut-tab-flagged", go(Route::Flagged));
+    use_shortcut(&["7"], "shortcut-tab-models", go(Route::Models));
+    // Results belong to the upload screen, and their reports to the history.
+    let active = match current {
+        Route::Result(_) => Route::Upload,
+        Route::Report(_) => Route::History,
+        route => route,
+    };
+    html! {
+        <ul class="nav nav-pills flex-nowrap flex-lg-wrap overflow-auto mb-3" aria-label={language.t("tabs")}>
+            {
+                for Route::TABS.iter().filter(|(route, _)| route.is_available() && role.allows(*route)).map(|(route, key)| html! {
+                    <li class="nav-item">
+                        <a
+                            class={classes!("nav-link", (*route == active).then_some("active"))}
+                            aria-current={(*route == active).then_some("page")}
+                            href={route.href()}
+                        >
+                            {language.t(key)}
+                        </a>
+                    </li>
+                })
+            }
+        </ul>
+    }
+}
@@ -0,0 +1,310 @@
+//! What is sent to and answered by the segmentation server, as described in
+//! `schema/openapi.json`. The server is held to the same file, so the tests below keep
+//! these types in line with it: an answer the description allows must decode, and one
+//! missing anything it requires must not.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{de, de::DeserializeOwned, Deserialize, Deserializer};
+use serde_json::Value;
+use std::rc::Rc;
+use web_sys::Blob;
+
+use crate::{geo::Georeference, http::Answer};
+
+/// Header naming the mask a server answers with as it is.
+pub const FILE_NAME_HEADER: &str = "X-File-Name";
+/// Header with the id a result was stored under, for masks answered as they are.
+pub const RESULT_ID_HEADER: &str = "X-Result-Id";
+/// Header with the SHA-256 digest of the image, in hexadecimal: on requests, of the bytes
+/// sent, and on answers, of the bytes the server received.
+pub const IMAGE_DIGEST_HEADER: &str = "X-Image-SHA256";
+/// What the frontend accepts from `/segment`: masks as they are, or in JSON from servers
+/// that only answer so.
+pub const SEGMENT_ACCEPT: &str = "image/*, application/json;q=0.5";
+
+#[derive(Deserialize, PartialEq, Clone)]
+pub struct FileDetails {
+    pub file_name: String,
+    pub file_type: String,
+    /// The file's bytes, shared by every copy of the details rather than copied with them.
+    #[serde(deserialize_with = "deserialize_file_data")]
+    pub data: Rc<[u8]>,
+    /// Where the image lies on the map, when known on the client side.
+    #[serde(skip)]
+    pub georef: Option<Georeference>,
+    /// Extra band sent alongside the image, such as a vegetation mask derived from it.
+    #[serde(skip)]
+    pub auxiliary: Option<Rc<FileDetails>>,
+    /// The file `data` was read from, sent as it is so that browsers stream it from disk
+    /// rather than from a copy in memory.
+    #[serde(skip)]
+    pub source: Option<Blob>,
+}
+
+fn deserialize_file_data<'de, D>(d: D) -> Result<Rc<[u8]>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let data = String::deserialize(d)?;
+    STANDARD
+        .decode(data)
+        .map(Rc::from)
+        .map_err(|e| de::Error::custom(format!("data is not valid base64: {e}")))
+}
+
+/// Why `value` does not hold the details of a file, naming the field at fault after
+/// `path`.
+fn check_file(value: &Value, path: &str) -> Result<(), String> {
+    let Some(object) = value.as_object() else {
+        return Err(match path.strip_suffix('.') {
+            Some(field) => format!("field `{field}` is not an object"),
+            None => "the answer is not an object".to_string(),
+        });
+    };
+    for field in ["file_name", "file_type", "data"] {
+        match object.get(field) {
+            None | Some(Value::Null) => return Err(format!("field `{path}{field}` missing")),
+            Some(Value::String(_)) => {}
+            Some(_) => return Err(format!("field `{path}{field}` is not a string")),
+        }
+    }
+    Ok(())
+}
+
+/// The answer in `body`, checked by `check` before it is decoded, so that what the server
+/// got wrong is told as plainly as it can be.
+fn decode<T: DeserializeOwned>(
+    body: &[u8],
+    check: impl Fn(&Value) -> Result<(), String>,
+) -> Result<T, String> {
+    let value: Value =
+        serde_json::from_slice(body).map_err(|e| format!("the answer is not JSON: {e}"))?;
+    check(&value)?;
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// The server's answer to a segmentation request.
+#[derive(Deserialize)]
+pub struct SegmentResponse {
+    #[serde(flatten)]
+    pub mask: FileDetails,
+    /// Id the result was stored under, when the server keeps results to be linked to.
+    #[serde(default)]
+    pub result_id: Option<String>,
+}
+
+impl SegmentResponse {
+    /// The response in `answer`, which is either the mask itself or the response in JSON.
+    pub fn from_answer(answer: Answer) -> Result<Self, String> {
+        match answer.content_type() {
+            Some(file_type) if file_type.starts_with("image/") => Ok(SegmentResponse {
+                mask: FileDetails {
+                    file_name: answer
+                        .header(FILE_NAME_HEADER)
+                        .unwrap_or("mask.png")
+                        .to_string(),
+                    file_type: file_type.to_string(),
+                    data: answer.body.clone(),
+                    georef: None,
+                    auxiliary: None,
+                    source: None,
+                },
+                result_id: answer.header(RESULT_ID_HEADER).map(String::from),
+            }),
+            _ => Self::from_json(&answer.body),
+        }
+    }
+
+    pub fn from_json(body: &[u8]) -> Result<Self, String> {
+        decode(body, |value| check_file(value, ""))
+    }
+}
+
+/// An image and its mask, as kept by the server under a result id.
+#[derive(Deserialize)]
+pub struct StoredResult {
+    pub image: FileDetails,
+    pub mask: FileDetails,
+}
+
+impl StoredResult {
+    pub fn from_json(body: &[u8]) -> Result<Self, String> {
+        decode(body, |value| {
+            check_file(&value["image"], "image.")?;
+            check_file(&value["mask"], "mask.")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde::de::DeserializeOwned;
+    use serde_json::{json, Value};
+    use std::rc::Rc;
+
+    use super::{FileDetails, SegmentResponse, StoredResult, FILE_NAME_HEADER, RESULT_ID_HEADER};
+    use crate::{fixtures, http::Answer};
+
+    fn description() -> Value {
+        serde_json::from_str(include_str!("../schema/openapi.json")).unwrap()
+    }
+
+    fn schema(name: &str) -> Value {
+        description()["components"]["schemas"][name].clone()
+    }
+
+    /// The properties `schema` requires, including those of the schemas it is built from.
+    fn required(schema: &Value) -> Vec<String> {
+        let own = schema["required"].as_array().into_iter().flatten();
+        let parts = schema["allOf"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|part| match part["$ref"].as_str() {
+                Some(path) => self::schema(path.rsplit('/').next().unwrap()),
+                None => part.clone(),
+            });
+        own.filter_map(|name| name.as_str().map(String::from))
+            .chain(parts.flat_map(|part| required(&part)))
+            .collect()
+    }
+
+    fn decodes<T: DeserializeOwned>(value: &Value) -> bool {
+        serde_json::from_value::<T>(value.clone()).is_ok()
+    }
+
+    /// The example of schema `name` decodes into `T`, and stops decoding without any of the
+    /// properties the schema requires, but not without the others.
+    fn check<T: DeserializeOwned>(name: &str) {
+        let schema = schema(name);
+        let example = &schema["example"];
+        assert!(
+            decodes::<T>(example),
+            "the example of {name} does not decode"
+        );
+        let required = required(&schema);
+        assert!(!required.is_empty(), "{name} requires nothing");
+        for property in example.as_object().unwrap().keys() {
+            let mut without = example.clone();
+            without.as_object_mut().unwrap().remove(property);
+            assert_eq!(
+                decodes::<T>(&without),
+                !required.contains(property),
+                "{name} without {property}"
+            );
+        }
+    }
+
+    #[test]
+    fn file_details_follow_the_description() {
+        check::<FileDetails>("FileDetails");
+    }
+
+    #[test]
+    fn segment_responses_follow_the_description() {
+        check::<SegmentResponse>("SegmentResponse");
+    }
+
+    #[test]
+    fn stored_results_follow_the_description() {
+        check::<StoredResult>("StoredResult");
+    }
+
+    #[test]
+    fn answers_name_the_described_schemas() {
+        let paths = &description()["paths"];
+        let answer = |path: &str, method: &str| {
+            paths[path][method]["responses"]["200"]["content"]["application/json"]["schema"]["$ref"]
+                .clone()
+        };
+        assert_eq!(
+            answer("/segment", "post"),
+            "#/components/schemas/SegmentResponse"
+        );
+        assert_eq!(
+            answer("/results/{result_id}", "get"),
+            "#/components/schemas/StoredResult"
+        );
+    }
+
+    #[test]
+    fn file_data_is_decoded_from_base64() {
+        let file: FileDetails = serde_json::from_value(json!({
+            "file_name": "mask.png",
+            "file_type": "image/png",
+            "data": STANDARD.encode(fixtures::MASK),
+        }))
+        .unwrap();
+        assert_eq!(&file.data[..], fixtures::MASK);
+        // Copies of the details share the bytes.
+        assert!(Rc::ptr_eq(&file.data, &file.clone().data));
+
+        let error = serde_json::from_value::<FileDetails>(json!({
+            "file_name": "mask.png",
+            "file_type": "image/png",
+            "data": "not base64!",
+        }))
+        .err()
+        .unwrap();
+        assert!(error.to_string().contains("not valid base64"), "{error}");
+    }
+
+    #[test]
+    fn masks_are_taken_as_they_come() {
+        let headers = |content_type: &str| {
+            vec![
+                ("content-type".to_string(), content_type.to_string()),
+                (RESULT_ID_HEADER.to_lowercase(), "42".to_string()),
+            ]
+        };
+        let mask = SegmentResponse::from_answer(Answer {
+            headers: headers("image/png"),
+            body: fixtures::MASK.into(),
+        })
+        .unwrap();
+        assert_eq!(mask.mask.file_name, "mask.png");
+        assert_eq!(mask.mask.file_type, "image/png");
+        assert_eq!(&mask.mask.data[..], fixtures::MASK);
+        assert_eq!(mask.result_id.as_deref(), Some("42"));
+
+        let example = schema("SegmentResponse")["example"].to_string();
+        let json = SegmentResponse::from_answer(Answer {
+            headers: headers("application/json"),
+            body: example.as_bytes().into(),
+        })
+        .unwrap();
+        assert_ne!(json.result_id.as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn masks_as_they_are_are_described() {
+        let image = &description()["paths"]["/segment"]["post"]["responses"]["200"];
+        assert!(image["content"]["image/*"].is_object());
+        for header in [FILE_NAME_HEADER, RESULT_ID_HEADER] {
+            assert!(image["headers"][header].is_object(), "{header}");
+        }
+    }
+
+    #[test]
+    fn malformed_answers_say_what_is_wrong() {
+        let error = |body: &str| SegmentResponse::from_json(body.as_bytes()).err().unwrap();
+        assert!(error("<html>").starts_with("the answer is not JSON"));
+        assert_eq!(error("[]"), "the answer is not an object");
+        assert_eq!(
+            error(r#"{"file_name": "mask.png", "file_type": "image/png"}"#),
+            "field `data` missing"
+        );
+        assert_eq!(
+            error(r#"{"file_name": "mask.png", "file_type": "image/png", "data": 1}"#),
+            "field `data` is not a string"
+        );
+        assert!(
+            error(r#"{"file_name": "mask.png", "file_type": "image/png", "data": "!"}"#)
+                .starts_with("data is not valid base64")
+        );
+
+        let stored = StoredResult::from_json(br#"{"image": {}, "mask": {}}"#);
+        assert_eq!(stored.err().unwrap(), "field `image.file_name` missing");
+    }
+}
@@ -0,0 +1,545 @@
+//! Segmenting the current image on the server and showing the mask it answers with.
+
+use shadow_clone::shadow_clone;
+use std::rc::Rc;
+use yew::{prelude::*, suspense::use_future_with};
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    api::{use_api, RequestControl, SegmentationApi},
+    clock,
+    collab::ReviewSession,
+    config, decoded,
+    diff::DiffPane,
+    error::{AppError, ErrorAlert},
+    evaluation::GroundTruthPane,
+    flags::{self, Flag},
+    geo::{mask_to_geojson, Georeference},
+    http::{AbortHandle, Progress},
+    i18n::{self, use_language, Language},
+    notify, offline,
+    permalink::CopyLinkButton,
+    postprocess::{self, PipelineStep},
+    project, raster,
+    schema::SegmentResponse,
+    settings::Settings,
+    state::{use_app_state, AppAction, SegmentationStatus},
+    storage::format_size,
+    telemetry::{self, Event},
+    viewer::FileViewer,
+    FileDetails,
+};
+
+/// The mask of the app's current image, asked from the server unless it is already known.
+#[function_component(CurrentSegmentsPane)]
+pub fn current_segments_pane() -> Html {
+    let state = use_app_state();
+    let language = use_language();
+    let onresult = {
+        let state = state.clone();
+        Callback::from(move |mask| state.dispatch(AppAction::Segmented(mask)))
+    };
+    let onstored = {
+        let state = state.clone();
+        Callback::from(move |result_id| state.dispatch(AppAction::Stored(result_id)))
+    };
+    let onqueued = {
+        let state = state.clone();
+        Callback::from(move |_| state.dispatch(AppAction::Queued))
+    };
+    let onstatus = {
+        let state = state.clone();
+        Callback::from(move |status| state.dispatch(AppAction::Progress(status)))
+    };
+
+    if state.status == SegmentationStatus::Cancelled {
+        return html!(<p>{language.t("segments-cancelled")}</p>);
+    }
+
+    html! {
+        <SegmentsPane
+            image_data={state.image.clone()}
+            known_mask={state.restored_mask.clone()}
+            {onresult}
+            {onstored}
+            {onqueued}
+            {onstatus}
+        />
+    }
+}
+
+#[autoprops_component(SegmentsPane)]
+pub fn segments_pane(
+    image_data: Rc<Option<FileDetails>>,
+    #[prop_or_default] known_mask: Option<Rc<FileDetails>>,
+    #[prop_or_default] onresult: Callback<Option<Rc<FileDetails>>>,
+    #[prop_or_default] onstored: Callback<Option<String>>,
+    #[prop_or_default] onqueued: Callback<()>,
+    #[prop_or_default] onstatus: Callback<SegmentationStatus>,
+) -> Html {
+    let language = use_language();
+    // Bumped to send the same image again after a failure.
+    let attempt = use_state(|| 0u32);
+    let onretry = {
+        let attempt = attempt.clone();
+        Callback::from(move |_| attempt.set(*attempt + 1))
+    };
+    let fallback = html!(
+        <h1 role="status">
+            {language.t("segments-processing")}
+            {" "}
+            <span class="spinner-border text-success" aria-hidden="true"></span>
+        </h1>
+    );
+
+    html!(
+        <Suspense {fallback}>
+            <SegmentsInnerPane
+                src_image={image_data}
+                {known_mask}
+                attempt={*attempt}
+                {onretry}
+                {onresult}
+                {onstored}
+                {onqueued}
+                {onstatus}
+            />
+        </Suspense>
+    )
+}
+
+#[derive(Properties, PartialEq)]
+struct SegmentsInnerPaneProps {
+    src_image: Rc<Option<FileDetails>>,
+    /// Mask already computed for `src_image`, used instead of asking the server.
+    #[prop_or_default]
+    known_mask: Option<Rc<FileDetails>>,
+    attempt: u32,
+    onretry: Callback<()>,
+    #[prop_or_default]
+    onresult: Callback<Option<Rc<FileDetails>>>,
+    /// Called with the id the server stored the result under, if any, just before
+    /// `onresult`.
+    #[prop_or_default]
+    onstored: Callback<Option<String>>,
+    /// Called when the image was queued for later because the app is offline.
+    #[prop_or_default]
+    onqueued: Callback<()>,
+    /// Called as the request for `src_image` moves on.
+    #[prop_or_default]
+    onstatus: Callback<SegmentationStatus>,
+}
+
+/// Sends `image` to `api`, reporting through `onstatus` how the request moves on, and runs
+/// the post-processing `steps` on the mask it answers with.
+async fn segment(
+    api: &dyn SegmentationApi,
+    image: FileDetails,
+    steps: &[PipelineStep],
+    onstatus: &Callback<SegmentationStatus>,
+    abort: Option<AbortHandle>,
+) -> Result<SegmentResponse, AppError> {
+    onstatus.emit(SegmentationStatus::Uploading(None));
+    let control = RequestControl {
+        abort,
+        onupload: onstatus
+            .reform(|progress: Progress| SegmentationStatus::Uploading(progress.percent())),
+        onanswer: onstatus.reform(|_| SegmentationStatus::Processing),
+    };
+    let response = api.segment(image, control).await?;
+    let mask = postprocess::apply(steps, response.mask).map_err(AppError::Decode)?;
+    Ok(SegmentResponse { mask, ..response })
+}
+
+#[function_component(SegmentsInnerPane)]
+fn segments_inner_pane(props: &SegmentsInnerPaneProps) -> HtmlResult {
+    let api = use_api();
+    let language = use_language();
+    let settings = use_context::<Rc<Settings>>().unwrap_or_default();
+    let deps = (
+        props.src_image.clone(),
+        props.known_mask.clone(),
+        props.attempt,
+    );
+    let onqueued = props.onqueued.clone();
+    let onstatus = props.onstatus.clone();
+    // The request under way is aborted when another one replaces it, or when the pane goes
+    // away because users cancelled it.
+    let request = use_mut_ref(|| None::<AbortHandle>);
+    {
+        let request = request.clone();
+        use_effect_with((), move |_| {
+            move || {
+                if let Some(abort) = request.borrow_mut().take() {
+                    abort.abort();
+                }
+            }
+        });
+    }
+    let res = use_future_with(deps, move |deps| async move {
+        let (src_image, known_mask, _) = &*deps;
+        if src_image.is_none() {
+            return None;
+        }
+        if let Some(mask) = known_mask {
+            return Some(Ok(SegmentResponse {
+                mask: (**mask).clone(),
+                result_id: None,
+            }));
+        }
+        let image = (**src_image).clone().unwrap();
+        telemetry::record(Event::Request {
+            bytes: image.data.len(),
+            file_type: image.file_type.clone(),
+        });
+        let abort = AbortHandle::new();
+        if let Some(previous) = request.borrow_mut().replace(abort.clone()) {
+            previous.abort();
+        }
+        let started = clock::now();
+        let result = segment(
+            &*api,
+            image.clone(),
+            &settings.postprocess,
+            &onstatus,
+            Some(abort),
+        )
+        .await;
+        let result = match result {
+            // Without a connection the request is kept for later instead of failing for good.
+            Err(AppError::Network { .. }) if offline::unreachable() => {
+                Err(AppError::Offline(match offline::queue(&image).await {
+                    Ok(()) => {
+                        onqueued.emit(());
+                        i18n::t("segments-queued")
+                    }
+                    Err(why) => i18n::t_args("segments-queue-failed", &[("reason", &why)]),
+                }))
+            }
+            result => {
+                if result.is_ok() {
+                    offline::reached();
+                }
+                result
+            }
+        };
+        let latency_ms = clock::now() - started;
+        match &result {
+            Ok(_) => telemetry::record(Event::Segmented { latency_ms }),
+            // Whatever replaced the request reports on its own.
+            Err(AppError::Cancelled) => {}
+            Err(error) => {
+                telemetry::record(Event::Failed {
+                    error: error.kind(),
+                    latency_ms,
+                });
+                onstatus.emit(SegmentationStatus::Failed(error.clone()));
+            }
+        }
+
+        Some(result)
+    })?;
+
+    {
+        let answer = match *res {
+            Some(Ok(ref response)) => (
+                Some(Rc::new(response.mask.clone())),
+                response.result_id.clone(),
+            ),
+            _ => (None, None),
+        };
+        let (onresult, onstored) = (props.onresult.clone(), props.onstored.clone());
+        use_effect_with(answer, move |(mask, result_id)| {
+            onstored.emit(result_id.clone());
+            onresult.emit(mask.clone());
+        });
+    }
+
+    let answer = match *res {
+        Some(ref res) => match res {
+            Ok(SegmentResponse { mask, result_id }) => html! {
+                <MaskViewer
+                    mask={Rc::new(mask.clone())}
+                    result_id={result_id.clone().map(AttrValue::from)}
+                    georef={(*props.src_image).as_ref().and_then(|src| src.georef)}
+                />
+            },
+            Err(error) => html!(
+                <ErrorAlert
+                    error={error.clone()}
+                    title={language.t("segments-failed")}
+                    onretry={props.onretry.clone()}
+                />
+            ),
+        },
+        None => html!({ language.t("segments-no-image") }),
+    };
+
+    Ok(answer)
+}
+
+/// A mask returned by the server, with a link to share it when the server stored it, a
+/// comparison against a reviewer's correction and scores against a ground truth.
+#[autoprops_component(MaskViewer)]
+pub fn mask_viewer(
+    mask: Rc<FileDetails>,
+    #[prop_or_default] result_id: Option<AttrValue>,
+    #[prop_or_default] georef: Option<Georeference>,
+) -> Html {
+    let language = use_language();
+    let alt = use_memo((mask.clone(), language), |(mask, language)| {
+        describe_mask(*language, mask)
+    });
+    let undecodable = use_memo(mask.clone(), |mask| decoded::mask(&mask.data).err());
+    // Results the server stored can be reviewed along with others opening them.
+    let shared = config::current().review_session_url.is_some();
+    let ongeojson = georef.map(|georef| {
+        shadow_clone!(mask);
+        Callback::from(move |_| match mask_to_geojson(&mask.data, &georef) {
+            Ok(geojson) => {
+                let stem = mask
+                    .file_name
+                    .rsplit_once('.')
+                    .map_or(mask.file_name.as_str(), |(stem, _)| stem);
+                project::download(
+                    &format!("{stem}.geojson"),
+                    "application/geo+json",
+                    geojson.as_bytes(),
+                );
+            }
+            Err(e) => notify::error(language.t("mask-geojson-failed"), e),
+        })
+    });
+    html! {
+        <div>
+            <h2>{&mask.file_name}</h2>
+            if let Some(result_id) = &result_id {
+                <CopyLinkButton result_id={result_id.clone()} />
+            }
+            if let Some(reason) = (*undecodable).clone() {
+                <UndecodableMask mask={mask.clone()} reason={AttrValue::from(reason)} />
+            } else {
+                if let (Some(result_id), true) = (result_id.clone(), shared) {
+                    <ReviewSession {result_id}>
+                        <FileViewer file={mask.clone()} alt={AttrValue::from((*alt).clone())} {georef} />
+                    </ReviewSession>
+                } else {
+                    <FileViewer file={mask.clone()} alt={AttrValue::from((*alt).clone())} {georef} />
+                }
+                if let Some(onclick) = ongeojson {
+                    <button class="btn btn-sm btn-outline-secondary mb-2" {onclick}>
+                        {language.t("mask-download-geojson")}
+                    </button>
+                }
+                if flags::enabled(Flag::Diff) {
+                    <DiffPane original={mask.clone()} />
+                }
+                if flags::enabled(Flag::Evaluation) {
+                    <GroundTruthPane mask={mask.clone()} />
+                }
+            }
+        </div>
+    }
+}
+
+/// Bytes shown from the start of a mask that could not be read.
+const LEADING_BYTES: usize = 32;
+
+/// The first `count` bytes of `data` in hexadecimal, in groups of four.
+fn leading_bytes(data: &[u8], count: usize) -> String {
+    data[..data.len().min(count)]
+        .chunks(4)
+        .map(|group| {
+            group
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// What is known of a mask the server answered with but that could not be read, with its
+/// bytes offered as they came, rather than an empty image.
+#[autoprops_component(UndecodableMask)]
+fn undecodable_mask(mask: Rc<FileDetails>, reason: AttrValue) -> Html {
+    let language = use_language();
+    let ondownload = {
+        let mask = mask.clone();
+        move |_| project::download(&mask.file_name, &mask.file_type, &mask.data)
+    };
+    html! {
+        <div class="card border-warning" role="alert">
+            <div class="card-body">
+                <h3 class="card-title h6">{language.t("mask-undecodable")}</h3>
+                <p class="card-text">{reason}</p>
+                <dl class="row small mb-2">
+                    <dt class="col-sm-3">{language.t("mask-undecodable-type")}</dt>
+                    <dd class="col-sm-9 font-monospace">{&mask.file_type}</dd>
+                    <dt class="col-sm-3">{language.t("mask-undecodable-size")}</dt>
+                    <dd class="col-sm-9">{format_size(mask.data.len() as u64)}</dd>
+                    <dt class="col-sm-3">{language.t("mask-undecodable-bytes")}</dt>
+                    <dd class="col-sm-9 font-monospace text-break">
+                        {leading_bytes(&mask.data, LEADING_BYTES)}
+                    </dd>
+                </dl>
+                <button class="btn btn-sm btn-outline-secondary" onclick={ondownload}>
+                    {language.t("mask-undecodable-download")}
+                </button>
+            </div>
+        </div>
+    }
+}
+
+/// Text standing in for `mask` for readers who cannot see it: the share of the image each
+/// class covers, largest first.
+fn describe_mask(language: Language, mask: &FileDetails) -> String {
+    let name = &mask.file_name;
+    let Ok(mask) = decoded::mask(&mask.data) else {
+        return language.t_args("mask-alt-undecodable", &[("name", name)]);
+    };
+    let counts = raster::class_counts(&mask);
+    let total = mask.as_raw().len().max(1);
+    let mut classes: Vec<_> = (0..=u8::MAX).filter(|&c| counts[c as usize] > 0).collect();
+    classes.sort_by_key(|&c| std::cmp::Reverse(counts[c as usize]));
+    let classes = classes
+        .into_iter()
+        .map(|class| {
+            let percent = format!(
+                "{:.1}",
+                counts[class as usize] as f64 * 100.0 / total as f64
+            );
+            language.t_args(
+                "mask-alt-class",
+                &[("class", &class), ("percent", &percent)],
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    language.t_args("mask-alt", &[("name", name), ("classes", &classes)])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+    use yew::Callback;
+
+    use super::{describe_mask, leading_bytes, segment};
+    use crate::{
+        api::mock::{block_on, MockApi},
+        diff::decode_mask,
+        error::AppError,
+        fixtures,
+        i18n::Language,
+        postprocess::{PipelineStep, RemoveSmallRegions, Step},
+        schema::SegmentResponse,
+        state::SegmentationStatus,
+    };
+
+    fn answer(data: &[u8]) -> Result<SegmentResponse, AppError> {
+        Ok(SegmentResponse {
+            mask: fixtures::png("mask.png", data),
+            result_id: None,
+        })
+    }
+
+    /// A callback and the statuses it was called with.
+    fn recorder() -> (
+        Callback<SegmentationStatus>,
+        Rc<RefCell<Vec<SegmentationStatus>>>,
+    ) {
+        let statuses = Rc::new(RefCell::new(vec![]));
+        let onstatus = {
+            let statuses = statuses.clone();
+            Callback::from(move |status| statuses.borrow_mut().push(status))
+        };
+        (onstatus, statuses)
+    }
+
+    #[test]
+    fn segment_reports_progress_and_returns_the_mask() {
+        let api = MockApi::answering([answer(fixtures::MASK)]);
+        let (onstatus, statuses) = recorder();
+        let image = fixtures::png("image.png", fixtures::IMAGE);
+
+        let response = block_on(segment(&api, image, &[], &onstatus, None)).unwrap();
+
+        assert_eq!(&response.mask.data[..], fixtures::MASK);
+        assert_eq!(*api.sent.borrow(), ["image.png"]);
+        assert_eq!(
+            *statuses.borrow(),
+            [
+                SegmentationStatus::Uploading(None),
+                SegmentationStatus::Processing
+            ]
+        );
+    }
+
+    #[test]
+    fn segment_post_processes_the_mask() {
+        let api = MockApi::answering([answer(fixtures::MASK)]);
+        let steps = [PipelineStep {
+            enabled: true,
+            step: Step::RemoveSmallRegions(RemoveSmallRegions { min_pixels: 2 }),
+        }];
+        let image = fixtures::png("image.png", fixtures::IMAGE);
+
+        let response = block_on(segment(&api, image, &steps, &Callback::noop(), None)).unwrap();
+
+        assert_eq!(
+            decode_mask(&response.mask.data).unwrap(),
+            decode_mask(fixtures::MASK_CORRECTED).unwrap()
+        );
+    }
+
+    #[test]
+    fn segment_passes_server_errors_on() {
+        let error = AppError::Http {
+            url: "mock://segment".to_string(),
+            status: 500,
+            message: "Internal server error".to_string(),
+        };
+        let api = MockApi::answering([Err(error.clone())]);
+        let (onstatus, statuses) = recorder();
+        let image = fixtures::png("image.png", fixtures::IMAGE);
+
+        let result = block_on(segment(&api, image, &[], &onstatus, None));
+
+        assert!(matches!(result, Err(e) if e == error));
+        assert_eq!(*statuses.borrow(), [SegmentationStatus::Uploading(None)]);
+    }
+
+    #[test]
+    fn segment_rejects_masks_that_cannot_be_post_processed() {
+        let api = MockApi::answering([answer(b"not an image")]);
+        let steps = [PipelineStep {
+            enabled: true,
+            step: Step::RemoveSmallRegions(RemoveSmallRegions { min_pixels: 2 }),
+        }];
+        let image = fixtures::png("image.png", fixtures::IMAGE);
+
+        let result = block_on(segment(&api, image, &steps, &Callback::noop(), None));
+
+        assert!(matches!(result, Err(AppError::Decode(_))));
+    }
+
+    #[test]
+    fn mask_descriptions_list_class_shares_largest_first() {
+        assert_eq!(
+            describe_mask(Language::En, &fixtures::png("mask.png", fixtures::MASK)),
+            "Segmentation mask mask.png: class 0 covers 73.4%, class 1 covers 25.0%, \
+             class 2 covers 1.6%"
+        );
+        assert_eq!(
+            describe_mask(Language::En, &fixtures::png("mask.png", b"not a png")),
+            "Segmentation mask mask.png, which could not be read"
+        );
+    }
+
+    #[test]
+    fn leading_bytes_are_shown_in_groups() {
+        assert_eq!(leading_bytes(b"\x89PNG\r\n", 32), "89504e47 0d0a");
+        assert_eq!(leading_bytes(b"<html>", 4), "3c68746d");
+        assert_eq!(leading_bytes(b"", 4), "");
+    }
+}
@@ -0,0 +1,138 @@
+//! Autosave of the session in progress, offered back after an accidental reload.
+
+use js_sys::Date;
+use shadow_clone::shadow_clone;
+use std::rc::Rc;
+use wasm_bindgen::JsValue;
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+
+use crate::{i18n::use_language, idb, notify, project::Project};
+
+const SESSION_KEY: &str = "current";
+/// How long the session has to stay unchanged before it is autosaved.
+pub const AUTOSAVE_DELAY_MS: u32 = 1000;
+
+/// Progress of the latest autosave, shown so users know their work is safe.
+#[derive(Clone, PartialEq)]
+pub enum AutosaveStatus {
+    /// Nothing to save yet.
+    Idle,
+    /// A change is waiting for the session to settle.
+    Pending,
+    Saving,
+    /// Saved at this many milliseconds since the Unix epoch.
+    Saved(f64),
+    Failed(String),
+}
+
+/// Replaces the autosaved session with `project`.
+pub async fn autosave(project: &Project) -> Result<(), String> {
+    let json = serde_json::to_string(project)
+        .map_err(|e| format!("Could not serialize the session: {e}"))?;
+    let db = idb::open().await?;
+    idb::put_with_key(
+        &db,
+        idb::SESSION_STORE,
+        &JsValue::from(json),
+        &SESSION_KEY.into(),
+    )
+    .await
+}
+
+/// The autosaved session, if there is one that can still be read.
+pub async fn load() -> Result<Option<Project>, String> {
+    let db = idb::open().await?;
+    let value = idb::get(&db, idb::SESSION_STORE, &SESSION_KEY.into()).await?;
+    let Some(json) = value.as_string() else {
+        return Ok(None);
+    };
+    Project::from_json(json.as_bytes()).map(Some)
+}
+
+/// Forgets the autosaved session, once it was restored, discarded or saved to a file.
+pub async fn clear() -> Result<(), String> {
+    let db = idb::open().await?;
+    idb::delete(&db, idb::SESSION_STORE, &SESSION_KEY.into()).await
+}
+
+#[autoprops_component(AutosaveIndicator)]
+pub fn autosave_indicator(status: AutosaveStatus) -> Html {
+    let language = use_language();
+    match status {
+        AutosaveStatus::Idle => html!(),
+        AutosaveStatus::Pending => {
+            html!(<small class="text-body-secondary">{language.t("session-unsaved")}</small>)
+        }
+        AutosaveStatus::Saving => {
+            html!(<small class="text-body-secondary">{language.t("session-saving")}</small>)
+        }
+        AutosaveStatus::Saved(timestamp) => {
+            let time: String = Date::new(&timestamp.into())
+                .to_locale_time_string("default")
+                .into();
+            html!(<small class="text-success">{language.t_args("session-saved", &[("time", &time)])}</small>)
+        }
+        AutosaveStatus::Failed(why) => {
+            html!(<small class="text-danger">{language.t_args("session-failed", &[("reason", &why)])}</small>)
+        }
+    }
+}
+
+/// Banner offering to restore the session autosaved before the page was last closed.
+#[autoprops_component(RestoreBanner)]
+pub fn restore_banner(onrestore: Callback<Rc<Project>>) -> Html {
+    let language = use_language();
+    let pending = use_state(|| None::<Rc<Project>>);
+
+    {
+        shadow_clone!(pending);
+        use_effect_with((), move |_| {
+            spawn_local(async move {
+                match load().await {
+                    Ok(project) => pending.set(project.map(Rc::new)),
+                    Err(why) => notify::warn(language.t("session-unreadable"), why),
+                }
+            });
+        });
+    }
+
+    let Some(project) = (*pending).clone() else {
+        return html!();
+    };
+
+    let onclick = {
+        shadow_clone!(pending, onrestore, project);
+        move |_| {
+            pending.set(None);
+            onrestore.emit(project.clone());
+        }
+    };
+
+    let ondiscard = {
+        shadow_clone!(pending);
+        move |_| {
+            pending.set(None);
+            spawn_local(async {
+                if let Err(why) = clear().await {
+                    log::warn!("Could not discard the autosaved session: {why}");
+                }
+            });
+        }
+    };
+
+    html! {
+        <div class="alert alert-info d-flex justify-content-between align-items-center">
+            <span>
+                {match project.image_name() {
+                    Some(image) => language.t_args("session-found-for", &[("image", &image)]),
+                    None => language.t("session-found"),
+                }}
+            </span>
+            <div class="btn-group btn-group-sm">
+                <button class="btn btn-primary" {onclick}>{language.t("session-restore")}</button>
+                <button class="btn btn-outline-secondary" onclick={ondiscard}>{language.t("session-discard")}</button>
+            </div>
+        </div>
+    }
+}
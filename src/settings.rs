@@ -0,0 +1,399 @@
+//! User settings persisted in local storage and shared with components through context.
+
+use gloo::storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use shadow_clone::shadow_clone;
+use std::rc::Rc;
+use web_sys::HtmlInputElement;
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    config,
+    i18n::use_language,
+    map::TileSource,
+    postprocess::PipelineStep,
+    roles::use_role,
+    sync::{self, SyncMessage},
+    telemetry, workspace,
+};
+
+pub const STORAGE_KEY: &str = "settings";
+
+/// Version 2 keeps the tile sources users register, which were forgotten on reload before,
+/// starting from the configured ones.
+fn keep_tile_sources(stored: &mut Map<String, Value>) {
+    stored.entry("tile_sources").or_insert_with(|| {
+        serde_json::to_value(config::current().tile_sources()).unwrap_or_default()
+    });
+}
+
+/// Upgrades stored settings of version `i + 1` to version `i + 2`. Fields added without
+/// a migration simply take their default value.
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[keep_tile_sources];
+const VERSION: u64 = MIGRATIONS.len() as u64 + 1;
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Base URL of the segmentation server, without a trailing slash.
+    pub server_url: String,
+    /// Bearer token sent with segmentation requests.
+    pub token: Option<String>,
+    /// Opacity the mask layer starts with on the map.
+    pub mask_opacity: f64,
+    /// Number of newest results the history is pruned down to, if limited.
+    pub keep_results: Option<u32>,
+    /// Age in days after which results are pruned from the history, if limited.
+    pub keep_days: Option<u32>,
+    /// Megabytes decoded masks are kept in, so that they are not decoded again.
+    pub decoded_cache_mb: u32,
+    /// Queued images sent to the server at once, so it never waits for the next one.
+    pub parallel_requests: u32,
+    /// Megabytes of selected files past which users confirm before they are read, or 0 to
+    /// read files of any size at once.
+    pub large_file_mb: u32,
+    /// Steps applied to every mask the server answers with.
+    pub postprocess: Vec<PipelineStep>,
+    /// Whether anonymous usage statistics are sent to the deployment's collector.
+    pub telemetry: bool,
+    /// Basemaps and imagery sources offered on the map, with the keys they are asked with.
+    pub tile_sources: Vec<TileSource>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            server_url: config::current().server_url.clone(),
+            token: None,
+            mask_opacity: 0.6,
+            keep_results: None,
+            keep_days: None,
+            decoded_cache_mb: 64,
+            parallel_requests: 2,
+            large_file_mb: 256,
+            postprocess: Vec::new(),
+            telemetry: false,
+            tile_sources: config::current().tile_sources(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn segment_url(&self) -> String {
+        format!("{}/segment", self.server_url)
+    }
+
+    pub fn result_url(&self, result_id: &str) -> String {
+        format!("{}/results/{}", self.server_url, result_id)
+    }
+
+    /// Settings saved in an earlier session, or the defaults when there are none or they
+    /// come from a newer version of the app.
+    pub fn load() -> Self {
+        let Ok(Value::Object(stored)) = LocalStorage::get::<Value>(workspace::key(STORAGE_KEY))
+        else {
+            return Settings::default();
+        };
+        Settings::from_stored(stored)
+    }
+
+    /// Settings stored by any version of the app, those before versioning included.
+    fn from_stored(mut stored: Map<String, Value>) -> Self {
+        let version = stored
+            .get("version")
+            .and_then(Value::as_u64)
+            .unwrap_or(1)
+            .max(1);
+        if version > VERSION {
+            log::warn!("Ignoring settings saved by a newer version ({version})");
+            return Settings::default();
+        }
+        for migration in &MIGRATIONS[version as usize - 1..] {
+            migration(&mut stored);
+        }
+        serde_json::from_value(Value::Object(stored)).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable settings: {e}");
+            Settings::default()
+        })
+    }
+
+    /// Saves the settings of the current workspace, telling the other tabs about it.
+    pub fn store(&self) {
+        match self.store_to(&workspace::key(STORAGE_KEY)) {
+            Ok(()) => sync::broadcast(SyncMessage::SettingsChanged),
+            Err(why) => log::warn!("{why}"),
+        }
+    }
+
+    pub fn store_to(&self, key: &str) -> Result<(), String> {
+        let mut value = serde_json::to_value(self).unwrap();
+        value["version"] = VERSION.into();
+        LocalStorage::set(key, value).map_err(|why| format!("Could not save settings: {why}"))
+    }
+}
+
+#[autoprops_component(SettingsPane)]
+pub fn settings_pane(settings: Rc<Settings>, onchange: Callback<Rc<Settings>>) -> Html {
+    let language = use_language();
+    let role = use_role();
+    let update = {
+        shadow_clone!(settings, onchange);
+        move |change: &dyn Fn(&mut Settings, String), e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            let mut new_settings = (*settings).clone();
+            change(&mut new_settings, input.value());
+            onchange.emit(Rc::new(new_settings));
+        }
+    };
+
+    let onserver = {
+        shadow_clone!(update);
+        move |e| {
+            update(
+                &|s, value| s.server_url = value.trim().trim_end_matches('/').to_string(),
+                e,
+            )
+        }
+    };
+    let ontoken = {
+        shadow_clone!(update);
+        move |e| {
+            update(
+                &|s, value| s.token = (!value.is_empty()).then_some(value),
+                e,
+            )
+        }
+    };
+    let onopacity = {
+        shadow_clone!(update);
+        move |e| {
+            update(
+                &|s, value| {
+                    if let Ok(opacity) = value.parse::<f64>() {
+                        s.mask_opacity = opacity.clamp(0.0, 1.0);
+                    }
+                },
+                e,
+            )
+        }
+    };
+    let onkeepresults = {
+        shadow_clone!(update);
+        move |e| update(&|s, value| s.keep_results = value.parse().ok(), e)
+    };
+    let onkeepdays = {
+        shadow_clone!(update);
+        move |e| update(&|s, value| s.keep_days = value.parse().ok(), e)
+    };
+    let ondecodedcache = {
+        shadow_clone!(update);
+        move |e| {
+            update(
+                &|s, value| {
+                    if let Ok(megabytes) = value.parse() {
+                        s.decoded_cache_mb = megabytes;
+                    }
+                },
+                e,
+            )
+        }
+    };
+    let onlargefile = {
+        shadow_clone!(update);
+        move |e| {
+            update(
+                &|s, value| {
+                    if let Ok(megabytes) = value.parse() {
+                        s.large_file_mb = megabytes;
+                    }
+                },
+                e,
+            )
+        }
+    };
+
+    let onparallel = {
+        shadow_clone!(update);
+        move |e| {
+            update(
+                &|s, value| {
+                    if let Ok(requests) = value.parse::<u32>() {
+                        s.parallel_requests = requests.max(1);
+                    }
+                },
+                e,
+            )
+        }
+    };
+    let ontelemetry = {
+        shadow_clone!(settings, onchange);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            onchange.emit(Rc::new(Settings {
+                telemetry: input.checked(),
+                ..(*settings).clone()
+            }));
+        }
+    };
+    let onreset = {
+        shadow_clone!(onchange);
+        move |_| onchange.emit(Rc::new(Settings::default()))
+    };
+
+    html! {
+        <div style="max-width: 40em;">
+            <div class="input-group input-group-sm mb-1">
+                <span class="input-group-text" style="width: 12em;">{language.t("settings-token")}</span>
+                <input
+                    type="password"
+                    class="form-control"
+                    value={settings.token.clone().unwrap_or_default()}
+                    onchange={ontoken}
+                />
+            </div>
+            // Users who may not change the rest can still sign in with another token.
+            if role.can_administer() {
+                <>
+                    <div class="input-group input-group-sm mb-1">
+                        <span class="input-group-text" style="width: 12em;">{language.t("settings-server-url")}</span>
+                        <input
+                            type="url"
+                            class="form-control"
+                            value={settings.server_url.clone()}
+                            onchange={onserver}
+                        />
+                    </div>
+                    <div class="input-group input-group-sm mb-1">
+                        <span class="input-group-text" style="width: 12em;">{language.t("settings-opacity")}</span>
+                        <input
+                            type="number"
+                            class="form-control"
+                            min="0"
+                            max="1"
+                            step="0.05"
+                            value={settings.mask_opacity.to_string()}
+                            onchange={onopacity}
+                        />
+                    </div>
+                    <div class="input-group input-group-sm mb-1">
+                        <span class="input-group-text" style="width: 12em;">{language.t("settings-keep-results")}</span>
+                        <input
+                            type="number"
+                            class="form-control"
+                            min="1"
+                            placeholder={language.t("settings-keep-all")}
+                            value={settings.keep_results.map(|n| n.to_string()).unwrap_or_default()}
+                            onchange={onkeepresults}
+                        />
+                    </div>
+                    <div class="input-group input-group-sm mb-1">
+                        <span class="input-group-text" style="width: 12em;">{language.t("settings-keep-days")}</span>
+                        <input
+                            type="number"
+                            class="form-control"
+                            min="1"
+                            placeholder={language.t("settings-keep-forever")}
+                            value={settings.keep_days.map(|n| n.to_string()).unwrap_or_default()}
+                            onchange={onkeepdays}
+                        />
+                    </div>
+                    <div class="input-group input-group-sm mb-1">
+                        <span class="input-group-text" style="width: 12em;">{language.t("settings-decoded-cache")}</span>
+                        <input
+                            type="number"
+                            class="form-control"
+                            min="0"
+                            value={settings.decoded_cache_mb.to_string()}
+                            onchange={ondecodedcache}
+                        />
+                    </div>
+                    <div class="input-group input-group-sm mb-1">
+                        <span class="input-group-text" style="width: 12em;">{language.t("settings-parallel-requests")}</span>
+                        <input
+                            type="number"
+                            class="form-control"
+                            min="1"
+                            value={settings.parallel_requests.to_string()}
+                            onchange={onparallel}
+                        />
+                    </div>
+                    <div class="input-group input-group-sm mb-1">
+                        <span class="input-group-text" style="width: 12em;">{language.t("settings-large-file")}</span>
+                        <input
+                            type="number"
+                            class="form-control"
+                            min="0"
+                            value={settings.large_file_mb.to_string()}
+                            onchange={onlargefile}
+                        />
+                    </div>
+                </>
+            }
+            if telemetry::is_available() {
+                <div class="form-check mb-1">
+                    <input
+                        type="checkbox"
+                        class="form-check-input"
+                        id="telemetry"
+                        checked={settings.telemetry}
+                        onchange={ontelemetry}
+                    />
+                    <label class="form-check-label" for="telemetry">
+                        {language.t("settings-telemetry")}
+                    </label>
+                </div>
+            }
+            if role.can_administer() {
+                <button class="btn btn-sm btn-outline-secondary" onclick={onreset}>
+                    {language.t("settings-reset")}
+                </button>
+            }
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::Settings;
+    use crate::map::TileSource;
+
+    fn stored(value: Value) -> Settings {
+        let Value::Object(stored) = value else {
+            unreachable!()
+        };
+        Settings::from_stored(stored)
+    }
+
+    #[test]
+    fn settings_of_any_version_are_read() {
+        let settings = stored(json!({"mask_opacity": 0.25}));
+        assert_eq!(settings.mask_opacity, 0.25);
+        let settings = stored(json!({"version": 0, "mask_opacity": 0.25}));
+        assert_eq!(settings.mask_opacity, 0.25);
+        let settings = stored(json!({"version": 1000, "mask_opacity": 0.25}));
+        assert_eq!(settings.mask_opacity, Settings::default().mask_opacity);
+    }
+
+    #[test]
+    fn tile_sources_survive_a_round_trip() {
+        let mut source = TileSource::osm();
+        source.auth = Some(("key".to_string(), "secret".to_string()));
+        let settings = Settings {
+            tile_sources: vec![source],
+            ..Settings::default()
+        };
+        let mut value = serde_json::to_value(&settings).unwrap();
+        value["version"] = super::VERSION.into();
+        assert_eq!(stored(value), settings);
+    }
+
+    #[test]
+    fn settings_from_before_tile_sources_start_with_the_configured_ones() {
+        let settings = stored(json!({"version": 1, "mask_opacity": 0.25}));
+        assert_eq!(settings.tile_sources, [TileSource::osm()]);
+    }
+}
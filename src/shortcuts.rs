@@ -0,0 +1,214 @@
+//! Keyboard shortcuts, registered by components into one registry that listens for keys
+//! once for the whole page, refuses a key already bound to something else, and lists
+//! every binding in an overlay shown with `?`.
+
+use gloo::events::EventListener;
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlElement, KeyboardEvent};
+use yew::prelude::*;
+
+use crate::i18n::use_language;
+
+struct Binding {
+    id: u32,
+    /// Values of `KeyboardEvent.key` triggering the binding.
+    keys: &'static [&'static str],
+    /// Message key of what the binding does, as listed in the overlay.
+    description: &'static str,
+    callback: Callback<()>,
+}
+
+thread_local! {
+    static NEXT_ID: Cell<u32> = const { Cell::new(0) };
+    static BINDINGS: RefCell<Vec<Binding>> = const { RefCell::new(Vec::new()) };
+    static LISTENER: RefCell<Option<EventListener>> = const { RefCell::new(None) };
+}
+
+/// Whether `e` is typing into a form field rather than a shortcut.
+fn is_typing(e: &KeyboardEvent) -> bool {
+    let Some(target) = e.target().and_then(|t| t.dyn_into::<HtmlElement>().ok()) else {
+        return false;
+    };
+    matches!(target.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+        || target.is_content_editable()
+}
+
+fn onkeydown(e: &KeyboardEvent) {
+    if e.ctrl_key() || e.meta_key() || e.alt_key() || is_typing(e) {
+        return;
+    }
+    let key = e.key();
+    let callback = BINDINGS.with(|bindings| {
+        bindings
+            .borrow()
+            .iter()
+            .find(|binding| binding.keys.contains(&key.as_str()))
+            .map(|binding| binding.callback.clone())
+    });
+    if let Some(callback) = callback {
+        e.prevent_default();
+        callback.emit(());
+    }
+}
+
+/// Keeps a binding for as long as it is alive.
+pub struct Registration(u32);
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        BINDINGS.with(|bindings| bindings.borrow_mut().retain(|binding| binding.id != self.0));
+    }
+}
+
+/// Binds `keys` to `callback`, unless one of them is bound already.
+pub fn register(
+    keys: &'static [&'static str],
+    description: &'static str,
+    callback: Callback<()>,
+) -> Result<Registration, String> {
+    let conflict = BINDINGS.with(|bindings| {
+        bindings.borrow().iter().find_map(|binding| {
+            keys.iter()
+                .find(|key| binding.keys.contains(key))
+                .map(|key| format!("{key} is already bound to {}", binding.description))
+        })
+    });
+    if let Some(conflict) = conflict {
+        return Err(format!("Could not bind {description}: {conflict}"));
+    }
+    LISTENER.with(|listener| {
+        listener.borrow_mut().get_or_insert_with(|| {
+            EventListener::new(&gloo::utils::window(), "keydown", |e| {
+                onkeydown(e.unchecked_ref())
+            })
+        });
+    });
+    let id = NEXT_ID.with(|id| id.replace(id.get() + 1));
+    BINDINGS.with(|bindings| {
+        bindings.borrow_mut().push(Binding {
+            id,
+            keys,
+            description,
+            callback,
+        })
+    });
+    Ok(Registration(id))
+}
+
+/// Binds `keys` to `callback` while the component is mounted, calling the `callback` of
+/// its latest render. Conflicts are logged, leaving the earlier binding in place.
+#[hook]
+pub fn use_shortcut(
+    keys: &'static [&'static str],
+    description: &'static str,
+    callback: Callback<()>,
+) {
+    let latest = use_mut_ref(Callback::noop);
+    *latest.borrow_mut() = callback;
+    use_effect_with((), move |_| {
+        let registration = register(
+            keys,
+            description,
+            Callback::from(move |_| latest.borrow().emit(())),
+        )
+        .map_err(|why| log::warn!("{why}"))
+        .ok();
+        move || drop(registration)
+    });
+}
+
+/// Every binding, as listed in the overlay.
+fn bindings() -> Vec<(&'static [&'static str], &'static str)> {
+    BINDINGS.with(|bindings| {
+        bindings
+            .borrow()
+            .iter()
+            .map(|binding| (binding.keys, binding.description))
+            .collect()
+    })
+}
+
+/// The list of shortcuts, shown and hidden with `?`.
+#[function_component(ShortcutHelp)]
+pub fn shortcut_help() -> Html {
+    let language = use_language();
+    let open = use_state(|| false);
+    let ontoggle = {
+        let open = open.clone();
+        Callback::from(move |_| open.set(!*open))
+    };
+    use_shortcut(&["?"], "shortcut-help", ontoggle.clone());
+    // Focus moves into the dialog while it is open, and back to where it was after.
+    let close_ref = use_node_ref();
+    use_effect_with((*open, close_ref.clone()), |(open, close_ref)| {
+        let previous = open
+            .then(|| gloo::utils::document().active_element())
+            .flatten()
+            .and_then(|element| element.dyn_into::<HtmlElement>().ok());
+        if let Some(close) = close_ref.cast::<HtmlElement>() {
+            let _ = close.focus();
+        }
+        move || {
+            if let Some(previous) = previous {
+                let _ = previous.focus();
+            }
+        }
+    });
+
+    if !*open {
+        return html!();
+    }
+    let onclose = {
+        let ontoggle = ontoggle.clone();
+        move |_| ontoggle.emit(())
+    };
+    let onkeydown = move |e: KeyboardEvent| {
+        if e.key() == "Escape" {
+            ontoggle.emit(());
+        }
+    };
+
+    html! {
+        <div
+            class="modal d-block"
+            tabindex="-1"
+            role="dialog"
+            aria-modal="true"
+            aria-labelledby="shortcut-help-title"
+            style="background: rgba(0, 0, 0, 0.5);"
+            {onkeydown}
+        >
+            <div class="modal-dialog">
+                <div class="modal-content">
+                    <div class="modal-header">
+                        <h5 class="modal-title" id="shortcut-help-title">{language.t("shortcuts")}</h5>
+                        <button
+                            type="button"
+                            class="btn-close"
+                            aria-label={language.t("action-close")}
+                            ref={close_ref}
+                            onclick={onclose}
+                        ></button>
+                    </div>
+                    <div class="modal-body">
+                        <table class="table table-sm mb-0">
+                            <tbody>
+                            {
+                                for bindings().into_iter().map(|(keys, description)| html! {
+                                    <tr>
+                                        <td>
+                                            {for keys.iter().map(|key| html!(<kbd class="me-1">{key}</kbd>))}
+                                        </td>
+                                        <td>{language.t(description)}</td>
+                                    </tr>
+                                })
+                            }
+                            </tbody>
+                        </table>
+                    </div>
+                </div>
+            </div>
+        </div>
+    }
+}
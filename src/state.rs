@@ -0,0 +1,387 @@
+//! State shared by the whole app, kept in one place and handed to components through
+//! context rather than passed down as props.
+
+use std::rc::Rc;
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    api::Api, crash, decoded, error::AppError, settings::Settings, telemetry, FileDetails,
+};
+
+/// Where segmenting the current image stands. Moves from `Idle` through `Reading`,
+/// `Uploading` and `Processing` to one of `Done`, `Failed` or `Cancelled`.
+#[derive(Clone, Default, PartialEq, Debug)]
+pub enum SegmentationStatus {
+    #[default]
+    Idle,
+    /// The selected files are being read.
+    Reading,
+    /// The image is being sent, with the percentage sent so far once browsers tell it.
+    /// This lasts until the server starts answering.
+    Uploading(Option<u8>),
+    /// The server's answer is being received and read.
+    Processing,
+    Done,
+    Failed(AppError),
+    Cancelled,
+}
+
+impl SegmentationStatus {
+    /// Whether a request is under way, and can be cancelled.
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            SegmentationStatus::Uploading(_) | SegmentationStatus::Processing
+        )
+    }
+}
+
+#[derive(Clone, Default, PartialEq)]
+pub struct AppState {
+    pub settings: Rc<Settings>,
+    /// The image being worked on.
+    pub image: Rc<Option<FileDetails>>,
+    /// Mask of `image`, once segmented.
+    pub mask: Option<Rc<FileDetails>>,
+    /// Mask reloaded for `image` from the history or a project, shown without asking the
+    /// server again.
+    pub restored_mask: Option<Rc<FileDetails>>,
+    /// Id of the history entry `image` was opened from.
+    pub opened_result: Option<u64>,
+    /// Id the server stored `mask` under, if it did.
+    pub result_id: Option<String>,
+    /// Mask of `image` drawn by hand, which its mask is scored against.
+    pub ground_truth: Option<Rc<FileDetails>>,
+    pub status: SegmentationStatus,
+    /// Changes whenever the stored history does.
+    pub history_revision: i32,
+    /// Changes whenever an image is queued to be segmented later.
+    pub outbox_revision: i32,
+}
+
+pub enum AppAction {
+    /// A new image to segment, or none.
+    Upload(Rc<Option<FileDetails>>),
+    /// An image together with the mask already computed for it.
+    Open {
+        image: Rc<Option<FileDetails>>,
+        mask: Option<Rc<FileDetails>>,
+        result: Option<u64>,
+    },
+    /// Files started being read, or stopped without giving an image.
+    Reading(bool),
+    /// The request for the current image moved on.
+    Progress(SegmentationStatus),
+    /// The server answered the request for the current image.
+    Segmented(Option<Rc<FileDetails>>),
+    /// The server stored its answer for the current image under this id, or did not.
+    Stored(Option<String>),
+    /// The user gave up on the request under way; its answer will be ignored.
+    Cancel,
+    /// The user asked to segment the current image again after cancelling.
+    Retry,
+    /// A ground truth for the current image, or none.
+    GroundTruth(Option<Rc<FileDetails>>),
+    SetSettings(Rc<Settings>),
+    HistoryChanged,
+    Queued,
+}
+
+impl AppAction {
+    /// Which action this is, without what it carries.
+    fn name(&self) -> &'static str {
+        match self {
+            AppAction::Upload(_) => "upload",
+            AppAction::Open { .. } => "open",
+            AppAction::Reading(_) => "reading",
+            AppAction::Progress(_) => "progress",
+            AppAction::Segmented(_) => "segmented",
+            AppAction::Stored(_) => "stored",
+            AppAction::Cancel => "cancel",
+            AppAction::Retry => "retry",
+            AppAction::GroundTruth(_) => "ground_truth",
+            AppAction::SetSettings(_) => "set_settings",
+            AppAction::HistoryChanged => "history_changed",
+            AppAction::Queued => "queued",
+        }
+    }
+}
+
+impl AppState {
+    /// Where the session stands, saying nothing of the files' names or content, nor of the
+    /// servers used.
+    pub fn summary(&self) -> String {
+        let status = match &self.status {
+            SegmentationStatus::Idle => "idle".to_string(),
+            SegmentationStatus::Reading => "reading".to_string(),
+            SegmentationStatus::Uploading(_) => "uploading".to_string(),
+            SegmentationStatus::Processing => "processing".to_string(),
+            SegmentationStatus::Done => "done".to_string(),
+            SegmentationStatus::Failed(error) => format!("failed ({})", error.kind()),
+            SegmentationStatus::Cancelled => "cancelled".to_string(),
+        };
+        let file = |file: Option<&FileDetails>| match file {
+            Some(file) => format!(
+                "{}, {} bytes{}",
+                file.file_type,
+                file.data.len(),
+                if file.georef.is_some() {
+                    ", georeferenced"
+                } else {
+                    ""
+                }
+            ),
+            None => "none".to_string(),
+        };
+        format!(
+            "status: {status}; image: {}; mask: {}; opened from history: {}; \
+             post-processing steps: {}",
+            file((*self.image).as_ref()),
+            file(self.mask.as_deref().or(self.restored_mask.as_deref())),
+            self.opened_result.is_some(),
+            self.settings.postprocess.len(),
+        )
+    }
+}
+
+impl Reducible for AppState {
+    type Action = AppAction;
+
+    fn reduce(self: Rc<Self>, action: AppAction) -> Rc<Self> {
+        let mut state = Rc::unwrap_or_clone(self);
+        crash::note_action(action.name());
+        match action {
+            AppAction::Upload(image) => {
+                state.status = match *image {
+                    Some(_) => SegmentationStatus::Uploading(None),
+                    None => SegmentationStatus::Idle,
+                };
+                state.image = image;
+                state.mask = None;
+                state.restored_mask = None;
+                state.opened_result = None;
+                state.result_id = None;
+                state.ground_truth = None;
+            }
+            AppAction::Open {
+                image,
+                mask,
+                result,
+            } => {
+                state.status = match (&*image, &mask) {
+                    (None, _) => SegmentationStatus::Idle,
+                    (Some(_), Some(_)) => SegmentationStatus::Done,
+                    (Some(_), None) => SegmentationStatus::Uploading(None),
+                };
+                state.image = image;
+                state.mask = None;
+                state.restored_mask = mask;
+                state.opened_result = result;
+                state.result_id = None;
+                state.ground_truth = None;
+            }
+            AppAction::Reading(true) => state.status = SegmentationStatus::Reading,
+            AppAction::Reading(false) => {
+                state.status = match state.mask {
+                    Some(_) => SegmentationStatus::Done,
+                    None => SegmentationStatus::Idle,
+                }
+            }
+            // Answers to cancelled requests, or for no image at all, are not taken.
+            AppAction::Progress(_) | AppAction::Segmented(_) | AppAction::Stored(_)
+                if state.status == SegmentationStatus::Cancelled || state.image.is_none() => {}
+            AppAction::Progress(status) => state.status = status,
+            AppAction::Segmented(mask) => {
+                if mask.is_some() {
+                    state.status = SegmentationStatus::Done;
+                }
+                state.mask = mask;
+            }
+            AppAction::Stored(result_id) => state.result_id = result_id,
+            AppAction::Cancel if state.status.is_active() => {
+                state.status = SegmentationStatus::Cancelled;
+                state.mask = None;
+            }
+            AppAction::Cancel => {}
+            AppAction::Retry if state.image.is_some() => {
+                state.status = SegmentationStatus::Uploading(None);
+            }
+            AppAction::Retry => {}
+            AppAction::GroundTruth(truth) => state.ground_truth = truth,
+            AppAction::SetSettings(settings) => state.settings = settings,
+            AppAction::HistoryChanged => state.history_revision += 1,
+            AppAction::Queued => state.outbox_revision += 1,
+        }
+        crash::note_state(state.summary());
+        Rc::new(state)
+    }
+}
+
+pub type AppContext = UseReducerHandle<AppState>;
+
+/// The shared state, and a way to change it by dispatching [`AppAction`]s.
+#[hook]
+pub fn use_app_state() -> AppContext {
+    use_context::<AppContext>().expect("components using the app state are inside AppStateProvider")
+}
+
+/// Provides the shared state to `children`, along with its settings on their own for
+/// components that only need those and the API of the server those settings point to.
+#[autoprops_component(AppStateProvider)]
+pub fn app_state_provider(children: Children) -> Html {
+    let state = use_reducer(|| AppState {
+        settings: Rc::new(Settings::load()),
+        ..Default::default()
+    });
+    let api = use_memo(state.settings.clone(), |settings| {
+        Api::http(settings.clone())
+    });
+    use_effect_with(state.settings.telemetry, |enabled| {
+        telemetry::set_enabled(*enabled)
+    });
+    use_effect_with(state.settings.decoded_cache_mb, |megabytes| {
+        decoded::set_budget(*megabytes)
+    });
+
+    html! {
+        <ContextProvider<AppContext> context={state.clone()}>
+            <ContextProvider<Rc<Settings>> context={state.settings.clone()}>
+                <ContextProvider<Api> context={(*api).clone()}>
+                    {children.clone()}
+                </ContextProvider<Api>>
+            </ContextProvider<Rc<Settings>>>
+        </ContextProvider<AppContext>>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use yew::Reducible;
+
+    use super::{AppAction, AppState, SegmentationStatus};
+    use crate::{error::AppError, fixtures};
+
+    fn reduce(actions: impl IntoIterator<Item = AppAction>) -> Rc<AppState> {
+        actions
+            .into_iter()
+            .fold(Rc::new(AppState::default()), |state, action| {
+                state.reduce(action)
+            })
+    }
+
+    fn upload() -> AppAction {
+        AppAction::Upload(Rc::new(Some(fixtures::png("image.png", fixtures::IMAGE))))
+    }
+
+    fn segmented() -> AppAction {
+        AppAction::Segmented(Some(Rc::new(fixtures::png("mask.png", fixtures::MASK))))
+    }
+
+    #[test]
+    fn uploads_move_through_to_done() {
+        let state = reduce([upload()]);
+        assert_eq!(state.status, SegmentationStatus::Uploading(None));
+
+        let state = state.reduce(AppAction::Progress(SegmentationStatus::Processing));
+        assert_eq!(state.status, SegmentationStatus::Processing);
+
+        let state = state.reduce(segmented());
+        assert_eq!(state.status, SegmentationStatus::Done);
+        assert!(state.mask.is_some());
+    }
+
+    #[test]
+    fn stored_results_are_forgotten_with_the_next_upload() {
+        let state = reduce([
+            upload(),
+            AppAction::Stored(Some("42".to_string())),
+            segmented(),
+        ]);
+        assert_eq!(state.result_id.as_deref(), Some("42"));
+
+        let state = state.reduce(upload());
+        assert_eq!(state.result_id, None);
+    }
+
+    #[test]
+    fn failures_are_kept_until_the_next_upload() {
+        let error = AppError::Timeout {
+            url: "mock://segment".to_string(),
+        };
+        let state = reduce([
+            upload(),
+            AppAction::Progress(SegmentationStatus::Failed(error.clone())),
+        ]);
+        assert_eq!(state.status, SegmentationStatus::Failed(error));
+
+        let state = state.reduce(upload());
+        assert_eq!(state.status, SegmentationStatus::Uploading(None));
+    }
+
+    #[test]
+    fn answers_to_cancelled_requests_are_ignored() {
+        let state = reduce([upload(), AppAction::Cancel, segmented()]);
+        assert_eq!(state.status, SegmentationStatus::Cancelled);
+        assert!(state.mask.is_none());
+
+        let state = state.reduce(AppAction::Retry).reduce(segmented());
+        assert_eq!(state.status, SegmentationStatus::Done);
+    }
+
+    #[test]
+    fn only_requests_under_way_can_be_cancelled() {
+        let state = reduce([upload(), segmented(), AppAction::Cancel]);
+        assert_eq!(state.status, SegmentationStatus::Done);
+        assert!(state.mask.is_some());
+    }
+
+    #[test]
+    fn answers_without_an_image_are_ignored() {
+        let state = reduce([AppAction::Upload(Rc::new(None)), segmented()]);
+        assert_eq!(state.status, SegmentationStatus::Idle);
+        assert!(state.mask.is_none());
+    }
+
+    #[test]
+    fn opened_results_are_done_without_a_request() {
+        let state = reduce([AppAction::Open {
+            image: Rc::new(Some(fixtures::png("image.png", fixtures::IMAGE))),
+            mask: Some(Rc::new(fixtures::png("mask.png", fixtures::MASK))),
+            result: Some(7),
+        }]);
+        assert_eq!(state.status, SegmentationStatus::Done);
+        assert!(state.restored_mask.is_some());
+        assert_eq!(state.opened_result, Some(7));
+    }
+
+    #[test]
+    fn ground_truths_go_with_their_image() {
+        let truth = AppAction::GroundTruth(Some(Rc::new(fixtures::png(
+            "truth.png",
+            fixtures::MASK_CORRECTED,
+        ))));
+        let state = reduce([upload(), segmented(), truth]);
+        assert!(state.ground_truth.is_some());
+
+        let state = state.reduce(upload());
+        assert!(state.ground_truth.is_none());
+    }
+
+    #[test]
+    fn summaries_tell_nothing_of_the_files() {
+        let state = reduce([upload(), segmented()]);
+        let summary = state.summary();
+        assert_eq!(
+            summary,
+            format!(
+                "status: done; image: image/png, {} bytes; mask: image/png, {} bytes; \
+                 opened from history: false; post-processing steps: 0",
+                fixtures::IMAGE.len(),
+                fixtures::MASK.len()
+            )
+        );
+        assert!(!summary.contains("image.png"));
+    }
+}
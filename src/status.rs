@@ -0,0 +1,178 @@
+//! What the app has to tell about the session as a whole, above the working area.
+
+use gloo::timers::callback::Interval;
+use std::rc::Rc;
+use yew::prelude::*;
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    api::SEGMENT_TIMEOUT_MS,
+    clock,
+    error::{AppError, ErrorAlert},
+    i18n::use_language,
+    memory::MemoryBanner,
+    offline::OutboxBanner,
+    project::{Project, ProjectPane},
+    session::{AutosaveIndicator, AutosaveStatus, RestoreBanner},
+    state::{use_app_state, AppAction, SegmentationStatus},
+    storage::QuotaBanner,
+};
+
+/// Banners about loading a shared link, requests queued offline, storage or memory running
+/// out and an autosaved session waiting to be restored.
+#[autoprops_component(StatusBar)]
+pub fn status_bar(
+    #[prop_or_default] permalink_error: Option<AppError>,
+    #[prop_or_default] history_error: Option<AttrValue>,
+    onrestore: Callback<Rc<Project>>,
+) -> Html {
+    let state = use_app_state();
+    let language = use_language();
+    let onhistorychange = {
+        let state = state.clone();
+        Callback::from(move |_| state.dispatch(AppAction::HistoryChanged))
+    };
+
+    html! {
+        <>
+            if let Some(error) = permalink_error {
+                <ErrorAlert error={error.clone()} title={language.t("permalink-failed")} />
+            }
+            <OutboxBanner revision={state.outbox_revision} onsubmitted={onhistorychange.clone()} />
+            <QuotaBanner
+                revision={state.history_revision}
+                write_error={history_error.clone()}
+                onchange={onhistorychange}
+            />
+            <MemoryBanner />
+            <RestoreBanner onrestore={onrestore.clone()} />
+        </>
+    }
+}
+
+/// Saving and opening the current image and mask as a project, and whether they are
+/// autosaved.
+#[autoprops_component(ResultToolbar)]
+pub fn result_toolbar(
+    autosave_status: AutosaveStatus,
+    onopen: Callback<Rc<Project>>,
+    #[prop_or_default] onsave: Callback<()>,
+) -> Html {
+    let state = use_app_state();
+
+    html! {
+        <>
+            <ProjectPane
+                image={state.image.clone()}
+                mask={state.mask.clone()}
+                settings={state.settings.clone()}
+                onopen={onopen.clone()}
+                onsave={onsave.clone()}
+            />
+            <AutosaveIndicator status={autosave_status.clone()} />
+        </>
+    }
+}
+
+/// Where segmenting the current image stands, with a way to cancel the request under way
+/// and to send it again once cancelled.
+#[function_component(SegmentationStatusBadge)]
+pub fn segmentation_status_badge() -> Html {
+    let state = use_app_state();
+    let language = use_language();
+    let oncancel = {
+        let state = state.clone();
+        move |_| state.dispatch(AppAction::Cancel)
+    };
+    let onretry = {
+        let state = state.clone();
+        move |_| state.dispatch(AppAction::Retry)
+    };
+
+    let (class, label) = match state.status {
+        SegmentationStatus::Idle => return html!(),
+        SegmentationStatus::Reading => ("text-bg-secondary", "status-reading"),
+        SegmentationStatus::Uploading(_) => ("text-bg-primary", "status-uploading"),
+        SegmentationStatus::Processing => ("text-bg-primary", "status-processing"),
+        SegmentationStatus::Done => ("text-bg-success", "status-done"),
+        SegmentationStatus::Failed(_) => ("text-bg-danger", "status-failed"),
+        SegmentationStatus::Cancelled => ("text-bg-secondary", "status-cancelled"),
+    };
+    let busy = state.status.is_active() || state.status == SegmentationStatus::Reading;
+
+    html! {
+        <div class="d-flex align-items-center gap-2 mb-2">
+            <span class={classes!("badge", class)} role="status" aria-live="polite">
+                if busy {
+                    <span class="spinner-border spinner-border-sm me-1" aria-hidden="true"></span>
+                }
+                {language.t(label)}
+                if let SegmentationStatus::Uploading(Some(percent)) = state.status {
+                    {format!(" {percent}%")}
+                }
+            </span>
+            if state.status.is_active() {
+                <RequestTimer />
+                <button class="btn btn-sm btn-outline-secondary" onclick={oncancel}>
+                    {language.t("action-cancel")}
+                </button>
+            }
+            if state.status == SegmentationStatus::Cancelled {
+                <button class="btn btn-sm btn-outline-primary" onclick={onretry}>
+                    {language.t("action-segment-again")}
+                </button>
+            }
+        </div>
+    }
+}
+
+/// Time after which a request is said to be still under way, so that a slow model is not
+/// taken for a stuck app.
+const STILL_WORKING_AFTER_MS: f64 = 15_000.0;
+
+/// `ms` as minutes and seconds.
+pub fn format_elapsed(ms: f64) -> String {
+    let seconds = (ms.max(0.0) / 1000.0) as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// How long the request under way has taken, out of how long it may. Counts from when it
+/// is first shown, as it is only shown while a request is under way.
+#[function_component(RequestTimer)]
+fn request_timer() -> Html {
+    let language = use_language();
+    let started = use_state(clock::now);
+    let now = use_state(clock::now);
+    {
+        let now = now.clone();
+        use_effect_with((), move |_| {
+            let interval = Interval::new(1_000, move || now.set(clock::now()));
+            move || drop(interval)
+        });
+    }
+
+    let elapsed = *now - *started;
+    html! {
+        <small class="text-body-secondary" aria-live="off">
+            {language.t_args("status-elapsed", &[
+                ("elapsed", &format_elapsed(elapsed)),
+                ("timeout", &format_elapsed(SEGMENT_TIMEOUT_MS.into())),
+            ])}
+            if elapsed >= STILL_WORKING_AFTER_MS {
+                {" "}{language.t("status-still-working")}
+            }
+        </small>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_elapsed;
+
+    #[test]
+    fn elapsed_times_are_minutes_and_seconds() {
+        assert_eq!(format_elapsed(0.0), "0:00");
+        assert_eq!(format_elapsed(9_999.0), "0:09");
+        assert_eq!(format_elapsed(300_000.0), "5:00");
+    }
+}
@@ -0,0 +1,334 @@
+//! Space taken by the stored results, and freeing it.
+
+use shadow_clone::shadow_clone;
+use std::{collections::HashSet, rc::Rc};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::StorageEstimate;
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    history::{self, HistoryEntry},
+    i18n::{self, use_language},
+    notify,
+    settings::Settings,
+};
+
+/// `bytes` in the largest unit that keeps the number at least 1.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+/// Bytes taken by the image and mask of `entry`.
+pub fn entry_size(entry: &HistoryEntry) -> u64 {
+    (entry.image.data.len() + entry.mask.data.len()) as u64
+}
+
+/// Share of the quota above which users are warned that storage is running out.
+const WARNING_USAGE: f64 = 0.9;
+/// Share of the quota cleanup frees storage down to.
+const CLEANUP_USAGE: f64 = 0.75;
+
+/// Bytes the app's origin uses and may use at most, as estimated by the browser.
+pub async fn estimate() -> Result<(f64, f64), String> {
+    let promise = gloo::utils::window()
+        .navigator()
+        .storage()
+        .estimate()
+        .map_err(|e| i18n::t_args("storage-estimate-failed", &[("error", &format!("{e:?}"))]))?;
+    let estimate: StorageEstimate = JsFuture::from(promise)
+        .await
+        .map_err(|e| i18n::t_args("storage-estimate-failed", &[("error", &format!("{e:?}"))]))?
+        .unchecked_into();
+    match (estimate.get_usage(), estimate.get_quota()) {
+        (Some(usage), Some(quota)) if quota > 0.0 => Ok((usage, quota)),
+        _ => Err(i18n::t("storage-no-quota")),
+    }
+}
+
+/// A warning if writing `bytes` more would bring storage close to the quota.
+pub async fn check_before_write(bytes: u64) -> Option<String> {
+    let (usage, quota) = estimate().await.ok()?;
+    ((usage + bytes as f64) / quota > WARNING_USAGE).then(|| {
+        i18n::t_args(
+            "storage-nearly-full",
+            &[
+                ("usage", &format_size(usage as u64)),
+                ("quota", &format_size(quota as u64)),
+            ],
+        )
+    })
+}
+
+/// Deletes the oldest results until usage is back to [`CLEANUP_USAGE`] of the quota,
+/// returning how many were deleted.
+async fn free_space() -> Result<usize, String> {
+    let (usage, quota) = estimate().await?;
+    let mut to_free = usage - quota * CLEANUP_USAGE;
+    let mut deleted = 0;
+    for entry in history::list().await?.iter().rev() {
+        if to_free <= 0.0 {
+            break;
+        }
+        if let Some(id) = entry.id {
+            history::remove(id).await?;
+            to_free -= entry_size(entry) as f64;
+            deleted += 1;
+        }
+    }
+    Ok(deleted)
+}
+
+/// Warning shown when storage is nearly full or a result could not be saved, offering to
+/// delete the oldest results. Storage use is checked again whenever `revision` changes.
+#[autoprops_component(QuotaBanner)]
+pub fn quota_banner(
+    revision: i32,
+    #[prop_or_default] write_error: Option<AttrValue>,
+    onchange: Callback<()>,
+) -> Html {
+    let language = use_language();
+    let warning = use_state(|| None::<String>);
+    let busy = use_state(|| false);
+
+    {
+        shadow_clone!(warning);
+        use_effect_with(revision, move |_| {
+            spawn_local(async move { warning.set(check_before_write(0).await) });
+        });
+    }
+
+    let oncleanup = {
+        shadow_clone!(busy, onchange);
+        move |_| {
+            shadow_clone!(busy, onchange);
+            busy.set(true);
+            spawn_local(async move {
+                match free_space().await {
+                    Ok(deleted) => {
+                        notify::success(language.t_args("storage-freed", &[("count", &deleted)]))
+                    }
+                    Err(why) => notify::error(language.t("storage-free-failed"), why),
+                }
+                busy.set(false);
+                onchange.emit(());
+            });
+        }
+    };
+
+    if warning.is_none() && write_error.is_none() {
+        return html!();
+    }
+
+    html! {
+        <div class="alert alert-warning d-flex justify-content-between align-items-center">
+            <span>
+                if let Some(why) = &write_error {
+                    <strong>{language.t("storage-write-failed")}</strong>{" "}{why}{" "}
+                }
+                if let Some(warning) = &*warning {
+                    {warning}
+                }
+            </span>
+            <button class="btn btn-sm btn-outline-dark" disabled={*busy} onclick={oncleanup}>
+                {language.t("storage-delete-oldest")}
+            </button>
+        </div>
+    }
+}
+
+/// Size of every stored result, with bulk deletion and pruning by the retention settings.
+/// Reloaded whenever `revision` changes; `onchange` is called after results were deleted.
+#[autoprops_component(StoragePane)]
+pub fn storage_pane(revision: i32, onchange: Callback<()>) -> Html {
+    let language = use_language();
+    let settings = use_context::<Rc<Settings>>().unwrap_or_default();
+    let entries = use_state(|| None::<Result<Vec<Rc<HistoryEntry>>, String>>);
+    let selected = use_state(HashSet::<u64>::new);
+    let busy = use_state(|| false);
+    let usage = use_state(|| None::<(f64, f64)>);
+
+    {
+        shadow_clone!(entries, selected, usage);
+        use_effect_with(revision, move |_| {
+            spawn_local(async move {
+                usage.set(estimate().await.ok());
+                selected.set(HashSet::new());
+                entries.set(Some(
+                    history::list()
+                        .await
+                        .map(|list| list.into_iter().map(Rc::new).collect()),
+                ));
+            });
+        });
+    }
+
+    let delete = {
+        shadow_clone!(busy, onchange);
+        move |ids: Vec<f64>| {
+            shadow_clone!(busy, onchange);
+            busy.set(true);
+            spawn_local(async move {
+                for id in ids {
+                    if let Err(why) = history::remove(id).await {
+                        notify::error(language.t("storage-delete-failed"), why);
+                    }
+                }
+                busy.set(false);
+                onchange.emit(());
+            });
+        }
+    };
+
+    let onprune = {
+        shadow_clone!(settings, busy, onchange);
+        move |_| {
+            shadow_clone!(settings, busy, onchange);
+            busy.set(true);
+            spawn_local(async move {
+                match history::prune(&settings).await {
+                    Ok(pruned) => {
+                        notify::success(language.t_args("storage-pruned", &[("count", &pruned)]))
+                    }
+                    Err(why) => notify::error(language.t("storage-prune-failed"), why),
+                }
+                busy.set(false);
+                onchange.emit(());
+            });
+        }
+    };
+
+    let entries = match &*entries {
+        None => return html!(<span class="spinner-border text-success"></span>),
+        Some(Err(why)) => return html!(<div class="alert alert-danger" role="alert">{why}</div>),
+        Some(Ok(entries)) => entries,
+    };
+
+    let total: u64 = entries.iter().map(|entry| entry_size(entry)).sum();
+    let quota = match *usage {
+        Some((usage, quota)) => language.t_args(
+            "storage-usage",
+            &[
+                ("usage", &format_size(usage as u64)),
+                ("quota", &format_size(quota as u64)),
+            ],
+        ),
+        None => String::new(),
+    };
+    let ids = |only_selected: bool| -> Vec<f64> {
+        entries
+            .iter()
+            .filter_map(|entry| entry.id)
+            .filter(|id| !only_selected || selected.contains(&(*id as u64)))
+            .collect()
+    };
+    let ondeleteselected = {
+        let ids = ids(true);
+        shadow_clone!(delete);
+        move |_| delete(ids.clone())
+    };
+    let ondeleteall = {
+        let ids = ids(false);
+        shadow_clone!(delete);
+        move |_| delete(ids.clone())
+    };
+    let policy = match (settings.keep_results, settings.keep_days) {
+        (None, None) => language.t("storage-keep-all"),
+        (Some(n), None) => language.t_args("storage-keep-newest", &[("count", &n)]),
+        (None, Some(days)) => language.t_args("storage-keep-days", &[("days", &days)]),
+        (Some(n), Some(days)) => language.t_args(
+            "storage-keep-newest-days",
+            &[("count", &n), ("days", &days)],
+        ),
+    };
+
+    html! {
+        <div>
+            <p>
+                {language.t_args("storage-total", &[("count", &entries.len()), ("size", &format_size(total))])}
+                {" "}{quota}{" "}
+                <small class="text-body-secondary">{policy}{" "}{language.t("storage-policy-hint")}</small>
+            </p>
+            <div class="btn-group btn-group-sm mb-2">
+                <button
+                    class="btn btn-outline-danger"
+                    disabled={*busy || selected.is_empty()}
+                    onclick={ondeleteselected}
+                >
+                    {language.t_args("storage-delete-selected", &[("count", &selected.len())])}
+                </button>
+                <button
+                    class="btn btn-outline-danger"
+                    disabled={*busy || entries.is_empty()}
+                    onclick={ondeleteall}
+                >
+                    {language.t("storage-delete-all")}
+                </button>
+                <button
+                    class="btn btn-outline-secondary"
+                    disabled={*busy || (settings.keep_results.is_none() && settings.keep_days.is_none())}
+                    onclick={onprune}
+                >
+                    {language.t("storage-prune")}
+                </button>
+            </div>
+            <table class="table table-sm">
+                <thead>
+                    <tr>
+                        <th></th>
+                        <th>{language.t("report-image")}</th>
+                        <th>{language.t("storage-date")}</th>
+                        <th class="text-end">{language.t("storage-size")}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                {
+                    for entries.iter().map(|entry| {
+                        let id = entry.id.map(|id| id as u64);
+                        let onselect = {
+                            shadow_clone!(selected);
+                            move |_| {
+                                let Some(id) = id else {
+                                    return;
+                                };
+                                let mut new_selected = (*selected).clone();
+                                if !new_selected.remove(&id) {
+                                    new_selected.insert(id);
+                                }
+                                selected.set(new_selected);
+                            }
+                        };
+                        html! {
+                            <tr>
+                                <td>
+                                    <input
+                                        type="checkbox"
+                                        class="form-check-input"
+                                        checked={id.is_some_and(|id| selected.contains(&id))}
+                                        onchange={onselect}
+                                    />
+                                </td>
+                                <td>{&entry.image.file_name}</td>
+                                <td>{entry.date()}</td>
+                                <td class="text-end">{format_size(entry_size(entry))}</td>
+                            </tr>
+                        }
+                    })
+                }
+                </tbody>
+            </table>
+        </div>
+    }
+}
@@ -0,0 +1,67 @@
+//! Keeping every open tab of the app up to date with changes made in the others.
+
+use gloo::events::EventListener;
+use wasm_bindgen::JsCast;
+use web_sys::{BroadcastChannel, MessageEvent};
+use yew::Callback;
+
+use crate::workspace;
+
+const CHANNEL_NAME: &str = "map-segmentation";
+
+/// Something that changed in shared storage and should be reloaded by the other tabs.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SyncMessage {
+    /// Results were added to, removed from or annotated in the history.
+    HistoryChanged,
+    SettingsChanged,
+}
+
+impl SyncMessage {
+    fn name(self) -> &'static str {
+        match self {
+            SyncMessage::HistoryChanged => "history",
+            SyncMessage::SettingsChanged => "settings",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "history" => Some(SyncMessage::HistoryChanged),
+            "settings" => Some(SyncMessage::SettingsChanged),
+            _ => None,
+        }
+    }
+}
+
+thread_local! {
+    // A single channel per tab, since messages are not delivered back to the channel
+    // that posted them. Tabs of other workspaces listen on channels of their own.
+    static CHANNEL: Option<BroadcastChannel> =
+        BroadcastChannel::new(&workspace::key(CHANNEL_NAME)).ok();
+}
+
+/// Tells the other tabs about `message`.
+pub fn broadcast(message: SyncMessage) {
+    CHANNEL.with(|channel| {
+        if let Some(channel) = channel {
+            if let Err(e) = channel.post_message(&message.name().into()) {
+                log::warn!("Could not notify other tabs: {e:?}");
+            }
+        }
+    });
+}
+
+/// Calls `onmessage` with every message from the other tabs, for as long as the returned
+/// listener is kept.
+pub fn subscribe(onmessage: Callback<SyncMessage>) -> Option<EventListener> {
+    CHANNEL.with(|channel| {
+        let channel = channel.as_ref()?;
+        Some(EventListener::new(channel, "message", move |e| {
+            let e: &MessageEvent = e.unchecked_ref();
+            if let Some(message) = e.data().as_string().as_deref().and_then(SyncMessage::parse) {
+                onmessage.emit(message);
+            }
+        }))
+    })
+}
@@ -0,0 +1,112 @@
+//! Opt-in usage statistics: anonymous events about segmentation requests, their latency and
+//! their failures, sent in batches to the collector named in `config.json`. Nothing is
+//! recorded unless the deployment names a collector and users turn statistics on in the
+//! settings.
+
+use gloo::{events::EventListener, timers::callback::Interval};
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+
+use crate::{clock, config};
+
+/// Events kept before they are sent without waiting for the interval.
+const BATCH_SIZE: usize = 20;
+const FLUSH_INTERVAL_MS: u32 = 60_000;
+
+/// Something that happened, carrying nothing about the user, the image's content or where
+/// it lies.
+#[derive(Clone, PartialEq, Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// An image of `bytes` bytes was sent to be segmented.
+    Request { bytes: usize, file_type: String },
+    /// The server answered with a mask after `latency_ms`.
+    Segmented { latency_ms: f64 },
+    /// The request failed after `latency_ms`, for a reason of the given kind.
+    Failed {
+        error: &'static str,
+        latency_ms: f64,
+    },
+}
+
+#[derive(Serialize)]
+struct Record {
+    #[serde(flatten)]
+    event: Event,
+    timestamp: f64,
+}
+
+#[derive(Serialize)]
+struct Batch<'a> {
+    app_version: &'static str,
+    events: &'a [Record],
+}
+
+thread_local! {
+    static ENABLED: Cell<bool> = const { Cell::new(false) };
+    static BUFFER: RefCell<Vec<Record>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Whether the deployment names a collector, so that users can be offered to opt in.
+pub fn is_available() -> bool {
+    config::current().telemetry_url.is_some()
+}
+
+/// Starts or stops recording, as users chose in the settings. Events not sent yet are
+/// dropped when stopping.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.set(enabled && is_available());
+    if !enabled {
+        BUFFER.with(|buffer| buffer.borrow_mut().clear());
+    }
+}
+
+/// Keeps `event` to be sent with the next batch, if users opted in.
+pub fn record(event: Event) {
+    if !ENABLED.get() {
+        return;
+    }
+    let full = BUFFER.with(|buffer| {
+        let mut buffer = buffer.borrow_mut();
+        buffer.push(Record {
+            event,
+            timestamp: clock::now(),
+        });
+        buffer.len() >= BATCH_SIZE
+    });
+    if full {
+        flush();
+    }
+}
+
+/// Sends the recorded events. They are dropped whether or not they arrive, so that an
+/// unreachable collector cannot make them pile up.
+fn flush() {
+    let Some(url) = config::current().telemetry_url.clone() else {
+        return;
+    };
+    let events = BUFFER.with(|buffer| buffer.take());
+    if events.is_empty() {
+        return;
+    }
+    let batch = Batch {
+        app_version: env!("CARGO_PKG_VERSION"),
+        events: &events,
+    };
+    let body = serde_json::to_string(&batch).expect("Events always serialize.");
+    // A beacon still goes out when the page is being closed.
+    match gloo::utils::window()
+        .navigator()
+        .send_beacon_with_opt_str(&url, Some(&body))
+    {
+        Ok(true) => {}
+        Ok(false) => log::warn!("Dropped {} usage events: the browser refused", events.len()),
+        Err(e) => log::warn!("Could not send usage events: {e:?}"),
+    }
+}
+
+/// Sends the recorded events every minute, and when the page is left.
+pub fn start() {
+    Interval::new(FLUSH_INTERVAL_MS, flush).forget();
+    EventListener::new(&gloo::utils::window(), "pagehide", |_| flush()).forget();
+}
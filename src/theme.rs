@@ -0,0 +1,143 @@
+//! Colour themes: Bootstrap's light and dark modes, and an organization theme whose CSS
+//! custom properties come from `config.json`, for embedding the app in a branded portal.
+
+use gloo::storage::{LocalStorage, Storage};
+use wasm_bindgen::JsCast;
+use web_sys::{HtmlElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::{config, i18n::use_language};
+
+/// Key of the theme users picked, shared by all workspaces.
+const STORAGE_KEY: &str = "theme";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Theme {
+    Light,
+    Dark,
+    /// Light, with the colours set in the configuration's `branding.colors`.
+    Organization,
+}
+
+impl Theme {
+    const ALL: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::Organization];
+
+    fn code(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+            Theme::Organization => "organization",
+        }
+    }
+
+    fn parse(code: &str) -> Option<Self> {
+        Theme::ALL.into_iter().find(|theme| theme.code() == code)
+    }
+
+    /// Whether the deployment configured what the theme needs.
+    fn is_available(self) -> bool {
+        self != Theme::Organization || !config::current().branding.colors.is_empty()
+    }
+
+    /// The Bootstrap colour mode the theme builds on.
+    fn color_mode(self) -> &'static str {
+        match self {
+            Theme::Dark => "dark",
+            Theme::Light | Theme::Organization => "light",
+        }
+    }
+}
+
+/// The theme picked earlier if it is still available, or else the organization's, or
+/// dark.
+fn stored_or_default() -> Theme {
+    LocalStorage::get::<String>(STORAGE_KEY)
+        .ok()
+        .and_then(|code| Theme::parse(&code))
+        .filter(|theme| theme.is_available())
+        .unwrap_or(if Theme::Organization.is_available() {
+            Theme::Organization
+        } else {
+            Theme::Dark
+        })
+}
+
+/// Sets the colour mode and custom properties of `theme` on the page.
+fn apply(theme: Theme) {
+    let document = gloo::utils::document();
+    let Some(root) = document
+        .document_element()
+        .and_then(|root| root.dyn_into::<HtmlElement>().ok())
+    else {
+        return;
+    };
+    if let Err(e) = root.set_attribute("data-bs-theme", theme.color_mode()) {
+        log::warn!("Could not set the colour mode: {e:?}");
+    }
+    let style = root.style();
+    let config = config::current();
+    for (name, value) in &config.branding.colors {
+        let result = match theme {
+            Theme::Organization => style.set_property(name, value),
+            _ => style.remove_property(name).map(drop),
+        };
+        if let Err(e) = result {
+            log::warn!("Could not set {name}: {e:?}");
+        }
+    }
+    // The browser's own interface follows the page's background.
+    let background = match theme {
+        Theme::Dark => "#212529",
+        Theme::Light => "#ffffff",
+        Theme::Organization => config
+            .branding
+            .colors
+            .get("--bs-body-bg")
+            .map_or("#ffffff", String::as_str),
+    };
+    if let Ok(Some(meta)) = document.query_selector("meta[name=theme-color]") {
+        let _ = meta.set_attribute("content", background);
+    }
+}
+
+/// Applies the theme users picked. Called once at startup, before rendering.
+pub fn init() {
+    apply(stored_or_default());
+}
+
+/// A choice of the available themes, remembered for the next visits.
+#[function_component(ThemeSwitcher)]
+pub fn theme_switcher() -> Html {
+    let language = use_language();
+    let current = use_state(stored_or_default);
+    let onchange = {
+        let current = current.clone();
+        move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            let Some(picked) = Theme::parse(&select.value()) else {
+                return;
+            };
+            if let Err(why) = LocalStorage::set(STORAGE_KEY, picked.code()) {
+                log::warn!("Could not save the theme: {why}");
+            }
+            apply(picked);
+            current.set(picked);
+        }
+    };
+
+    html! {
+        <select
+            class="form-select form-select-sm w-auto"
+            aria-label={language.t("theme")}
+            {onchange}
+        >
+            {
+                for Theme::ALL.into_iter().filter(|theme| theme.is_available()).map(|theme| html! {
+                    <option value={theme.code()} selected={theme == *current}>
+                        {language.t(&format!("theme-{}", theme.code()))}
+                    </option>
+                })
+            }
+        </select>
+    }
+}
@@ -0,0 +1,114 @@
+//! Throttling for continuous controls such as sliders, whose input events come faster than
+//! what they change can be redrawn.
+
+use gloo::timers::callback::Timeout;
+use std::{cell::RefCell, rc::Rc};
+use yew::prelude::*;
+
+/// Which values to pass on: the first at once, then at most one per interval, ending with
+/// the last one offered.
+struct Throttle<T> {
+    /// Whether an interval is running since a value was passed on.
+    busy: bool,
+    /// The last value offered during the interval.
+    pending: Option<T>,
+}
+
+impl<T> Throttle<T> {
+    fn new() -> Self {
+        Throttle {
+            busy: false,
+            pending: None,
+        }
+    }
+
+    /// `value`, when it is to be passed on now, starting an interval.
+    fn offer(&mut self, value: T) -> Option<T> {
+        if self.busy {
+            self.pending = Some(value);
+            None
+        } else {
+            self.busy = true;
+            Some(value)
+        }
+    }
+
+    /// The value to pass on as an interval ends, starting another one, if any was offered.
+    fn end_interval(&mut self) -> Option<T> {
+        self.pending.take().or_else(|| {
+            self.busy = false;
+            None
+        })
+    }
+}
+
+struct Throttled<T> {
+    throttle: Throttle<T>,
+    timer: Option<Timeout>,
+    callback: Callback<T>,
+}
+
+/// Emits `value` through the callback and waits `interval_ms` before emitting any other.
+fn emit<T: 'static>(state: &Rc<RefCell<Throttled<T>>>, value: T, interval_ms: u32) {
+    let callback = state.borrow().callback.clone();
+    let timer = {
+        let state = state.clone();
+        Timeout::new(interval_ms, move || {
+            let next = state.borrow_mut().throttle.end_interval();
+            match next {
+                Some(value) => emit(&state, value, interval_ms),
+                None => state.borrow_mut().timer = None,
+            }
+        })
+    };
+    state.borrow_mut().timer = Some(timer);
+    callback.emit(value);
+}
+
+/// `callback`, called at most once every `interval_ms` however often the result is, and
+/// always with the last value it was called with.
+#[hook]
+pub fn use_throttled<T>(callback: Callback<T>, interval_ms: u32) -> Callback<T>
+where
+    T: 'static,
+{
+    let state = use_mut_ref(|| Throttled {
+        throttle: Throttle::new(),
+        timer: None,
+        callback: callback.clone(),
+    });
+    state.borrow_mut().callback = callback;
+    {
+        let state = state.clone();
+        // Nothing is emitted once the component is gone.
+        use_effect_with((), move |_| move || drop(state.borrow_mut().timer.take()));
+    }
+    use_memo(interval_ms, move |interval_ms| {
+        let interval_ms = *interval_ms;
+        Callback::from(move |value| {
+            let now = state.borrow_mut().throttle.offer(value);
+            if let Some(value) = now {
+                emit(&state, value, interval_ms);
+            }
+        })
+    })
+    .as_ref()
+    .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Throttle;
+
+    #[test]
+    fn the_first_and_last_values_are_passed_on() {
+        let mut throttle = Throttle::new();
+        assert_eq!(throttle.offer(1), Some(1));
+        assert_eq!(throttle.offer(2), None);
+        assert_eq!(throttle.offer(3), None);
+        assert_eq!(throttle.end_interval(), Some(3));
+        assert_eq!(throttle.end_interval(), None);
+        // Once an interval passes without values, the next one goes through at once.
+        assert_eq!(throttle.offer(4), Some(4));
+    }
+}
@@ -0,0 +1,855 @@
+//! Getting the image to segment, either from uploaded files or from the map.
+
+use js_sys::{Reflect, Uint8Array};
+use shadow_clone::shadow_clone;
+use std::{borrow::Borrow, collections::HashMap, io::Cursor, rc::Rc};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, DragEvent, Event, File, FileList, HtmlInputElement};
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+use yew_hooks::prelude::*;
+
+use crate::{
+    aoi::{SavedAoi, SavedAoisPane},
+    audit::{self, AuditAction},
+    codec::EncodeJob,
+    evaluation,
+    flags::{self, Flag},
+    geo::{self, clip_to_area, warp_to_mercator, Georeference},
+    history::{self, HistoryEntry},
+    http::Progress,
+    i18n::{self, use_language},
+    map::{MapOverlay, MapPane, TileSource},
+    ndvi::NdviPane,
+    route::Route,
+    settings::Settings,
+    shortcuts::use_shortcut,
+    state::{use_app_state, AppAction},
+    storage::format_size,
+    viewer::{BlobUrl, FileViewer},
+    workers::encode,
+    FileDetails,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum InputMode {
+    Upload,
+    Map,
+}
+
+/// The current image, uploaded as files or picked on the map, where it is shown together
+/// with its mask.
+#[function_component(ImageUploader)]
+pub fn image_uploader() -> Html {
+    let language = use_language();
+    let state = use_app_state();
+    let image = state.image.clone();
+    let mask = state.mask.clone();
+    let input_mode = use_state(|| InputMode::Upload);
+    let file_input = use_node_ref();
+    let tile_sources = use_memo(state.settings.clone(), |settings| {
+        Rc::new(settings.tile_sources.clone())
+    });
+
+    let image_overlay = use_memo(image.clone(), |src_image| {
+        let src_image = (**src_image).as_ref()?;
+        map_overlay(src_image, &src_image.georef?)
+    });
+
+    let mask_overlay = use_memo((image.clone(), mask.clone()), |(src_image, mask)| {
+        map_overlay(mask.as_ref()?, &(**src_image).as_ref()?.georef?)
+    });
+
+    // The ground truth lies where the image does, whatever its own file says.
+    let truth_overlays = use_memo(
+        (image.clone(), mask.clone(), state.ground_truth.clone()),
+        |(src_image, mask, truth)| {
+            let georef = (**src_image).as_ref()?.georef?;
+            let layers = evaluation::truth_layers(mask.as_ref()?, truth.as_ref()?)
+                .inspect_err(|why| log::warn!("Not showing the ground truth on the map: {why}"))
+                .ok()?;
+            Some((
+                map_overlay(&layers.truth, &georef),
+                map_overlay(&layers.errors, &georef),
+            ))
+        },
+    );
+    let (truth_overlay, errors_overlay) = (*truth_overlays).clone().unwrap_or_default();
+
+    // An image already in the history, with the entry it is in, waiting for users to choose
+    // between the result there and segmenting it again.
+    let duplicate = use_state(|| None::<(Rc<Option<FileDetails>>, Rc<HistoryEntry>)>);
+
+    let upload = {
+        shadow_clone!(state);
+        Callback::from(move |image: Rc<Option<FileDetails>>| {
+            if let Some(file) = &*image {
+                audit::record(
+                    AuditAction::Upload,
+                    file.file_name.clone(),
+                    format_size(file.data.len() as u64),
+                );
+            }
+            state.dispatch(AppAction::Upload(image));
+            if matches!(Route::current(), Route::Result(_)) {
+                Route::Upload.push();
+            }
+        })
+    };
+
+    let onupload = {
+        shadow_clone!(upload, duplicate);
+        Callback::from(move |image: Rc<Option<FileDetails>>| {
+            duplicate.set(None);
+            let Some(file) = (*image).clone() else {
+                upload.emit(image);
+                return;
+            };
+            shadow_clone!(upload, duplicate);
+            spawn_local(async move {
+                match history::find_image(&file).await {
+                    Ok(Some(entry)) => duplicate.set(Some((image, Rc::new(entry)))),
+                    Ok(None) => upload.emit(image),
+                    Err(why) => {
+                        log::warn!("Could not look for the image in the history: {why}");
+                        upload.emit(image);
+                    }
+                }
+            });
+        })
+    };
+
+    let onopenexisting = {
+        shadow_clone!(duplicate);
+        move |_| {
+            if let Some(id) = (*duplicate).as_ref().and_then(|(_, entry)| entry.id) {
+                Route::Result(id as u64).push();
+            }
+            duplicate.set(None);
+        }
+    };
+
+    let onsegmentagain = {
+        shadow_clone!(duplicate, upload);
+        move |_| {
+            if let Some((image, _)) = (*duplicate).clone() {
+                upload.emit(image);
+            }
+            duplicate.set(None);
+        }
+    };
+
+    let onreading = {
+        shadow_clone!(state);
+        Callback::from(move |reading| state.dispatch(AppAction::Reading(reading)))
+    };
+
+    let onsourceschange = {
+        shadow_clone!(state);
+        move |sources: Rc<Vec<TileSource>>| {
+            let new_settings = Rc::new(Settings {
+                tile_sources: (*sources).clone(),
+                ..(*state.settings).clone()
+            });
+            new_settings.store();
+            state.dispatch(AppAction::SetSettings(new_settings));
+        }
+    };
+
+    use_shortcut(&["o", "O"], "shortcut-open", {
+        shadow_clone!(file_input);
+        Callback::from(move |_| {
+            if let Some(input) = file_input.cast::<HtmlInputElement>() {
+                let _ = input.focus();
+                input.click();
+            }
+        })
+    });
+
+    let mode_tab = |mode: InputMode, title: String| {
+        let onclick = {
+            shadow_clone!(input_mode);
+            move |_| input_mode.set(mode)
+        };
+        html! {
+            <li class="nav-item">
+                <button
+                    class={classes!("nav-link", (*input_mode == mode).then_some("active"))}
+                    {onclick}
+                >
+                    {title}
+                </button>
+            </li>
+        }
+    };
+
+    html! {
+        <>
+            <h1>{language.t("upload-heading")}</h1>
+            if let Some((_, entry)) = &*duplicate {
+                <div class="alert alert-info d-flex flex-wrap align-items-center gap-2" role="status">
+                    <span>
+                        {language.t_args(
+                            "upload-duplicate",
+                            &[("name", &entry.image.file_name), ("date", &entry.date())],
+                        )}
+                    </span>
+                    <button class="btn btn-sm btn-primary" onclick={onopenexisting}>
+                        {language.t("upload-open-existing")}
+                    </button>
+                    <button class="btn btn-sm btn-outline-secondary" onclick={onsegmentagain}>
+                        {language.t("action-segment-again")}
+                    </button>
+                </div>
+            }
+            <ul class="nav nav-tabs mb-3">
+                {mode_tab(InputMode::Upload, language.t("upload-tab-file"))}
+                if flags::enabled(Flag::Map) {
+                    {mode_tab(InputMode::Map, language.t("upload-tab-map"))}
+                }
+            </ul>
+            {
+                match *input_mode {
+                    InputMode::Upload => html!(
+                        <UploadPane onupload={onupload.clone()} {onreading} input_ref={file_input.clone()} />
+                    ),
+                    InputMode::Map => html!(
+                        <MapPane
+                            onupload={onupload.clone()}
+                            sources={(*tile_sources).clone()}
+                            {onsourceschange}
+                            image={(*image_overlay).clone()}
+                            mask={(*mask_overlay).clone()}
+                            truth={truth_overlay}
+                            errors={errors_overlay}
+                            georef={(*image).as_ref().and_then(|src| src.georef)}
+                        />
+                    ),
+                }
+            }
+        </>
+    }
+}
+
+/// Places `file`, covering the extent of `georef`, on the basemap's Web Mercator grid.
+fn map_overlay(file: &FileDetails, georef: &Georeference) -> Option<MapOverlay> {
+    // Images already on the basemap's Web Mercator grid are shown as they are.
+    let (url, bounds) = if georef.epsg == 3857 {
+        (
+            BlobUrl::shared(&file.data, &file.file_type),
+            georef.bounds()?,
+        )
+    } else {
+        match warp_to_mercator(&file.data, georef) {
+            Ok((data, bounds)) => (BlobUrl::new(&data, "image/png"), bounds),
+            Err(why) => {
+                log::warn!("Not showing {} on the map: {why}", file.file_name);
+                return None;
+            }
+        }
+    };
+    Some(MapOverlay { url, bounds })
+}
+
+/// Bytes read from a file at a time. Reading a whole file at once takes as much memory
+/// again on the JavaScript side, which for large scenes is more than browsers give.
+const READ_CHUNK_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Reads `file` a slice at a time straight into the buffer that keeps it, reporting through
+/// `onprogress`. Stops with `None` once `stopped` says so.
+pub async fn read_in_chunks(
+    file: &Blob,
+    onprogress: &Callback<Progress>,
+    stopped: &dyn Fn() -> bool,
+) -> Result<Option<Rc<[u8]>>, String> {
+    let size = file.size() as u64;
+    // Collected from an iterator of known length, the buffer is allocated once, in place.
+    let mut data: Rc<[u8]> = std::iter::repeat_n(0, size as usize).collect();
+    let buffer = Rc::get_mut(&mut data).expect("The buffer was just made.");
+    let mut loaded = 0;
+    while loaded < size {
+        if stopped() {
+            return Ok(None);
+        }
+        let end = (loaded + READ_CHUNK_SIZE).min(size);
+        let chunk = file
+            .slice_with_f64_and_f64(loaded as f64, end as f64)
+            .map_err(|e| format!("{e:?}"))?;
+        let chunk = JsFuture::from(chunk.array_buffer())
+            .await
+            .map_err(|e| format!("{e:?}"))?;
+        let chunk = Uint8Array::new(&chunk);
+        if chunk.length() as u64 != end - loaded {
+            return Err(i18n::t("upload-file-changed"));
+        }
+        chunk.copy_to(&mut buffer[loaded as usize..end as usize]);
+        loaded = end;
+        onprogress.emit(Progress {
+            loaded,
+            total: Some(size),
+        });
+    }
+    Ok(Some(data))
+}
+
+/// The media type of a file whose browser reported `reported`, which is empty for types
+/// browsers do not know, such as TIFF on some systems. Those are told by their bytes.
+pub fn media_type(reported: &str, data: &[u8]) -> String {
+    if !reported.is_empty() {
+        return reported.to_string();
+    }
+    image::guess_format(data)
+        .map_or("application/octet-stream", |format| format.to_mime_type())
+        .to_string()
+}
+
+/// Whether `data` starts like an image of a known format whose header can be read, which
+/// only takes its first bytes, so that corrupt files are told before being sent.
+///
+/// TIFFs are read by the tiff crate itself, as the image crate refuses the sample formats
+/// it cannot decode, such as the 32-bit and floating point bands of multispectral imagery.
+pub fn check_header(data: &[u8]) -> Result<(), String> {
+    let (format, dimensions) = if data.starts_with(b"II*\0") || data.starts_with(b"MM\0*") {
+        let dimensions = tiff::decoder::Decoder::new(Cursor::new(data))
+            .and_then(|mut decoder| decoder.dimensions())
+            .map_err(|e| e.to_string());
+        (image::ImageFormat::Tiff, dimensions)
+    } else {
+        let reader = image::io::Reader::new(Cursor::new(data))
+            .with_guessed_format()
+            .map_err(|e| e.to_string())?;
+        let Some(format) = reader.format() else {
+            return Err(i18n::t("upload-unknown-format"));
+        };
+        (format, reader.into_dimensions().map_err(|e| e.to_string()))
+    };
+    match dimensions {
+        Ok((0, _) | (_, 0)) => Err(i18n::t_args(
+            "upload-no-pixels",
+            &[("format", &format!("{format:?}"))],
+        )),
+        Ok(_) => Ok(()),
+        Err(e) => Err(i18n::t_args(
+            "upload-unreadable-header",
+            &[("format", &format!("{format:?}")), ("error", &e)],
+        )),
+    }
+}
+
+/// `chips` stitched into one image in a worker, so that the page keeps responding
+/// meanwhile. They are checked here first, to fail before being sent when they cannot fit
+/// together.
+async fn mosaic_in_worker(
+    chips: Vec<(Rc<[u8]>, Georeference)>,
+) -> Result<(Vec<u8>, Georeference), String> {
+    let georef = geo::mosaic_grid(&chips.iter().map(|(_, g)| *g).collect::<Vec<_>>())?;
+    let png = encode(EncodeJob::Mosaic { chips }).await?;
+    Ok((png, georef))
+}
+
+/// Bytes of memory the app takes for every byte of a file read: the bytes read, the copy
+/// handed to the viewer or a worker, and the decoded pixels, roughly.
+const MEMORY_PER_FILE_BYTE: u64 = 3;
+
+/// Speed uploads are estimated at when the browser does not tell it, in megabits per
+/// second.
+const ASSUMED_MEGABITS_PER_S: f64 = 8.0;
+
+/// Memory files of `bytes` in total take once read, and the seconds they take to upload
+/// at `megabits_per_s`.
+fn estimate(bytes: u64, megabits_per_s: f64) -> (u64, f64) {
+    let seconds = bytes as f64 * 8.0 / (megabits_per_s.max(0.1) * 1_000_000.0);
+    (bytes * MEMORY_PER_FILE_BYTE, seconds)
+}
+
+/// The browser's estimate of the connection's speed, in megabits per second. It measures
+/// downloads, which is as close to uploads as browsers tell.
+fn connection_speed() -> f64 {
+    Reflect::get(&gloo::utils::window().navigator(), &"connection".into())
+        .and_then(|connection| Reflect::get(&connection, &"downlink".into()))
+        .ok()
+        .and_then(|downlink| downlink.as_f64())
+        .filter(|downlink| *downlink > 0.0)
+        .unwrap_or(ASSUMED_MEGABITS_PER_S)
+}
+
+/// `seconds` in the largest unit that keeps them readable.
+pub fn format_duration(seconds: f64) -> String {
+    match seconds.round() as u64 {
+        seconds @ 0..=59 => i18n::t_args("duration-seconds", &[("count", &seconds)]),
+        seconds @ 60..=3599 => {
+            i18n::t_args("duration-minutes", &[("count", &((seconds + 30) / 60))])
+        }
+        seconds => i18n::t_args(
+            "duration-hours",
+            &[("count", &format!("{:.1}", seconds as f64 / 3600.0))],
+        ),
+    }
+}
+
+/// Images read from files chosen or dropped by the user. `onreading` is called with
+/// `true` when files start being read, and with `false` if that gave no image.
+#[autoprops_component(UploadPane)]
+pub fn upload_pane(
+    #[prop_or_default] onupload: Callback<Rc<Option<FileDetails>>>,
+    #[prop_or_default] onreading: Callback<bool>,
+    /// Reference to the file input, to open the file dialog from elsewhere.
+    #[prop_or_default]
+    input_ref: NodeRef,
+) -> Html {
+    let language = use_language();
+    let src_image_state = use_state(|| Rc::new(None));
+    let readers = use_map(HashMap::new());
+    let read_files = use_mut_ref(Vec::new);
+    // Why files of the current selection could not be read.
+    let read_failures = use_mut_ref(Vec::<String>::new);
+    let world_file_epsg = use_state(|| 4326u32);
+    let clip_error = use_state(|| None::<String>);
+    let upload_error = use_state(|| None::<String>);
+    let settings = use_context::<Rc<Settings>>().unwrap_or_default();
+    // Files large enough to freeze the tab, waiting for users to confirm reading them.
+    let large_files = use_state(|| None::<Rc<Vec<File>>>);
+
+    // Once every selected file has been read, pairs images with their world files, if any,
+    // and mosaics them when there are several.
+    let on_complete_read = {
+        shadow_clone!(
+            src_image_state,
+            readers,
+            read_files,
+            read_failures,
+            world_file_epsg,
+            upload_error,
+            onupload,
+            onreading
+        );
+        move |file: web_sys::File, read: Result<Rc<[u8]>, String>| {
+            let file_name = file.name();
+            readers.remove(&file_name);
+            match read {
+                Ok(data) => {
+                    log::info!("Finished reading {file_name}");
+                    read_files
+                        .borrow_mut()
+                        .push((file_name, file.type_(), data, file));
+                }
+                // Files can go away between being chosen and being read.
+                Err(why) => read_failures.borrow_mut().push(i18n::t_args(
+                    "upload-read-failed",
+                    &[("name", &file_name), ("error", &why)],
+                )),
+            }
+            if !readers.current().is_empty() {
+                return;
+            }
+            let (world_files, images): (Vec<_>, Vec<_>) = read_files
+                .take()
+                .into_iter()
+                .partition(|(name, _, _, _)| geo::is_world_file(name));
+            // Files that are not images are left out here rather than refused by the server.
+            let images: Vec<_> = images
+                .into_iter()
+                .filter(|(name, _, data, _)| match check_header(data) {
+                    Ok(()) => true,
+                    Err(why) => {
+                        read_failures.borrow_mut().push(i18n::t_args(
+                            "upload-refused",
+                            &[("name", &name), ("error", &why)],
+                        ));
+                        false
+                    }
+                })
+                .collect();
+            let read_error = Some(read_failures.take().join(" ")).filter(|e| !e.is_empty());
+            let stem = |name: &str| {
+                name.rsplit_once('.')
+                    .map_or(name, |(stem, _)| stem)
+                    .to_string()
+            };
+            // World files are paired with images by name, or with the only image uploaded.
+            let georef_of = |name: &str, data: &[u8]| {
+                Georeference::from_geotiff(data).or_else(|| {
+                    let (_, _, world_file, _) = world_files
+                        .iter()
+                        .find(|(world_name, _, _, _)| stem(world_name) == stem(name))
+                        .or_else(|| (images.len() == 1).then(|| world_files.first())?)?;
+                    let (width, height) = image::io::Reader::new(Cursor::new(data))
+                        .with_guessed_format()
+                        .ok()?
+                        .into_dimensions()
+                        .ok()?;
+                    Georeference::from_world_file(
+                        &String::from_utf8_lossy(world_file),
+                        *world_file_epsg,
+                        width,
+                        height,
+                    )
+                })
+            };
+
+            let publish = {
+                shadow_clone!(upload_error, src_image_state, onupload);
+                shadow_clone!(read_error);
+                move |src_img: FileDetails| {
+                    upload_error.set(read_error.clone());
+                    let src_img = Rc::new(Some(src_img));
+                    src_image_state.set(src_img.clone());
+                    onupload.emit(src_img);
+                }
+            };
+            let chips = match &images[..] {
+                [] => {
+                    upload_error.set(read_error);
+                    onreading.emit(false);
+                    return;
+                }
+                [(file_name, file_type, data, source)] => {
+                    publish(FileDetails {
+                        file_name: file_name.clone(),
+                        file_type: media_type(file_type, data),
+                        data: data.clone(),
+                        georef: georef_of(file_name, data),
+                        auxiliary: None,
+                        source: Some(source.clone().into()),
+                    });
+                    return;
+                }
+                // Several chips are mosaicked so the whole area is segmented in one request.
+                _ => images
+                    .iter()
+                    .map(|(name, _, data, _)| {
+                        georef_of(name, data)
+                            .map(|georef| (data.clone(), georef))
+                            .ok_or_else(|| {
+                                i18n::t_args("upload-not-georeferenced", &[("name", &name)])
+                            })
+                    })
+                    .collect::<Result<Vec<_>, _>>(),
+            };
+            let file_name = format!("mosaic-{}.png", images.len());
+            shadow_clone!(upload_error, onreading);
+            spawn_local(async move {
+                let mosaic = match chips {
+                    Ok(chips) => mosaic_in_worker(chips).await,
+                    Err(why) => Err(why),
+                };
+                match mosaic {
+                    Ok((data, georef)) => publish(FileDetails {
+                        file_name,
+                        file_type: "image/png".to_string(),
+                        data: data.into(),
+                        georef: Some(georef),
+                        auxiliary: None,
+                        source: None,
+                    }),
+                    Err(why) => {
+                        upload_error.set(Some(i18n::t_args(
+                            "upload-mosaic-failed",
+                            &[("error", &why)],
+                        )));
+                        onreading.emit(false);
+                    }
+                }
+            });
+        }
+    };
+
+    let onattach = {
+        shadow_clone!(src_image_state, onupload);
+        move |auxiliary: Option<Rc<FileDetails>>| {
+            let Some(file) = (**src_image_state).clone() else {
+                return;
+            };
+            let src_img = Rc::new(Some(FileDetails { auxiliary, ..file }));
+            src_image_state.set(src_img.clone());
+            onupload.emit(src_img);
+        }
+    };
+
+    let onclip = {
+        shadow_clone!(src_image_state, clip_error, onupload);
+        move |aoi: Rc<SavedAoi>| {
+            let Some(file) = (**src_image_state).clone() else {
+                return;
+            };
+            let Some(georef) = file.georef else {
+                return;
+            };
+            match clip_to_area(&file.data, &georef, &aoi.points) {
+                Ok((data, georef)) => {
+                    let stem = file
+                        .file_name
+                        .rsplit_once('.')
+                        .map_or(file.file_name.as_str(), |(stem, _)| stem);
+                    let src_img = Rc::new(Some(FileDetails {
+                        file_name: format!("{stem}-{}.png", aoi.name),
+                        file_type: "image/png".to_string(),
+                        data: data.into(),
+                        georef: Some(georef),
+                        auxiliary: None,
+                        source: None,
+                    }));
+                    clip_error.set(None);
+                    src_image_state.set(src_img.clone());
+                    onupload.emit(src_img);
+                }
+                Err(why) => clip_error.set(Some(why)),
+            }
+        }
+    };
+
+    let is_mounted = use_is_mounted();
+    let start_reading = {
+        shadow_clone!(readers, onreading);
+        move |files: Vec<File>| {
+            log::info!(
+                "New image: {:?}",
+                files.iter().map(web_sys::File::name).collect::<Vec<_>>()
+            );
+            if !files.is_empty() {
+                onreading.emit(true);
+            }
+            for file in files.into_iter() {
+                let size = file.size() as u64;
+                readers.insert(
+                    file.name(),
+                    Progress {
+                        loaded: 0,
+                        total: Some(size),
+                    },
+                );
+                let onprogress = {
+                    shadow_clone!(readers);
+                    let file_name = file.name();
+                    Callback::from(move |progress| readers.update(&file_name, progress))
+                };
+                shadow_clone!(on_complete_read, is_mounted);
+                spawn_local(async move {
+                    let stopped = || !is_mounted();
+                    match read_in_chunks(&file, &onprogress, &stopped).await {
+                        Ok(None) => {}
+                        Ok(Some(data)) => on_complete_read(file, Ok(data)),
+                        Err(why) => on_complete_read(file, Err(why)),
+                    }
+                });
+            }
+        }
+    };
+
+    let load_files = {
+        shadow_clone!(start_reading, large_files);
+        let threshold = u64::from(settings.large_file_mb) << 20;
+        move |files: FileList| {
+            let files = (0..files.length())
+                .filter_map(|i| files.get(i))
+                .collect::<Vec<_>>();
+            let total: u64 = files.iter().map(|file| file.size() as u64).sum();
+            if threshold > 0 && total > threshold {
+                large_files.set(Some(Rc::new(files)));
+            } else {
+                large_files.set(None);
+                start_reading(files);
+            }
+        }
+    };
+
+    let onreadlarge = {
+        shadow_clone!(large_files);
+        move |_| {
+            if let Some(files) = (*large_files).clone() {
+                large_files.set(None);
+                start_reading(Rc::unwrap_or_clone(files));
+            }
+        }
+    };
+
+    let oncancellarge = {
+        shadow_clone!(large_files);
+        move |_| large_files.set(None)
+    };
+
+    let onupload = {
+        shadow_clone!(load_files);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Some(files) = input.files() {
+                load_files(files);
+            }
+        }
+    };
+
+    let ondrop = move |e: DragEvent| {
+        e.prevent_default();
+        if let Some(files) = e.data_transfer().and_then(|t| t.files()) {
+            load_files(files);
+        }
+    };
+
+    let onepsg = {
+        shadow_clone!(world_file_epsg);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            if let Ok(epsg) = input.value().parse() {
+                world_file_epsg.set(epsg);
+            }
+        }
+    };
+
+    let reading = readers.current();
+    let reading_percent = (!reading.is_empty())
+        .then(|| {
+            Progress {
+                loaded: reading.values().map(|progress| progress.loaded).sum(),
+                total: Some(reading.values().filter_map(|progress| progress.total).sum()),
+            }
+            .percent()
+        })
+        .flatten();
+
+    let large_files_card = (*large_files).as_ref().map(|files| {
+        let total: u64 = files.iter().map(|file| file.size() as u64).sum();
+        let (memory, seconds) = estimate(total, connection_speed());
+        html! {
+            <div class="card border-warning mb-3" role="alertdialog">
+                <div class="card-body">
+                    <h3 class="card-title h6">
+                        {language.t_args("upload-large-title", &[
+                            ("files", &match &files[..] {
+                                [file] => file.name(),
+                                files => language.t_args("upload-file-count", &[("count", &files.len())]),
+                            }),
+                            ("size", &format_size(total)),
+                        ])}
+                    </h3>
+                    <p class="card-text">
+                        {language.t_args(
+                            "upload-large-cost",
+                            &[("memory", &format_size(memory)), ("time", &format_duration(seconds))],
+                        )}
+                    </p>
+                    <button class="btn btn-sm btn-warning me-2" onclick={onreadlarge}>
+                        {language.t("upload-read-anyway")}
+                    </button>
+                    <button class="btn btn-sm btn-outline-secondary" onclick={oncancellarge}>
+                        {language.t("action-cancel")}
+                    </button>
+                </div>
+            </div>
+        }
+    });
+
+    html!(
+        <div ondragover={|e: DragEvent| e.prevent_default()} {ondrop}>
+        {large_files_card}
+        {
+            if let Some(file) = (*src_image_state).borrow() {
+                html! {
+                    <div>
+                        <h2>{&file.file_name}</h2>
+                        <FileViewer file={Rc::new(file.clone())} georef={file.georef} />
+                        if flags::enabled(Flag::Ndvi) {
+                            <NdviPane file={Rc::new(file.clone())} {onattach} />
+                        }
+                        if file.georef.is_some() {
+                            <details class="mb-2">
+                                <summary>{language.t("upload-clip")}</summary>
+                                <SavedAoisPane select_label={language.t("upload-clip-select")} onselect={onclip} />
+                                if let Some(why) = &*clip_error {
+                                    <div class="alert alert-danger" role="alert">{why}</div>
+                                }
+                            </details>
+                        }
+                    </div>
+                }
+            } else {
+                html! {
+                    <p>{language.t("upload-none")}</p>
+                }
+            }
+        }
+        if let Some(why) = &*upload_error {
+            <div class="alert alert-danger" role="alert">{why}</div>
+        }
+        if let Some(percent) = reading_percent {
+            <div
+                class="progress my-2"
+                role="progressbar"
+                aria-label={language.t("status-reading")}
+                aria-valuenow={percent.to_string()}
+                aria-valuemin="0"
+                aria-valuemax="100"
+            >
+                <div class="progress-bar" style={format!("width: {percent}%;")}>
+                    {format!("{percent}%")}
+                </div>
+            </div>
+        }
+        <input
+            ref={input_ref.clone()}
+            type="file"
+            aria-label={language.t("upload-input")}
+            accept="image/*,.wld,.pgw,.pngw,.jgw,.jpgw,.tfw,.tifw,.gfw"
+            multiple={true}
+            onchange={onupload}
+        />
+        <div class="input-group input-group-sm mt-1" style="width: 16em;">
+            <span class="input-group-text">{language.t("upload-world-file-epsg")}</span>
+            <input
+                type="number"
+                class="form-control"
+                aria-label={language.t("upload-world-file-epsg")}
+                value={world_file_epsg.to_string()}
+                onchange={onepsg}
+            />
+        </div>
+        </div>
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use tiff::encoder::{colortype, TiffEncoder};
+
+    use super::{check_header, estimate, format_duration, media_type};
+    use crate::fixtures;
+
+    #[test]
+    fn files_of_unknown_types_are_told_by_their_bytes() {
+        assert_eq!(media_type("image/tiff", fixtures::IMAGE), "image/tiff");
+        assert_eq!(media_type("", fixtures::IMAGE), "image/png");
+        assert_eq!(media_type("", b"not an image"), "application/octet-stream");
+    }
+
+    #[test]
+    fn large_files_are_estimated_in_memory_and_upload_time() {
+        let (memory, seconds) = estimate(1_200_000_000, 8.0);
+        assert_eq!(memory, 3_600_000_000);
+        assert_eq!(seconds, 1200.0);
+        assert_eq!(format_duration(seconds), "20 min");
+        assert_eq!(format_duration(42.4), "42 s");
+        assert_eq!(format_duration(5400.0), "1.5 h");
+    }
+
+    #[test]
+    fn files_that_are_not_readable_images_are_told_before_upload() {
+        assert_eq!(check_header(fixtures::IMAGE), Ok(()));
+        assert!(check_header(b"<html>Not found</html>").is_err());
+        // A PNG cut off within its header.
+        let error = check_header(&fixtures::IMAGE[..20]).unwrap_err();
+        assert!(error.contains("Png"), "{error}");
+    }
+
+    #[test]
+    fn tiffs_with_floating_point_bands_are_readable() {
+        let mut data = Cursor::new(Vec::new());
+        TiffEncoder::new(&mut data)
+            .unwrap()
+            .write_image::<colortype::Gray32Float>(3, 2, &[0.1; 6])
+            .unwrap();
+        let data = data.into_inner();
+        assert_eq!(check_header(&data), Ok(()));
+        let error = check_header(&data[..6]).unwrap_err();
+        assert!(error.contains("Tiff"), "{error}");
+    }
+}
@@ -0,0 +1,719 @@
+use gloo::{
+    events::{EventListener, EventListenerOptions},
+    file::{Blob, ObjectUrl},
+    timers::callback::Timeout,
+};
+use js_sys::Reflect;
+use shadow_clone::shadow_clone;
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    ops::Deref,
+    ptr,
+    rc::{Rc, Weak},
+};
+use wasm_bindgen::JsCast;
+use web_sys::{Element, TouchEvent};
+use yew::{platform::spawn_local, prelude::*};
+use yew_autoprops::autoprops_component;
+
+use crate::{
+    codec::EncodeJob,
+    geo::{Georeference, LatLng},
+    i18n::{self, use_language},
+    workers::encode,
+    FileDetails,
+};
+
+/// A blob URL for some bytes, revoked once its last clone is dropped. Unlike a data URL
+/// it costs no more than a copy of the bytes, however large they are.
+#[derive(Clone)]
+pub struct BlobUrl(ObjectUrl);
+
+impl BlobUrl {
+    pub fn new(data: &[u8], file_type: &str) -> Self {
+        Self(ObjectUrl::from(Blob::new_with_options(
+            data,
+            Some(file_type),
+        )))
+    }
+
+    /// A blob URL for `data` as it is, shared with everything else showing the same bytes.
+    pub fn shared(data: &Rc<[u8]>, file_type: &str) -> Self {
+        if let Some(Some(url)) = URLS.with(|urls| urls.borrow_mut().get(data, "original")) {
+            return url;
+        }
+        let url = Self::new(data, file_type);
+        URLS.with(|urls| {
+            urls.borrow_mut()
+                .insert(data, "original", Some(url.clone()))
+        });
+        url
+    }
+}
+
+impl PartialEq for BlobUrl {
+    fn eq(&self, other: &Self) -> bool {
+        *self.0 == *other.0
+    }
+}
+
+impl Deref for BlobUrl {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Entries kept by a [`UrlCache`].
+const CACHE_SIZE: usize = 64;
+
+/// What was made of some bytes, by what it is, kept for as long as the bytes are alive so
+/// that showing the same image again, after a remount or in another component, does not
+/// copy or encode it again. The oldest entries are forgotten past [`CACHE_SIZE`].
+struct UrlCache<V> {
+    entries: VecDeque<(Weak<[u8]>, &'static str, V)>,
+}
+
+impl<V: Clone> UrlCache<V> {
+    const fn new() -> Self {
+        UrlCache {
+            entries: VecDeque::new(),
+        }
+    }
+
+    fn get(&mut self, bytes: &Rc<[u8]>, kind: &'static str) -> Option<V> {
+        // The bytes of dropped files cannot be asked for again.
+        self.entries
+            .retain(|(entry, _, _)| entry.strong_count() > 0);
+        // A weak reference keeps its allocation, so no other bytes can have this address.
+        self.entries
+            .iter()
+            .find(|(entry, entry_kind, _)| {
+                *entry_kind == kind && ptr::addr_eq(entry.as_ptr(), Rc::as_ptr(bytes))
+            })
+            .map(|(_, _, value)| value.clone())
+    }
+
+    fn insert(&mut self, bytes: &Rc<[u8]>, kind: &'static str, value: V) {
+        if self.entries.len() == CACHE_SIZE {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((Rc::downgrade(bytes), kind, value));
+    }
+}
+
+thread_local! {
+    static URLS: RefCell<UrlCache<Option<BlobUrl>>> = const { RefCell::new(UrlCache::new()) };
+}
+
+/// How many blob URLs are kept to show images again.
+pub fn cached_urls() -> usize {
+    URLS.with(|urls| urls.borrow().entries.len())
+}
+
+/// Forgets the blob URLs kept to show images again, revoking those no image shows.
+pub fn forget_urls() {
+    // Taken out first, as revoking runs while the entries are dropped.
+    let entries = URLS.with(|urls| std::mem::take(&mut urls.borrow_mut().entries));
+    drop(entries);
+}
+
+/// A blob URL for the PNG `job` makes of `file`: `None` until the worker answers, then the
+/// URL, or `None` within if the image could not be decoded or `job` had nothing to do for
+/// `file`. Encoded again whenever `file` changes, unless `job`, named `kind`, was already
+/// run on the same bytes.
+#[hook]
+pub fn use_encoded(
+    file: Rc<FileDetails>,
+    kind: &'static str,
+    job: fn(&FileDetails) -> Option<EncodeJob>,
+) -> Option<Option<BlobUrl>> {
+    let cached =
+        move |file: &FileDetails| URLS.with(|urls| urls.borrow_mut().get(&file.data, kind));
+    let encoded = use_state(|| cached(&file));
+    {
+        shadow_clone!(encoded);
+        use_effect_with(file, move |file| {
+            // Answers for a file shown no longer are dropped.
+            let current = Rc::new(Cell::new(true));
+            match (cached(file), job(file)) {
+                (Some(url), _) => encoded.set(Some(url)),
+                (None, Some(job)) => {
+                    encoded.set(None);
+                    shadow_clone!(current);
+                    let bytes = file.data.clone();
+                    spawn_local(async move {
+                        let url = encode(job)
+                            .await
+                            .map(|png| BlobUrl::new(&png, "image/png"))
+                            .map_err(|why| log::warn!("Could not make the {kind}: {why}"))
+                            .ok();
+                        URLS.with(|urls| urls.borrow_mut().insert(&bytes, kind, url.clone()));
+                        if current.get() {
+                            encoded.set(Some(url));
+                        }
+                    });
+                }
+                (None, None) => encoded.set(Some(None)),
+            }
+            move || current.set(false)
+        });
+    }
+    (*encoded).clone()
+}
+
+/// Whether browsers show images of `file_type` by themselves. Others, such as TIFF, are
+/// decoded by the app first.
+fn shown_by_browsers(file_type: &str) -> bool {
+    matches!(
+        file_type,
+        "image/png"
+            | "image/jpeg"
+            | "image/gif"
+            | "image/webp"
+            | "image/avif"
+            | "image/bmp"
+            | "image/svg+xml"
+    )
+}
+
+/// Longest side of the preview shown while a large image is decoded.
+const PREVIEW_SIZE: u32 = 512;
+
+fn preview_job(file: &FileDetails) -> Option<EncodeJob> {
+    (!shown_by_browsers(&file.file_type)).then(|| EncodeJob::Thumbnail {
+        data: file.data.clone(),
+        size: PREVIEW_SIZE,
+    })
+}
+
+fn full_size_job(file: &FileDetails) -> Option<EncodeJob> {
+    (!shown_by_browsers(&file.file_type)).then(|| EncodeJob::Png {
+        data: file.data.clone(),
+    })
+}
+
+/// [`ImageViewer`] for `file`, shown from a blob URL, with `children` over it. `alt`
+/// describes it to screen readers and defaults to the file name.
+#[autoprops_component(FileViewer)]
+pub fn file_viewer(
+    file: Rc<FileDetails>,
+    #[prop_or_default] georef: Option<Georeference>,
+    #[prop_or_default] alt: Option<AttrValue>,
+    #[prop_or_default] children: Children,
+) -> Html {
+    let language = use_language();
+    let direct = use_memo(file.clone(), |file| {
+        shown_by_browsers(&file.file_type).then(|| BlobUrl::shared(&file.data, &file.file_type))
+    });
+    // The worker answers jobs in order, so the small preview comes first, to be replaced
+    // by the full image once that is decoded.
+    let preview = use_encoded(file.clone(), "preview", preview_job);
+    let full_size = use_encoded(file.clone(), "full size", full_size_job);
+    let alt = alt.clone().unwrap_or_else(|| file.file_name.clone().into());
+
+    match ((*direct).clone(), full_size, preview) {
+        (Some(src), _, _) | (None, Some(Some(src)), _) => {
+            html!(<ImageViewer src={src.to_string()} {alt} {georef}>{children.clone()}</ImageViewer>)
+        }
+        (None, Some(None), _) => html! {
+            <div class="alert alert-secondary">
+                {language.t_args("viewer-undecodable", &[("name", &file.file_name)])}
+            </div>
+        },
+        (None, None, Some(Some(preview))) => html! {
+            <div aria-busy="true">
+                <ImageViewer src={preview.to_string()} {alt} {georef}>{children.clone()}</ImageViewer>
+                <small class="text-body-secondary">
+                    {language.t("viewer-preview")}
+                </small>
+            </div>
+        },
+        (None, None, _) => html! {
+            <div class="d-flex align-items-center gap-2 py-4" aria-busy="true">
+                <span class="spinner-border spinner-border-sm" aria-hidden="true"></span>
+                {language.t_args("viewer-decoding", &[("name", &file.file_name)])}
+            </div>
+        },
+    }
+}
+
+/// Human-readable WGS84 position, followed by the projected coordinates in the
+/// image's CRS when those differ from plain latitude and longitude.
+pub fn describe_position(point: LatLng, georef: Option<&Georeference>) -> String {
+    let mut text = i18n::t_args(
+        "viewer-position",
+        &[
+            ("lat", &format!("{:.6}", point.lat)),
+            ("lng", &format!("{:.6}", point.lng)),
+        ],
+    );
+    if let Some(georef) = georef.filter(|g| g.epsg != 4326) {
+        if let Some((x, y)) = georef.lat_lng_to_crs(point) {
+            text += &format!(" | EPSG:{} X {x:.2}, Y {y:.2}", georef.epsg);
+        }
+    }
+    text
+}
+
+/// How far an image is zoomed in, and panned, in CSS pixels, from its top left corner.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct View {
+    scale: f64,
+    x: f64,
+    y: f64,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        View {
+            scale: 1.0,
+            x: 0.0,
+            y: 0.0,
+        }
+    }
+}
+
+/// Furthest an image can be zoomed in by pinching.
+const MAX_SCALE: f64 = 8.0;
+/// Time a finger is held still on an image for its inspector to open.
+const LONG_PRESS_MS: u32 = 600;
+/// Distance, in CSS pixels, a finger may drift and still be held still.
+const LONG_PRESS_SLOP: f64 = 10.0;
+
+/// Two fingers, relative to the top left corner of the image's frame.
+type Fingers = [(f64, f64); 2];
+
+/// `start` zoomed by how far the fingers moved apart from `from` to `to`, and panned as
+/// their midpoint moved, so that the point of the image between them stays between them.
+/// The image is kept covering its frame of `size`.
+fn pinch(start: View, from: Fingers, to: Fingers, size: (f64, f64)) -> View {
+    let spread = |[a, b]: Fingers| (a.0 - b.0).hypot(a.1 - b.1).max(1.0);
+    let middle = |[a, b]: Fingers| ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0);
+    let scale = (start.scale * spread(to) / spread(from)).clamp(1.0, MAX_SCALE);
+    let (from_x, from_y) = middle(from);
+    let (to_x, to_y) = middle(to);
+    let x = to_x - scale * (from_x - start.x) / start.scale;
+    let y = to_y - scale * (from_y - start.y) / start.scale;
+    View {
+        scale,
+        x: x.clamp(size.0 * (1.0 - scale), 0.0),
+        y: y.clamp(size.1 * (1.0 - scale), 0.0),
+    }
+}
+
+/// The touches of `e`, relative to the top left corner of `frame`.
+fn touches(e: &TouchEvent, frame: &Element) -> Vec<(f64, f64)> {
+    let rect = frame.get_bounding_client_rect();
+    let list = e.touches();
+    (0..list.length())
+        .filter_map(|i| list.get(i))
+        .map(|touch| {
+            (
+                touch.client_x() as f64 - rect.left(),
+                touch.client_y() as f64 - rect.top(),
+            )
+        })
+        .collect()
+}
+
+/// What lies under `point` of `img`, a point in its frame: the pixel of the image, and
+/// where it is on the ground when the image is georeferenced.
+fn inspect(
+    img: &Element,
+    frame: &Element,
+    point: (f64, f64),
+    georef: Option<Georeference>,
+) -> String {
+    let rect = img.get_bounding_client_rect();
+    let frame_rect = frame.get_bounding_client_rect();
+    let u = (point.0 + frame_rect.left() - rect.left()) / rect.width().max(1.0);
+    let v = (point.1 + frame_rect.top() - rect.top()) / rect.height().max(1.0);
+    // The size of the image itself, read without enabling `HtmlImageElement`.
+    let natural = |key: &str| {
+        Reflect::get(img, &key.into())
+            .ok()
+            .and_then(|size| size.as_f64())
+            .unwrap_or(0.0)
+    };
+    let (width, height) = match georef {
+        Some(georef) => (georef.width as f64, georef.height as f64),
+        None => (natural("naturalWidth"), natural("naturalHeight")),
+    };
+    let (col, row) = (u * width, v * height);
+    let pixel = i18n::t_args(
+        "viewer-pixel",
+        &[("col", &col.floor()), ("row", &row.floor())],
+    );
+    let Some(georef) = georef else {
+        return pixel;
+    };
+    let (x, y) = georef.pixel_to_crs(col, row);
+    match georef.crs_to_lat_lng(x, y) {
+        Some(point) => format!("{pixel} · {}", describe_position(point, Some(&georef))),
+        None => format!("{pixel} · EPSG:{} X {x:.2}, Y {y:.2}", georef.epsg),
+    }
+}
+
+/// How an image is zoomed, and the gesture under way on it.
+#[derive(Default)]
+struct Gesture {
+    view: View,
+    /// The view and the fingers when two of them were put down.
+    pinch: Option<(View, Fingers)>,
+    /// Where a single finger was put down, and the timer opening the inspector if it stays.
+    press: Option<((f64, f64), Timeout)>,
+}
+
+impl Gesture {
+    fn start_pinch(&mut self, fingers: Fingers) {
+        self.press = None;
+        self.pinch = Some((self.view, fingers));
+    }
+
+    /// Zooms and pans for the fingers now at `to`, if they are pinching.
+    fn move_pinch(&mut self, to: Fingers, size: (f64, f64)) -> bool {
+        let Some((start, from)) = self.pinch else {
+            return false;
+        };
+        self.view = pinch(start, from, to, size);
+        true
+    }
+
+    /// Shows the whole image again, returning whether it was zoomed.
+    fn reset(&mut self) -> bool {
+        self.pinch = None;
+        std::mem::take(&mut self.view) != View::default()
+    }
+}
+
+/// Image with a readout of the coordinates under the cursor, when it is georeferenced. On
+/// touch screens, two fingers zoom and pan it, and holding one still inspects the point
+/// under it. `children` are laid over the image, zoomed with it, and let events through.
+#[autoprops_component(ImageViewer)]
+pub fn image_viewer(
+    src: AttrValue,
+    alt: AttrValue,
+    #[prop_or_default] georef: Option<Georeference>,
+    #[prop_or_default] children: Children,
+) -> Html {
+    let language = use_language();
+    let position = use_state(|| None::<String>);
+    // Read and written by the touch listeners as well as when rendering.
+    let gesture = use_mut_ref(Gesture::default);
+    let redraw = use_force_update();
+    // What was inspected, and where in the frame.
+    let inspected = use_state(|| None::<(String, (f64, f64))>);
+    let frame = use_node_ref();
+    let img = use_node_ref();
+
+    {
+        shadow_clone!(gesture, redraw);
+        // Another image is shown whole.
+        use_effect_with(src.clone(), move |_| {
+            let zoomed = gesture.borrow_mut().reset();
+            if zoomed {
+                redraw.force_update();
+            }
+        });
+    }
+    {
+        let (gesture, redraw, inspected, frame, img) = (
+            gesture.clone(),
+            redraw.clone(),
+            inspected.clone(),
+            frame.clone(),
+            img.clone(),
+        );
+        use_effect_with(georef, move |&georef| {
+            let element = frame.cast::<Element>();
+            // Listeners that may prevent the browser's own scrolling and zooming, which
+            // Yew's touch listeners, being passive, cannot.
+            let listeners = element.map(|element| {
+                let options = EventListenerOptions::enable_prevent_default();
+                let ontouchstart = {
+                    let (gesture, element, inspected, img) = (
+                        gesture.clone(),
+                        element.clone(),
+                        inspected.clone(),
+                        img.clone(),
+                    );
+                    EventListener::new_with_options(
+                        &element.clone(),
+                        "touchstart",
+                        options,
+                        move |e| {
+                            let e: &TouchEvent = e.unchecked_ref();
+                            let points = touches(e, &element);
+                            // Before borrowing the gesture, as it may redraw at once.
+                            if points.len() == 1 {
+                                inspected.set(None);
+                            }
+                            let mut gesture = gesture.borrow_mut();
+                            match points[..] {
+                                [a, b] => {
+                                    e.prevent_default();
+                                    gesture.start_pinch([a, b]);
+                                }
+                                [point] => {
+                                    let (element, inspected, img) =
+                                        (element.clone(), inspected.clone(), img.clone());
+                                    let timer = Timeout::new(LONG_PRESS_MS, move || {
+                                        if let Some(img) = img.cast::<Element>() {
+                                            inspected.set(Some((
+                                                inspect(&img, &element, point, georef),
+                                                point,
+                                            )));
+                                        }
+                                    });
+                                    gesture.press = Some((point, timer));
+                                }
+                                _ => gesture.press = None,
+                            }
+                        },
+                    )
+                };
+                let ontouchmove = {
+                    let (gesture, element, redraw) =
+                        (gesture.clone(), element.clone(), redraw.clone());
+                    EventListener::new_with_options(
+                        &element.clone(),
+                        "touchmove",
+                        options,
+                        move |e| {
+                            let e: &TouchEvent = e.unchecked_ref();
+                            let points = touches(e, &element);
+                            let size = (
+                                element.client_width() as f64,
+                                element.client_height() as f64,
+                            );
+                            // Released before redrawing, which reads it.
+                            let pinched = {
+                                let mut gesture = gesture.borrow_mut();
+                                match &points[..] {
+                                    &[a, b] => gesture.move_pinch([a, b], size),
+                                    [point] => {
+                                        let drifted =
+                                            gesture.press.as_ref().is_some_and(|((x, y), _)| {
+                                                (point.0 - x).hypot(point.1 - y) > LONG_PRESS_SLOP
+                                            });
+                                        if drifted {
+                                            gesture.press = None;
+                                        }
+                                        false
+                                    }
+                                    _ => false,
+                                }
+                            };
+                            if pinched {
+                                e.prevent_default();
+                                redraw.force_update();
+                            }
+                        },
+                    )
+                };
+                let ontouchend = ["touchend", "touchcancel"].map(|name| {
+                    let gesture = gesture.clone();
+                    EventListener::new(&element, name, move |e| {
+                        let e: &TouchEvent = e.unchecked_ref();
+                        let mut gesture = gesture.borrow_mut();
+                        gesture.press = None;
+                        if e.touches().length() < 2 {
+                            gesture.pinch = None;
+                        }
+                    })
+                });
+                (ontouchstart, ontouchmove, ontouchend)
+            });
+            move || drop(listeners)
+        });
+    }
+
+    let onmousemove = {
+        shadow_clone!(position);
+        move |e: MouseEvent| {
+            let Some(georef) = georef else {
+                return;
+            };
+            let img: Element = e.target_unchecked_into();
+            let col = e.offset_x() as f64 / img.client_width().max(1) as f64 * georef.width as f64;
+            let row =
+                e.offset_y() as f64 / img.client_height().max(1) as f64 * georef.height as f64;
+            let (x, y) = georef.pixel_to_crs(col, row);
+            position.set(Some(match georef.crs_to_lat_lng(x, y) {
+                Some(point) => describe_position(point, Some(&georef)),
+                None => format!("EPSG:{} X {x:.2}, Y {y:.2}", georef.epsg),
+            }));
+        }
+    };
+
+    let onmouseleave = {
+        shadow_clone!(position);
+        move |_| position.set(None)
+    };
+
+    let onreset = {
+        shadow_clone!(gesture, redraw, inspected);
+        move |_| {
+            gesture.borrow_mut().reset();
+            inspected.set(None);
+            redraw.force_update();
+        }
+    };
+    let view = gesture.borrow().view;
+    let oncloseinspector = {
+        shadow_clone!(inspected);
+        move |_| inspected.set(None)
+    };
+
+    html! {
+        <div>
+            <div ref={frame} class="position-relative overflow-hidden">
+                <div
+                    class="position-relative"
+                    style={format!(
+                        "transform: translate({}px, {}px) scale({}); transform-origin: 0 0;",
+                        view.x, view.y, view.scale
+                    )}
+                >
+                    <img
+                        ref={img}
+                        class="d-block"
+                        width={"100%"}
+                        {src}
+                        {alt}
+                        {onmousemove}
+                        {onmouseleave}
+                    />
+                    if !children.is_empty() {
+                        <div class="position-absolute top-0 start-0 w-100 h-100" style="pointer-events: none;">
+                            {children.clone()}
+                        </div>
+                    }
+                </div>
+                if let Some((text, (x, y))) = &*inspected {
+                    <div
+                        class="position-absolute bg-body border rounded small px-2 py-1 shadow"
+                        style={format!("left: {x}px; top: {y}px; max-width: 90%; transform: translate(-50%, -120%);")}
+                        role="status"
+                    >
+                        {text}
+                        <button
+                            type="button"
+                            class="btn-close btn-sm ms-2 align-middle"
+                            aria-label={language.t("viewer-close")}
+                            onclick={oncloseinspector}
+                        ></button>
+                    </div>
+                }
+            </div>
+            if georef.is_some() {
+                <small class="font-monospace text-body-secondary" aria-live="polite">
+                    {(*position).clone().unwrap_or_else(|| language.t("viewer-hover"))}
+                </small>
+            }
+            if view != View::default() {
+                <button class="btn btn-sm btn-link" onclick={onreset}>{language.t("viewer-reset-zoom")}</button>
+            }
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::{
+        full_size_job, pinch, preview_job, shown_by_browsers, Gesture, UrlCache, View, CACHE_SIZE,
+        MAX_SCALE,
+    };
+    use crate::fixtures;
+
+    #[test]
+    fn cached_values_last_as_long_as_their_bytes() {
+        let mut cache = UrlCache::new();
+        let bytes: Rc<[u8]> = Rc::from(&b"image"[..]);
+        cache.insert(&bytes, "thumbnail", 1);
+        assert_eq!(cache.get(&bytes.clone(), "thumbnail"), Some(1));
+        assert_eq!(cache.get(&bytes, "preview"), None);
+        // Equal bytes elsewhere are other bytes, which may change on their own.
+        assert_eq!(cache.get(&Rc::from(&b"image"[..]), "thumbnail"), None);
+        drop(bytes);
+        assert_eq!(cache.get(&Rc::from(&b"other"[..]), "thumbnail"), None);
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn the_oldest_values_are_forgotten_first() {
+        let mut cache = UrlCache::new();
+        let files: Vec<Rc<[u8]>> = (0..=CACHE_SIZE).map(|i| Rc::from(vec![i as u8])).collect();
+        for (i, bytes) in files.iter().enumerate() {
+            cache.insert(bytes, "thumbnail", i);
+        }
+        assert_eq!(cache.get(&files[0], "thumbnail"), None);
+        assert_eq!(cache.get(&files[CACHE_SIZE], "thumbnail"), Some(CACHE_SIZE));
+    }
+
+    #[test]
+    fn only_formats_browsers_cannot_show_are_decoded() {
+        assert!(shown_by_browsers("image/png"));
+        assert!(!shown_by_browsers("image/tiff"));
+
+        let png = fixtures::png("image.png", fixtures::IMAGE);
+        assert!(preview_job(&png).is_none() && full_size_job(&png).is_none());
+        let tiff = crate::FileDetails {
+            file_type: "image/tiff".to_string(),
+            ..png
+        };
+        assert!(preview_job(&tiff).is_some() && full_size_job(&tiff).is_some());
+    }
+
+    #[test]
+    fn pinching_keeps_the_point_between_the_fingers_under_them() {
+        let size = (400.0, 300.0);
+        let from = [(100.0, 100.0), (200.0, 100.0)];
+        let apart = [(50.0, 100.0), (250.0, 100.0)];
+
+        let zoomed = pinch(View::default(), from, apart, size);
+
+        assert_eq!(zoomed.scale, 2.0);
+        // The midpoint, at 150, 100, stays where it was.
+        assert_eq!((zoomed.x, zoomed.y), (-150.0, -100.0));
+    }
+
+    #[test]
+    fn pinching_keeps_the_image_over_its_frame() {
+        let size = (400.0, 300.0);
+        let from = [(100.0, 100.0), (200.0, 100.0)];
+        let together = [(140.0, 100.0), (160.0, 100.0)];
+        assert_eq!(
+            pinch(View::default(), from, together, size),
+            View::default()
+        );
+
+        let far_apart = [(-10_000.0, 100.0), (1_000.0, 100.0)];
+        let zoomed = pinch(View::default(), from, far_apart, size);
+        assert_eq!(zoomed.scale, MAX_SCALE);
+        assert_eq!(zoomed.x, size.0 * (1.0 - MAX_SCALE));
+    }
+
+    #[test]
+    fn pinching_after_a_reset_starts_from_the_whole_image() {
+        let size = (400.0, 300.0);
+        let from = [(100.0, 100.0), (200.0, 100.0)];
+        let apart = [(50.0, 100.0), (250.0, 100.0)];
+        let mut gesture = Gesture::default();
+        gesture.start_pinch(from);
+        assert!(gesture.move_pinch(apart, size));
+        assert_eq!(gesture.view.scale, 2.0);
+
+        assert!(gesture.reset());
+        assert!(!gesture.move_pinch(apart, size));
+        gesture.start_pinch(from);
+        gesture.move_pinch(from, size);
+
+        assert_eq!(gesture.view, View::default());
+    }
+}
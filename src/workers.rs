@@ -0,0 +1,114 @@
+//! The pool of web workers, built from `src/bin/worker.rs`, that encoding and compositing
+//! heavy enough to freeze the page is handed to. There are as many as the device has cores,
+//! up to [`MAX_WORKERS`], so that several jobs run at once.
+
+use gloo::worker::{oneshot::OneshotBridge, Spawnable};
+use std::{cell::RefCell, collections::VecDeque};
+use yew::platform::pinned::oneshot;
+
+use crate::codec::{EncodeJob, Encoder};
+
+/// Loader Trunk builds for the worker in `src/bin/worker.rs`.
+const WORKER_LOADER: &str = "./worker_loader.js";
+
+/// Workers started at most. Each holds a copy of the app and of the bytes of its job.
+const MAX_WORKERS: usize = 4;
+
+/// Hands out up to `limit` workers, started as they are first needed, to one job at a time
+/// and to jobs in the order they asked. Jobs waiting for a worker only hold on to bytes
+/// they share with the app, so however many wait, only running jobs cost memory.
+struct Scheduler<W> {
+    limit: usize,
+    started: usize,
+    idle: Vec<W>,
+    waiting: VecDeque<oneshot::Sender<W>>,
+}
+
+impl<W> Scheduler<W> {
+    fn new(limit: usize) -> Self {
+        Scheduler {
+            limit,
+            started: 0,
+            idle: Vec::new(),
+            waiting: VecDeque::new(),
+        }
+    }
+
+    /// A worker free for a job, started with `start` if need be, or where one will be sent
+    /// once free.
+    fn acquire(&mut self, start: impl FnOnce() -> W) -> Result<W, oneshot::Receiver<W>> {
+        if let Some(worker) = self.idle.pop() {
+            return Ok(worker);
+        }
+        if self.started < self.limit {
+            self.started += 1;
+            return Ok(start());
+        }
+        let (sender, receiver) = oneshot::channel();
+        self.waiting.push_back(sender);
+        Err(receiver)
+    }
+
+    /// Hands `worker`, done with its job, to the first job still waiting, or keeps it.
+    fn release(&mut self, mut worker: W) {
+        while let Some(waiting) = self.waiting.pop_front() {
+            match waiting.send(worker) {
+                Ok(()) => return,
+                Err(unsent) => worker = unsent,
+            }
+        }
+        self.idle.push(worker);
+    }
+}
+
+thread_local! {
+    static POOL: RefCell<Scheduler<OneshotBridge<Encoder>>> =
+        RefCell::new(Scheduler::new(worker_count()));
+}
+
+fn worker_count() -> usize {
+    let cores = gloo::utils::window().navigator().hardware_concurrency() as usize;
+    cores.clamp(1, MAX_WORKERS)
+}
+
+/// Runs `job` in the first worker free, keeping the page responsive meanwhile.
+pub async fn encode(job: EncodeJob) -> Result<Vec<u8>, String> {
+    let start = || Encoder::spawner().spawn_with_loader(WORKER_LOADER);
+    let acquired = POOL.with(|pool| pool.borrow_mut().acquire(start));
+    let mut worker = match acquired {
+        Ok(worker) => worker,
+        Err(waiting) => waiting
+            .await
+            .map_err(|_| "The workers stopped".to_string())?,
+    };
+    let result = worker.run(job).await;
+    POOL.with(|pool| pool.borrow_mut().release(worker));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Scheduler;
+    use crate::api::mock::block_on;
+
+    #[test]
+    fn jobs_wait_for_workers_in_turn() {
+        let mut scheduler = Scheduler::new(2);
+        let first = scheduler.acquire(|| 1).ok().unwrap();
+        let second = scheduler.acquire(|| 2).ok().unwrap();
+        assert_eq!((first, second), (1, 2));
+
+        let given_up = scheduler.acquire(|| unreachable!()).err().unwrap();
+        let third = scheduler.acquire(|| unreachable!()).err().unwrap();
+        let fourth = scheduler.acquire(|| unreachable!()).err().unwrap();
+        drop(given_up);
+        scheduler.release(first);
+        assert_eq!(block_on(third).unwrap(), 1);
+        scheduler.release(second);
+        assert_eq!(block_on(fourth).unwrap(), 2);
+
+        // Free workers are used again rather than more started.
+        scheduler.release(1);
+        assert_eq!(scheduler.acquire(|| unreachable!()).ok(), Some(1));
+    }
+}
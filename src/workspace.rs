@@ -0,0 +1,193 @@
+//! Named workspaces, each with its own history, areas of interest and settings.
+//!
+//! Every piece of stored state is kept under a key suffixed with the workspace name; the
+//! default workspace uses the plain keys, so data from before workspaces existed lands there.
+
+use gloo::storage::{LocalStorage, Storage};
+use shadow_clone::shadow_clone;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+use crate::{
+    i18n::{self, use_language},
+    notify,
+    settings::Settings,
+};
+
+pub const DEFAULT_WORKSPACE: &str = "Default";
+const WORKSPACES_KEY: &str = "workspaces";
+const CURRENT_KEY: &str = "current-workspace";
+/// Databases of deleted workspaces, removed on the next start before any is opened.
+const DELETED_KEY: &str = "deleted-workspace-databases";
+
+/// Name of the workspace this page works in.
+pub fn current() -> String {
+    LocalStorage::get(CURRENT_KEY).unwrap_or_else(|_| DEFAULT_WORKSPACE.to_string())
+}
+
+/// Every workspace, the default one first.
+pub fn list() -> Vec<String> {
+    let mut names: Vec<String> = LocalStorage::get(WORKSPACES_KEY).unwrap_or_default();
+    names.retain(|name| name != DEFAULT_WORKSPACE);
+    names.insert(0, DEFAULT_WORKSPACE.to_string());
+    names
+}
+
+fn store_list(names: &[String]) {
+    if let Err(why) = LocalStorage::set(WORKSPACES_KEY, names) {
+        log::warn!("Could not save the workspaces: {why}");
+    }
+}
+
+/// `base` made specific to the current workspace, for storage keys and database names.
+pub fn key(base: &str) -> String {
+    key_in(base, &current())
+}
+
+fn key_in(base: &str, workspace: &str) -> String {
+    if workspace == DEFAULT_WORKSPACE {
+        base.to_string()
+    } else {
+        format!("{base}@{workspace}")
+    }
+}
+
+/// Makes `name` the current workspace and reloads the page, so everything is read again
+/// from that workspace's storage.
+pub fn switch(name: &str) {
+    if let Err(why) = LocalStorage::set(CURRENT_KEY, name) {
+        notify::error(i18n::t("workspace-switch-failed"), why);
+        return;
+    }
+    if let Err(e) = gloo::utils::window().location().reload() {
+        log::error!("Could not reload the page: {e:?}");
+    }
+}
+
+/// Adds a workspace starting out with `settings`, and switches to it.
+pub fn create(name: &str, settings: &Settings) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(i18n::t("workspace-unnamed"));
+    }
+    let mut names = list();
+    if names.iter().any(|existing| existing == name) {
+        return Err(i18n::t_args("workspace-exists", &[("name", &name)]));
+    }
+    names.push(name.to_string());
+    store_list(&names);
+    settings.store_to(&key_in(crate::settings::STORAGE_KEY, name))?;
+    switch(name);
+    Ok(())
+}
+
+/// Removes the current workspace with everything stored in it, and switches to the
+/// default one.
+pub fn delete_current() {
+    let name = current();
+    if name == DEFAULT_WORKSPACE {
+        return;
+    }
+    for base in [crate::settings::STORAGE_KEY, crate::aoi::STORAGE_KEY] {
+        LocalStorage::delete(key_in(base, &name));
+    }
+    // The database is still open in this page, so it is deleted after the reload.
+    let mut deleted: Vec<String> = LocalStorage::get(DELETED_KEY).unwrap_or_default();
+    deleted.push(key_in(crate::idb::DB_NAME, &name));
+    if let Err(why) = LocalStorage::set(DELETED_KEY, deleted) {
+        log::warn!("Could not delete the workspace history: {why}");
+    }
+    let mut names = list();
+    names.retain(|existing| *existing != name);
+    store_list(&names);
+    switch(DEFAULT_WORKSPACE);
+}
+
+/// Deletes the databases of workspaces deleted before the page was last reloaded.
+pub fn purge_deleted() {
+    let deleted: Vec<String> = LocalStorage::get(DELETED_KEY).unwrap_or_default();
+    if deleted.is_empty() {
+        return;
+    }
+    let Some(factory) = gloo::utils::window().indexed_db().ok().flatten() else {
+        return;
+    };
+    for name in deleted {
+        if let Err(e) = factory.delete_database(&name) {
+            log::warn!("Could not delete the database {name}: {e:?}");
+        }
+    }
+    LocalStorage::delete(DELETED_KEY);
+}
+
+/// Picker for the current workspace, with creation and deletion of workspaces.
+#[function_component(WorkspaceSwitcher)]
+pub fn workspace_switcher() -> Html {
+    let language = use_language();
+    let settings = use_context::<std::rc::Rc<Settings>>().unwrap_or_default();
+    let names = use_memo((), |_| list());
+    let current = use_memo((), |_| current());
+    let new_name = use_state(String::new);
+    let error = use_state(|| None::<String>);
+
+    let onswitch = {
+        shadow_clone!(names);
+        move |e: Event| {
+            let select: HtmlSelectElement = e.target_unchecked_into();
+            if let Some(name) = names.get(select.selected_index() as usize) {
+                switch(name);
+            }
+        }
+    };
+    let onname = {
+        shadow_clone!(new_name);
+        move |e: Event| {
+            let input: HtmlInputElement = e.target_unchecked_into();
+            new_name.set(input.value());
+        }
+    };
+    let oncreate = {
+        shadow_clone!(new_name, error);
+        move |_| {
+            if let Err(why) = create(&new_name, &settings) {
+                error.set(Some(why));
+            }
+        }
+    };
+    let ondelete = move |_| {
+        let confirmed = gloo::utils::window()
+            .confirm_with_message(&language.t("workspace-delete-confirm"))
+            .unwrap_or(false);
+        if confirmed {
+            delete_current();
+        }
+    };
+
+    html! {
+        <div class="mb-3">
+            <div class="input-group input-group-sm" style="max-width: 40em;">
+                <span class="input-group-text">{language.t("workspace")}</span>
+                <select class="form-select" onchange={onswitch}>
+                    {
+                        for names.iter().map(|name| html! {
+                            <option selected={*name == *current}>{name}</option>
+                        })
+                    }
+                </select>
+                if *current != DEFAULT_WORKSPACE {
+                    <button class="btn btn-outline-danger" onclick={ondelete}>{language.t("history-delete")}</button>
+                }
+                <input
+                    class="form-control"
+                    placeholder={language.t("workspace-new")}
+                    value={(*new_name).clone()}
+                    onchange={onname}
+                />
+                <button class="btn btn-outline-primary" onclick={oncreate}>{language.t("workspace-create")}</button>
+            </div>
+            if let Some(why) = &*error {
+                <div class="alert alert-danger mt-2" role="alert">{why}</div>
+            }
+        </div>
+    }
+}